@@ -199,6 +199,16 @@ impl BalanceManager {
     pub fn entry_count(&self) -> usize {
         self.balances.len()
     }
+
+    /// Sum of `available + frozen` per asset across all users.
+    #[must_use]
+    pub fn asset_totals(&self) -> HashMap<Asset, Decimal> {
+        let mut totals: HashMap<Asset, Decimal> = HashMap::new();
+        for ((_, asset), entry) in &self.balances {
+            *totals.entry(asset.clone()).or_default() += entry.available + entry.frozen;
+        }
+        totals
+    }
 }
 
 #[cfg(test)]
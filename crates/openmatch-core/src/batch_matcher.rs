@@ -148,9 +148,17 @@ impl BatchMatcher {
                     }
 
                     let fill_qty = buy.remaining_qty.min(sell.remaining_qty);
-                    let quote_amount = cp
-                        .checked_mul(fill_qty)
-                        .unwrap_or(rust_decimal::Decimal::MAX);
+                    // A fill whose quote amount overflows is skipped, like a
+                    // self-trade, rather than saturated to `Decimal::MAX`.
+                    let Some(quote_amount) = Trade::checked_quote_amount(cp, fill_qty) else {
+                        tracing::warn!(
+                            buy_order = %buy.id,
+                            sell_order = %sell.id,
+                            "Fill skipped: quote amount overflows"
+                        );
+                        sell_idx += 1;
+                        continue;
+                    };
 
                     // Deterministic trade ID: same batch + fill sequence → same ID
                     let trade_id = TradeId::deterministic(batch_id.0, fill_sequence);
@@ -383,7 +391,11 @@ mod tests {
         let result = matcher.match_batch(buf).unwrap();
         // All sells at or below clearing price should match
         let total_qty: Decimal = result.trades.iter().map(|t| t.quantity).sum();
-        assert_eq!(total_qty, dec(10), "Large buy should consume all eligible sells");
+        assert_eq!(
+            total_qty,
+            dec(10),
+            "Large buy should consume all eligible sells"
+        );
     }
 
     #[test]
@@ -537,10 +549,20 @@ mod tests {
         let attacker = UserId::new();
 
         let mut buf = PendingBuffer::new(BatchId(1));
-        buf.push(make_order_for_user(attacker, OrderSide::Buy, dec(100), dec(5)))
-            .unwrap();
-        buf.push(make_order_for_user(attacker, OrderSide::Sell, dec(100), dec(5)))
-            .unwrap();
+        buf.push(make_order_for_user(
+            attacker,
+            OrderSide::Buy,
+            dec(100),
+            dec(5),
+        ))
+        .unwrap();
+        buf.push(make_order_for_user(
+            attacker,
+            OrderSide::Sell,
+            dec(100),
+            dec(5),
+        ))
+        .unwrap();
         buf.seal().unwrap();
 
         let result = matcher.match_batch(buf).unwrap();
@@ -561,19 +583,38 @@ mod tests {
 
         let mut buf = PendingBuffer::new(BatchId(1));
         // Attacker's buy
-        buf.push(make_order_for_user(attacker, OrderSide::Buy, dec(100), dec(5)))
-            .unwrap();
+        buf.push(make_order_for_user(
+            attacker,
+            OrderSide::Buy,
+            dec(100),
+            dec(5),
+        ))
+        .unwrap();
         // Attacker's sell (self-trade attempt)
-        buf.push(make_order_for_user(attacker, OrderSide::Sell, dec(100), dec(5)))
-            .unwrap();
+        buf.push(make_order_for_user(
+            attacker,
+            OrderSide::Sell,
+            dec(100),
+            dec(5),
+        ))
+        .unwrap();
         // Honest seller
-        buf.push(make_order_for_user(honest_seller, OrderSide::Sell, dec(100), dec(3)))
-            .unwrap();
+        buf.push(make_order_for_user(
+            honest_seller,
+            OrderSide::Sell,
+            dec(100),
+            dec(3),
+        ))
+        .unwrap();
         buf.seal().unwrap();
 
         let result = matcher.match_batch(buf).unwrap();
         // Only the legitimate trade should execute
-        assert_eq!(result.trades.len(), 1, "Only attacker-vs-honest trade should match");
+        assert_eq!(
+            result.trades.len(),
+            1,
+            "Only attacker-vs-honest trade should match"
+        );
         assert_eq!(result.trades[0].quantity, dec(3));
         // Verify it's the honest seller
         assert_eq!(result.trades[0].maker_user_id, honest_seller);
@@ -587,9 +628,24 @@ mod tests {
         let honest = UserId::new();
 
         let mut orders = Vec::new();
-        orders.push(make_order_for_user(attacker, OrderSide::Buy, dec(100), dec(10)));
-        orders.push(make_order_for_user(attacker, OrderSide::Sell, dec(100), dec(5)));
-        orders.push(make_order_for_user(honest, OrderSide::Sell, dec(100), dec(8)));
+        orders.push(make_order_for_user(
+            attacker,
+            OrderSide::Buy,
+            dec(100),
+            dec(10),
+        ));
+        orders.push(make_order_for_user(
+            attacker,
+            OrderSide::Sell,
+            dec(100),
+            dec(5),
+        ));
+        orders.push(make_order_for_user(
+            honest,
+            OrderSide::Sell,
+            dec(100),
+            dec(8),
+        ));
 
         // Match on two different "nodes"
         let matcher_a = BatchMatcher::new(NodeId([1u8; 32]));
@@ -51,5 +51,5 @@ pub use orderbook::OrderBook;
 pub use pending_buffer::PendingBuffer;
 pub use security::{
     NonceTracker, OrderRateLimiter, PriceSanityChecker, SecuredBalanceManager,
-    SettlementIdempotencyGuard, SupplyConservation, WithdrawLock,
+    SettlementIdempotencyGuard, SupplyCheckCadence, SupplyConservation, WithdrawLock,
 };
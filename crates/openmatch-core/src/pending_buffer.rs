@@ -44,6 +44,8 @@ impl PendingBuffer {
     /// # Errors
     /// Returns `BufferAlreadySealed` if the buffer has been sealed.
     /// Returns `BufferFull` if `MAX_ORDERS_PER_BATCH` is reached.
+    /// Returns `Internal` if the sequence counter is exhausted — wrapping
+    /// to 0 would silently hand the order top time priority.
     pub fn push(&mut self, mut order: Order) -> Result<u64> {
         if self.sealed {
             return Err(OpenmatchError::BufferAlreadySealed);
@@ -53,9 +55,12 @@ impl PendingBuffer {
         }
 
         let seq = self.sequence_counter;
+        let next = seq.checked_add(1).ok_or_else(|| {
+            OpenmatchError::Internal("pending buffer sequence counter exhausted".into())
+        })?;
         order.sequence = seq;
         order.batch_id = Some(self.batch_id);
-        self.sequence_counter += 1;
+        self.sequence_counter = next;
         self.orders.push(order);
         Ok(seq)
     }
@@ -203,6 +208,23 @@ mod tests {
         assert_eq!(buf.len(), 2);
     }
 
+    #[test]
+    fn push_at_sequence_limit_errors_instead_of_wrapping() {
+        let mut buf = PendingBuffer::new(BatchId(1));
+        buf.sequence_counter = u64::MAX - 1;
+
+        let seq = buf
+            .push(make_order(OrderSide::Buy, Decimal::new(100, 0), Decimal::ONE))
+            .unwrap();
+        assert_eq!(seq, u64::MAX - 1);
+
+        let err = buf
+            .push(make_order(OrderSide::Buy, Decimal::new(100, 0), Decimal::ONE))
+            .unwrap_err();
+        assert!(matches!(err, OpenmatchError::Internal(_)));
+        assert_eq!(buf.len(), 1);
+    }
+
     #[test]
     fn push_after_seal_fails() {
         let mut buf = PendingBuffer::new(BatchId(1));
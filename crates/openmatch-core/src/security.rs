@@ -45,6 +45,9 @@ use rust_decimal::Decimal;
 
 /// Prevents double-settlement of the same trade.
 ///
+/// Shared with the settlement plane: this is an alias of
+/// [`openmatch_types::IdempotencyGuard`].
+///
 /// # Attack Vector (with source code knowledge)
 ///
 /// An attacker who controls a node could try to replay settlement messages
@@ -57,65 +60,7 @@ use rust_decimal::Decimal;
 /// - Bounded size with LRU eviction (oldest entries removed first)
 /// - The eviction is safe because trades older than the retention window
 ///   cannot be replayed (epoch sequencing prevents it)
-#[derive(Debug)]
-pub struct SettlementIdempotencyGuard {
-    settled: HashSet<TradeId>,
-    /// Insertion-ordered queue for LRU eviction.
-    order: VecDeque<TradeId>,
-    /// Maximum number of trade IDs to retain.
-    max_size: usize,
-}
-
-impl SettlementIdempotencyGuard {
-    /// Create a new guard with the given capacity.
-    #[must_use]
-    pub fn new(max_size: usize) -> Self {
-        Self {
-            settled: HashSet::with_capacity(max_size),
-            order: VecDeque::with_capacity(max_size),
-            max_size,
-        }
-    }
-
-    /// Attempt to mark a trade as settled.
-    ///
-    /// Returns `Ok(())` if the trade was not previously settled.
-    /// Returns `Err(TradeAlreadySettled)` if it was — blocking the replay.
-    pub fn mark_settled(&mut self, trade_id: TradeId) -> Result<()> {
-        if self.settled.contains(&trade_id) {
-            return Err(OpenmatchError::TradeAlreadySettled(trade_id));
-        }
-
-        // Evict oldest if at capacity
-        if self.settled.len() >= self.max_size {
-            if let Some(oldest) = self.order.pop_front() {
-                self.settled.remove(&oldest);
-            }
-        }
-
-        self.settled.insert(trade_id);
-        self.order.push_back(trade_id);
-        Ok(())
-    }
-
-    /// Check if a trade has already been settled (without marking).
-    #[must_use]
-    pub fn is_settled(&self, trade_id: &TradeId) -> bool {
-        self.settled.contains(trade_id)
-    }
-
-    /// Number of trade IDs currently tracked.
-    #[must_use]
-    pub fn len(&self) -> usize {
-        self.settled.len()
-    }
-
-    /// Returns `true` if no trades have been tracked.
-    #[must_use]
-    pub fn is_empty(&self) -> bool {
-        self.settled.is_empty()
-    }
-}
+pub use openmatch_types::SettlementIdempotencyGuard;
 
 // ═══════════════════════════════════════════════════════════════════
 // 2. NONCE TRACKER (Freeze Proof Replay Prevention)
@@ -135,12 +80,23 @@ impl SettlementIdempotencyGuard {
 /// Each node's nonce set is bounded. When the limit is reached, we
 /// reject new proofs from that node until the epoch advances (which
 /// clears stale nonces). This prevents memory exhaustion attacks.
+///
+/// # Monotonic Mode
+///
+/// With [`NonceTracker::require_monotonic`], each node's nonces must be
+/// strictly increasing (like Ethereum account nonces). Only the highest
+/// accepted nonce is kept per node, so memory is O(1) per node and the
+/// per-node quota does not apply. High-water marks survive `clear_*`.
 #[derive(Debug, Default)]
 pub struct NonceTracker {
     /// `NodeId → Set<nonce>` — used nonces per issuing node.
     used_nonces: HashMap<NodeId, HashSet<u64>>,
     /// Maximum nonces per node before rejection.
     max_per_node: usize,
+    /// Reject any nonce `<=` the node's high-water mark.
+    monotonic: bool,
+    /// `NodeId → highest accepted nonce` (monotonic mode only).
+    high_water: HashMap<NodeId, u64>,
 }
 
 impl NonceTracker {
@@ -150,12 +106,34 @@ impl NonceTracker {
         Self {
             used_nonces: HashMap::new(),
             max_per_node,
+            monotonic: false,
+            high_water: HashMap::new(),
         }
     }
 
+    /// Switch to monotonic mode: only nonces above the node's high-water
+    /// mark are accepted.
+    #[must_use]
+    pub fn require_monotonic(mut self) -> Self {
+        self.monotonic = true;
+        self
+    }
+
     /// Check and record a nonce. Returns error if the nonce was already used
-    /// or if the node has exceeded its nonce quota.
+    /// or if the node has exceeded its nonce quota. In monotonic mode, any
+    /// nonce at or below the node's high-water mark is a replay.
     pub fn check_and_record(&mut self, node_id: &NodeId, nonce: u64) -> Result<()> {
+        if self.monotonic {
+            if self.high_water.get(node_id).is_some_and(|&hw| nonce <= hw) {
+                return Err(OpenmatchError::NonceReplay {
+                    node_hex: hex::encode(node_id.0),
+                    nonce,
+                });
+            }
+            self.high_water.insert(*node_id, nonce);
+            return Ok(());
+        }
+
         let nonces = self.used_nonces.entry(*node_id).or_default();
 
         if nonces.contains(&nonce) {
@@ -194,6 +172,40 @@ impl NonceTracker {
     pub fn total_nonces(&self) -> usize {
         self.used_nonces.values().map(HashSet::len).sum()
     }
+
+    /// Highest nonce accepted from `node_id` in monotonic mode.
+    #[must_use]
+    pub fn high_water_mark(&self, node_id: &NodeId) -> Option<u64> {
+        self.high_water.get(node_id).copied()
+    }
+
+    /// Quota usage for `node_id` as `(used, max)`.
+    ///
+    /// In monotonic mode no nonce set is kept and the quota doesn't apply,
+    /// so `used` is always 0.
+    #[must_use]
+    pub fn node_usage(&self, node_id: &NodeId) -> (usize, usize) {
+        let used = self.used_nonces.get(node_id).map_or(0, HashSet::len);
+        (used, self.max_per_node)
+    }
+
+    /// Nodes whose quota utilization is at or above `pct` (0.0–1.0),
+    /// sorted by `NodeId`. Alert on these before `RateLimitExceeded` fires.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn nodes_near_capacity(&self, pct: f64) -> Vec<NodeId> {
+        if self.max_per_node == 0 {
+            return Vec::new();
+        }
+        let mut nodes: Vec<NodeId> = self
+            .used_nonces
+            .iter()
+            .filter(|(_, nonces)| nonces.len() as f64 / self.max_per_node as f64 >= pct)
+            .map(|(node_id, _)| *node_id)
+            .collect();
+        nodes.sort_unstable();
+        nodes
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════
@@ -246,10 +258,7 @@ impl SupplyConservation {
     /// `actual_totals` should be `Asset → sum(available + frozen)` for all users.
     ///
     /// Returns `Ok(())` if the invariant holds, or `Err` with details of the violation.
-    pub fn verify(
-        &self,
-        actual_totals: &HashMap<String, Decimal>,
-    ) -> Result<()> {
+    pub fn verify(&self, actual_totals: &HashMap<String, Decimal>) -> Result<()> {
         // Collect all assets seen in any map
         let mut all_assets: HashSet<&str> = HashSet::new();
         for k in self.total_deposits.keys() {
@@ -274,10 +283,7 @@ impl SupplyConservation {
                 .copied()
                 .unwrap_or(Decimal::ZERO);
             let expected = deposited - withdrawn;
-            let actual = actual_totals
-                .get(asset)
-                .copied()
-                .unwrap_or(Decimal::ZERO);
+            let actual = actual_totals.get(asset).copied().unwrap_or(Decimal::ZERO);
 
             if expected != actual {
                 return Err(OpenmatchError::SupplyInvariantViolation {
@@ -327,10 +333,22 @@ impl SupplyConservation {
 /// Tracks timestamps of recent orders per user. When a new order arrives,
 /// expired timestamps are pruned. If the count exceeds the limit, the
 /// order is rejected.
+///
+/// # Per-Market Scoping
+///
+/// With [`OrderRateLimiter::with_max_per_market_window`], orders checked
+/// through [`OrderRateLimiter::check_and_record_market`] are also windowed
+/// per `(user, market)`, so heavy quoting in one market can't exhaust the
+/// budget of another. The per-user window and epoch cap still apply on top.
 #[derive(Debug, Default)]
 pub struct OrderRateLimiter {
     /// `UserId → timestamps of recent orders` (monotonically increasing)
     windows: HashMap<UserId, VecDeque<u64>>,
+    /// `(UserId, MarketPair) → timestamps of recent orders in that market`
+    market_windows: HashMap<(UserId, MarketPair), VecDeque<u64>>,
+    /// Maximum orders per user per market within the window (`None`
+    /// disables per-market scoping).
+    max_per_market_window: Option<usize>,
     /// Window size in milliseconds.
     window_ms: u64,
     /// Maximum orders per user within the window.
@@ -347,6 +365,8 @@ impl OrderRateLimiter {
     pub fn new(window_ms: u64, max_per_window: usize, max_per_epoch: usize) -> Self {
         Self {
             windows: HashMap::new(),
+            market_windows: HashMap::new(),
+            max_per_market_window: None,
             window_ms,
             max_per_window,
             max_per_epoch,
@@ -354,6 +374,50 @@ impl OrderRateLimiter {
         }
     }
 
+    /// Cap each user at `max` orders per market within the window.
+    /// `max_per_window` keeps acting as the overall per-user cap.
+    #[must_use]
+    pub fn with_max_per_market_window(mut self, max: usize) -> Self {
+        self.max_per_market_window = Some(max);
+        self
+    }
+
+    /// Check if a user can submit an order in `market` at the given
+    /// timestamp, against both the per-market and the overall limits.
+    ///
+    /// Without a per-market cap this is [`Self::check_and_record`]. An
+    /// order rejected by either limit is recorded in neither.
+    pub fn check_and_record_market(
+        &mut self,
+        user_id: &UserId,
+        market: &MarketPair,
+        now_ms: u64,
+    ) -> Result<()> {
+        let Some(max_per_market) = self.max_per_market_window else {
+            return self.check_and_record(user_id, now_ms);
+        };
+
+        let key = (*user_id, market.clone());
+        let window = self.market_windows.entry(key.clone()).or_default();
+        prune_window(window, now_ms.saturating_sub(self.window_ms));
+        if window.len() >= max_per_market {
+            return Err(OpenmatchError::RateLimitExceeded {
+                reason: format!(
+                    "User submitted {} orders in {market} in {}ms window (limit: {max_per_market})",
+                    window.len(),
+                    self.window_ms,
+                ),
+            });
+        }
+
+        self.check_and_record(user_id, now_ms)?;
+        self.market_windows
+            .entry(key)
+            .or_default()
+            .push_back(now_ms);
+        Ok(())
+    }
+
     /// Check if a user can submit an order at the given timestamp.
     ///
     /// Returns `Ok(())` if allowed, or `Err` with the specific limit exceeded.
@@ -371,14 +435,7 @@ impl OrderRateLimiter {
         let window = self.windows.entry(*user_id).or_default();
 
         // Prune expired entries
-        let cutoff = now_ms.saturating_sub(self.window_ms);
-        while let Some(&front) = window.front() {
-            if front < cutoff {
-                window.pop_front();
-            } else {
-                break;
-            }
-        }
+        prune_window(window, now_ms.saturating_sub(self.window_ms));
 
         if window.len() >= self.max_per_window {
             return Err(OpenmatchError::RateLimitExceeded {
@@ -401,6 +458,7 @@ impl OrderRateLimiter {
     pub fn reset_epoch(&mut self) {
         self.epoch_counts.clear();
         self.windows.clear();
+        self.market_windows.clear();
     }
 
     /// Get the current order count for a user in this epoch.
@@ -410,6 +468,17 @@ impl OrderRateLimiter {
     }
 }
 
+/// Drop timestamps older than `cutoff` from the front of a window.
+fn prune_window(window: &mut VecDeque<u64>, cutoff: u64) {
+    while let Some(&front) = window.front() {
+        if front < cutoff {
+            window.pop_front();
+        } else {
+            break;
+        }
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════
 // 5. PRICE SANITY CHECKER
 // ═══════════════════════════════════════════════════════════════════
@@ -432,10 +501,19 @@ impl OrderRateLimiter {
 /// Even knowing the threshold, the attacker can only submit prices
 /// within the allowed range. Within that range, the clearing price
 /// algorithm ensures fair execution.
+///
+/// # Cold Start
+///
+/// A market with no reference is unbounded, so whoever trades first sets
+/// the initial price. Operators close that window with
+/// [`PriceSanityChecker::seed_reference`] (oracle or last-session price);
+/// a seeded market is enforced from its very first order.
 #[derive(Debug)]
 pub struct PriceSanityChecker {
     /// `MarketPair → last known reference price`
     reference_prices: HashMap<MarketPair, Decimal>,
+    /// Markets whose current reference was seeded, not observed.
+    seeded: HashSet<MarketPair>,
     /// Maximum deviation multiplier (e.g., 10 = price can be 10x or 1/10x reference).
     max_deviation: Decimal,
 }
@@ -446,22 +524,42 @@ impl PriceSanityChecker {
     pub fn new(max_deviation_multiplier: u64) -> Self {
         Self {
             reference_prices: HashMap::new(),
+            seeded: HashSet::new(),
             max_deviation: Decimal::from(max_deviation_multiplier),
         }
     }
 
     /// Update the reference price for a market (typically after each batch).
+    /// An observed price replaces any seeded one.
     pub fn update_reference(&mut self, market: &MarketPair, price: Decimal) {
         if price > Decimal::ZERO {
             self.reference_prices.insert(market.clone(), price);
+            self.seeded.remove(market);
         }
     }
 
+    /// Seed a market's reference before it has traded, so even its first
+    /// batch is bounded. Non-positive prices are ignored.
+    pub fn seed_reference(&mut self, market: &MarketPair, price: Decimal) {
+        if price > Decimal::ZERO {
+            self.reference_prices.insert(market.clone(), price);
+            self.seeded.insert(market.clone());
+        }
+    }
+
+    /// Whether the market's current reference came from
+    /// [`PriceSanityChecker::seed_reference`] rather than trading.
+    #[must_use]
+    pub fn is_seeded(&self, market: &MarketPair) -> bool {
+        self.seeded.contains(market)
+    }
+
     /// Check if an order price is within acceptable range.
     ///
     /// Returns `Ok(())` if acceptable, or `Err(SuspiciousPrice)` if not.
     ///
-    /// **First order for a market always passes** (no reference yet).
+    /// **First order for an unseeded market always passes** (no reference
+    /// yet). Seeded markets are enforced like any other.
     pub fn check_price(&self, market: &MarketPair, price: Decimal) -> Result<()> {
         // Reject non-positive prices
         if price <= Decimal::ZERO {
@@ -575,6 +673,22 @@ impl Default for WithdrawLock {
 // 7. SECURED BALANCE MANAGER (Integrates All Guards)
 // ═══════════════════════════════════════════════════════════════════
 
+/// When [`SecuredBalanceManager`] runs supply conservation on its own.
+///
+/// Checking after every operation is O(entries) each time; never checking
+/// lets a leak go unnoticed. The cadence picks the trade-off.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SupplyCheckCadence {
+    /// Only when [`SecuredBalanceManager::verify_supply_conservation`] is
+    /// called explicitly.
+    #[default]
+    Manual,
+    /// After every `n`th balance operation (`0` never fires).
+    EveryNOps(u64),
+    /// Whenever [`SecuredBalanceManager::set_phase`] enters this phase.
+    OnPhase(EpochPhase),
+}
+
 /// A security-hardened wrapper around [`BalanceManager`](crate::BalanceManager)
 /// that integrates all protection layers.
 ///
@@ -604,6 +718,10 @@ pub struct SecuredBalanceManager {
     supply_tracker: SupplyConservation,
     /// Total operations processed (audit counter).
     ops_count: u64,
+    /// When supply conservation runs automatically.
+    supply_check_cadence: SupplyCheckCadence,
+    /// Automatic supply checks run so far.
+    supply_checks_run: u64,
 }
 
 impl SecuredBalanceManager {
@@ -616,15 +734,27 @@ impl SecuredBalanceManager {
             withdraw_lock: WithdrawLock::new(),
             supply_tracker: SupplyConservation::new(),
             ops_count: 0,
+            supply_check_cadence: SupplyCheckCadence::Manual,
+            supply_checks_run: 0,
         }
     }
 
+    /// Run supply conservation automatically at `cadence`.
+    ///
+    /// A failed automatic check is logged and returned from the operation
+    /// that triggered it. That operation has already been applied; the
+    /// error reports that the ledger no longer reconciles.
+    #[must_use]
+    pub fn with_supply_check_cadence(mut self, cadence: SupplyCheckCadence) -> Self {
+        self.supply_check_cadence = cadence;
+        self
+    }
+
     /// Deposit funds (available balance increases).
     pub fn deposit(&mut self, user_id: &UserId, asset: &str, amount: Decimal) -> Result<()> {
         self.inner.deposit(user_id, asset, amount)?;
         self.supply_tracker.record_deposit(asset, amount);
-        self.ops_count += 1;
-        Ok(())
+        self.record_op()
     }
 
     /// Withdraw funds. **Blocked during MATCH/SETTLE phases.**
@@ -634,22 +764,19 @@ impl SecuredBalanceManager {
 
         self.inner.withdraw(user_id, asset, amount)?;
         self.supply_tracker.record_withdrawal(asset, amount);
-        self.ops_count += 1;
-        Ok(())
+        self.record_op()
     }
 
     /// Freeze balance for an order's escrow.
     pub fn freeze(&mut self, user_id: &UserId, asset: &str, amount: Decimal) -> Result<()> {
         self.inner.freeze(user_id, asset, amount)?;
-        self.ops_count += 1;
-        Ok(())
+        self.record_op()
     }
 
     /// Unfreeze balance (order cancelled).
     pub fn unfreeze(&mut self, user_id: &UserId, asset: &str, amount: Decimal) -> Result<()> {
         self.inner.unfreeze(user_id, asset, amount)?;
-        self.ops_count += 1;
-        Ok(())
+        self.record_op()
     }
 
     /// Settle a trade with **idempotency protection**.
@@ -661,13 +788,20 @@ impl SecuredBalanceManager {
 
         // Execute the settlement
         self.inner.settle_trade(trade, market)?;
-        self.ops_count += 1;
-        Ok(())
+        self.record_op()
     }
 
     /// Set the current epoch phase (controls withdraw lock).
-    pub fn set_phase(&mut self, phase: EpochPhase) {
+    ///
+    /// # Errors
+    /// `SupplyInvariantViolation` if the cadence checks supply on entering
+    /// `phase` and the check fails. The phase is set regardless.
+    pub fn set_phase(&mut self, phase: EpochPhase) -> Result<()> {
         self.withdraw_lock.set_phase(phase);
+        if self.supply_check_cadence == SupplyCheckCadence::OnPhase(phase) {
+            return self.run_supply_check();
+        }
+        Ok(())
     }
 
     /// Verify the supply conservation invariant.
@@ -679,20 +813,28 @@ impl SecuredBalanceManager {
     }
 
     /// Compute the actual total (available + frozen) per asset across all users.
+    ///
+    /// O(entries); the [`SupplyCheckCadence`] bounds how often it runs.
     fn compute_actual_totals(&self) -> HashMap<String, Decimal> {
-        // We need to iterate all entries in the inner manager.
-        // This is O(n) but only runs at epoch boundaries.
-        let mut totals: HashMap<String, Decimal> = HashMap::new();
-        // Access through the inner manager's user_balances.
-        // Since we don't have a full iteration method, we track via supply_tracker.
-        // For a real implementation, BalanceManager would expose `all_entries()`.
-        // For now, we rely on the supply tracker's own accounting.
-        let _ = totals; // placeholder
-        // In production, this would iterate all balances.
-        // The supply tracker's verify() method does the comparison.
-        // We return an empty map and let verify handle it.
-        // TODO: Add `all_balances()` to BalanceManager for full audit.
-        totals
+        self.inner.asset_totals()
+    }
+
+    /// Count an applied operation and run the supply check if it is due.
+    fn record_op(&mut self) -> Result<()> {
+        self.ops_count += 1;
+        match self.supply_check_cadence {
+            SupplyCheckCadence::EveryNOps(n) if n > 0 && self.ops_count % n == 0 => {
+                self.run_supply_check()
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn run_supply_check(&mut self) -> Result<()> {
+        self.supply_checks_run += 1;
+        self.verify_supply_conservation().inspect_err(|err| {
+            tracing::error!(ops = self.ops_count, %err, "supply conservation check failed");
+        })
     }
 
     /// Get a balance entry.
@@ -713,6 +855,12 @@ impl SecuredBalanceManager {
         self.ops_count
     }
 
+    /// Automatic supply checks run so far under the cadence.
+    #[must_use]
+    pub fn supply_checks_run(&self) -> u64 {
+        self.supply_checks_run
+    }
+
     /// Set emergency withdraw lock.
     pub fn set_emergency_lock(&mut self, locked: bool) {
         self.withdraw_lock.set_emergency_lock(locked);
@@ -757,7 +905,10 @@ mod tests {
         guard.mark_settled(trade_id).unwrap();
 
         let result = guard.mark_settled(trade_id);
-        assert!(matches!(result, Err(OpenmatchError::TradeAlreadySettled(_))));
+        assert!(matches!(
+            result,
+            Err(OpenmatchError::TradeAlreadySettled(_))
+        ));
     }
 
     #[test]
@@ -821,6 +972,22 @@ mod tests {
         assert!(tracker.check_and_record(&node_b, 42).is_ok());
     }
 
+    #[test]
+    fn nonce_tracker_reports_nodes_near_capacity() {
+        let mut tracker = NonceTracker::new(10);
+        let busy = NodeId([1u8; 32]);
+        let quiet = NodeId([2u8; 32]);
+        for nonce in 0..8 {
+            tracker.check_and_record(&busy, nonce).unwrap();
+        }
+        tracker.check_and_record(&quiet, 0).unwrap();
+
+        assert_eq!(tracker.node_usage(&busy), (8, 10));
+        assert_eq!(tracker.node_usage(&NodeId([3u8; 32])), (0, 10));
+        assert_eq!(tracker.nodes_near_capacity(0.75), vec![busy]);
+        assert!(tracker.nodes_near_capacity(0.9).is_empty());
+    }
+
     #[test]
     fn nonce_tracker_rejects_at_capacity() {
         let mut tracker = NonceTracker::new(3);
@@ -853,6 +1020,49 @@ mod tests {
         assert_eq!(tracker.total_nonces(), 1);
     }
 
+    #[test]
+    fn monotonic_nonce_tracker_accepts_increasing() {
+        let mut tracker = NonceTracker::new(100).require_monotonic();
+        let node = NodeId([1u8; 32]);
+        tracker.check_and_record(&node, 1).unwrap();
+        tracker.check_and_record(&node, 5).unwrap();
+        tracker.check_and_record(&node, 6).unwrap();
+        assert_eq!(tracker.high_water_mark(&node), Some(6));
+    }
+
+    #[test]
+    fn monotonic_nonce_tracker_rejects_stale() {
+        let mut tracker = NonceTracker::new(100).require_monotonic();
+        let node = NodeId([1u8; 32]);
+        tracker.check_and_record(&node, 10).unwrap();
+
+        for stale in [10, 9, 0] {
+            let result = tracker.check_and_record(&node, stale);
+            assert!(matches!(result, Err(OpenmatchError::NonceReplay { .. })));
+        }
+        assert_eq!(tracker.high_water_mark(&node), Some(10));
+
+        // Other nodes have their own mark
+        assert!(tracker.check_and_record(&NodeId([2u8; 32]), 1).is_ok());
+    }
+
+    #[test]
+    fn monotonic_nonce_tracker_memory_is_constant_per_node() {
+        let mut tracker = NonceTracker::new(3).require_monotonic();
+        let node = NodeId([1u8; 32]);
+
+        // Far beyond the per-node quota: nothing is stored per nonce
+        for nonce in 1..=10_000 {
+            tracker.check_and_record(&node, nonce).unwrap();
+        }
+        assert_eq!(tracker.total_nonces(), 0);
+        assert_eq!(tracker.high_water.len(), 1);
+
+        // Clearing the epoch's nonce sets does not reopen old nonces
+        tracker.clear_all();
+        assert!(tracker.check_and_record(&node, 10_000).is_err());
+    }
+
     // ──────────────────── Supply Conservation ────────────────────
 
     #[test]
@@ -1018,6 +1228,50 @@ mod tests {
         assert!(limiter.check_and_record(&user, 300).is_ok());
     }
 
+    #[test]
+    fn rate_limiter_market_limit_does_not_block_other_markets() {
+        let mut limiter = OrderRateLimiter::new(1000, 5, 50).with_max_per_market_window(2);
+        let user = UserId::new();
+        let btc = MarketPair::new("BTC", "USDT");
+        let eth = MarketPair::new("ETH", "USDT");
+
+        limiter.check_and_record_market(&user, &btc, 100).unwrap();
+        limiter.check_and_record_market(&user, &btc, 200).unwrap();
+        let result = limiter.check_and_record_market(&user, &btc, 300);
+        assert!(matches!(
+            result,
+            Err(OpenmatchError::RateLimitExceeded { .. })
+        ));
+
+        // BTC is saturated, ETH still has its own budget
+        assert!(limiter.check_and_record_market(&user, &eth, 300).is_ok());
+        // The rejected BTC order wasn't counted
+        assert_eq!(limiter.epoch_count(&user), 3);
+    }
+
+    #[test]
+    fn rate_limiter_overall_cap_spans_markets() {
+        let mut limiter = OrderRateLimiter::new(1000, 3, 50).with_max_per_market_window(2);
+        let user = UserId::new();
+        let btc = MarketPair::new("BTC", "USDT");
+        let eth = MarketPair::new("ETH", "USDT");
+        let sol = MarketPair::new("SOL", "USDT");
+
+        limiter.check_and_record_market(&user, &btc, 100).unwrap();
+        limiter.check_and_record_market(&user, &btc, 200).unwrap();
+        limiter.check_and_record_market(&user, &eth, 300).unwrap();
+
+        // SOL is untouched, but the user's overall window is full
+        let result = limiter.check_and_record_market(&user, &sol, 400);
+        assert!(matches!(
+            result,
+            Err(OpenmatchError::RateLimitExceeded { .. })
+        ));
+
+        // Once the window slides, SOL goes through
+        assert!(limiter.check_and_record_market(&user, &sol, 1250).is_ok());
+    }
+
     // ──────────────────── Price Sanity Checker ────────────────────
 
     #[test]
@@ -1027,6 +1281,32 @@ mod tests {
         assert!(checker.check_price(&market, dec(50000)).is_ok());
     }
 
+    #[test]
+    fn price_sanity_seeded_market_enforces_first_order() {
+        let mut checker = PriceSanityChecker::new(10);
+        let market = MarketPair::new("BTC", "USDT");
+        checker.seed_reference(&market, dec(50000));
+        assert!(checker.is_seeded(&market));
+
+        // Very first order: outside the seeded bounds is rejected
+        let result = checker.check_price(&market, dec(1));
+        assert!(matches!(
+            result,
+            Err(OpenmatchError::SuspiciousPrice { .. })
+        ));
+        assert!(checker.check_price(&market, dec(49000)).is_ok());
+
+        // Unseeded markets still pass without a reference
+        let other = MarketPair::new("ETH", "USDT");
+        assert!(!checker.is_seeded(&other));
+        assert!(checker.check_price(&other, dec(1)).is_ok());
+
+        // An observed clearing price replaces the seed
+        checker.update_reference(&market, dec(48000));
+        assert!(!checker.is_seeded(&market));
+        assert_eq!(checker.reference_price(&market), Some(dec(48000)));
+    }
+
     #[test]
     fn price_sanity_within_range_passes() {
         let mut checker = PriceSanityChecker::new(10);
@@ -1047,7 +1327,10 @@ mod tests {
 
         // 500,001 > 10x reference
         let result = checker.check_price(&market, Decimal::new(500_001, 0));
-        assert!(matches!(result, Err(OpenmatchError::SuspiciousPrice { .. })));
+        assert!(matches!(
+            result,
+            Err(OpenmatchError::SuspiciousPrice { .. })
+        ));
     }
 
     #[test]
@@ -1058,7 +1341,10 @@ mod tests {
 
         // 4999 < 1/10x reference
         let result = checker.check_price(&market, Decimal::new(4999, 0));
-        assert!(matches!(result, Err(OpenmatchError::SuspiciousPrice { .. })));
+        assert!(matches!(
+            result,
+            Err(OpenmatchError::SuspiciousPrice { .. })
+        ));
     }
 
     #[test]
@@ -1066,7 +1352,10 @@ mod tests {
         let checker = PriceSanityChecker::new(10);
         let market = MarketPair::new("BTC", "USDT");
         let result = checker.check_price(&market, Decimal::ZERO);
-        assert!(matches!(result, Err(OpenmatchError::SuspiciousPrice { .. })));
+        assert!(matches!(
+            result,
+            Err(OpenmatchError::SuspiciousPrice { .. })
+        ));
     }
 
     #[test]
@@ -1074,7 +1363,10 @@ mod tests {
         let checker = PriceSanityChecker::new(10);
         let market = MarketPair::new("BTC", "USDT");
         let result = checker.check_price(&market, dec(-100));
-        assert!(matches!(result, Err(OpenmatchError::SuspiciousPrice { .. })));
+        assert!(matches!(
+            result,
+            Err(OpenmatchError::SuspiciousPrice { .. })
+        ));
     }
 
     // ──────────────────── Withdraw Lock ────────────────────
@@ -1142,7 +1434,7 @@ mod tests {
         let user = UserId::new();
         mgr.deposit(&user, "USDT", dec(10000)).unwrap();
 
-        mgr.set_phase(EpochPhase::Settle);
+        mgr.set_phase(EpochPhase::Settle).unwrap();
         let result = mgr.withdraw(&user, "USDT", dec(1000));
         assert!(
             matches!(result, Err(OpenmatchError::WithdrawLockedDuringSettle)),
@@ -1202,6 +1494,46 @@ mod tests {
         assert_eq!(mgr.ops_count(), 2);
     }
 
+    #[test]
+    fn secured_manager_checks_supply_every_n_ops() {
+        let mut mgr = SecuredBalanceManager::new(1000)
+            .with_supply_check_cadence(SupplyCheckCadence::EveryNOps(3));
+        let user = UserId::new();
+
+        mgr.deposit(&user, "USDT", dec(1000)).unwrap();
+        mgr.freeze(&user, "USDT", dec(500)).unwrap();
+        assert_eq!(mgr.supply_checks_run(), 0);
+        mgr.unfreeze(&user, "USDT", dec(500)).unwrap();
+        assert_eq!(mgr.supply_checks_run(), 1);
+
+        // Inject phantom supply: the tracker expects 1 USDT nobody holds
+        mgr.supply_tracker.record_deposit("USDT", dec(1));
+        mgr.freeze(&user, "USDT", dec(100)).unwrap();
+        mgr.unfreeze(&user, "USDT", dec(100)).unwrap();
+        let err = mgr.freeze(&user, "USDT", dec(100)).unwrap_err();
+        assert!(matches!(
+            err,
+            OpenmatchError::SupplyInvariantViolation { .. }
+        ));
+        assert_eq!(mgr.supply_checks_run(), 2);
+    }
+
+    #[test]
+    fn secured_manager_checks_supply_on_phase() {
+        let mut mgr = SecuredBalanceManager::new(1000)
+            .with_supply_check_cadence(SupplyCheckCadence::OnPhase(EpochPhase::Settle));
+        let user = UserId::new();
+        mgr.deposit(&user, "USDT", dec(1000)).unwrap();
+
+        mgr.set_phase(EpochPhase::Match).unwrap();
+        assert_eq!(mgr.supply_checks_run(), 0);
+        mgr.set_phase(EpochPhase::Settle).unwrap();
+        assert_eq!(mgr.supply_checks_run(), 1);
+
+        mgr.supply_tracker.record_withdrawal("USDT", dec(1));
+        assert!(mgr.set_phase(EpochPhase::Settle).is_err());
+    }
+
     #[test]
     fn secured_manager_emergency_lock() {
         let mut mgr = SecuredBalanceManager::new(1000);
@@ -200,16 +200,16 @@ fn withdraw_during_settle_attack_blocked() {
     assert_eq!(mgr.get(&attacker, "USDT").available, dec(40_000));
 
     // Epoch transitions to MATCH → SETTLE
-    mgr.set_phase(EpochPhase::Match);
+    mgr.set_phase(EpochPhase::Match).unwrap();
     let result = mgr.withdraw(&attacker, "USDT", dec(40_000));
     assert!(result.is_err(), "Withdraw during MATCH must be blocked");
 
-    mgr.set_phase(EpochPhase::Settle);
+    mgr.set_phase(EpochPhase::Settle).unwrap();
     let result = mgr.withdraw(&attacker, "USDT", dec(40_000));
     assert!(result.is_err(), "Withdraw during SETTLE must be blocked");
 
     // After SETTLE, withdrawal resumes
-    mgr.set_phase(EpochPhase::Collect);
+    mgr.set_phase(EpochPhase::Collect).unwrap();
     let result = mgr.withdraw(&attacker, "USDT", dec(40_000));
     assert!(result.is_ok(), "Withdraw should work after SETTLE completes");
 }
@@ -468,14 +468,14 @@ fn full_epoch_attack_sequence() {
     mgr.freeze(&victim, "BTC", dec(1)).unwrap();
 
     // Step 3: MATCH phase — attacker tries to withdraw
-    mgr.set_phase(EpochPhase::Match);
+    mgr.set_phase(EpochPhase::Match).unwrap();
     assert!(
         mgr.withdraw(&attacker, "USDT", dec(50_000)).is_err(),
         "Step 3: Withdraw during MATCH must fail"
     );
 
     // Step 4: Settlement
-    mgr.set_phase(EpochPhase::Settle);
+    mgr.set_phase(EpochPhase::Settle).unwrap();
     let trade = Trade {
         id: TradeId::deterministic(1, 0),
         batch_id: BatchId(1),
@@ -507,7 +507,7 @@ fn full_epoch_attack_sequence() {
     );
 
     // Step 6: COLLECT phase — verify final balances
-    mgr.set_phase(EpochPhase::Collect);
+    mgr.set_phase(EpochPhase::Collect).unwrap();
 
     // Attacker: 50,000 USDT available + 0 frozen + 1 BTC available
     assert_eq!(mgr.get(&attacker, "USDT").available, dec(50_000));
@@ -0,0 +1,239 @@
+//! Agent registry — which agents may trade for which users.
+//!
+//! Every agent order must come from an agent bound to the order's user and
+//! currently active. Paused agents (epoch loss breach) and disabled agents
+//! (daily loss breach) are refused until reactivated.
+
+use std::collections::HashMap;
+
+use openmatch_types::{AgentBinding, AgentId, OpenmatchError, Result, RiskRejectionReason, UserId};
+
+/// Activation state of a bound agent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AgentState {
+    /// Agent may submit orders.
+    Active,
+    /// Agent is halted until reviewed (e.g. epoch loss limit breached).
+    Paused,
+    /// Agent is halted until an admin re-enables it (e.g. daily loss breached).
+    Disabled,
+}
+
+/// Store of agent bindings and their activation state.
+#[derive(Debug, Default)]
+pub struct AgentRegistry {
+    /// Bindings with their current state. `binding.active` mirrors
+    /// `state == AgentState::Active`.
+    agents: HashMap<AgentId, (AgentBinding, AgentState)>,
+}
+
+impl AgentRegistry {
+    /// Create an empty registry.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register an agent. Its initial state follows `binding.active`
+    /// (inactive bindings start paused).
+    ///
+    /// # Errors
+    /// Returns `Configuration` if the agent is already bound; unbind it
+    /// first to move it to another user.
+    pub fn bind(&mut self, binding: AgentBinding) -> Result<()> {
+        if let Some((existing, _)) = self.agents.get(&binding.agent_id) {
+            return Err(OpenmatchError::Configuration(format!(
+                "{} is already bound to {}",
+                binding.agent_id, existing.user_id
+            )));
+        }
+        let state = if binding.active {
+            AgentState::Active
+        } else {
+            AgentState::Paused
+        };
+        self.agents.insert(binding.agent_id, (binding, state));
+        Ok(())
+    }
+
+    /// Remove an agent's binding, returning it if it existed.
+    pub fn unbind(&mut self, agent_id: &AgentId) -> Option<AgentBinding> {
+        self.agents.remove(agent_id).map(|(binding, _)| binding)
+    }
+
+    /// Look up an agent's binding.
+    #[must_use]
+    pub fn get(&self, agent_id: &AgentId) -> Option<&AgentBinding> {
+        self.agents.get(agent_id).map(|(binding, _)| binding)
+    }
+
+    /// Current state of an agent, or `None` if it isn't bound.
+    #[must_use]
+    pub fn state(&self, agent_id: &AgentId) -> Option<AgentState> {
+        self.agents.get(agent_id).map(|(_, state)| *state)
+    }
+
+    /// Pause an active agent. A disabled agent stays disabled.
+    ///
+    /// # Errors
+    /// Returns `Configuration` if the agent isn't bound.
+    pub fn pause(&mut self, agent_id: &AgentId) -> Result<()> {
+        let (binding, state) = self.entry_mut(agent_id)?;
+        if *state == AgentState::Active {
+            *state = AgentState::Paused;
+            binding.active = false;
+        }
+        Ok(())
+    }
+
+    /// Disable an agent (requires [`AgentRegistry::reactivate`] to resume).
+    ///
+    /// # Errors
+    /// Returns `Configuration` if the agent isn't bound.
+    pub fn disable(&mut self, agent_id: &AgentId) -> Result<()> {
+        let (binding, state) = self.entry_mut(agent_id)?;
+        *state = AgentState::Disabled;
+        binding.active = false;
+        Ok(())
+    }
+
+    /// Return a paused or disabled agent to the active state.
+    ///
+    /// # Errors
+    /// Returns `Configuration` if the agent isn't bound.
+    pub fn reactivate(&mut self, agent_id: &AgentId) -> Result<()> {
+        let (binding, state) = self.entry_mut(agent_id)?;
+        *state = AgentState::Active;
+        binding.active = true;
+        Ok(())
+    }
+
+    /// Returns `true` if the agent is bound to `user_id` and active.
+    #[must_use]
+    pub fn authorized(&self, agent_id: &AgentId, user_id: &UserId) -> bool {
+        self.agents.get(agent_id).is_some_and(|(binding, state)| {
+            binding.user_id == *user_id && *state == AgentState::Active
+        })
+    }
+
+    /// Fail-closed form of [`AgentRegistry::authorized`] for the ingress path.
+    ///
+    /// # Errors
+    /// Returns `RiskRejected(AgentNotActive)` if the agent is unknown, bound
+    /// to a different user, paused or disabled.
+    pub fn check(&self, agent_id: &AgentId, user_id: &UserId) -> Result<()> {
+        if self.authorized(agent_id, user_id) {
+            Ok(())
+        } else {
+            Err(OpenmatchError::RiskRejected(
+                RiskRejectionReason::AgentNotActive,
+            ))
+        }
+    }
+
+    /// Number of bound agents.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.agents.len()
+    }
+
+    /// Returns `true` if no agents are bound.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.agents.is_empty()
+    }
+
+    fn entry_mut(&mut self, agent_id: &AgentId) -> Result<&mut (AgentBinding, AgentState)> {
+        self.agents
+            .get_mut(agent_id)
+            .ok_or_else(|| OpenmatchError::Configuration(format!("{agent_id} is not bound")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use openmatch_types::RiskLimits;
+
+    use super::*;
+
+    fn make_binding(user_id: UserId) -> AgentBinding {
+        AgentBinding {
+            agent_id: AgentId::new(),
+            user_id,
+            limits: RiskLimits::default(),
+            active: true,
+            name: "TestBot".to_string(),
+        }
+    }
+
+    #[test]
+    fn bound_active_agent_is_authorized_for_its_user_only() {
+        let mut registry = AgentRegistry::new();
+        let user = UserId::new();
+        let binding = make_binding(user);
+        let agent = binding.agent_id;
+        registry.bind(binding).unwrap();
+
+        assert!(registry.authorized(&agent, &user));
+        assert!(!registry.authorized(&agent, &UserId::new()));
+        assert!(!registry.authorized(&AgentId::new(), &user));
+        assert!(registry.check(&agent, &user).is_ok());
+    }
+
+    #[test]
+    fn double_bind_rejected() {
+        let mut registry = AgentRegistry::new();
+        let binding = make_binding(UserId::new());
+        registry.bind(binding.clone()).unwrap();
+        assert!(registry.bind(binding).is_err());
+        assert_eq!(registry.len(), 1);
+    }
+
+    #[test]
+    fn pause_blocks_and_reactivate_restores() {
+        let mut registry = AgentRegistry::new();
+        let user = UserId::new();
+        let binding = make_binding(user);
+        let agent = binding.agent_id;
+        registry.bind(binding).unwrap();
+
+        registry.pause(&agent).unwrap();
+        assert_eq!(registry.state(&agent), Some(AgentState::Paused));
+        assert!(!registry.get(&agent).unwrap().active);
+        let err = registry.check(&agent, &user).unwrap_err();
+        assert!(matches!(
+            err,
+            OpenmatchError::RiskRejected(RiskRejectionReason::AgentNotActive)
+        ));
+
+        registry.reactivate(&agent).unwrap();
+        assert!(registry.authorized(&agent, &user));
+        assert!(registry.get(&agent).unwrap().active);
+    }
+
+    #[test]
+    fn pause_does_not_downgrade_disabled() {
+        let mut registry = AgentRegistry::new();
+        let binding = make_binding(UserId::new());
+        let agent = binding.agent_id;
+        registry.bind(binding).unwrap();
+
+        registry.disable(&agent).unwrap();
+        registry.pause(&agent).unwrap();
+        assert_eq!(registry.state(&agent), Some(AgentState::Disabled));
+    }
+
+    #[test]
+    fn unbind_revokes_authorization() {
+        let mut registry = AgentRegistry::new();
+        let user = UserId::new();
+        let binding = make_binding(user);
+        let agent = binding.agent_id;
+        registry.bind(binding).unwrap();
+
+        assert!(registry.unbind(&agent).is_some());
+        assert!(!registry.authorized(&agent, &user));
+        assert!(registry.pause(&agent).is_err());
+        assert!(registry.is_empty());
+    }
+}
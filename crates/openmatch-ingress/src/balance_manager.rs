@@ -6,7 +6,10 @@
 
 use std::collections::HashMap;
 
-use openmatch_types::{Asset, BalanceEntry, OpenmatchError, Result, UserId};
+use openmatch_types::{
+    Asset, BalanceEntry, BalanceLedger, IntoAsset, OpenmatchError, Result, RiskRejectionReason,
+    UserId,
+};
 use rust_decimal::Decimal;
 
 /// Manages user balances with available/frozen accounting.
@@ -29,11 +32,8 @@ impl BalanceManager {
     }
 
     /// Deposit funds (increases available balance).
-    pub fn deposit(&mut self, user_id: UserId, asset: &str, amount: Decimal) {
-        let entry = self
-            .balances
-            .entry((user_id, asset.to_string()))
-            .or_default();
+    pub fn deposit(&mut self, user_id: UserId, asset: impl Into<Asset>, amount: Decimal) {
+        let entry = self.balances.entry((user_id, asset.into())).or_default();
         entry.available += amount;
     }
 
@@ -41,13 +41,19 @@ impl BalanceManager {
     ///
     /// # Errors
     /// Returns `InsufficientBalance` if available < amount.
-    pub fn freeze(&mut self, user_id: UserId, asset: &str, amount: Decimal) -> Result<()> {
-        let entry = self.balances.get_mut(&(user_id, asset.to_string())).ok_or(
-            OpenmatchError::InsufficientBalance {
+    pub fn freeze(
+        &mut self,
+        user_id: UserId,
+        asset: impl IntoAsset,
+        amount: Decimal,
+    ) -> Result<()> {
+        let entry = self
+            .balances
+            .get_mut(&(user_id, asset.into_asset()?))
+            .ok_or(OpenmatchError::InsufficientBalance {
                 needed: amount,
                 available: Decimal::ZERO,
-            },
-        )?;
+            })?;
 
         if entry.available < amount {
             return Err(OpenmatchError::InsufficientBalance {
@@ -61,14 +67,76 @@ impl BalanceManager {
         Ok(())
     }
 
+    /// Freeze funds, keeping at least `min_reserve` available afterwards.
+    ///
+    /// Freezing down to exactly `min_reserve` is allowed.
+    ///
+    /// # Errors
+    /// Returns `InsufficientBalance` if available < amount, or
+    /// `RiskRejected(ReserveViolation)` if the freeze would leave less
+    /// than `min_reserve` available. The balance is unchanged on error.
+    pub fn freeze_with_reserve(
+        &mut self,
+        user_id: UserId,
+        asset: impl IntoAsset,
+        amount: Decimal,
+        min_reserve: Decimal,
+    ) -> Result<()> {
+        let asset = asset.into_asset()?;
+        let available_after = self.balance(user_id, &asset).available - amount;
+        if available_after >= Decimal::ZERO && available_after < min_reserve {
+            return Err(OpenmatchError::RiskRejected(
+                RiskRejectionReason::ReserveViolation {
+                    available_after,
+                    min_reserve,
+                },
+            ));
+        }
+        self.freeze(user_id, asset, amount)
+    }
+
+    /// Freeze as much as possible up to `desired` (available → frozen).
+    ///
+    /// Freezes `min(desired, available)` in one step and returns the amount
+    /// actually frozen, so callers sizing best-effort orders don't race
+    /// between a balance query and the freeze.
+    ///
+    /// # Errors
+    /// Returns `InsufficientBalance` if nothing is available.
+    pub fn freeze_up_to(
+        &mut self,
+        user_id: UserId,
+        asset: impl IntoAsset,
+        desired: Decimal,
+    ) -> Result<Decimal> {
+        let entry = self
+            .balances
+            .get_mut(&(user_id, asset.into_asset()?))
+            .filter(|entry| !entry.available.is_zero())
+            .ok_or(OpenmatchError::InsufficientBalance {
+                needed: desired,
+                available: Decimal::ZERO,
+            })?;
+
+        let amount = desired.min(entry.available).max(Decimal::ZERO);
+        entry.available -= amount;
+        entry.frozen += amount;
+        Ok(amount)
+    }
+
     /// Unfreeze funds (frozen → available). Used when releasing a SpendRight.
     ///
     /// # Errors
     /// Returns `InsufficientFrozen` if frozen < amount.
-    pub fn unfreeze(&mut self, user_id: UserId, asset: &str, amount: Decimal) -> Result<()> {
+    pub fn unfreeze(
+        &mut self,
+        user_id: UserId,
+        asset: impl IntoAsset,
+        amount: Decimal,
+    ) -> Result<()> {
         let entry = self
             .balances
-            .get_mut(&(user_id, asset.to_string()))
+            .get_mut(&(user_id, asset.into_asset()?))
             .ok_or(OpenmatchError::InsufficientFrozen)?;
 
         if entry.frozen < amount {
@@ -85,10 +153,15 @@ impl BalanceManager {
     ///
     /// # Errors
     /// Returns `InsufficientFrozen` if frozen < amount.
-    pub fn consume_frozen(&mut self, user_id: UserId, asset: &str, amount: Decimal) -> Result<()> {
+    pub fn consume_frozen(
+        &mut self,
+        user_id: UserId,
+        asset: impl IntoAsset,
+        amount: Decimal,
+    ) -> Result<()> {
         let entry = self
             .balances
-            .get_mut(&(user_id, asset.to_string()))
+            .get_mut(&(user_id, asset.into_asset()?))
             .ok_or(OpenmatchError::InsufficientFrozen)?;
 
         if entry.frozen < amount {
@@ -100,31 +173,135 @@ impl BalanceManager {
     }
 
     /// Credit available balance (for settlement — receiving side).
-    pub fn credit(&mut self, user_id: UserId, asset: &str, amount: Decimal) {
+    pub fn credit(&mut self, user_id: UserId, asset: impl Into<Asset>, amount: Decimal) {
+        let entry = self.balances.entry((user_id, asset.into())).or_default();
+        entry.available += amount;
+    }
+
+    /// Reserve funds for an external withdrawal (available → withdraw
+    /// pending). Supply is unchanged until the withdrawal is confirmed.
+    ///
+    /// # Errors
+    /// Returns `InsufficientBalance` if available < amount.
+    pub fn reserve_for_withdrawal(
+        &mut self,
+        user_id: UserId,
+        asset: impl IntoAsset,
+        amount: Decimal,
+    ) -> Result<()> {
         let entry = self
             .balances
-            .entry((user_id, asset.to_string()))
-            .or_default();
+            .get_mut(&(user_id, asset.into_asset()?))
+            .ok_or(OpenmatchError::InsufficientBalance {
+                needed: amount,
+                available: Decimal::ZERO,
+            })?;
+
+        if entry.available < amount {
+            return Err(OpenmatchError::InsufficientBalance {
+                needed: amount,
+                available: entry.available,
+            });
+        }
+
+        entry.available -= amount;
+        entry.withdraw_pending += amount;
+        Ok(())
+    }
+
+    /// Confirm a reserved withdrawal: the funds leave the system and total
+    /// supply decreases by `amount`.
+    ///
+    /// # Errors
+    /// Returns `BalanceUnderflow` if withdraw pending < amount.
+    pub fn confirm_withdrawal(
+        &mut self,
+        user_id: UserId,
+        asset: impl IntoAsset,
+        amount: Decimal,
+    ) -> Result<()> {
+        let entry = self.pending_entry(user_id, asset.into_asset()?, amount)?;
+        entry.withdraw_pending -= amount;
+        Ok(())
+    }
+
+    /// Cancel a reserved withdrawal (withdraw pending → available).
+    ///
+    /// # Errors
+    /// Returns `BalanceUnderflow` if withdraw pending < amount.
+    pub fn cancel_withdrawal(
+        &mut self,
+        user_id: UserId,
+        asset: impl IntoAsset,
+        amount: Decimal,
+    ) -> Result<()> {
+        let entry = self.pending_entry(user_id, asset.into_asset()?, amount)?;
+        entry.withdraw_pending -= amount;
         entry.available += amount;
+        Ok(())
     }
 
     /// Get the balance for a (user, asset) pair.
     #[must_use]
     pub fn balance(&self, user_id: UserId, asset: &str) -> BalanceEntry {
-        self.balances
-            .get(&(user_id, asset.to_string()))
+        // An invalid symbol can never have been credited.
+        Asset::new(asset)
+            .ok()
+            .and_then(|asset| self.balances.get(&(user_id, asset)))
             .cloned()
             .unwrap_or_default()
     }
 
-    /// Total supply of an asset (sum of all users' available + frozen).
+    /// Total supply of an asset (sum of all users' available, frozen and
+    /// withdraw-pending balances).
+    ///
+    /// # Errors
+    /// Returns `SupplyInvariantViolation` if the sum overflows `Decimal`.
+    pub fn total_supply(&self, asset: &str) -> Result<Decimal> {
+        let mut total = Decimal::ZERO;
+        for ((user_id, _), entry) in self.balances.iter().filter(|((_, a), _)| *a == asset) {
+            total = entry
+                .checked_total()
+                .and_then(|entry_total| total.checked_add(entry_total))
+                .ok_or_else(|| OpenmatchError::SupplyInvariantViolation {
+                    reason: format!("Asset {asset}: balance overflow summing user {user_id}"),
+                })?;
+        }
+        Ok(total)
+    }
+
+    /// Number of (user, asset) entries held, including zero ones.
     #[must_use]
-    pub fn total_supply(&self, asset: &str) -> Decimal {
+    pub fn entry_count(&self) -> usize {
+        self.balances.len()
+    }
+
+    /// Drop entries with nothing available and nothing frozen, returning
+    /// how many were removed.
+    ///
+    /// A removed entry reads back as zero from [`BalanceManager::balance`]
+    /// and is recreated on the next deposit or credit, so this is safe at
+    /// any epoch boundary.
+    pub fn gc(&mut self) -> usize {
+        let before = self.balances.len();
+        self.balances.retain(|_, entry| !entry.is_zero());
+        before - self.balances.len()
+    }
+
+    fn pending_entry(
+        &mut self,
+        user_id: UserId,
+        asset: Asset,
+        amount: Decimal,
+    ) -> Result<&mut BalanceEntry> {
+        let key = (user_id, asset);
         self.balances
-            .iter()
-            .filter(|((_, a), _)| a == asset)
-            .map(|(_, entry)| entry.total())
-            .sum()
+            .get_mut(&key)
+            .filter(|entry| entry.withdraw_pending >= amount)
+            .ok_or_else(|| OpenmatchError::BalanceUnderflow {
+                user_id,
+                asset: key.1.clone(),
+            })
     }
 }
 
@@ -134,15 +311,95 @@ impl Default for BalanceManager {
     }
 }
 
+impl BalanceLedger for BalanceManager {
+    fn balance_of(&self, user_id: UserId, asset: &Asset) -> BalanceEntry {
+        self.balance(user_id, asset)
+    }
+
+    fn freeze_funds(&mut self, user_id: UserId, asset: &Asset, amount: Decimal) -> Result<()> {
+        self.freeze(user_id, asset, amount)
+    }
+
+    fn unfreeze_funds(&mut self, user_id: UserId, asset: &Asset, amount: Decimal) -> Result<()> {
+        self.unfreeze(user_id, asset, amount)
+    }
+
+    fn consume_frozen_funds(
+        &mut self,
+        user_id: UserId,
+        asset: &Asset,
+        amount: Decimal,
+    ) -> Result<()> {
+        self.consume_frozen(user_id, asset, amount)
+    }
+
+    fn credit_funds(&mut self, user_id: UserId, asset: &Asset, amount: Decimal) {
+        self.credit(user_id, asset, amount);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn invalid_asset_symbol_is_an_error_not_a_panic() {
+        let mut bm = BalanceManager::new();
+        let user = UserId::new();
+        bm.deposit(user, Asset::lit("USDT"), Decimal::new(100, 0));
+
+        let err = bm.freeze(user, "usdt", Decimal::ONE).unwrap_err();
+        assert!(matches!(err, OpenmatchError::Configuration(_)));
+        assert_eq!(bm.balance(user, "usdt"), BalanceEntry::default());
+        assert_eq!(bm.balance(user, "USDT").available, Decimal::new(100, 0));
+    }
+
+    #[test]
+    fn freeze_up_to_below_available_freezes_desired() {
+        let mut bm = BalanceManager::new();
+        let user = UserId::new();
+        bm.deposit(user, Asset::lit("USDT"), Decimal::new(1000, 0));
+
+        let frozen = bm.freeze_up_to(user, "USDT", Decimal::new(300, 0)).unwrap();
+        assert_eq!(frozen, Decimal::new(300, 0));
+        let bal = bm.balance(user, "USDT");
+        assert_eq!(bal.available, Decimal::new(700, 0));
+        assert_eq!(bal.frozen, Decimal::new(300, 0));
+    }
+
+    #[test]
+    fn freeze_up_to_above_available_freezes_all() {
+        let mut bm = BalanceManager::new();
+        let user = UserId::new();
+        bm.deposit(user, Asset::lit("USDT"), Decimal::new(250, 0));
+
+        let frozen = bm
+            .freeze_up_to(user, "USDT", Decimal::new(1000, 0))
+            .unwrap();
+        assert_eq!(frozen, Decimal::new(250, 0));
+        let bal = bm.balance(user, "USDT");
+        assert_eq!(bal.available, Decimal::ZERO);
+        assert_eq!(bal.frozen, Decimal::new(250, 0));
+
+        // Nothing left: now it errors
+        let err = bm.freeze_up_to(user, "USDT", Decimal::ONE).unwrap_err();
+        assert!(matches!(err, OpenmatchError::InsufficientBalance { .. }));
+    }
+
+    #[test]
+    fn freeze_up_to_unknown_account_errors() {
+        let mut bm = BalanceManager::new();
+        let err = bm
+            .freeze_up_to(UserId::new(), "USDT", Decimal::ONE)
+            .unwrap_err();
+        assert!(matches!(err, OpenmatchError::InsufficientBalance { .. }));
+    }
+
     #[test]
     fn deposit_increases_available() {
         let mut bm = BalanceManager::new();
         let user = UserId::new();
-        bm.deposit(user, "USDT", Decimal::new(1000, 0));
+        bm.deposit(user, Asset::lit("USDT"), Decimal::new(1000, 0));
         let bal = bm.balance(user, "USDT");
         assert_eq!(bal.available, Decimal::new(1000, 0));
         assert_eq!(bal.frozen, Decimal::ZERO);
@@ -152,7 +409,7 @@ mod tests {
     fn freeze_moves_to_frozen() {
         let mut bm = BalanceManager::new();
         let user = UserId::new();
-        bm.deposit(user, "USDT", Decimal::new(1000, 0));
+        bm.deposit(user, Asset::lit("USDT"), Decimal::new(1000, 0));
         bm.freeze(user, "USDT", Decimal::new(400, 0)).unwrap();
         let bal = bm.balance(user, "USDT");
         assert_eq!(bal.available, Decimal::new(600, 0));
@@ -163,7 +420,7 @@ mod tests {
     fn freeze_insufficient_fails() {
         let mut bm = BalanceManager::new();
         let user = UserId::new();
-        bm.deposit(user, "USDT", Decimal::new(100, 0));
+        bm.deposit(user, Asset::lit("USDT"), Decimal::new(100, 0));
         let err = bm.freeze(user, "USDT", Decimal::new(200, 0)).unwrap_err();
         assert!(matches!(err, OpenmatchError::InsufficientBalance { .. }));
         // Balance unchanged
@@ -171,11 +428,41 @@ mod tests {
         assert_eq!(bal.available, Decimal::new(100, 0));
     }
 
+    #[test]
+    fn freeze_with_reserve_stops_at_reserve() {
+        let mut bm = BalanceManager::new();
+        let user = UserId::new();
+        bm.deposit(user, Asset::lit("USDT"), Decimal::new(100, 0));
+
+        let err = bm
+            .freeze_with_reserve(user, "USDT", Decimal::new(91, 0), Decimal::new(10, 0))
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            OpenmatchError::RiskRejected(RiskRejectionReason::ReserveViolation { available_after, .. })
+                if available_after == Decimal::new(9, 0)
+        ));
+        assert_eq!(bm.balance(user, "USDT").available, Decimal::new(100, 0));
+
+        // Down to exactly the reserve is fine
+        bm.freeze_with_reserve(user, "USDT", Decimal::new(90, 0), Decimal::new(10, 0))
+            .unwrap();
+        let bal = bm.balance(user, "USDT");
+        assert_eq!(bal.available, Decimal::new(10, 0));
+        assert_eq!(bal.frozen, Decimal::new(90, 0));
+
+        // Overdrawing is still an insufficient balance, not a reserve breach
+        let err = bm
+            .freeze_with_reserve(user, "USDT", Decimal::new(20, 0), Decimal::ZERO)
+            .unwrap_err();
+        assert!(matches!(err, OpenmatchError::InsufficientBalance { .. }));
+    }
+
     #[test]
     fn unfreeze_restores_available() {
         let mut bm = BalanceManager::new();
         let user = UserId::new();
-        bm.deposit(user, "USDT", Decimal::new(1000, 0));
+        bm.deposit(user, Asset::lit("USDT"), Decimal::new(1000, 0));
         bm.freeze(user, "USDT", Decimal::new(400, 0)).unwrap();
         bm.unfreeze(user, "USDT", Decimal::new(400, 0)).unwrap();
         let bal = bm.balance(user, "USDT");
@@ -187,7 +474,7 @@ mod tests {
     fn consume_frozen_reduces_frozen() {
         let mut bm = BalanceManager::new();
         let user = UserId::new();
-        bm.deposit(user, "USDT", Decimal::new(1000, 0));
+        bm.deposit(user, Asset::lit("USDT"), Decimal::new(1000, 0));
         bm.freeze(user, "USDT", Decimal::new(500, 0)).unwrap();
         bm.consume_frozen(user, "USDT", Decimal::new(500, 0))
             .unwrap();
@@ -200,7 +487,7 @@ mod tests {
     fn credit_adds_to_available() {
         let mut bm = BalanceManager::new();
         let user = UserId::new();
-        bm.credit(user, "BTC", Decimal::ONE);
+        bm.credit(user, Asset::lit("BTC"), Decimal::ONE);
         let bal = bm.balance(user, "BTC");
         assert_eq!(bal.available, Decimal::ONE);
     }
@@ -210,10 +497,21 @@ mod tests {
         let mut bm = BalanceManager::new();
         let u1 = UserId::new();
         let u2 = UserId::new();
-        bm.deposit(u1, "USDT", Decimal::new(1000, 0));
-        bm.deposit(u2, "USDT", Decimal::new(500, 0));
+        bm.deposit(u1, Asset::lit("USDT"), Decimal::new(1000, 0));
+        bm.deposit(u2, Asset::lit("USDT"), Decimal::new(500, 0));
         bm.freeze(u1, "USDT", Decimal::new(300, 0)).unwrap();
-        assert_eq!(bm.total_supply("USDT"), Decimal::new(1500, 0));
+        assert_eq!(bm.total_supply("USDT").unwrap(), Decimal::new(1500, 0));
+    }
+
+    #[test]
+    fn total_supply_overflow_is_an_error_not_a_panic() {
+        let mut bm = BalanceManager::new();
+        bm.deposit(UserId::new(), Asset::lit("USDT"), Decimal::MAX);
+        bm.deposit(UserId::new(), Asset::lit("USDT"), Decimal::MAX);
+        assert!(matches!(
+            bm.total_supply("USDT"),
+            Err(OpenmatchError::SupplyInvariantViolation { .. })
+        ));
     }
 
     #[test]
@@ -222,4 +520,72 @@ mod tests {
         let bal = bm.balance(UserId::new(), "BTC");
         assert!(bal.is_zero());
     }
+
+    #[test]
+    fn reserve_then_confirm_reduces_supply() {
+        let mut bm = BalanceManager::new();
+        let user = UserId::new();
+        bm.deposit(user, Asset::lit("BTC"), Decimal::new(5, 0));
+
+        bm.reserve_for_withdrawal(user, "BTC", Decimal::TWO)
+            .unwrap();
+        let bal = bm.balance(user, "BTC");
+        assert_eq!(bal.available, Decimal::new(3, 0));
+        assert_eq!(bal.withdraw_pending, Decimal::TWO);
+        // Still in supply while custody hasn't confirmed
+        assert_eq!(bm.total_supply("BTC").unwrap(), Decimal::new(5, 0));
+
+        bm.confirm_withdrawal(user, "BTC", Decimal::TWO).unwrap();
+        assert_eq!(bm.balance(user, "BTC").withdraw_pending, Decimal::ZERO);
+        assert_eq!(bm.total_supply("BTC").unwrap(), Decimal::new(3, 0));
+
+        // Nothing left pending to confirm
+        let err = bm
+            .confirm_withdrawal(user, "BTC", Decimal::ONE)
+            .unwrap_err();
+        assert!(matches!(err, OpenmatchError::BalanceUnderflow { .. }));
+    }
+
+    #[test]
+    fn reserve_then_cancel_restores_available() {
+        let mut bm = BalanceManager::new();
+        let user = UserId::new();
+        bm.deposit(user, Asset::lit("USDT"), Decimal::new(100, 0));
+
+        let err = bm
+            .reserve_for_withdrawal(user, "USDT", Decimal::new(101, 0))
+            .unwrap_err();
+        assert!(matches!(err, OpenmatchError::InsufficientBalance { .. }));
+
+        bm.reserve_for_withdrawal(user, "USDT", Decimal::new(60, 0))
+            .unwrap();
+        bm.cancel_withdrawal(user, "USDT", Decimal::new(60, 0))
+            .unwrap();
+        let bal = bm.balance(user, "USDT");
+        assert_eq!(bal.available, Decimal::new(100, 0));
+        assert_eq!(bal.withdraw_pending, Decimal::ZERO);
+        assert_eq!(bm.total_supply("USDT").unwrap(), Decimal::new(100, 0));
+    }
+
+    #[test]
+    fn gc_reclaims_emptied_entries() {
+        let mut bm = BalanceManager::new();
+        let (churny, holder) = (UserId::new(), UserId::new());
+        bm.deposit(churny, Asset::lit("USDT"), Decimal::new(100, 0));
+        bm.deposit(holder, Asset::lit("USDT"), Decimal::new(100, 0));
+        bm.freeze(churny, "USDT", Decimal::new(100, 0)).unwrap();
+        bm.consume_frozen(churny, "USDT", Decimal::new(100, 0))
+            .unwrap();
+        assert_eq!(bm.entry_count(), 2);
+
+        assert_eq!(bm.gc(), 1);
+        assert_eq!(bm.entry_count(), 1);
+        assert!(bm.balance(churny, "USDT").is_zero());
+        assert_eq!(bm.total_supply("USDT").unwrap(), Decimal::new(100, 0));
+        assert_eq!(bm.gc(), 0);
+
+        // The account works again after collection
+        bm.deposit(churny, Asset::lit("USDT"), Decimal::ONE);
+        assert_eq!(bm.balance(churny, "USDT").available, Decimal::ONE);
+    }
 }
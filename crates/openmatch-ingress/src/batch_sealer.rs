@@ -65,10 +65,13 @@ impl BatchSealer {
     /// This hash commits to:
     /// - Epoch ID
     /// - Number of orders
-    /// - Each order's ID, user_id, side, type, price, quantity, sequence
+    /// - Each order's ID, user_id, side, type (with a cancel's target),
+    ///   price, quantity, sequence, expiry
+    ///
+    /// Decimals are normalized so `100` and `100.00` hash the same.
     fn compute_batch_hash(epoch_id: EpochId, orders: &[Order]) -> [u8; 32] {
         let mut hasher = Sha256::new();
-        hasher.update(b"openmatch:batch:v2:");
+        hasher.update(b"openmatch:batch:v5:");
         hasher.update(epoch_id.0.to_le_bytes());
         hasher.update((orders.len() as u64).to_le_bytes());
 
@@ -80,16 +83,30 @@ impl BatchSealer {
                 openmatch_types::OrderSide::Buy => &[0u8],
                 openmatch_types::OrderSide::Sell => &[1u8],
             });
-            hasher.update(match order.order_type {
-                openmatch_types::OrderType::Limit => &[0u8],
-                openmatch_types::OrderType::Market => &[1u8],
-                openmatch_types::OrderType::Cancel => &[2u8],
-            });
+            match order.order_type {
+                openmatch_types::OrderType::Limit { all_or_none: false } => hasher.update([0u8]),
+                openmatch_types::OrderType::Market => hasher.update([1u8]),
+                openmatch_types::OrderType::Limit { all_or_none: true } => hasher.update([3u8]),
+                openmatch_types::OrderType::Cancel { target } => {
+                    hasher.update([2u8]);
+                    hasher.update(target.0.as_bytes());
+                }
+            }
             if let Some(price) = &order.price {
-                hasher.update(price.to_string().as_bytes());
+                hasher.update(price.normalize().to_string().as_bytes());
             }
-            hasher.update(order.quantity.to_string().as_bytes());
+            hasher.update(b"\0");
+            hasher.update(order.quantity.normalize().to_string().as_bytes());
+            hasher.update(b"\0");
             hasher.update(order.sequence.to_le_bytes());
+            match order.expires_at {
+                None => hasher.update([0u8]),
+                Some(at) => {
+                    hasher.update([1u8]);
+                    hasher.update(at.timestamp().to_le_bytes());
+                    hasher.update(at.timestamp_subsec_nanos().to_le_bytes());
+                }
+            }
         }
 
         let result = hasher.finalize();
@@ -141,6 +158,23 @@ mod tests {
         assert_eq!(batch.orders[0].sequence, 0);
         assert_eq!(batch.orders[1].sequence, 1);
         assert_eq!(batch.orders[2].sequence, 2);
+        batch.verify_canonical_order().unwrap();
+    }
+
+    #[test]
+    fn equal_sequences_tie_break_on_order_id() {
+        let sealer = make_sealer();
+        let a = Order::dummy_limit(OrderSide::Buy, Decimal::new(100, 0), Decimal::ONE);
+        let b = Order::dummy_limit(OrderSide::Sell, Decimal::new(101, 0), Decimal::ONE);
+        let low = a.id.min(b.id);
+
+        let forward = sealer.seal(EpochId(1), vec![a.clone(), b.clone()]);
+        let reversed = sealer.seal(EpochId(1), vec![b, a]);
+
+        assert_eq!(forward.orders[0].id, low);
+        assert_eq!(reversed.orders[0].id, low);
+        assert_eq!(forward.batch_hash, reversed.batch_hash);
+        forward.verify_canonical_order().unwrap();
     }
 
     #[test]
@@ -157,6 +191,24 @@ mod tests {
         assert_eq!(batch1.batch_hash, batch2.batch_hash);
     }
 
+    #[test]
+    fn expiry_changes_batch_hash() {
+        let sealer = make_sealer();
+        let order = Order::dummy_limit(OrderSide::Buy, Decimal::new(100, 0), Decimal::ONE);
+        let mut expiring = order.clone();
+        expiring.expires_at = Some(chrono::DateTime::UNIX_EPOCH);
+
+        let plain = sealer.seal(EpochId(1), vec![order]);
+        let gtd = sealer.seal(EpochId(1), vec![expiring.clone()]);
+        assert_ne!(plain.batch_hash, gtd.batch_hash);
+
+        expiring.expires_at = Some(chrono::DateTime::UNIX_EPOCH + chrono::Duration::nanoseconds(1));
+        assert_ne!(
+            gtd.batch_hash,
+            sealer.seal(EpochId(1), vec![expiring]).batch_hash
+        );
+    }
+
     #[test]
     fn different_epochs_different_hash() {
         let sealer = make_sealer();
@@ -211,4 +263,17 @@ mod tests {
         assert_eq!(digest.batch_hash, batch.batch_hash);
         assert_eq!(digest.order_count, 2);
     }
+
+    #[test]
+    fn decimal_scale_does_not_change_hash() {
+        let sealer = make_sealer();
+        let order = Order::dummy_limit(OrderSide::Buy, Decimal::new(100, 0), Decimal::ONE);
+        let mut rescaled = order.clone();
+        rescaled.price = Some(Decimal::new(10000, 2));
+        rescaled.quantity = Decimal::new(1000, 3);
+
+        let batch1 = sealer.seal(EpochId(1), vec![order]);
+        let batch2 = sealer.seal(EpochId(1), vec![rescaled]);
+        assert_eq!(batch1.batch_hash, batch2.batch_hash);
+    }
 }
@@ -3,30 +3,99 @@
 //! The EscrowManager atomically freezes funds and mints a SpendRight.
 //! When an order is cancelled or a SR expires, it releases the funds
 //! by unfreezing them and marking the SR as RELEASED.
+//!
+//! An SR minted for an order ([`EscrowManager::mint_for_order`]) expires
+//! with the order, so the stale sweep never unfreezes an order still
+//! resting on the book. Bare [`EscrowManager::mint`] uses the epoch-driven
+//! [`ExpiryPolicy`].
+//!
+//! The SR set can be exported to an [`EscrowSnapshot`] and imported after
+//! a restart, so frozen balances can be reconciled against it.
 
 use std::{
-    collections::HashMap,
-    sync::atomic::{AtomicU64, Ordering},
+    collections::{HashMap, HashSet},
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
 };
 
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use openmatch_types::{
-    EpochId, NodeId, OpenmatchError, OrderId, Result, SpendRight, SpendRightId, SpendRightState,
-    UserId,
+    Clock, EpochConfig, EpochId, IntoAsset, NodeId, OpenmatchError, Order, OrderId, Result,
+    SpendRight, SpendRightId, SpendRightLedger, SpendRightState, SystemClock, UserId,
 };
 use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
 
 use crate::balance_manager::BalanceManager;
 
 /// Monotonic nonce counter for SpendRight minting.
 static NONCE_COUNTER: AtomicU64 = AtomicU64::new(0);
 
+/// How long a `SpendRight` minted by [`EscrowManager::mint`] stays valid.
+///
+/// Such an SR only needs to outlive the epoch it funds: it is sealed,
+/// matched and settled within one epoch cycle. Minting during COLLECT and
+/// expiring one full cycle later (plus `grace`) covers the minting epoch
+/// regardless of where in COLLECT it arrived. Orders that may rest on the
+/// book across epochs go through [`EscrowManager::mint_for_order`], which
+/// ignores this policy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExpiryPolicy {
+    /// Length of one epoch cycle (all four phases).
+    pub epoch_duration: Duration,
+    /// Extra slack after the epoch ends before the SR is considered stale.
+    pub grace: Duration,
+}
+
+impl ExpiryPolicy {
+    /// Derive the policy from epoch timing: one full cycle plus the seal grace.
+    #[must_use]
+    pub fn from_epoch_config(config: &EpochConfig) -> Self {
+        Self {
+            epoch_duration: config.total_duration(),
+            grace: config.seal_grace,
+        }
+    }
+
+    /// Total lifetime of a minted SR.
+    #[must_use]
+    pub fn ttl(&self) -> Duration {
+        self.epoch_duration + self.grace
+    }
+}
+
+impl Default for ExpiryPolicy {
+    fn default() -> Self {
+        Self::from_epoch_config(&EpochConfig::default())
+    }
+}
+
+/// Serializable state of an [`EscrowManager`] for crash recovery.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EscrowSnapshot {
+    /// Every tracked `SpendRight` in any state, in minting (nonce) order.
+    pub spend_rights: Vec<SpendRight>,
+    /// The next nonce to be issued at export time.
+    pub next_nonce: u64,
+}
+
 /// Manages the SpendRight lifecycle: minting, releasing, and lookup.
 pub struct EscrowManager {
     /// All SpendRights indexed by their ID.
     spend_rights: HashMap<SpendRightId, SpendRight>,
+    /// Reverse index: the `SpendRight` funding each order.
+    by_order: HashMap<OrderId, SpendRightId>,
+    /// ACTIVE `SpendRight`s per user. Entries leave on release or spend.
+    active_by_user: HashMap<UserId, HashSet<SpendRightId>>,
     /// The node identity for signing SRs.
     node_id: NodeId,
+    /// Lifetime of newly minted SRs.
+    expiry: ExpiryPolicy,
+    /// Time source for minting timestamps and expiry checks.
+    clock: Arc<dyn Clock>,
 }
 
 impl EscrowManager {
@@ -35,10 +104,34 @@ impl EscrowManager {
     pub fn new(node_id: NodeId) -> Self {
         Self {
             spend_rights: HashMap::new(),
+            by_order: HashMap::new(),
+            active_by_user: HashMap::new(),
             node_id,
+            expiry: ExpiryPolicy::default(),
+            clock: SystemClock::shared(),
         }
     }
 
+    /// Use the given expiry policy for SRs minted from now on.
+    #[must_use]
+    pub fn with_expiry_policy(mut self, policy: ExpiryPolicy) -> Self {
+        self.expiry = policy;
+        self
+    }
+
+    /// Read time from `clock` when minting and checking expiry.
+    #[must_use]
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// The expiry policy applied by [`EscrowManager::mint`].
+    #[must_use]
+    pub fn expiry_policy(&self) -> ExpiryPolicy {
+        self.expiry
+    }
+
     /// Atomically freeze funds and mint a SpendRight.
     ///
     /// 1. Freeze `amount` of `asset` from the user's balance
@@ -48,43 +141,127 @@ impl EscrowManager {
     /// If the freeze fails (insufficient balance), no SR is minted.
     ///
     /// # Errors
-    /// Returns `InsufficientBalance` if the user doesn't have enough funds.
+    /// - `InvalidOrder` if `amount` is not positive or is `Decimal::MAX`
+    ///   (a market-order price sentinel must never reach the ledger)
+    /// - `InvalidSpendRight` if `order_id` already has an ACTIVE SR
+    ///   (minting again would freeze the order's funds twice)
+    /// - `InsufficientBalance` if the user doesn't have enough funds
     pub fn mint(
         &mut self,
         balance_manager: &mut BalanceManager,
         order_id: OrderId,
         user_id: UserId,
-        asset: &str,
+        asset: impl IntoAsset,
+        amount: Decimal,
+        epoch_id: EpochId,
+    ) -> Result<SpendRightId> {
+        let ttl = chrono::Duration::from_std(self.expiry.ttl()).unwrap_or(chrono::Duration::MAX);
+        self.mint_until(
+            balance_manager,
+            order_id,
+            user_id,
+            asset,
+            amount,
+            epoch_id,
+            |now| {
+                now.checked_add_signed(ttl)
+                    .unwrap_or(DateTime::<Utc>::MAX_UTC)
+            },
+        )
+    }
+
+    /// [`EscrowManager::mint`] with the expiry computed from the mint time.
+    #[allow(clippy::too_many_arguments)]
+    fn mint_until(
+        &mut self,
+        balance_manager: &mut BalanceManager,
+        order_id: OrderId,
+        user_id: UserId,
+        asset: impl IntoAsset,
         amount: Decimal,
         epoch_id: EpochId,
+        expires_at: impl FnOnce(DateTime<Utc>) -> DateTime<Utc>,
     ) -> Result<SpendRightId> {
+        let asset = asset.into_asset()?;
+        if amount <= Decimal::ZERO || amount == Decimal::MAX {
+            return Err(OpenmatchError::InvalidOrder {
+                reason: format!("escrow amount {amount} is not a finite positive amount"),
+            });
+        }
+        if let Some(existing) = self
+            .sr_for_order(&order_id)
+            .filter(|sr_id| self.spend_rights[sr_id].state == SpendRightState::Active)
+        {
+            return Err(OpenmatchError::InvalidSpendRight {
+                reason: format!("order {order_id} is already funded by ACTIVE {existing}"),
+            });
+        }
+
         // Step 1: Freeze funds (atomic — if this fails, nothing changes)
-        balance_manager.freeze(user_id, asset, amount)?;
+        balance_manager.freeze(user_id, &asset, amount)?;
 
         // Step 2: Create the SpendRight
         let sr_id = SpendRightId::new();
-        let now = Utc::now();
+        let now = self.clock.now();
         let sr = SpendRight {
             id: sr_id,
             order_id,
             user_id,
-            asset: asset.to_string(),
+            asset,
             amount,
+            consumed: Decimal::ZERO,
             issuer_node: self.node_id,
             state: SpendRightState::Active,
             signature: vec![0u8; 64], // Placeholder — real impl uses ed25519
             nonce: NONCE_COUNTER.fetch_add(1, Ordering::Relaxed),
             epoch_id,
             created_at: now,
-            expires_at: now + chrono::Duration::hours(1),
+            expires_at: expires_at(now),
         };
 
         // Step 3: Store and return
         self.spend_rights.insert(sr_id, sr);
+        self.by_order.insert(order_id, sr_id);
+        self.active_by_user
+            .entry(user_id)
+            .or_default()
+            .insert(sr_id);
         Ok(sr_id)
     }
 
-    /// Release a SpendRight (cancel or expire). Unfreezes the funds.
+    /// Mint the `SpendRight` funding `order`, sized by
+    /// [`Order::escrow_requirement`]. Market buys escrow at
+    /// `reference_price` (e.g. the last clearing price).
+    ///
+    /// The SR lives as long as the order may rest on the book: it expires
+    /// at the order's `expires_at`, and an order without one funds an SR
+    /// that never expires — it is released when the order is cancelled or
+    /// spent when it fills. The [`ExpiryPolicy`] does not apply.
+    ///
+    /// # Errors
+    /// Any error from [`Order::escrow_requirement`] or
+    /// [`EscrowManager::mint`].
+    pub fn mint_for_order(
+        &mut self,
+        balance_manager: &mut BalanceManager,
+        order: &Order,
+        reference_price: Option<Decimal>,
+        epoch_id: EpochId,
+    ) -> Result<SpendRightId> {
+        let (asset, amount) = order.escrow_requirement(reference_price)?;
+        self.mint_until(
+            balance_manager,
+            order.id,
+            order.user_id,
+            asset,
+            amount,
+            epoch_id,
+            |_| order.expires_at.unwrap_or(DateTime::<Utc>::MAX_UTC),
+        )
+    }
+
+    /// Release a SpendRight (cancel or expire). Unfreezes the funds fills
+    /// haven't consumed.
     ///
     /// # Errors
     /// - `InvalidSpendRight` if the SR doesn't exist or isn't ACTIVE
@@ -107,14 +284,56 @@ impl EscrowManager {
             });
         }
 
-        // Unfreeze the funds
-        balance_manager.unfreeze(sr.user_id, &sr.asset, sr.amount)?;
+        // Unfreeze whatever settled fills haven't paid out
+        balance_manager.unfreeze(sr.user_id, &sr.asset, sr.remaining())?;
 
         // Mark SR as released
         sr.mark_released()?;
+        let user_id = sr.user_id;
+        self.deactivate(user_id, sr_id);
         Ok(())
     }
 
+    /// Release the `SpendRight` funding an order (order cancelled).
+    ///
+    /// # Errors
+    /// - `OrderNotFound` if no `SpendRight` was minted for the order
+    /// - `InvalidSpendRight` if the SR isn't ACTIVE
+    /// - `InsufficientFrozen` if the unfreeze fails
+    pub fn release_by_order(
+        &mut self,
+        balance_manager: &mut BalanceManager,
+        order_id: OrderId,
+    ) -> Result<()> {
+        let sr_id = self
+            .sr_for_order(&order_id)
+            .ok_or(OpenmatchError::OrderNotFound(order_id))?;
+        self.release(balance_manager, sr_id)
+    }
+
+    /// Release every ACTIVE `SpendRight` whose expiry has passed.
+    ///
+    /// Returns the released SR IDs in sorted order. SRs whose unfreeze
+    /// fails are left ACTIVE so the discrepancy stays visible.
+    pub fn release_expired(&mut self, balance_manager: &mut BalanceManager) -> Vec<SpendRightId> {
+        let now = self.clock.now();
+        let mut expired: Vec<SpendRightId> = self
+            .spend_rights
+            .values()
+            .filter(|sr| sr.state == SpendRightState::Active && sr.is_expired_at(now))
+            .map(|sr| sr.id)
+            .collect();
+        expired.sort();
+        expired.retain(|sr_id| self.release(balance_manager, *sr_id).is_ok());
+        expired
+    }
+
+    /// Look up the `SpendRightId` minted for an order.
+    #[must_use]
+    pub fn sr_for_order(&self, order_id: &OrderId) -> Option<SpendRightId> {
+        self.by_order.get(order_id).copied()
+    }
+
     /// Mark a SpendRight as SPENT (called during settlement).
     ///
     /// Note: This does NOT unfreeze funds — the settlement engine
@@ -130,7 +349,10 @@ impl EscrowManager {
                     reason: format!("SpendRight {sr_id} not found"),
                 })?;
 
-        sr.mark_spent()
+        sr.mark_spent()?;
+        let user_id = sr.user_id;
+        self.deactivate(user_id, sr_id);
+        Ok(())
     }
 
     /// Look up a SpendRight by ID.
@@ -142,9 +364,25 @@ impl EscrowManager {
     /// Check if a SpendRight is currently active.
     #[must_use]
     pub fn is_active(&self, sr_id: &SpendRightId) -> bool {
+        let now = self.clock.now();
         self.spend_rights
             .get(sr_id)
-            .is_some_and(SpendRight::is_active)
+            .is_some_and(|sr| sr.is_active_at(now))
+    }
+
+    /// A user's ACTIVE `SpendRight`s (funds locked by open orders), in
+    /// minting order.
+    #[must_use]
+    pub fn active_for_user(&self, user_id: &UserId) -> Vec<&SpendRight> {
+        let mut active: Vec<&SpendRight> = self
+            .active_by_user
+            .get(user_id)
+            .into_iter()
+            .flatten()
+            .filter_map(|sr_id| self.spend_rights.get(sr_id))
+            .collect();
+        active.sort_by_key(|sr| (sr.nonce, sr.id));
+        active
     }
 
     /// Number of SpendRights tracked.
@@ -167,10 +405,98 @@ impl EscrowManager {
     pub fn node_id(&self) -> NodeId {
         self.node_id
     }
+
+    /// Export every `SpendRight` and the nonce counter position.
+    #[must_use]
+    pub fn export(&self) -> EscrowSnapshot {
+        let mut spend_rights: Vec<SpendRight> = self.spend_rights.values().cloned().collect();
+        spend_rights.sort_by_key(|sr| (sr.nonce, sr.id));
+        EscrowSnapshot {
+            spend_rights,
+            next_nonce: NONCE_COUNTER.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Rebuild an escrow manager from a snapshot, with the default expiry
+    /// policy.
+    ///
+    /// SRs keep their states. The nonce counter is advanced to at least
+    /// the exported position, so no nonce is ever reissued. When several
+    /// SRs fund one order, the last minted is the one found by
+    /// [`EscrowManager::sr_for_order`], as before the export.
+    #[must_use]
+    pub fn import(snapshot: EscrowSnapshot, node_id: NodeId) -> Self {
+        let mut manager = Self::new(node_id);
+        let mut spend_rights = snapshot.spend_rights;
+        spend_rights.sort_by_key(|sr| (sr.nonce, sr.id));
+        let mut next_nonce = snapshot.next_nonce;
+        for sr in spend_rights {
+            next_nonce = next_nonce.max(sr.nonce.saturating_add(1));
+            manager.by_order.insert(sr.order_id, sr.id);
+            if sr.state == SpendRightState::Active {
+                manager
+                    .active_by_user
+                    .entry(sr.user_id)
+                    .or_default()
+                    .insert(sr.id);
+            }
+            manager.spend_rights.insert(sr.id, sr);
+        }
+        NONCE_COUNTER.fetch_max(next_nonce, Ordering::Relaxed);
+        manager
+    }
+
+    /// Drop an SR that just left the ACTIVE state from the user index.
+    fn deactivate(&mut self, user_id: UserId, sr_id: SpendRightId) {
+        if let Some(ids) = self.active_by_user.get_mut(&user_id) {
+            ids.remove(&sr_id);
+            if ids.is_empty() {
+                self.active_by_user.remove(&user_id);
+            }
+        }
+    }
+}
+
+impl SpendRightLedger for EscrowManager {
+    fn spend_right_for_order(&self, order_id: &OrderId) -> Option<&SpendRight> {
+        self.sr_for_order(order_id)
+            .and_then(|sr_id| self.spend_rights.get(&sr_id))
+    }
+
+    fn is_active(&self, sr_id: &SpendRightId) -> bool {
+        Self::is_active(self, sr_id)
+    }
+
+    fn consume(&mut self, sr_id: SpendRightId, amount: Decimal) -> Result<()> {
+        let sr = self
+            .spend_rights
+            .get_mut(&sr_id)
+            .filter(|sr| sr.state == SpendRightState::Active)
+            .ok_or_else(|| OpenmatchError::InvalidSpendRight {
+                reason: format!("SpendRight {sr_id} is not ACTIVE"),
+            })?;
+        if amount > sr.remaining() {
+            return Err(OpenmatchError::InvalidSpendRight {
+                reason: format!(
+                    "SpendRight {sr_id} has {} remaining, fills need {amount}",
+                    sr.remaining()
+                ),
+            });
+        }
+        sr.consumed += amount;
+        Ok(())
+    }
+
+    fn mark_spent(&mut self, sr_id: SpendRightId) -> Result<()> {
+        Self::mark_spent(self, sr_id)
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use chrono::DateTime;
+    use openmatch_types::{Asset, FixedClock, OrderSide, OrderType};
+
     use super::*;
 
     fn setup() -> (EscrowManager, BalanceManager) {
@@ -179,11 +505,184 @@ mod tests {
         (em, bm)
     }
 
+    #[test]
+    fn default_expiry_follows_epoch_config() {
+        let (mut em, mut bm) = setup();
+        let user = UserId::new();
+        bm.deposit(user, Asset::lit("USDT"), Decimal::new(100, 0));
+        let sr_id = em
+            .mint(
+                &mut bm,
+                OrderId::new(),
+                user,
+                "USDT",
+                Decimal::ONE,
+                EpochId(1),
+            )
+            .unwrap();
+
+        let sr = em.get(&sr_id).unwrap();
+        let ttl = (sr.expires_at - sr.created_at).to_std().unwrap();
+        assert_eq!(ttl, ExpiryPolicy::default().ttl());
+        assert!(ttl < Duration::from_secs(60));
+    }
+
+    #[test]
+    fn fixed_clock_makes_timestamps_deterministic() {
+        let t0 = DateTime::UNIX_EPOCH + chrono::Duration::days(20_000);
+        let clock = FixedClock::new(t0);
+        let (em, mut bm) = setup();
+        let mut em = em.with_clock(Arc::new(clock.clone()));
+        let user = UserId::new();
+        bm.deposit(user, Asset::lit("USDT"), Decimal::new(100, 0));
+        let sr_id = em
+            .mint(
+                &mut bm,
+                OrderId::new(),
+                user,
+                "USDT",
+                Decimal::new(40, 0),
+                EpochId(1),
+            )
+            .unwrap();
+
+        let ttl = chrono::Duration::from_std(ExpiryPolicy::default().ttl()).unwrap();
+        let sr = em.get(&sr_id).unwrap();
+        assert_eq!(sr.created_at, t0);
+        assert_eq!(sr.expires_at, t0 + ttl);
+
+        // Exactly at expiry the SR is still live; one tick later it is swept
+        clock.set(t0 + ttl);
+        assert!(em.is_active(&sr_id));
+        assert!(em.release_expired(&mut bm).is_empty());
+        clock.advance(chrono::Duration::nanoseconds(1));
+        assert!(!em.is_active(&sr_id));
+        assert_eq!(em.release_expired(&mut bm), vec![sr_id]);
+        assert_eq!(bm.balance(user, "USDT").available, Decimal::new(100, 0));
+    }
+
+    #[test]
+    fn short_expiry_is_swept() {
+        let clock = FixedClock::new(DateTime::UNIX_EPOCH + chrono::Duration::days(20_000));
+        let (em, mut bm) = setup();
+        let mut em = em
+            .with_clock(Arc::new(clock.clone()))
+            .with_expiry_policy(ExpiryPolicy {
+                epoch_duration: Duration::ZERO,
+                grace: Duration::from_millis(1),
+            });
+        let user = UserId::new();
+        bm.deposit(user, Asset::lit("USDT"), Decimal::new(100, 0));
+        let sr_id = em
+            .mint(
+                &mut bm,
+                OrderId::new(),
+                user,
+                "USDT",
+                Decimal::new(40, 0),
+                EpochId(1),
+            )
+            .unwrap();
+
+        assert!(em.release_expired(&mut bm).is_empty());
+        clock.advance(chrono::Duration::milliseconds(2));
+        assert!(em.get(&sr_id).unwrap().is_expired_at(clock.now()));
+        assert!(!em.is_active(&sr_id));
+
+        assert_eq!(em.release_expired(&mut bm), vec![sr_id]);
+        assert_eq!(em.get(&sr_id).unwrap().state, SpendRightState::Released);
+        let bal = bm.balance(user, "USDT");
+        assert_eq!(bal.available, Decimal::new(100, 0));
+        assert_eq!(bal.frozen, Decimal::ZERO);
+        // Second sweep finds nothing
+        assert!(em.release_expired(&mut bm).is_empty());
+    }
+
+    #[test]
+    fn resting_order_outlives_expiry_policy() {
+        let t0 = DateTime::UNIX_EPOCH + chrono::Duration::days(20_000);
+        let clock = FixedClock::new(t0);
+        let (em, mut bm) = setup();
+        let mut em = em.with_clock(Arc::new(clock.clone()));
+        let mut order = Order::dummy_limit(OrderSide::Buy, Decimal::new(10, 0), Decimal::ONE);
+        bm.deposit(order.user_id, Asset::lit("USDT"), Decimal::new(100, 0));
+        order.sr_id = em
+            .mint_for_order(&mut bm, &order, None, EpochId(1))
+            .unwrap();
+
+        // Many epochs later the order may still rest: nothing is swept
+        clock.advance(chrono::Duration::days(30));
+        assert!(em.release_expired(&mut bm).is_empty());
+        assert!(em.is_active(&order.sr_id));
+        assert_eq!(
+            bm.balance(order.user_id, "USDT").frozen,
+            Decimal::new(10, 0)
+        );
+
+        // The open-ended expiry survives a snapshot round trip
+        let json = serde_json::to_string(&em.export()).unwrap();
+        let restored = EscrowManager::import(serde_json::from_str(&json).unwrap(), em.node_id());
+        assert_eq!(
+            restored.get(&order.sr_id).unwrap().expires_at,
+            DateTime::<Utc>::MAX_UTC
+        );
+    }
+
+    #[test]
+    fn good_till_date_order_expires_with_its_spend_right() {
+        let t0 = DateTime::UNIX_EPOCH + chrono::Duration::days(20_000);
+        let clock = FixedClock::new(t0);
+        let (em, mut bm) = setup();
+        let mut em = em.with_clock(Arc::new(clock.clone()));
+        let mut order = Order::dummy_limit(OrderSide::Buy, Decimal::new(10, 0), Decimal::ONE);
+        order.expires_at = Some(t0 + chrono::Duration::hours(2));
+        bm.deposit(order.user_id, Asset::lit("USDT"), Decimal::new(100, 0));
+        order.sr_id = em
+            .mint_for_order(&mut bm, &order, None, EpochId(1))
+            .unwrap();
+        assert_eq!(
+            em.get(&order.sr_id).unwrap().expires_at,
+            t0 + chrono::Duration::hours(2)
+        );
+
+        // Long past the policy TTL, the SR lives until the order expires
+        clock.set(t0 + chrono::Duration::hours(2));
+        assert!(em.release_expired(&mut bm).is_empty());
+
+        clock.advance(chrono::Duration::nanoseconds(1));
+        assert!(order.is_expired_at(clock.now()));
+        assert_eq!(em.release_expired(&mut bm), vec![order.sr_id]);
+        assert_eq!(
+            bm.balance(order.user_id, "USDT").available,
+            Decimal::new(100, 0)
+        );
+    }
+
+    #[test]
+    fn sweep_skips_live_spend_rights() {
+        let (mut em, mut bm) = setup();
+        let user = UserId::new();
+        bm.deposit(user, Asset::lit("USDT"), Decimal::new(100, 0));
+        let sr_id = em
+            .mint(
+                &mut bm,
+                OrderId::new(),
+                user,
+                "USDT",
+                Decimal::ONE,
+                EpochId(1),
+            )
+            .unwrap();
+
+        assert!(em.release_expired(&mut bm).is_empty());
+        assert!(em.is_active(&sr_id));
+    }
+
     #[test]
     fn mint_freezes_and_creates_sr() {
         let (mut em, mut bm) = setup();
         let user = UserId::new();
-        bm.deposit(user, "USDT", Decimal::new(10000, 0));
+        bm.deposit(user, Asset::lit("USDT"), Decimal::new(10000, 0));
 
         let sr_id = em
             .mint(
@@ -207,11 +706,73 @@ mod tests {
         assert_eq!(em.active_count(), 1);
     }
 
+    #[test]
+    fn limit_orders_escrow_exactly_their_requirement() {
+        let (mut em, mut bm) = setup();
+        let user = UserId::new();
+        bm.deposit(user, Asset::lit("USDT"), Decimal::new(1_000, 0));
+        bm.deposit(user, Asset::lit("BTC"), Decimal::new(10, 0));
+
+        // Buy 3 @ 150.5 freezes 451.5 quote.
+        let mut buy = Order::dummy_limit(OrderSide::Buy, Decimal::new(1505, 1), Decimal::new(3, 0));
+        buy.user_id = user;
+        let sr_id = em.mint_for_order(&mut bm, &buy, None, EpochId(1)).unwrap();
+        assert_eq!(em.get(&sr_id).unwrap().asset, "USDT");
+        assert_eq!(em.get(&sr_id).unwrap().order_id, buy.id);
+        assert_eq!(bm.balance(user, "USDT").frozen, Decimal::new(4515, 1));
+
+        // Sell 2 freezes 2 base, whatever the price.
+        let mut sell = Order::dummy_limit(OrderSide::Sell, Decimal::new(9_999, 0), Decimal::TWO);
+        sell.user_id = user;
+        let sr_id = em.mint_for_order(&mut bm, &sell, None, EpochId(1)).unwrap();
+        assert_eq!(em.get(&sr_id).unwrap().amount, Decimal::TWO);
+        assert_eq!(bm.balance(user, "BTC").frozen, Decimal::TWO);
+    }
+
+    #[test]
+    fn market_buy_escrows_at_reference_price() {
+        let (mut em, mut bm) = setup();
+        let user = UserId::new();
+        bm.deposit(user, Asset::lit("USDT"), Decimal::new(1_000_000, 0));
+        let mut order = Order::dummy_limit(OrderSide::Buy, Decimal::ONE, Decimal::TWO);
+        order.user_id = user;
+        order.order_type = OrderType::Market;
+        order.price = None;
+
+        // No reference price: nothing is frozen.
+        assert!(matches!(
+            em.mint_for_order(&mut bm, &order, None, EpochId(1)),
+            Err(OpenmatchError::InvalidOrder { .. })
+        ));
+        assert!(bm.balance(user, "USDT").frozen.is_zero());
+
+        let sr_id = em
+            .mint_for_order(&mut bm, &order, Some(Decimal::new(50_000, 0)), EpochId(1))
+            .unwrap();
+        assert_eq!(em.get(&sr_id).unwrap().amount, Decimal::new(100_000, 0));
+        assert_eq!(bm.balance(user, "USDT").frozen, Decimal::new(100_000, 0));
+
+        // The sentinel itself is refused outright.
+        bm.deposit(user, Asset::lit("BTC"), Decimal::MAX);
+        let err = em
+            .mint(
+                &mut bm,
+                OrderId::new(),
+                user,
+                "BTC",
+                Decimal::MAX,
+                EpochId(1),
+            )
+            .unwrap_err();
+        assert!(matches!(err, OpenmatchError::InvalidOrder { .. }));
+        assert!(bm.balance(user, "BTC").frozen.is_zero());
+    }
+
     #[test]
     fn mint_fails_insufficient_balance() {
         let (mut em, mut bm) = setup();
         let user = UserId::new();
-        bm.deposit(user, "USDT", Decimal::new(100, 0));
+        bm.deposit(user, Asset::lit("USDT"), Decimal::new(100, 0));
 
         let err = em
             .mint(
@@ -231,11 +792,75 @@ mod tests {
         assert_eq!(bm.balance(user, "USDT").available, Decimal::new(100, 0));
     }
 
+    #[test]
+    fn active_for_user_tracks_mint_release_and_spend() {
+        let (mut em, mut bm) = setup();
+        let (alice, bob) = (UserId::new(), UserId::new());
+        bm.deposit(alice, Asset::lit("USDT"), Decimal::new(10000, 0));
+        bm.deposit(bob, Asset::lit("USDT"), Decimal::new(10000, 0));
+        let mut mint = |em: &mut EscrowManager, user| {
+            em.mint(
+                &mut bm,
+                OrderId::new(),
+                user,
+                "USDT",
+                Decimal::new(1000, 0),
+                EpochId(1),
+            )
+            .unwrap()
+        };
+        let first = mint(&mut em, alice);
+        let second = mint(&mut em, alice);
+        let third = mint(&mut em, alice);
+        mint(&mut em, bob);
+
+        let ids = |em: &EscrowManager| {
+            em.active_for_user(&alice)
+                .iter()
+                .map(|sr| sr.id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids(&em), vec![first, second, third]);
+
+        em.release(&mut bm, first).unwrap();
+        assert_eq!(ids(&em), vec![second, third]);
+        em.mark_spent(third).unwrap();
+        assert_eq!(ids(&em), vec![second]);
+        assert_eq!(em.active_for_user(&bob).len(), 1);
+        assert!(em.active_for_user(&UserId::new()).is_empty());
+
+        let restored = EscrowManager::import(em.export(), em.node_id());
+        assert_eq!(ids(&restored), vec![second]);
+    }
+
+    #[test]
+    fn second_mint_for_order_rejected_while_first_active() {
+        let (mut em, mut bm) = setup();
+        let (user, order_id) = (UserId::new(), OrderId::new());
+        bm.deposit(user, Asset::lit("USDT"), Decimal::new(100, 0));
+        let mint = |em: &mut EscrowManager, bm: &mut BalanceManager| {
+            em.mint(bm, order_id, user, "USDT", Decimal::new(40, 0), EpochId(1))
+        };
+
+        let first = mint(&mut em, &mut bm).unwrap();
+        let err = mint(&mut em, &mut bm).unwrap_err();
+        assert!(matches!(err, OpenmatchError::InvalidSpendRight { .. }));
+        assert!(em.is_active(&first));
+        assert_eq!(em.sr_for_order(&order_id), Some(first));
+        assert_eq!(bm.balance(user, "USDT").frozen, Decimal::new(40, 0));
+
+        em.release(&mut bm, first).unwrap();
+        let second = mint(&mut em, &mut bm).unwrap();
+        assert_ne!(second, first);
+        assert_eq!(em.sr_for_order(&order_id), Some(second));
+        assert_eq!(bm.balance(user, "USDT").frozen, Decimal::new(40, 0));
+    }
+
     #[test]
     fn release_unfreezes_and_marks_released() {
         let (mut em, mut bm) = setup();
         let user = UserId::new();
-        bm.deposit(user, "USDT", Decimal::new(10000, 0));
+        bm.deposit(user, Asset::lit("USDT"), Decimal::new(10000, 0));
 
         let sr_id = em
             .mint(
@@ -261,11 +886,68 @@ mod tests {
         assert_eq!(sr.state, SpendRightState::Released);
     }
 
+    #[test]
+    fn export_import_round_trip_preserves_states() {
+        let (mut em, mut bm) = setup();
+        let user = UserId::new();
+        bm.deposit(user, Asset::lit("USDT"), Decimal::new(300, 0));
+
+        let mut mint = |em: &mut EscrowManager| {
+            em.mint(
+                &mut bm,
+                OrderId::new(),
+                user,
+                "USDT",
+                Decimal::new(100, 0),
+                EpochId(1),
+            )
+            .unwrap()
+        };
+        let active = mint(&mut em);
+        let spent = mint(&mut em);
+        let released = mint(&mut em);
+        em.mark_spent(spent).unwrap();
+        em.release(&mut bm, released).unwrap();
+
+        let snapshot = em.export();
+        let json = serde_json::to_string(&snapshot).unwrap();
+        let restored = EscrowManager::import(serde_json::from_str(&json).unwrap(), em.node_id());
+
+        assert_eq!(serde_json::to_string(&restored.export()).unwrap(), json);
+        assert_eq!(restored.count(), 3);
+        assert_eq!(restored.active_count(), em.active_count());
+        for sr_id in [active, spent, released] {
+            assert_eq!(restored.is_active(&sr_id), em.is_active(&sr_id));
+            assert_eq!(
+                restored.get(&sr_id).unwrap().state,
+                em.get(&sr_id).unwrap().state
+            );
+        }
+        assert!(restored.is_active(&active));
+        let order_id = restored.get(&active).unwrap().order_id;
+        assert_eq!(restored.sr_for_order(&order_id), Some(active));
+
+        // Nonces continue past the exported position
+        let (mut restored, mut bm) = (restored, bm);
+        bm.deposit(user, Asset::lit("USDT"), Decimal::new(100, 0));
+        let fresh = restored
+            .mint(
+                &mut bm,
+                OrderId::new(),
+                user,
+                "USDT",
+                Decimal::new(100, 0),
+                EpochId(2),
+            )
+            .unwrap();
+        assert!(restored.get(&fresh).unwrap().nonce >= snapshot.next_nonce);
+    }
+
     #[test]
     fn double_release_fails() {
         let (mut em, mut bm) = setup();
         let user = UserId::new();
-        bm.deposit(user, "USDT", Decimal::new(10000, 0));
+        bm.deposit(user, Asset::lit("USDT"), Decimal::new(10000, 0));
 
         let sr_id = em
             .mint(
@@ -287,7 +969,7 @@ mod tests {
     fn mark_spent_transitions_state() {
         let (mut em, mut bm) = setup();
         let user = UserId::new();
-        bm.deposit(user, "USDT", Decimal::new(10000, 0));
+        bm.deposit(user, Asset::lit("USDT"), Decimal::new(10000, 0));
 
         let sr_id = em
             .mint(
@@ -311,7 +993,7 @@ mod tests {
     fn spent_cannot_be_released() {
         let (mut em, mut bm) = setup();
         let user = UserId::new();
-        bm.deposit(user, "USDT", Decimal::new(10000, 0));
+        bm.deposit(user, Asset::lit("USDT"), Decimal::new(10000, 0));
 
         let sr_id = em
             .mint(
@@ -329,6 +1011,42 @@ mod tests {
         assert!(matches!(err, OpenmatchError::InvalidSpendRight { .. }));
     }
 
+    #[test]
+    fn release_by_order_unfreezes() {
+        let (mut em, mut bm) = setup();
+        let user = UserId::new();
+        let order_id = OrderId::new();
+        bm.deposit(user, Asset::lit("USDT"), Decimal::new(10000, 0));
+
+        let sr_id = em
+            .mint(
+                &mut bm,
+                order_id,
+                user,
+                "USDT",
+                Decimal::new(5000, 0),
+                EpochId(1),
+            )
+            .unwrap();
+        assert_eq!(em.sr_for_order(&order_id), Some(sr_id));
+
+        em.release_by_order(&mut bm, order_id).unwrap();
+        assert_eq!(bm.balance(user, "USDT").frozen, Decimal::ZERO);
+        assert_eq!(em.get(&sr_id).unwrap().state, SpendRightState::Released);
+
+        // Second release hits the SR state check
+        let err = em.release_by_order(&mut bm, order_id).unwrap_err();
+        assert!(matches!(err, OpenmatchError::InvalidSpendRight { .. }));
+    }
+
+    #[test]
+    fn release_by_unknown_order_errors() {
+        let (mut em, mut bm) = setup();
+        let order_id = OrderId::new();
+        let err = em.release_by_order(&mut bm, order_id).unwrap_err();
+        assert!(matches!(err, OpenmatchError::OrderNotFound(id) if id == order_id));
+    }
+
     #[test]
     fn nonexistent_sr_errors() {
         let (mut em, mut bm) = setup();
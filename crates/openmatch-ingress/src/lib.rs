@@ -9,6 +9,7 @@
 //! 1. **BalanceManager**: tracks available/frozen balances per (user, asset)
 //! 2. **EscrowManager**: freezes funds and mints SpendRights
 //! 3. **RiskKernel**: hard gate — validates order against risk limits
+//!    (and, for agent orders, the agent's binding in the `AgentRegistry`)
 //! 4. **PendingBuffer**: collects validated orders during COLLECT phase
 //! 5. **BatchSealer**: seals the buffer into a `SealedBatch` + `BatchDigest`
 //!
@@ -21,14 +22,16 @@
 //!
 //! Every order entering MatchCore **must** have a valid SpendRight.
 
+pub mod agent_registry;
 pub mod balance_manager;
 pub mod batch_sealer;
 pub mod escrow;
 pub mod pending_buffer;
 pub mod risk_kernel;
 
+pub use agent_registry::{AgentRegistry, AgentState};
 pub use balance_manager::BalanceManager;
 pub use batch_sealer::BatchSealer;
-pub use escrow::EscrowManager;
+pub use escrow::{EscrowManager, EscrowSnapshot, ExpiryPolicy};
 pub use pending_buffer::PendingBuffer;
-pub use risk_kernel::RiskKernel;
+pub use risk_kernel::{RiskKernel, TickPolicy};
@@ -3,8 +3,21 @@
 //! Orders that have passed risk validation and have an active SpendRight
 //! are pushed into the PendingBuffer. When the SEAL phase begins, the
 //! buffer is sealed into a `SealedBatch`.
+//!
+//! Sealing with [`PendingBuffer::seal_with_grace`] keeps a short window
+//! open after the cutoff for orders created before it that were delayed
+//! in transit (see `EpochConfig::seal_grace`).
+//!
+//! An order ID may appear only once per batch; a repeat is refused at
+//! push time rather than dropped later at book insertion.
+//!
+//! [`PendingBuffer::push_sequenced`] stamps time priority from a counter
+//! that keeps rising across epochs and refuses to wrap.
 
-use openmatch_types::{OpenmatchError, Order, Result, constants};
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+use openmatch_types::{EpochPhase, OpenmatchError, Order, OrderId, Result, constants};
 
 /// Collects validated orders during the COLLECT phase.
 ///
@@ -13,10 +26,16 @@ use openmatch_types::{OpenmatchError, Order, Result, constants};
 pub struct PendingBuffer {
     /// Orders in arrival order.
     orders: Vec<Order>,
+    /// IDs of the buffered orders.
+    ids: HashSet<OrderId>,
     /// Whether the buffer has been sealed.
     sealed: bool,
     /// Maximum number of orders before the buffer is full.
     max_orders: usize,
+    /// Late-order grace window: `(seal deadline, accept until)`.
+    grace: Option<(DateTime<Utc>, DateTime<Utc>)>,
+    /// Sequence number the next [`PendingBuffer::push_sequenced`] assigns.
+    next_sequence: u64,
 }
 
 impl PendingBuffer {
@@ -25,8 +44,11 @@ impl PendingBuffer {
     pub fn new() -> Self {
         Self {
             orders: Vec::new(),
+            ids: HashSet::new(),
             sealed: false,
             max_orders: constants::MAX_ORDERS_PER_BATCH,
+            grace: None,
+            next_sequence: 0,
         }
     }
 
@@ -35,24 +57,93 @@ impl PendingBuffer {
     pub fn with_capacity(max_orders: usize) -> Self {
         Self {
             orders: Vec::with_capacity(max_orders),
+            ids: HashSet::with_capacity(max_orders),
             sealed: false,
             max_orders,
+            grace: None,
+            next_sequence: 0,
         }
     }
 
     /// Push a validated order into the buffer.
     ///
     /// # Errors
-    /// - `BufferAlreadySealed` if the buffer has been sealed
+    /// - `BufferAlreadySealed` if the buffer has been sealed and the order
+    ///   doesn't qualify for the grace window
+    /// - `DuplicateOrder` if an order with the same ID is already buffered
     /// - `BufferFull` if the buffer is at capacity
     pub fn push(&mut self, order: Order) -> Result<()> {
-        if self.sealed {
+        self.push_at(order, Utc::now())
+    }
+
+    /// Stamp `order.sequence` from the buffer's counter, then push it.
+    /// Returns the assigned sequence number.
+    ///
+    /// # Errors
+    /// Same as [`PendingBuffer::push`], or `Internal` if the counter is
+    /// exhausted: wrapping to 0 would hand the order top time priority.
+    pub fn push_sequenced(&mut self, mut order: Order) -> Result<u64> {
+        let seq = self.next_sequence;
+        let next = seq.checked_add(1).ok_or_else(|| {
+            OpenmatchError::Internal("pending buffer sequence counter exhausted".into())
+        })?;
+        order.sequence = seq;
+        self.push(order)?;
+        self.next_sequence = next;
+        Ok(seq)
+    }
+
+    /// Continue sequence numbering at `next`, e.g. past the highest
+    /// sequence among orders still resting from earlier epochs.
+    pub fn resume_sequence(&mut self, next: u64) {
+        self.next_sequence = next;
+    }
+
+    /// Sequence number the next [`PendingBuffer::push_sequenced`] assigns.
+    #[must_use]
+    pub fn next_sequence(&self) -> u64 {
+        self.next_sequence
+    }
+
+    /// [`PendingBuffer::push`] with an explicit arrival time.
+    ///
+    /// After a [`PendingBuffer::seal_with_grace`], an order is still
+    /// admitted if it arrives by `accept_until` and was created before the
+    /// seal deadline. It is inserted after every buffered order created at
+    /// or before it, keeping the buffer in creation order.
+    ///
+    /// # Errors
+    /// Same as [`PendingBuffer::push`].
+    pub fn push_at(&mut self, order: Order, now: DateTime<Utc>) -> Result<()> {
+        let in_grace = self.grace.is_some_and(|(deadline, accept_until)| {
+            now <= accept_until && order.created_at < deadline
+        });
+        let phase = self.phase();
+        let admitted = if order.order_type.is_cancel() {
+            phase.allows_cancels()
+        } else {
+            phase.allows_new_orders()
+        };
+        if !admitted && !in_grace {
             return Err(OpenmatchError::BufferAlreadySealed);
         }
+        if self.ids.contains(&order.id) {
+            return Err(OpenmatchError::DuplicateOrder(order.id));
+        }
         if self.orders.len() >= self.max_orders {
             return Err(OpenmatchError::BufferFull);
         }
-        self.orders.push(order);
+        self.ids.insert(order.id);
+        if self.sealed {
+            let pos = self
+                .orders
+                .iter()
+                .rposition(|o| o.created_at <= order.created_at)
+                .map_or(0, |i| i + 1);
+            self.orders.insert(pos, order);
+        } else {
+            self.orders.push(order);
+        }
         Ok(())
     }
 
@@ -68,6 +159,21 @@ impl PendingBuffer {
         Ok(())
     }
 
+    /// Seal the buffer, admitting late orders created before `deadline`
+    /// until `accept_until`. Drain only after `accept_until` has passed.
+    ///
+    /// # Errors
+    /// Returns `BufferAlreadySealed` if already sealed.
+    pub fn seal_with_grace(
+        &mut self,
+        deadline: DateTime<Utc>,
+        accept_until: DateTime<Utc>,
+    ) -> Result<()> {
+        self.seal()?;
+        self.grace = Some((deadline, accept_until));
+        Ok(())
+    }
+
     /// Drain all orders from the buffer (consumes the content).
     ///
     /// Used by the `BatchSealer` to extract orders for the `SealedBatch`.
@@ -81,6 +187,9 @@ impl PendingBuffer {
                 reason: "Cannot drain unsealed buffer".to_string(),
             });
         }
+        // Anything arriving after the drain belongs to no batch.
+        self.grace = None;
+        self.ids.clear();
         Ok(std::mem::take(&mut self.orders))
     }
 
@@ -90,6 +199,16 @@ impl PendingBuffer {
         self.sealed
     }
 
+    /// The epoch phase the buffer is in: COLLECT until sealed, SEAL after.
+    #[must_use]
+    pub fn phase(&self) -> EpochPhase {
+        if self.sealed {
+            EpochPhase::Seal
+        } else {
+            EpochPhase::Collect
+        }
+    }
+
     /// Number of orders currently in the buffer.
     #[must_use]
     pub fn len(&self) -> usize {
@@ -102,10 +221,13 @@ impl PendingBuffer {
         self.orders.is_empty()
     }
 
-    /// Reset the buffer for a new epoch.
+    /// Reset the buffer for a new epoch. The sequence counter carries on,
+    /// so later epochs never reuse an earlier order's priority.
     pub fn reset(&mut self) {
         self.orders.clear();
+        self.ids.clear();
         self.sealed = false;
+        self.grace = None;
     }
 }
 
@@ -141,6 +263,38 @@ mod tests {
         assert!(!buf.is_empty());
     }
 
+    #[test]
+    fn push_sequenced_numbers_orders_across_resets() {
+        let mut buf = PendingBuffer::new();
+        let order = || Order::dummy_limit(OrderSide::Buy, Decimal::new(100, 0), Decimal::ONE);
+        assert_eq!(buf.push_sequenced(order()).unwrap(), 0);
+        assert_eq!(buf.push_sequenced(order()).unwrap(), 1);
+        buf.seal().unwrap();
+        let seqs: Vec<u64> = buf.drain().unwrap().iter().map(|o| o.sequence).collect();
+        assert_eq!(seqs, vec![0, 1]);
+
+        buf.reset();
+        assert_eq!(buf.push_sequenced(order()).unwrap(), 2);
+
+        // A refused push doesn't consume a number
+        buf.seal().unwrap();
+        assert!(buf.push_sequenced(order()).is_err());
+        assert_eq!(buf.next_sequence(), 3);
+    }
+
+    #[test]
+    fn push_sequenced_at_limit_errors_instead_of_wrapping() {
+        let mut buf = PendingBuffer::new();
+        buf.resume_sequence(u64::MAX - 1);
+        let order = || Order::dummy_limit(OrderSide::Buy, Decimal::new(100, 0), Decimal::ONE);
+
+        assert_eq!(buf.push_sequenced(order()).unwrap(), u64::MAX - 1);
+        let err = buf.push_sequenced(order()).unwrap_err();
+        assert!(matches!(err, OpenmatchError::Internal(_)));
+        assert_eq!(buf.len(), 1);
+        assert_eq!(buf.next_sequence(), u64::MAX);
+    }
+
     #[test]
     fn push_after_seal_fails() {
         let mut buf = PendingBuffer::new();
@@ -155,6 +309,79 @@ mod tests {
         assert!(matches!(err, OpenmatchError::BufferAlreadySealed));
     }
 
+    #[test]
+    fn sealed_buffer_is_in_seal_phase_and_refuses_cancels() {
+        let mut buf = PendingBuffer::new();
+        assert_eq!(buf.phase(), EpochPhase::Collect);
+        buf.seal().unwrap();
+        assert_eq!(buf.phase(), EpochPhase::Seal);
+
+        let mut cancel = Order::dummy_limit(OrderSide::Buy, Decimal::new(100, 0), Decimal::ONE);
+        cancel.order_type = OrderType::Cancel {
+            target: OrderId::new(),
+        };
+        let err = buf.push(cancel).unwrap_err();
+        assert!(matches!(err, OpenmatchError::BufferAlreadySealed));
+
+        buf.reset();
+        assert_eq!(buf.phase(), EpochPhase::Collect);
+    }
+
+    fn order_created_at(created_at: DateTime<Utc>) -> Order {
+        let mut order = Order::dummy_limit(OrderSide::Buy, Decimal::new(100, 0), Decimal::ONE);
+        order.created_at = created_at;
+        order
+    }
+
+    #[test]
+    fn late_order_within_grace_admitted_in_creation_order() {
+        let deadline = Utc::now();
+        let ms = chrono::Duration::milliseconds;
+        let mut buf = PendingBuffer::new();
+        let early = order_created_at(deadline - ms(30));
+        let last = order_created_at(deadline - ms(5));
+        buf.push_at(early, deadline - ms(29)).unwrap();
+        buf.push_at(last, deadline - ms(4)).unwrap();
+        buf.seal_with_grace(deadline, deadline + ms(50)).unwrap();
+
+        // Created before the cutoff, arrived 10ms after it.
+        let delayed = order_created_at(deadline - ms(10));
+        let delayed_id = delayed.id;
+        buf.push_at(delayed, deadline + ms(10)).unwrap();
+
+        let orders = buf.drain().unwrap();
+        assert_eq!(orders.len(), 3);
+        assert_eq!(orders[1].id, delayed_id);
+    }
+
+    #[test]
+    fn late_order_outside_grace_rejected() {
+        let deadline = Utc::now();
+        let ms = chrono::Duration::milliseconds;
+        let mut buf = PendingBuffer::new();
+        buf.seal_with_grace(deadline, deadline + ms(50)).unwrap();
+
+        // Arrived after the grace window.
+        let err = buf
+            .push_at(order_created_at(deadline - ms(10)), deadline + ms(51))
+            .unwrap_err();
+        assert!(matches!(err, OpenmatchError::BufferAlreadySealed));
+
+        // Within the window, but created after the cutoff.
+        let err = buf
+            .push_at(order_created_at(deadline + ms(1)), deadline + ms(2))
+            .unwrap_err();
+        assert!(matches!(err, OpenmatchError::BufferAlreadySealed));
+        assert!(buf.is_empty());
+
+        // Window closes once the batch is drained.
+        buf.drain().unwrap();
+        assert!(
+            buf.push_at(order_created_at(deadline - ms(1)), deadline + ms(1))
+                .is_err()
+        );
+    }
+
     #[test]
     fn double_seal_fails() {
         let mut buf = PendingBuffer::new();
@@ -242,4 +469,34 @@ mod tests {
         ))
         .unwrap();
     }
+
+    #[test]
+    fn duplicate_order_id_rejected() {
+        let mut buf = PendingBuffer::new();
+        let order = Order::dummy_limit(OrderSide::Buy, Decimal::new(100, 0), Decimal::ONE);
+        let id = order.id;
+        buf.push(order.clone()).unwrap();
+
+        let mut resubmitted = order;
+        resubmitted.price = Some(Decimal::new(101, 0));
+        assert!(matches!(
+            buf.push(resubmitted),
+            Err(OpenmatchError::DuplicateOrder(dup)) if dup == id
+        ));
+        assert_eq!(buf.len(), 1);
+    }
+
+    #[test]
+    fn distinct_order_ids_accepted() {
+        let mut buf = PendingBuffer::new();
+        for _ in 0..3 {
+            buf.push(Order::dummy_limit(
+                OrderSide::Sell,
+                Decimal::new(100, 0),
+                Decimal::ONE,
+            ))
+            .unwrap();
+        }
+        assert_eq!(buf.len(), 3);
+    }
 }
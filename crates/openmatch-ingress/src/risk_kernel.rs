@@ -9,11 +9,41 @@
 //! - **No bypass**: Every order path goes through the kernel
 //! - **Pluggable**: Enterprise risk logic can tighten (never weaken) rules
 //! - **Zero latency impact on MatchCore**: All risk checks happen in ingress
+//!
+//! [`RiskKernel::check_batch_net_positions`] runs after matching instead:
+//! it flags users whose fills in one bundle build too large a net position,
+//! so their orders can be cancelled before the bundle settles.
+
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+
+use chrono::{DateTime, Duration, Utc};
+
+use openmatch_types::{
+    AgentId, EpochId, EpochPhase, MarketConfig, OpenmatchError, Order, OrderId, Result,
+    RiskRejectionReason, TradeBundle, UserId,
+};
+use rust_decimal::{Decimal, RoundingStrategy};
+
+use crate::agent_registry::AgentRegistry;
 
-use std::collections::HashMap;
+/// Outcome of a single risk check.
+type Check = std::result::Result<(), RiskRejectionReason>;
 
-use openmatch_types::{EpochId, OpenmatchError, Order, OrderType, Result, UserId};
-use rust_decimal::Decimal;
+/// What [`RiskKernel::normalize_price`] does with a limit price that is
+/// not a multiple of the market's tick size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TickPolicy {
+    /// Refuse the order.
+    #[default]
+    Reject,
+    /// Round the price down to the tick below. This makes a buy less
+    /// aggressive but a sell *more* aggressive (it will accept a lower
+    /// price than the user asked for).
+    SnapDown,
+    /// Round to the nearest tick; an exact half-tick rounds to the even
+    /// tick.
+    SnapToNearest,
+}
 
 /// Hard risk gate that validates orders before they enter the pending buffer.
 pub struct RiskKernel {
@@ -23,12 +53,52 @@ pub struct RiskKernel {
     max_order_size: Decimal,
     /// Maximum price deviation from last known price (multiplier).
     max_price_deviation: Decimal,
+    /// Maximum live (resting or pending) orders per user (`None` disables
+    /// the check).
+    max_open_orders: Option<usize>,
+    /// Maximum accepted orders per user in any one-second window
+    /// (`None` disables the check).
+    max_orders_per_second: Option<u32>,
+    /// Per-user acceptance times inside the current one-second window.
+    recent_orders: HashMap<UserId, VecDeque<DateTime<Utc>>>,
+    /// Maximum accepted orders per user and market in any one-second
+    /// window (`None` disables the check).
+    max_orders_per_second_per_market: Option<u32>,
+    /// Per-user, per-market acceptance times inside the current
+    /// one-second window.
+    recent_market_orders: HashMap<(UserId, String), VecDeque<DateTime<Utc>>>,
     /// Per-user order count for the current epoch.
     epoch_order_counts: HashMap<UserId, usize>,
+    /// Maximum accepted orders per user per sealed batch (`None` disables
+    /// the check).
+    max_orders_per_user_per_batch: Option<usize>,
+    /// Per-user order count for the batch currently collecting.
+    batch_order_counts: HashMap<UserId, usize>,
+    /// Owner of every accepted order not yet filled, cancelled or expired.
+    /// Only tracked while the open-order cap is enabled.
+    open_orders: HashMap<OrderId, UserId>,
+    /// Per-user number of entries in `open_orders`.
+    open_order_counts: HashMap<UserId, usize>,
     /// Current epoch.
     current_epoch: EpochId,
+    /// Current epoch phase; gates new orders and cancels.
+    phase: EpochPhase,
     /// Last known prices per market (for price sanity checks).
     last_prices: HashMap<String, Decimal>,
+    /// Markets whose entry in `last_prices` was seeded, not traded.
+    seeded_prices: HashSet<String>,
+    /// Markets whose `MarketConfig::trading_enabled` is `false`.
+    halted_markets: HashSet<String>,
+    /// Tick size per market, from [`RiskKernel::apply_market_config`].
+    tick_sizes: HashMap<String, Decimal>,
+    /// Handling of off-tick prices in [`RiskKernel::normalize_price`].
+    tick_policy: TickPolicy,
+    /// Largest allowed distance between an order ID's embedded `UUIDv7`
+    /// time and the node clock (`None` disables the check).
+    max_order_id_skew: Option<Duration>,
+    /// Largest net base position one batch may build per user and market
+    /// (`None` disables the check).
+    max_batch_net_position: Option<Decimal>,
 }
 
 impl RiskKernel {
@@ -39,9 +109,25 @@ impl RiskKernel {
             max_orders_per_user_per_epoch: 50,
             max_order_size: Decimal::new(100, 0), // 100 base units
             max_price_deviation: Decimal::new(10, 0), // 10x deviation
+            max_open_orders: None,
+            max_orders_per_second: None,
+            recent_orders: HashMap::new(),
+            max_orders_per_second_per_market: None,
+            recent_market_orders: HashMap::new(),
             epoch_order_counts: HashMap::new(),
+            max_orders_per_user_per_batch: None,
+            batch_order_counts: HashMap::new(),
+            open_orders: HashMap::new(),
+            open_order_counts: HashMap::new(),
             current_epoch: EpochId(0),
+            phase: EpochPhase::Collect,
             last_prices: HashMap::new(),
+            seeded_prices: HashSet::new(),
+            halted_markets: HashSet::new(),
+            tick_sizes: HashMap::new(),
+            tick_policy: TickPolicy::default(),
+            max_order_id_skew: None,
+            max_batch_net_position: None,
         }
     }
 
@@ -56,77 +142,604 @@ impl RiskKernel {
             max_orders_per_user_per_epoch,
             max_order_size,
             max_price_deviation,
+            max_open_orders: None,
+            max_orders_per_second: None,
+            recent_orders: HashMap::new(),
+            max_orders_per_second_per_market: None,
+            recent_market_orders: HashMap::new(),
             epoch_order_counts: HashMap::new(),
+            max_orders_per_user_per_batch: None,
+            batch_order_counts: HashMap::new(),
+            open_orders: HashMap::new(),
+            open_order_counts: HashMap::new(),
             current_epoch: EpochId(0),
+            phase: EpochPhase::Collect,
             last_prices: HashMap::new(),
+            seeded_prices: HashSet::new(),
+            halted_markets: HashSet::new(),
+            tick_sizes: HashMap::new(),
+            tick_policy: TickPolicy::default(),
+            max_order_id_skew: None,
+            max_batch_net_position: None,
         }
     }
 
-    /// Advance to a new epoch. Resets per-epoch counters.
+    /// Cap the number of open orders per user.
+    ///
+    /// Slots are freed by [`RiskKernel::apply_bundle`] (fills and cancels)
+    /// and [`RiskKernel::order_closed`] (expiries and other removals), so a
+    /// node enabling the cap must report every closed order.
+    #[must_use]
+    pub fn with_max_open_orders(mut self, max_open_orders: usize) -> Self {
+        self.max_open_orders = Some(max_open_orders);
+        self
+    }
+
+    /// Limit each user to `limit` accepted orders per second, typically
+    /// `RiskLimits::max_orders_per_second`. Complements the per-epoch cap.
+    #[must_use]
+    pub fn with_max_orders_per_second(mut self, limit: u32) -> Self {
+        self.max_orders_per_second = Some(limit);
+        self
+    }
+
+    /// Limit each user to `limit` accepted orders per second in any one
+    /// market, so heavy quoting in one market can't use up the budget for
+    /// the user's others. [`RiskKernel::with_max_orders_per_second`] still
+    /// caps the total across markets.
+    #[must_use]
+    pub fn with_max_orders_per_second_per_market(mut self, limit: u32) -> Self {
+        self.max_orders_per_second_per_market = Some(limit);
+        self
+    }
+
+    /// Limit each user to `limit` accepted orders per batch, so one account
+    /// can't dominate the pending buffer when batches are finer than epochs.
+    #[must_use]
+    pub fn with_max_orders_per_user_per_batch(mut self, limit: usize) -> Self {
+        self.max_orders_per_user_per_batch = Some(limit);
+        self
+    }
+
+    /// Refuse orders whose ID embeds a time more than `tolerance` away
+    /// from the node clock, in either direction. Catches replayed old
+    /// order IDs and badly skewed client clocks.
+    #[must_use]
+    pub fn with_max_order_id_skew(mut self, tolerance: Duration) -> Self {
+        self.max_order_id_skew = Some(tolerance);
+        self
+    }
+
+    /// Cap the net base position (bought minus sold, either direction) a
+    /// single batch may build per user and market; see
+    /// [`RiskKernel::check_batch_net_positions`].
+    #[must_use]
+    pub fn with_max_batch_net_position(mut self, limit: Decimal) -> Self {
+        self.max_batch_net_position = Some(limit);
+        self
+    }
+
+    /// Choose how [`RiskKernel::normalize_price`] treats off-tick prices.
+    #[must_use]
+    pub fn with_tick_policy(mut self, policy: TickPolicy) -> Self {
+        self.tick_policy = policy;
+        self
+    }
+
+    /// Advance to a new epoch. Resets per-epoch and per-batch counters.
+    ///
+    /// Open-order counts carry over: resting orders stay open across epochs.
     pub fn advance_epoch(&mut self, epoch_id: EpochId) {
         self.current_epoch = epoch_id;
         self.epoch_order_counts.clear();
+        self.batch_order_counts.clear();
+    }
+
+    /// Track the epoch phase. Orders are refused outside the phases that
+    /// [`EpochPhase::allows_new_orders`] / [`EpochPhase::allows_cancels`]
+    /// admit them in.
+    ///
+    /// Re-setting the current phase is a no-op. Skipping a phase would
+    /// reopen order intake out of turn, so it is rejected and the phase is
+    /// left unchanged.
+    ///
+    /// # Errors
+    /// [`OpenmatchError::WrongEpochPhase`] if `phase` can't follow the
+    /// current phase; `expected` is the legal next phase.
+    pub fn set_phase(&mut self, phase: EpochPhase) -> Result<()> {
+        if phase != self.phase && !phase.can_follow(self.phase) {
+            return Err(OpenmatchError::WrongEpochPhase {
+                expected: self.phase.next(),
+                actual: phase,
+            });
+        }
+        self.phase = phase;
+        Ok(())
     }
 
-    /// Update the last known price for a market.
+    /// Report that the pending buffer was sealed. Resets per-batch counters.
+    pub fn batch_sealed(&mut self) {
+        self.batch_order_counts.clear();
+    }
+
+    /// Update the last known price for a market. A traded price replaces
+    /// any seeded one.
     pub fn set_last_price(&mut self, market: &str, price: Decimal) {
         self.last_prices.insert(market.to_string(), price);
+        self.seeded_prices.remove(market);
     }
 
-    /// Validate an order against all risk checks.
+    /// Seed a market's reference price before it has traded (an oracle or
+    /// last-session price), so the deviation check bounds even its first
+    /// order. Without a reference, a market's first orders pass unchecked
+    /// and whoever trades first sets the price. Non-positive prices are
+    /// ignored.
+    pub fn seed_reference_price(&mut self, market: &str, price: Decimal) {
+        if price > Decimal::ZERO {
+            self.last_prices.insert(market.to_string(), price);
+            self.seeded_prices.insert(market.to_string());
+        }
+    }
+
+    /// Whether `market`'s reference price was seeded rather than traded.
+    #[must_use]
+    pub fn is_reference_seeded(&self, market: &str) -> bool {
+        self.seeded_prices.contains(market)
+    }
+
+    /// Apply a market's config. Markets with `trading_enabled == false`
+    /// are halted; re-applying with the flag set resumes them. The tick
+    /// size is enforced on limit prices from then on.
+    pub fn apply_market_config(&mut self, config: &MarketConfig) {
+        if config.tick_size > Decimal::ZERO {
+            self.tick_sizes.insert(config.symbol(), config.tick_size);
+        }
+        if config.trading_enabled {
+            self.halted_markets.remove(&config.symbol());
+        } else {
+            self.halted_markets.insert(config.symbol());
+        }
+    }
+
+    /// Returns `true` if the market is currently halted.
+    #[must_use]
+    pub fn is_market_halted(&self, market: &str) -> bool {
+        self.halted_markets.contains(market)
+    }
+
+    /// Bring an order's limit price onto its market's tick grid according
+    /// to the [`TickPolicy`]. Call before [`RiskKernel::validate`], which
+    /// refuses off-tick prices. Orders without a price, and markets with
+    /// no applied config, are left alone.
+    ///
+    /// # Errors
+    /// `InvalidOrder` if the price is off-tick under [`TickPolicy::Reject`],
+    /// or if snapping would leave a non-positive price.
+    pub fn normalize_price(&self, order: &mut Order) -> Result<()> {
+        let (Some(price), Some(&tick)) = (order.price, self.tick_sizes.get(&order.market.symbol()))
+        else {
+            return Ok(());
+        };
+        let steps = price / tick;
+        if steps.fract().is_zero() {
+            return Ok(());
+        }
+        let snapped = match self.tick_policy {
+            TickPolicy::Reject => return Err(Self::off_tick(price, tick)),
+            TickPolicy::SnapDown => steps.floor() * tick,
+            TickPolicy::SnapToNearest => {
+                steps.round_dp_with_strategy(0, RoundingStrategy::MidpointNearestEven) * tick
+            }
+        };
+        if snapped <= Decimal::ZERO {
+            return Err(OpenmatchError::InvalidOrder {
+                reason: format!("price {price} snaps to {snapped} on tick {tick}"),
+            });
+        }
+        order.price = Some(snapped.normalize());
+        Ok(())
+    }
+
+    /// Validate an order against all risk checks, timestamped now.
     ///
     /// # Errors
     /// Returns specific error for each check that fails.
     pub fn validate(&mut self, order: &Order) -> Result<()> {
-        // 1. Basic validation
+        self.validate_at(order, Utc::now())
+    }
+
+    /// Validate an order received at `now`.
+    ///
+    /// `now` keys the per-second rate limit; callers should pass
+    /// non-decreasing timestamps.
+    ///
+    /// # Errors
+    /// Returns specific error for each check that fails.
+    pub fn validate_at(&mut self, order: &Order, now: DateTime<Utc>) -> Result<()> {
+        if let Some(reason) = self.run_checks(order, now, true).into_iter().next() {
+            return Err(Self::rejection_error(reason));
+        }
+        self.record_accepted(order, now);
+        Ok(())
+    }
+
+    /// Run every independent check and return all violations, timestamped
+    /// now. An empty vec means the order was approved and counted, exactly
+    /// as if [`RiskKernel::validate`] had returned `Ok`.
+    ///
+    /// The phase gate and the quantity check are prerequisites: if either
+    /// fails, its reason is the only one returned.
+    pub fn validate_all(&mut self, order: &Order) -> Vec<RiskRejectionReason> {
+        self.validate_all_at(order, Utc::now())
+    }
+
+    /// [`RiskKernel::validate_all`] for an order received at `now`.
+    pub fn validate_all_at(
+        &mut self,
+        order: &Order,
+        now: DateTime<Utc>,
+    ) -> Vec<RiskRejectionReason> {
+        let violations = self.run_checks(order, now, false);
+        if violations.is_empty() {
+            self.record_accepted(order, now);
+        }
+        violations
+    }
+
+    /// Run the checks in order, stopping at the first violation if
+    /// `fail_fast`. Does not count the order.
+    fn run_checks(
+        &mut self,
+        order: &Order,
+        now: DateTime<Utc>,
+        fail_fast: bool,
+    ) -> Vec<RiskRejectionReason> {
+        // 0. Phase gate, 1. Basic validation
+        if let Err(err) = self
+            .check_phase(order)
+            .and_then(|()| Self::check_quantity(order))
+        {
+            return vec![err];
+        }
+
+        // 2. Cancel orders bypass most checks (including a market halt,
+        //    so users can always exit)
+        if order.order_type.is_cancel() {
+            return Vec::new();
+        }
+
+        self.prune_rate_windows(order, now);
+        let checks: [fn(&Self, &Order) -> Check; 7] = [
+            Self::check_halt,
+            Self::check_size,
+            Self::check_price,
+            Self::check_open_orders,
+            Self::check_rate,
+            Self::check_epoch_count,
+            Self::check_batch_count,
+        ];
+        let mut violations = Vec::new();
+        for check in checks {
+            if let Err(err) = check(self, order) {
+                violations.push(err);
+                if fail_fast {
+                    return violations;
+                }
+            }
+        }
+        violations.extend(self.check_order_id_skew(order, now).err());
+        violations
+    }
+
+    /// The error [`RiskKernel::validate`] reports for `reason`.
+    fn rejection_error(reason: RiskRejectionReason) -> OpenmatchError {
+        use RiskRejectionReason as R;
+        let invalid = |reason: String| OpenmatchError::InvalidOrder { reason };
+        match reason {
+            R::PhaseClosed { phase } => OpenmatchError::WrongEpochPhase {
+                expected: EpochPhase::Collect,
+                actual: phase,
+            },
+            R::NonPositiveQuantity { .. } => invalid("Quantity must be positive".to_string()),
+            R::MarketHalted { .. } => invalid("market halted".to_string()),
+            R::OrderTooLarge { size, limit } => {
+                invalid(format!("Order size {size} exceeds maximum {limit}"))
+            }
+            R::NonPositivePrice { .. } => OpenmatchError::SuspiciousPrice {
+                reason: "Price must be positive".to_string(),
+            },
+            R::OffTick { price, tick } => Self::off_tick(price, tick),
+            R::PriceDeviation {
+                price,
+                reference,
+                ratio,
+                limit,
+            } => OpenmatchError::SuspiciousPrice {
+                reason: format!(
+                    "Price {price} deviates {ratio}x from last known {reference} (max {limit}x)"
+                ),
+            },
+            R::EpochOrderLimit { count, .. } | R::BatchOrderLimit { count, .. } => {
+                OpenmatchError::OrderFloodDetected {
+                    count,
+                    window_ms: 0, // epoch/batch-based, not time-based
+                }
+            }
+            R::OrderIdSkew { skew_ms, limit_ms } => invalid(format!(
+                "order id time is {skew_ms}ms from the node clock (max {limit_ms}ms)"
+            )),
+            reason => OpenmatchError::RiskRejected(reason),
+        }
+    }
+
+    /// 10. Order ID timestamp against the node clock
+    fn check_order_id_skew(&self, order: &Order, now: DateTime<Utc>) -> Check {
+        let Some(tolerance) = self.max_order_id_skew else {
+            return Ok(());
+        };
+        let embedded = i64::try_from(order.id.timestamp_ms()).unwrap_or(i64::MAX);
+        let skew_ms = now.timestamp_millis().abs_diff(embedded);
+        if skew_ms > tolerance.num_milliseconds().unsigned_abs() {
+            return Err(RiskRejectionReason::OrderIdSkew {
+                skew_ms,
+                limit_ms: tolerance.num_milliseconds(),
+            });
+        }
+        Ok(())
+    }
+
+    fn record_accepted(&mut self, order: &Order, now: DateTime<Utc>) {
+        if order.order_type.is_cancel() {
+            return;
+        }
+        *self.epoch_order_counts.entry(order.user_id).or_insert(0) += 1;
+        *self.batch_order_counts.entry(order.user_id).or_insert(0) += 1;
+        if self.max_open_orders.is_some()
+            && self.open_orders.insert(order.id, order.user_id).is_none()
+        {
+            *self.open_order_counts.entry(order.user_id).or_insert(0) += 1;
+        }
+        if let Some(recent) = self.recent_orders.get_mut(&order.user_id) {
+            recent.push_back(now);
+        }
+        if let Some(recent) = self
+            .recent_market_orders
+            .get_mut(&(order.user_id, order.market.symbol()))
+        {
+            recent.push_back(now);
+        }
+    }
+
+    fn check_phase(&self, order: &Order) -> Check {
+        let admitted = if order.order_type.is_cancel() {
+            self.phase.allows_cancels()
+        } else {
+            self.phase.allows_new_orders()
+        };
+        if admitted {
+            Ok(())
+        } else {
+            Err(RiskRejectionReason::PhaseClosed { phase: self.phase })
+        }
+    }
+
+    fn check_quantity(order: &Order) -> Check {
         if order.quantity.is_zero() || order.quantity.is_sign_negative() {
-            return Err(OpenmatchError::InvalidOrder {
-                reason: "Quantity must be positive".to_string(),
+            return Err(RiskRejectionReason::NonPositiveQuantity {
+                quantity: order.quantity,
             });
         }
+        Ok(())
+    }
 
-        // 2. Cancel orders bypass most checks
-        if order.order_type == OrderType::Cancel {
-            return Ok(());
+    /// 3. Market halt
+    fn check_halt(&self, order: &Order) -> Check {
+        let market = order.market.symbol();
+        if self.is_market_halted(&market) {
+            return Err(RiskRejectionReason::MarketHalted { market });
         }
+        Ok(())
+    }
 
-        // 3. Order size check
+    /// 4. Order size check
+    fn check_size(&self, order: &Order) -> Check {
         if order.quantity > self.max_order_size {
-            return Err(OpenmatchError::InvalidOrder {
-                reason: format!(
-                    "Order size {} exceeds maximum {}",
-                    order.quantity, self.max_order_size,
-                ),
+            return Err(RiskRejectionReason::OrderTooLarge {
+                size: order.quantity,
+                limit: self.max_order_size,
             });
         }
+        Ok(())
+    }
 
-        // 4. Price sanity check (for limit orders)
-        if order.order_type == OrderType::Limit {
+    /// 5. Price sanity check (for limit orders)
+    fn check_price(&self, order: &Order) -> Check {
+        if order.order_type.is_limit() {
             if let Some(price) = order.price {
                 if price.is_zero() || price.is_sign_negative() {
-                    return Err(OpenmatchError::SuspiciousPrice {
-                        reason: "Price must be positive".to_string(),
-                    });
+                    return Err(RiskRejectionReason::NonPositivePrice { price });
+                }
+                if let Some(&tick) = self.tick_sizes.get(&order.market.symbol()) {
+                    if !(price / tick).fract().is_zero() {
+                        return Err(RiskRejectionReason::OffTick { price, tick });
+                    }
                 }
                 self.check_price_deviation(&order.market.symbol(), price)?;
             }
         }
+        Ok(())
+    }
 
-        // 5. Per-user epoch rate limit
-        let count = self.epoch_order_counts.entry(order.user_id).or_insert(0);
-        if *count >= self.max_orders_per_user_per_epoch {
-            return Err(OpenmatchError::OrderFloodDetected {
-                count: *count,
-                window_ms: 0, // epoch-based, not time-based
+    /// 6. Open-order cap
+    fn check_open_orders(&self, order: &Order) -> Check {
+        let Some(limit) = self.max_open_orders else {
+            return Ok(());
+        };
+        let open = self.open_order_count(&order.user_id);
+        if open >= limit {
+            return Err(RiskRejectionReason::OrderCountExceeded {
+                current: open,
+                limit,
             });
         }
-        *count += 1;
+        Ok(())
+    }
+
+    /// Drop the order's user's acceptance times at least one second older
+    /// than `now`, in every enabled window.
+    fn prune_rate_windows(&mut self, order: &Order, now: DateTime<Utc>) {
+        let prune = |recent: &mut VecDeque<DateTime<Utc>>| {
+            while recent
+                .front()
+                .is_some_and(|t| now - *t >= Duration::seconds(1))
+            {
+                recent.pop_front();
+            }
+        };
+        if self.max_orders_per_second.is_some() {
+            prune(self.recent_orders.entry(order.user_id).or_default());
+        }
+        if self.max_orders_per_second_per_market.is_some() {
+            let key = (order.user_id, order.market.symbol());
+            prune(self.recent_market_orders.entry(key).or_default());
+        }
+    }
 
+    /// 7. Per-user sliding one-second rate limits, overall and per market
+    ///    (windows already pruned)
+    fn check_rate(&self, order: &Order) -> Check {
+        let check = |recent: Option<&VecDeque<DateTime<Utc>>>, limit: u32| {
+            let in_window = recent.map_or(0, |r| u32::try_from(r.len()).unwrap_or(u32::MAX));
+            if in_window >= limit {
+                return Err(RiskRejectionReason::RateLimitExceeded {
+                    orders_this_second: in_window,
+                    limit,
+                });
+            }
+            Ok(())
+        };
+        if let Some(limit) = self.max_orders_per_second {
+            check(self.recent_orders.get(&order.user_id), limit)?;
+        }
+        if let Some(limit) = self.max_orders_per_second_per_market {
+            let key = (order.user_id, order.market.symbol());
+            check(self.recent_market_orders.get(&key), limit)?;
+        }
         Ok(())
     }
 
+    /// 8. Per-user epoch rate limit
+    fn check_epoch_count(&self, order: &Order) -> Check {
+        let count = self.user_order_count(&order.user_id);
+        let limit = self.max_orders_per_user_per_epoch;
+        if count >= limit {
+            return Err(RiskRejectionReason::EpochOrderLimit { count, limit });
+        }
+        Ok(())
+    }
+
+    /// 9. Per-user batch cap
+    fn check_batch_count(&self, order: &Order) -> Check {
+        let count = self.user_batch_order_count(&order.user_id);
+        match self.max_orders_per_user_per_batch {
+            Some(limit) if count >= limit => {
+                Err(RiskRejectionReason::BatchOrderLimit { count, limit })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Report that an open order left the book (expired, or removed
+    /// outside a bundle), freeing its owner's slot under the open-order
+    /// cap. Closing an unknown or already closed order is a no-op.
+    pub fn order_closed(&mut self, order_id: &OrderId) {
+        let Some(user_id) = self.open_orders.remove(order_id) else {
+            return;
+        };
+        if let Some(count) = self.open_order_counts.get_mut(&user_id) {
+            *count -= 1;
+            if *count == 0 {
+                self.open_order_counts.remove(&user_id);
+            }
+        }
+    }
+
+    /// Close every order `bundle` fully filled or cancelled.
+    ///
+    /// An order that traded but is still among the bundle's remaining
+    /// orders stays open.
+    pub fn apply_bundle(&mut self, bundle: &TradeBundle) {
+        let still_open: HashSet<OrderId> = bundle.remaining_orders.iter().map(|o| o.id).collect();
+        let filled = bundle
+            .trades
+            .iter()
+            .flat_map(|t| [t.taker_order_id, t.maker_order_id])
+            .filter(|id| !still_open.contains(id));
+        let cancelled = bundle.cancelled_orders.iter().map(|o| o.id);
+        for order_id in filled.chain(cancelled).collect::<Vec<_>>() {
+            self.order_closed(&order_id);
+        }
+    }
+
+    /// Validate an order submitted by an agent.
+    ///
+    /// The agent must be bound to the order's user and active in `registry`
+    /// before any other check runs.
+    ///
+    /// # Errors
+    /// `RiskRejected(AgentNotActive)` if the agent isn't authorized,
+    /// otherwise any error from [`RiskKernel::validate`].
+    pub fn validate_agent_order(
+        &mut self,
+        registry: &AgentRegistry,
+        agent_id: &AgentId,
+        order: &Order,
+    ) -> Result<()> {
+        registry.check(agent_id, &order.user_id)?;
+        self.validate(order)
+    }
+
+    /// Pre-settlement check: every user whose net base delta in a market
+    /// across `bundle` exceeds the batch net-position limit, paired with a
+    /// `BatchNetPositionExceeded` rejection, ordered by user then market.
+    ///
+    /// Empty when no limit is configured or every position is within it.
+    #[must_use]
+    pub fn check_batch_net_positions(
+        &self,
+        bundle: &TradeBundle,
+    ) -> Vec<(UserId, RiskRejectionReason)> {
+        let Some(limit) = self.max_batch_net_position else {
+            return Vec::new();
+        };
+        let mut net: BTreeMap<(UserId, String), Decimal> = BTreeMap::new();
+        for trade in &bundle.trades {
+            let (buyer_id, seller_id) = if trade.taker_is_buyer() {
+                (trade.taker_user_id, trade.maker_user_id)
+            } else {
+                (trade.maker_user_id, trade.taker_user_id)
+            };
+            let market = trade.market.symbol();
+            *net.entry((buyer_id, market.clone())).or_default() += trade.quantity;
+            *net.entry((seller_id, market)).or_default() -= trade.quantity;
+        }
+        net.into_iter()
+            .filter(|(_, delta)| delta.abs() > limit)
+            .map(|((user_id, market), net)| {
+                let reason = RiskRejectionReason::BatchNetPositionExceeded { market, net, limit };
+                (user_id, reason)
+            })
+            .collect()
+    }
+
+    fn off_tick(price: Decimal, tick: Decimal) -> OpenmatchError {
+        OpenmatchError::InvalidOrder {
+            reason: format!("price {price} is not a multiple of tick {tick}"),
+        }
+    }
+
     /// Check if a price deviates too far from the last known price.
-    fn check_price_deviation(&self, market: &str, price: Decimal) -> Result<()> {
+    fn check_price_deviation(&self, market: &str, price: Decimal) -> Check {
         if let Some(last_price) = self.last_prices.get(market) {
             if !last_price.is_zero() {
                 let ratio = if price > *last_price {
@@ -135,12 +748,11 @@ impl RiskKernel {
                     *last_price / price
                 };
                 if ratio > self.max_price_deviation {
-                    return Err(OpenmatchError::SuspiciousPrice {
-                        reason: format!(
-                            "Price {price} deviates {ratio}x from last known {last_price} \
-                             (max {max}x)",
-                            max = self.max_price_deviation,
-                        ),
+                    return Err(RiskRejectionReason::PriceDeviation {
+                        price,
+                        reference: *last_price,
+                        ratio,
+                        limit: self.max_price_deviation,
                     });
                 }
             }
@@ -148,11 +760,23 @@ impl RiskKernel {
         Ok(())
     }
 
+    /// Number of accepted orders for a user that are still open.
+    #[must_use]
+    pub fn open_order_count(&self, user_id: &UserId) -> usize {
+        self.open_order_counts.get(user_id).copied().unwrap_or(0)
+    }
+
     /// Get the order count for a user in the current epoch.
     #[must_use]
     pub fn user_order_count(&self, user_id: &UserId) -> usize {
         self.epoch_order_counts.get(user_id).copied().unwrap_or(0)
     }
+
+    /// Get the order count for a user in the batch currently collecting.
+    #[must_use]
+    pub fn user_batch_order_count(&self, user_id: &UserId) -> usize {
+        self.batch_order_counts.get(user_id).copied().unwrap_or(0)
+    }
 }
 
 impl Default for RiskKernel {
@@ -207,6 +831,32 @@ mod tests {
         assert!(matches!(err, OpenmatchError::SuspiciousPrice { .. }));
     }
 
+    #[test]
+    fn seeded_reference_bounds_first_order() {
+        let mut rk = RiskKernel::new();
+        let far = make_buy(Decimal::new(2000, 0), Decimal::ONE);
+
+        // No reference: the first order passes unchecked
+        assert!(rk.validate(&far).is_ok());
+
+        let mut rk = RiskKernel::new();
+        rk.seed_reference_price("BTC/USDT", Decimal::new(100, 0));
+        assert!(rk.is_reference_seeded("BTC/USDT"));
+        assert!(matches!(
+            rk.validate(&far),
+            Err(OpenmatchError::SuspiciousPrice { .. })
+        ));
+        assert!(
+            rk.validate(&make_buy(Decimal::new(150, 0), Decimal::ONE))
+                .is_ok()
+        );
+
+        // A traded price replaces the seed
+        rk.set_last_price("BTC/USDT", Decimal::new(1500, 0));
+        assert!(!rk.is_reference_seeded("BTC/USDT"));
+        assert!(rk.validate(&far).is_ok());
+    }
+
     #[test]
     fn reasonable_price_passes() {
         let mut rk = RiskKernel::new();
@@ -254,11 +904,481 @@ mod tests {
         assert!(rk.validate(&order).is_ok());
     }
 
+    #[test]
+    fn tick_policy_reject_refuses_off_tick_price() {
+        let mut rk = RiskKernel::new();
+        rk.apply_market_config(&MarketConfig::btc_usdt());
+        let mut order = make_buy(Decimal::new(100_005, 3), Decimal::ONE);
+
+        assert!(rk.normalize_price(&mut order).is_err());
+        assert!(matches!(
+            rk.validate(&order),
+            Err(OpenmatchError::InvalidOrder { .. })
+        ));
+        let mut on_tick = make_buy(Decimal::new(10_001, 2), Decimal::ONE);
+        rk.normalize_price(&mut on_tick).unwrap();
+        assert!(rk.validate(&on_tick).is_ok());
+    }
+
+    #[test]
+    fn tick_policy_snap_down_lowers_both_sides() {
+        let mut rk = RiskKernel::new().with_tick_policy(TickPolicy::SnapDown);
+        rk.apply_market_config(&MarketConfig::btc_usdt());
+
+        let mut buy = make_buy(Decimal::new(100_009, 3), Decimal::ONE);
+        rk.normalize_price(&mut buy).unwrap();
+        assert_eq!(buy.price, Some(Decimal::new(10_000, 2)));
+
+        // For a sell, snapping down is the more aggressive direction.
+        let mut sell = Order::dummy_limit(OrderSide::Sell, Decimal::new(100_009, 3), Decimal::ONE);
+        rk.normalize_price(&mut sell).unwrap();
+        assert_eq!(sell.price, Some(Decimal::new(10_000, 2)));
+        assert!(rk.validate(&sell).is_ok());
+
+        // Below one tick there is nothing to snap down to.
+        let mut dust = make_buy(Decimal::new(5, 3), Decimal::ONE);
+        assert!(rk.normalize_price(&mut dust).is_err());
+    }
+
+    #[test]
+    fn tick_policy_snap_to_nearest() {
+        let mut rk = RiskKernel::new().with_tick_policy(TickPolicy::SnapToNearest);
+        rk.apply_market_config(&MarketConfig::btc_usdt());
+
+        let mut order = make_buy(Decimal::new(100_009, 3), Decimal::ONE);
+        rk.normalize_price(&mut order).unwrap();
+        assert_eq!(order.price, Some(Decimal::new(10_001, 2)));
+
+        // Half-tick goes to the even tick.
+        let mut half = make_buy(Decimal::new(100_015, 3), Decimal::ONE);
+        rk.normalize_price(&mut half).unwrap();
+        assert_eq!(half.price, Some(Decimal::new(10_002, 2)));
+        assert!(rk.validate(&half).is_ok());
+    }
+
+    #[test]
+    fn batch_cap_rejects_until_next_seal() {
+        let mut rk = RiskKernel::new().with_max_orders_per_user_per_batch(2);
+        let (user, other) = (UserId::new(), UserId::new());
+        let order_for = |user_id| {
+            let mut order = make_buy(Decimal::new(100, 0), Decimal::ONE);
+            order.user_id = user_id;
+            order
+        };
+
+        rk.validate(&order_for(user)).unwrap();
+        rk.validate(&order_for(user)).unwrap();
+        let err = rk.validate(&order_for(user)).unwrap_err();
+        assert!(matches!(
+            err,
+            OpenmatchError::OrderFloodDetected { count: 2, .. }
+        ));
+        // Other users have their own budget.
+        rk.validate(&order_for(other)).unwrap();
+
+        rk.batch_sealed();
+        assert_eq!(rk.user_batch_order_count(&user), 0);
+        rk.validate(&order_for(user)).unwrap();
+        // The epoch count keeps accumulating across batches.
+        assert_eq!(rk.user_order_count(&user), 3);
+    }
+
+    #[test]
+    fn open_order_cap_blocks_next_order() {
+        let mut rk = RiskKernel::new().with_max_open_orders(3);
+        let user = UserId::new();
+
+        for _ in 0..3 {
+            let mut order = make_buy(Decimal::new(100, 0), Decimal::ONE);
+            order.user_id = user;
+            rk.validate(&order).unwrap();
+        }
+        assert_eq!(rk.open_order_count(&user), 3);
+
+        let mut order = make_buy(Decimal::new(100, 0), Decimal::ONE);
+        order.user_id = user;
+        let err = rk.validate(&order).unwrap_err();
+        assert!(matches!(
+            err,
+            OpenmatchError::RiskRejected(RiskRejectionReason::OrderCountExceeded {
+                current: 3,
+                limit: 3
+            })
+        ));
+        // Rejected order is not counted
+        assert_eq!(rk.open_order_count(&user), 3);
+
+        // Another user is unaffected
+        assert!(
+            rk.validate(&make_buy(Decimal::new(100, 0), Decimal::ONE))
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn closed_order_frees_open_slot() {
+        let mut rk = RiskKernel::new().with_max_open_orders(1);
+        let user = UserId::new();
+        let mut order = make_buy(Decimal::new(100, 0), Decimal::ONE);
+        order.user_id = user;
+
+        rk.validate(&order).unwrap();
+        assert!(rk.validate(&order).is_err());
+
+        rk.order_closed(&order.id);
+        assert_eq!(rk.open_order_count(&user), 0);
+        // Closing the same order twice frees only one slot.
+        rk.order_closed(&order.id);
+        assert_eq!(rk.open_order_count(&user), 0);
+        assert!(rk.validate(&order).is_ok());
+    }
+
+    #[test]
+    fn open_order_cap_is_opt_in() {
+        let mut rk = RiskKernel::with_limits(usize::MAX, Decimal::new(100, 0), Decimal::TEN);
+        let user = UserId::new();
+        for _ in 0..=constants::DEFAULT_MAX_ORDERS_PER_USER {
+            let mut order = make_buy(Decimal::new(100, 0), Decimal::ONE);
+            order.user_id = user;
+            rk.validate(&order).unwrap();
+        }
+        assert_eq!(rk.open_order_count(&user), 0);
+    }
+
+    #[test]
+    fn open_orders_survive_epoch_advance() {
+        let mut rk = RiskKernel::new().with_max_open_orders(1);
+        let user = UserId::new();
+        let mut order = make_buy(Decimal::new(100, 0), Decimal::ONE);
+        order.user_id = user;
+
+        rk.validate(&order).unwrap();
+        rk.advance_epoch(EpochId(1));
+        assert!(rk.validate(&order).is_err());
+    }
+
+    #[test]
+    fn agent_orders_require_active_binding() {
+        let mut rk = RiskKernel::new();
+        let mut registry = AgentRegistry::new();
+        let order = make_buy(Decimal::new(100, 0), Decimal::ONE);
+        let agent = AgentId::new();
+
+        let err = rk
+            .validate_agent_order(&registry, &agent, &order)
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            OpenmatchError::RiskRejected(RiskRejectionReason::AgentNotActive)
+        ));
+
+        registry
+            .bind(AgentBinding {
+                agent_id: agent,
+                user_id: order.user_id,
+                limits: RiskLimits::default(),
+                active: true,
+                name: "bot".to_string(),
+            })
+            .unwrap();
+        assert!(rk.validate_agent_order(&registry, &agent, &order).is_ok());
+
+        registry.pause(&agent).unwrap();
+        assert!(rk.validate_agent_order(&registry, &agent, &order).is_err());
+        // Rejected before counting toward the user's limits
+        assert_eq!(rk.user_order_count(&order.user_id), 1);
+    }
+
+    #[test]
+    fn halted_market_rejects_new_orders_but_accepts_cancels() {
+        let mut rk = RiskKernel::new();
+        let mut config = MarketConfig::btc_usdt();
+        config.trading_enabled = false;
+        rk.apply_market_config(&config);
+        assert!(rk.is_market_halted("BTC/USDT"));
+
+        let order = make_buy(Decimal::new(100, 0), Decimal::ONE);
+        let err = rk.validate(&order).unwrap_err();
+        assert!(
+            matches!(err, OpenmatchError::InvalidOrder { ref reason } if reason == "market halted")
+        );
+        assert_eq!(rk.open_order_count(&order.user_id), 0);
+
+        let mut cancel = make_buy(Decimal::new(100, 0), Decimal::ONE);
+        cancel.order_type = OrderType::Cancel {
+            target: OrderId::new(),
+        };
+        assert!(rk.validate(&cancel).is_ok());
+
+        // Other markets keep trading
+        let mut eth = make_buy(Decimal::new(100, 0), Decimal::ONE);
+        eth.market = MarketPair::new(Asset::lit("ETH"), Asset::lit("USDT"));
+        assert!(rk.validate(&eth).is_ok());
+
+        config.trading_enabled = true;
+        rk.apply_market_config(&config);
+        assert!(!rk.is_market_halted("BTC/USDT"));
+        assert!(rk.validate(&order).is_ok());
+    }
+
     #[test]
     fn cancel_orders_bypass_size_check() {
         let mut rk = RiskKernel::with_limits(50, Decimal::new(1, 0), Decimal::new(10, 0));
         let mut order = make_buy(Decimal::new(100, 0), Decimal::new(999, 0));
-        order.order_type = OrderType::Cancel;
+        order.order_type = OrderType::Cancel {
+            target: OrderId::new(),
+        };
+        assert!(rk.validate(&order).is_ok());
+    }
+
+    #[test]
+    fn orders_refused_outside_collect() {
+        let mut rk = RiskKernel::new();
+        let order = make_buy(Decimal::new(100, 0), Decimal::ONE);
+        let mut cancel = order.clone();
+        cancel.order_type = OrderType::Cancel { target: order.id };
+
+        rk.set_phase(EpochPhase::Seal).unwrap();
+        rk.set_phase(EpochPhase::Match).unwrap();
+        for o in [&order, &cancel] {
+            assert!(matches!(
+                rk.validate(o),
+                Err(OpenmatchError::WrongEpochPhase {
+                    expected: EpochPhase::Collect,
+                    actual: EpochPhase::Match
+                })
+            ));
+        }
+
+        rk.set_phase(EpochPhase::Finalize).unwrap();
+        rk.set_phase(EpochPhase::Collect).unwrap();
+        assert!(rk.validate(&order).is_ok());
+        assert!(rk.validate(&cancel).is_ok());
+    }
+
+    #[test]
+    fn phase_skip_is_rejected() {
+        let mut rk = RiskKernel::new();
+        rk.set_phase(EpochPhase::Collect).unwrap();
+        assert!(matches!(
+            rk.set_phase(EpochPhase::Match),
+            Err(OpenmatchError::WrongEpochPhase {
+                expected: EpochPhase::Seal,
+                actual: EpochPhase::Match
+            })
+        ));
+        // Still collecting
+        let order = make_buy(Decimal::new(100, 0), Decimal::ONE);
         assert!(rk.validate(&order).is_ok());
     }
+
+    #[test]
+    fn per_second_rate_limit_uses_sliding_window() {
+        let mut rk = RiskKernel::new().with_max_orders_per_second(3);
+        let ms = Duration::milliseconds;
+        let t0 = Utc::now();
+        let mut order = make_buy(Decimal::new(100, 0), Decimal::ONE);
+        order.user_id = UserId::new();
+
+        for i in 0..3 {
+            rk.validate_at(&order, t0 + ms(i * 100)).unwrap();
+        }
+        let err = rk.validate_at(&order, t0 + ms(900)).unwrap_err();
+        assert!(matches!(
+            err,
+            OpenmatchError::RiskRejected(RiskRejectionReason::RateLimitExceeded {
+                orders_this_second: 3,
+                limit: 3
+            })
+        ));
+        // Rejected burst is not counted toward the epoch cap
+        assert_eq!(rk.user_order_count(&order.user_id), 3);
+
+        // Other users have their own window
+        assert!(
+            rk.validate_at(&make_buy(Decimal::new(100, 0), Decimal::ONE), t0 + ms(900))
+                .is_ok()
+        );
+
+        // One second after the first order, its slot frees up
+        rk.validate_at(&order, t0 + ms(1000)).unwrap();
+        assert!(rk.validate_at(&order, t0 + ms(1050)).is_err());
+        // A full second later the window is empty again
+        for i in 0..3 {
+            rk.validate_at(&order, t0 + ms(2100 + i)).unwrap();
+        }
+    }
+
+    #[test]
+    fn per_market_rate_limit_leaves_other_markets_open() {
+        let mut rk = RiskKernel::new()
+            .with_max_orders_per_second(3)
+            .with_max_orders_per_second_per_market(2);
+        let now = Utc::now();
+        let user = UserId::new();
+        let order_in = |market: &MarketPair| {
+            let mut order = make_buy(Decimal::new(100, 0), Decimal::ONE);
+            order.user_id = user;
+            order.market = market.clone();
+            order
+        };
+        let btc = MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT"));
+        let eth = MarketPair::new(Asset::lit("ETH"), Asset::lit("USDT"));
+
+        rk.validate_at(&order_in(&btc), now).unwrap();
+        rk.validate_at(&order_in(&btc), now).unwrap();
+        assert!(matches!(
+            rk.validate_at(&order_in(&btc), now),
+            Err(OpenmatchError::RiskRejected(
+                RiskRejectionReason::RateLimitExceeded {
+                    orders_this_second: 2,
+                    limit: 2
+                }
+            ))
+        ));
+
+        // BTC is saturated, ETH is not...
+        rk.validate_at(&order_in(&eth), now).unwrap();
+        // ...but the overall cap still counts both markets
+        assert!(matches!(
+            rk.validate_at(&order_in(&eth), now),
+            Err(OpenmatchError::RiskRejected(
+                RiskRejectionReason::RateLimitExceeded {
+                    orders_this_second: 3,
+                    limit: 3
+                }
+            ))
+        ));
+    }
+
+    #[test]
+    fn validate_all_reports_every_violation() {
+        let mut rk = RiskKernel::with_limits(50, Decimal::new(10, 0), Decimal::new(10, 0))
+            .with_max_orders_per_second(1);
+        rk.apply_market_config(&MarketConfig::btc_usdt());
+        let now = Utc::now();
+        let mut order = make_buy(Decimal::new(100, 0), Decimal::ONE);
+        assert!(rk.validate_all_at(&order, now).is_empty());
+        assert_eq!(rk.user_order_count(&order.user_id), 1);
+
+        // Oversized, off-tick and over the per-second rate, all at once
+        let mut bad = make_buy(Decimal::new(100_005, 3), Decimal::new(20, 0));
+        bad.user_id = order.user_id;
+        let violations = rk.validate_all_at(&bad, now);
+        assert_eq!(violations.len(), 3, "{violations:?}");
+        assert_eq!(
+            violations,
+            vec![
+                RiskRejectionReason::OrderTooLarge {
+                    size: Decimal::new(20, 0),
+                    limit: Decimal::new(10, 0),
+                },
+                RiskRejectionReason::OffTick {
+                    price: Decimal::new(100_005, 3),
+                    tick: MarketConfig::btc_usdt().tick_size,
+                },
+                RiskRejectionReason::RateLimitExceeded {
+                    orders_this_second: 1,
+                    limit: 1,
+                },
+            ]
+        );
+        // Rejected orders are not counted; validate still stops at the first
+        assert_eq!(rk.user_order_count(&order.user_id), 1);
+        assert!(matches!(
+            rk.validate_at(&bad, now),
+            Err(OpenmatchError::InvalidOrder { .. })
+        ));
+
+        order.quantity = Decimal::ZERO;
+        assert_eq!(
+            rk.validate_all_at(&order, now),
+            vec![RiskRejectionReason::NonPositiveQuantity {
+                quantity: Decimal::ZERO
+            }]
+        );
+    }
+
+    #[test]
+    fn order_id_skew_rejects_far_past_and_future_ids() {
+        let mut rk = RiskKernel::new().with_max_order_id_skew(Duration::minutes(5));
+        let now = Utc::now();
+        let with_id_at = |at: DateTime<Utc>| {
+            let mut bytes = [0u8; 16];
+            bytes[..6].copy_from_slice(&at.timestamp_millis().to_be_bytes()[2..]);
+            bytes[6] = 0x70; // version 7
+            let mut order = make_buy(Decimal::new(100, 0), Decimal::ONE);
+            order.id = OrderId::from_bytes(bytes);
+            order
+        };
+
+        assert!(
+            rk.validate_at(&with_id_at(now - Duration::minutes(4)), now)
+                .is_ok()
+        );
+        assert!(
+            rk.validate_at(&with_id_at(now + Duration::seconds(30)), now)
+                .is_ok()
+        );
+        for at in [now - Duration::days(30), now + Duration::hours(1)] {
+            assert!(matches!(
+                rk.validate_at(&with_id_at(at), now),
+                Err(OpenmatchError::InvalidOrder { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn batch_net_position_flags_combined_fills() {
+        let (whale, alice, bob) = (UserId::new(), UserId::new(), UserId::new());
+        let fill = |seq, buyer, seller, qty| Trade {
+            id: TradeId::deterministic(1, seq),
+            epoch_id: EpochId(1),
+            market: MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")),
+            taker_order_id: OrderId::new(),
+            taker_user_id: buyer,
+            maker_order_id: OrderId::new(),
+            maker_user_id: seller,
+            price: Decimal::new(100, 0),
+            quantity: qty,
+            quote_amount: Decimal::new(100, 0) * qty,
+            taker_side: OrderSide::Buy,
+            matcher_node: NodeId([0u8; 32]),
+            executed_at: Utc::now(),
+            quote_dust: None,
+        };
+        // Each fill is within the limit; together the whale nets +2.5 BTC.
+        let bundle = TradeBundle {
+            epoch_id: EpochId(1),
+            trades: vec![
+                fill(0, whale, alice, Decimal::new(15, 1)),
+                fill(1, whale, bob, Decimal::ONE),
+            ],
+            trade_root: [0u8; 32],
+            input_hash: [0u8; 32],
+            clearing_price: Some(Decimal::new(100, 0)),
+            remaining_orders: vec![],
+            cancelled_orders: vec![],
+            unfilled_reasons: vec![],
+            wash_trade_attempts: vec![],
+        };
+
+        let rk = RiskKernel::new().with_max_batch_net_position(Decimal::TWO);
+        assert_eq!(
+            rk.check_batch_net_positions(&bundle),
+            vec![(
+                whale,
+                RiskRejectionReason::BatchNetPositionExceeded {
+                    market: "BTC/USDT".to_string(),
+                    net: Decimal::new(25, 1),
+                    limit: Decimal::TWO,
+                }
+            )]
+        );
+        assert!(
+            RiskKernel::new()
+                .check_batch_net_positions(&bundle)
+                .is_empty()
+        );
+    }
 }
@@ -4,13 +4,20 @@
 //! computes the uniform clearing price where supply meets demand.
 //!
 //! The clearing price algorithm is deterministic: same inputs → same price.
+//! This holds byte-for-byte, not just numerically: every `Decimal` in the
+//! [`ClearingResult`] is normalized, so numerically equal prices with
+//! different scales (`100` vs `100.00`) cannot leak insertion order into
+//! the result.
 
-use rust_decimal::Decimal;
+use std::cmp::Ordering;
 
-use crate::OrderBook;
+use openmatch_types::{MarketConfig, OpenmatchError, Order, OrderId, Result};
+use rust_decimal::{Decimal, RoundingStrategy};
+
+use crate::{OrderBook, PriceLevel};
 
 /// Result of clearing price computation.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ClearingResult {
     /// The uniform clearing price, if supply and demand cross.
     pub clearing_price: Option<Decimal>,
@@ -20,106 +27,436 @@ pub struct ClearingResult {
     pub best_bid: Option<Decimal>,
     /// Best ask that contributed to the crossing.
     pub best_ask: Option<Decimal>,
+    /// Number of candidate prices swept (zero when the early no-overlap
+    /// check or the market-only path decided the result).
+    pub candidates_evaluated: usize,
+}
+
+/// Post-trade analytics for a clearing result.
+///
+/// Derived from the orders that execute at the clearing price, filled in
+/// price-time priority up to `matchable_volume`. Market orders carry no
+/// limit price, so they fill but add no surplus.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ClearingMetrics {
+    /// `Σ (bid limit − clearing price) × filled quantity`.
+    pub buyer_surplus: Decimal,
+    /// `Σ (clearing price − ask limit) × filled quantity`.
+    pub seller_surplus: Decimal,
+    /// Least-aggressive filled order whose limit equals the clearing
+    /// price. If both sides have one, the rationed side (the one with
+    /// excess volume at the price) wins, bids on an exact balance. `None`
+    /// when the price falls strictly between levels.
+    pub marginal_price_setter: Option<OrderId>,
+}
+
+/// How to pick the final price when several candidate prices clear the
+/// same (maximal) volume with the same demand/supply imbalance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ClearingTieBreak {
+    /// Highest tied candidate price (benefits sellers).
+    #[default]
+    FavorSellers,
+    /// Lowest tied candidate price (benefits buyers).
+    FavorBuyers,
+    /// `(p_low + p_high) / 2` of the tied range (snapped to the tick like
+    /// any other off-tick price).
+    Midpoint,
+}
+
+/// Tunable parameters for clearing price selection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ClearingConfig {
+    /// Final price selection among equally good candidates.
+    pub tie_break: ClearingTieBreak,
+    /// Price granularity. The final clearing price is snapped onto this
+    /// grid before trades are emitted; `None` leaves it unrounded.
+    pub tick_size: Option<Decimal>,
+    /// Price used when the crossing volume consists only of market orders,
+    /// which carry no price of their own (typically the last traded price).
+    pub reference_price: Option<Decimal>,
+    /// Sell-side reserve: the batch never clears below this price. If no
+    /// candidate at or above it moves volume, the batch does not clear.
+    pub reserve_price: Option<Decimal>,
+}
+
+impl ClearingConfig {
+    /// Default config quantizing to `market`'s tick size.
+    #[must_use]
+    pub fn for_market(market: &MarketConfig) -> Self {
+        Self {
+            tick_size: Some(market.tick_size),
+            ..Self::default()
+        }
+    }
+}
+
+/// Compute the uniform clearing price for a given order book using the
+/// default [`ClearingConfig`].
+#[must_use]
+pub fn compute_clearing_price(book: &OrderBook) -> ClearingResult {
+    compute_clearing_price_with(book, &ClearingConfig::default())
+}
+
+/// Compute the uniform clearing price for a given order book.
+///
+/// Same as [`try_compute_clearing_price`], except that a market-only book
+/// without a reference price is reported as "no crossing".
+#[must_use]
+pub fn compute_clearing_price_with(book: &OrderBook, config: &ClearingConfig) -> ClearingResult {
+    try_compute_clearing_price(book, config).unwrap_or_else(|_| ClearingResult {
+        clearing_price: None,
+        matchable_volume: Decimal::ZERO,
+        best_bid: book.best_bid().map(|p| p.normalize()),
+        best_ask: book.best_ask().map(|p| p.normalize()),
+        candidates_evaluated: 0,
+    })
 }
 
 /// Compute the uniform clearing price for a given order book.
 ///
 /// Algorithm:
-/// 1. Walk bid levels top-down, ask levels bottom-up
-/// 2. Accumulate demand (cumulative bid qty) and supply (cumulative ask qty)
-/// 3. Find the price level where cumulative demand ≥ cumulative supply
-/// 4. Clearing price = midpoint of the crossing bid and ask
+/// 0. If either side is empty or the best bid is below the best ask,
+///    return "no crossing" straight away: the book's best prices are read
+///    in O(1) and no candidate is built or swept
+/// 1. Every distinct limit price is a candidate price `p`; the market-order
+///    sentinels (`Decimal::MAX` bids, zero-priced asks) are not, and
+///    neither are orders with nothing remaining
+/// 2. `demand(p)` = bid quantity priced `>= p`, `supply(p)` = ask quantity
+///    priced `<= p`, `matchable(p)` = `min(demand(p), supply(p))`
+/// 3. Keep the candidates that maximize `matchable`, then minimize
+///    `|demand - supply|`, ignoring candidates below `config.reserve_price`
+/// 4. Pick the final price from the tied range `[p_low, p_high]` according
+///    to `config.tie_break`
+/// 5. Snap an off-tick price onto `config.tick_size` (see below)
+///
+/// # Final tie rule
+///
+/// Candidates are normalized and deduplicated, so every tied optimum is a
+/// distinct price and the tied set is summarized by its bounds
+/// `[p_low, p_high]` alone — no step depends on iteration order.
+///
+/// # Tick quantization
+///
+/// An off-tick price snaps to the adjacent tick inside `[p_low, p_high]`.
+/// If both or neither neighbour is in range, it moves toward the side that
+/// reduces imbalance: up on excess demand, down on excess supply. A
+/// balanced price exactly halfway between two ticks rounds to the even
+/// tick (banker's rounding). A snap below the reserve price goes up
+/// instead, and `matchable_volume` is re-measured at the snapped price.
+///
+/// # Determinism
+///
+/// The result does not depend on the order in which orders were inserted
+/// into the book. Level keys keep the scale of whichever order created the
+/// level, so all prices and volumes are normalized before being returned.
+///
+/// # Market orders
+///
+/// Market orders add volume at every candidate but never propose a price.
+/// A market buy facing limit sells therefore clears at a sell's limit
+/// price, and market sells on the same side only add supply there. If the
+/// book crosses but holds no limit price at all (market buys facing market
+/// sells only), the batch clears at `config.reference_price`, raised to
+/// `config.reserve_price` if it lies below it.
 ///
 /// # Returns
 /// A [`ClearingResult`] with the clearing price and matchable volume.
 /// If no crossing exists (best bid < best ask), `clearing_price` is `None`.
-#[must_use]
-pub fn compute_clearing_price(book: &OrderBook) -> ClearingResult {
-    let best_bid = book.best_bid();
-    let best_ask = book.best_ask();
+///
+/// # Errors
+/// `MatchingFailed` if only market orders cross and no reference price is
+/// configured.
+#[allow(clippy::too_many_lines)]
+pub fn try_compute_clearing_price(
+    book: &OrderBook,
+    config: &ClearingConfig,
+) -> Result<ClearingResult> {
+    let best_bid = book.best_bid().map(|p| p.normalize());
+    let best_ask = book.best_ask().map(|p| p.normalize());
 
-    // No crossing possible if either side is empty or bid < ask
+    let no_cross = ClearingResult {
+        clearing_price: None,
+        matchable_volume: Decimal::ZERO,
+        best_bid,
+        best_ask,
+        candidates_evaluated: 0,
+    };
+
+    // No crossing possible if either side is empty or bid < ask. This is
+    // the quiet-market hot path, so it runs before any level is collected.
     match (best_bid, best_ask) {
         (Some(bid), Some(ask)) if bid >= ask => {}
-        _ => {
-            return ClearingResult {
-                clearing_price: None,
-                matchable_volume: Decimal::ZERO,
-                best_bid,
-                best_ask,
-            };
-        }
+        _ => return Ok(no_cross),
     }
 
-    // Collect bid and ask levels for the crossing computation
-    let bid_levels: Vec<(Decimal, Decimal)> = book
+    // Aggregate levels in ascending price order for both sides. Levels
+    // holding only fully-filled orders carry no volume and must not
+    // propose a candidate price.
+    let mut bid_levels: Vec<(Decimal, Decimal)> = book
         .bid_levels()
         .map(|level| (level.price, level.total_quantity()))
+        .filter(|&(_, qty)| !qty.is_zero())
         .collect();
+    bid_levels.reverse();
 
     let ask_levels: Vec<(Decimal, Decimal)> = book
         .ask_levels()
         .map(|level| (level.price, level.total_quantity()))
+        .filter(|&(_, qty)| !qty.is_zero())
         .collect();
+    if bid_levels.is_empty() || ask_levels.is_empty() {
+        return Ok(no_cross);
+    }
 
-    // Walk from both ends to find the crossing
-    let mut cum_demand = Decimal::ZERO;
-    let mut cum_supply = Decimal::ZERO;
-    let mut matchable = Decimal::ZERO;
+    let mut candidates: Vec<Decimal> = bid_levels
+        .iter()
+        .map(|&(price, _)| price)
+        .filter(|&price| price != Decimal::MAX)
+        .chain(
+            ask_levels
+                .iter()
+                .map(|&(price, _)| price)
+                .filter(|price| !price.is_zero()),
+        )
+        .collect();
+    // Normalize before dedup so `100` and `100.00` are one candidate
+    for p in &mut candidates {
+        *p = p.normalize();
+    }
+    candidates.sort_unstable();
+    candidates.dedup();
+    let below_reserve = |price: Decimal| config.reserve_price.is_some_and(|r| price < r);
 
+    if candidates.is_empty() {
+        // Crossing, but only market orders: nothing in the book names a price.
+        let Some(reference) = config.reference_price else {
+            return Err(OpenmatchError::MatchingFailed {
+                reason: "only market orders cross and no reference price is configured".to_string(),
+            });
+        };
+        let demand: Decimal = bid_levels.iter().map(|&(_, qty)| qty).sum();
+        let supply: Decimal = ask_levels.iter().map(|&(_, qty)| qty).sum();
+        let mut price = match config.tick_size {
+            Some(tick) if tick > Decimal::ZERO => {
+                snap_to_tick(reference, tick, (reference, reference), demand.cmp(&supply))
+            }
+            _ => reference,
+        };
+        // Market orders accept any price, so the reserve simply lifts the
+        // reference (onto the next tick at or above it).
+        if let Some(reserve) = config.reserve_price.filter(|_| below_reserve(price)) {
+            price = match config.tick_size {
+                Some(tick) if tick > Decimal::ZERO => (reserve / tick).ceil() * tick,
+                _ => reserve,
+            };
+        }
+        return Ok(ClearingResult {
+            clearing_price: Some(price.normalize()),
+            matchable_volume: demand.min(supply).normalize(),
+            best_bid,
+            best_ask,
+            candidates_evaluated: 0,
+        });
+    }
+
+    // Sweep candidates in ascending order: supply grows, demand shrinks.
+    let mut demand: Decimal = bid_levels.iter().map(|&(_, qty)| qty).sum();
+    let mut supply = Decimal::ZERO;
     let mut bid_idx = 0;
     let mut ask_idx = 0;
 
-    while bid_idx < bid_levels.len() && ask_idx < ask_levels.len() {
-        let (bid_price, bid_qty) = bid_levels[bid_idx];
-        let (ask_price, ask_qty) = ask_levels[ask_idx];
+    // (volume, imbalance, p_low, p_high)
+    let mut best: Option<(Decimal, Decimal, Decimal, Decimal)> = None;
+    let candidates_evaluated = candidates.len();
+    let no_cross = ClearingResult {
+        candidates_evaluated,
+        ..no_cross
+    };
 
-        // No more crossing once bid < ask
-        if bid_price < ask_price {
-            break;
+    for p in candidates {
+        while bid_idx < bid_levels.len() && bid_levels[bid_idx].0 < p {
+            demand -= bid_levels[bid_idx].1;
+            bid_idx += 1;
+        }
+        while ask_idx < ask_levels.len() && ask_levels[ask_idx].0 <= p {
+            supply += ask_levels[ask_idx].1;
+            ask_idx += 1;
         }
 
-        cum_demand += bid_qty;
-        cum_supply += ask_qty;
-        matchable = cum_demand.min(cum_supply);
+        let matchable = demand.min(supply);
+        if matchable.is_zero() || below_reserve(p) {
+            continue;
+        }
+        let imbalance = (demand - supply).abs();
 
-        bid_idx += 1;
-        ask_idx += 1;
+        best = match best {
+            None => Some((matchable, imbalance, p, p)),
+            Some((volume, best_imbalance, low, _))
+                if matchable == volume && imbalance == best_imbalance =>
+            {
+                Some((volume, best_imbalance, low, p))
+            }
+            Some((volume, best_imbalance, ..))
+                if matchable > volume || (matchable == volume && imbalance < best_imbalance) =>
+            {
+                Some((matchable, imbalance, p, p))
+            }
+            keep => keep,
+        };
     }
 
-    // If we have remaining bids that cross the current ask level
-    while bid_idx < bid_levels.len() && ask_idx > 0 {
-        let (bid_price, bid_qty) = bid_levels[bid_idx];
-        let (ask_price, _) = ask_levels[ask_idx - 1];
-        if bid_price < ask_price {
-            break;
+    let Some((mut matchable, _, p_low, p_high)) = best else {
+        return Ok(no_cross);
+    };
+
+    let mut clearing = match config.tie_break {
+        ClearingTieBreak::FavorSellers => p_high,
+        ClearingTieBreak::FavorBuyers => p_low,
+        ClearingTieBreak::Midpoint => (p_low + p_high) / Decimal::TWO,
+    };
+
+    if let Some(tick) = config.tick_size.filter(|t| *t > Decimal::ZERO) {
+        // Volume on each side at an arbitrary price, sentinels included.
+        let demand_at = |p: Decimal| -> Decimal {
+            bid_levels
+                .iter()
+                .filter(|&&(price, _)| price >= p)
+                .map(|&(_, qty)| qty)
+                .sum()
+        };
+        let supply_at = |p: Decimal| -> Decimal {
+            ask_levels
+                .iter()
+                .filter(|&&(price, _)| price <= p)
+                .map(|&(_, qty)| qty)
+                .sum()
+        };
+        let excess = demand_at(clearing).cmp(&supply_at(clearing));
+        let mut snapped = snap_to_tick(clearing, tick, (p_low, p_high), excess);
+        if below_reserve(snapped) {
+            snapped += tick;
+        }
+        if snapped != clearing {
+            matchable = demand_at(snapped).min(supply_at(snapped));
+            if matchable.is_zero() {
+                return Ok(no_cross);
+            }
+            clearing = snapped;
         }
-        cum_demand += bid_qty;
-        matchable = cum_demand.min(cum_supply);
-        bid_idx += 1;
     }
 
-    if matchable.is_zero() {
-        return ClearingResult {
-            clearing_price: None,
-            matchable_volume: Decimal::ZERO,
-            best_bid,
-            best_ask,
-        };
+    Ok(ClearingResult {
+        clearing_price: Some(clearing.normalize()),
+        matchable_volume: matchable.normalize(),
+        best_bid,
+        best_ask,
+        candidates_evaluated,
+    })
+}
+
+/// Snap `price` onto the `tick` grid, preferring a neighbour inside `range`
+/// and otherwise moving in the direction `excess` (demand vs. supply)
+/// calls for. See "Tick quantization" on [`try_compute_clearing_price`].
+fn snap_to_tick(
+    price: Decimal,
+    tick: Decimal,
+    range: (Decimal, Decimal),
+    excess: Ordering,
+) -> Decimal {
+    let steps = price / tick;
+    if steps.fract().is_zero() {
+        return price;
     }
+    let down = steps.floor() * tick;
+    let up = steps.ceil() * tick;
+    let in_range = |p: Decimal| range.0 <= p && p <= range.1;
+    match (in_range(down), in_range(up)) {
+        (true, false) => down,
+        (false, true) => up,
+        _ => match excess {
+            Ordering::Greater => up,
+            Ordering::Less => down,
+            Ordering::Equal => {
+                steps.round_dp_with_strategy(0, RoundingStrategy::MidpointNearestEven) * tick
+            }
+        },
+    }
+}
 
-    // Clearing price = midpoint of best bid and best ask
-    let clearing = match (best_bid, best_ask) {
-        (Some(b), Some(a)) => Some((b + a) / Decimal::TWO),
-        _ => None,
+/// Compute surplus and price-setter metrics for `result` against `book`.
+///
+/// Returns zeroed metrics if `result` has no clearing price.
+#[must_use]
+pub fn compute_clearing_metrics(book: &OrderBook, result: &ClearingResult) -> ClearingMetrics {
+    let Some(price) = result.clearing_price else {
+        return ClearingMetrics::default();
     };
+    let volume = result.matchable_volume;
 
-    ClearingResult {
-        clearing_price: clearing,
-        matchable_volume: matchable,
-        best_bid,
-        best_ask,
+    let bids = fill_side(book.bid_levels(), volume, |limit| {
+        (limit >= price).then(|| (limit != Decimal::MAX).then(|| limit - price))
+    });
+    let asks = fill_side(book.ask_levels(), volume, |limit| {
+        (limit <= price).then(|| (!limit.is_zero()).then(|| price - limit))
+    });
+
+    let at_price = |last: Option<&Order>| {
+        last.filter(|o| o.price.is_some_and(|p| p == price))
+            .map(|o| o.id)
+    };
+    let (bid_setter, ask_setter) = (at_price(bids.last), at_price(asks.last));
+    let marginal_price_setter = if asks.eligible > bids.eligible {
+        ask_setter.or(bid_setter)
+    } else {
+        bid_setter.or(ask_setter)
+    };
+
+    ClearingMetrics {
+        buyer_surplus: bids.surplus.normalize(),
+        seller_surplus: asks.surplus.normalize(),
+        marginal_price_setter,
+    }
+}
+
+struct SideFill<'a> {
+    surplus: Decimal,
+    /// Total quantity willing to trade at the clearing price.
+    eligible: Decimal,
+    /// Last order (in priority order) that received a fill.
+    last: Option<&'a Order>,
+}
+
+/// Walk one side best-first, filling up to `volume`. `gain(limit)` is
+/// `None` for ineligible levels, `Some(None)` for market-order levels and
+/// `Some(Some(per_unit))` otherwise.
+fn fill_side<'a>(
+    levels: impl Iterator<Item = &'a PriceLevel>,
+    volume: Decimal,
+    gain: impl Fn(Decimal) -> Option<Option<Decimal>>,
+) -> SideFill<'a> {
+    let mut fill = SideFill {
+        surplus: Decimal::ZERO,
+        eligible: Decimal::ZERO,
+        last: None,
+    };
+    let mut left = volume;
+    for level in levels {
+        let Some(per_unit) = gain(level.price) else {
+            break;
+        };
+        fill.eligible += level.total_quantity();
+        for order in &level.orders {
+            if left.is_zero() {
+                break;
+            }
+            let qty = order.remaining_qty.min(left);
+            left -= qty;
+            fill.surplus += per_unit.unwrap_or(Decimal::ZERO) * qty;
+            fill.last = Some(order);
+        }
     }
+    fill
 }
 
 #[cfg(test)]
@@ -135,7 +472,7 @@ mod tests {
 
     #[test]
     fn no_crossing_when_empty() {
-        let book = OrderBook::new(MarketPair::new("BTC", "USDT"));
+        let book = OrderBook::new(MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")));
         let result = compute_clearing_price(&book);
         assert!(result.clearing_price.is_none());
         assert_eq!(result.matchable_volume, Decimal::ZERO);
@@ -143,7 +480,7 @@ mod tests {
 
     #[test]
     fn no_crossing_when_bid_below_ask() {
-        let mut book = OrderBook::new(MarketPair::new("BTC", "USDT"));
+        let mut book = OrderBook::new(MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")));
         book.insert_order(make_order(
             OrderSide::Buy,
             Decimal::new(99, 0),
@@ -160,9 +497,56 @@ mod tests {
         assert!(result.clearing_price.is_none());
     }
 
+    #[test]
+    fn non_crossing_book_short_circuits_for_any_config() {
+        // 2,000 levels per side, bids 1..=2000 and asks 2001..=4000.
+        let mut book = OrderBook::new(MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")));
+        for i in 1..=2000 {
+            book.insert_order(make_order(OrderSide::Buy, Decimal::new(i, 0), Decimal::ONE))
+                .unwrap();
+            book.insert_order(make_order(
+                OrderSide::Sell,
+                Decimal::new(2000 + i, 0),
+                Decimal::ONE,
+            ))
+            .unwrap();
+        }
+        let expected = ClearingResult {
+            clearing_price: None,
+            matchable_volume: Decimal::ZERO,
+            best_bid: Some(Decimal::new(2000, 0)),
+            best_ask: Some(Decimal::new(2001, 0)),
+            candidates_evaluated: 0,
+        };
+
+        // Nothing past the early return can influence the result: tie
+        // break, tick, reserve and reference price are all irrelevant.
+        for config in [
+            ClearingConfig::default(),
+            ClearingConfig {
+                tie_break: ClearingTieBreak::FavorSellers,
+                tick_size: Some(Decimal::new(7, 0)),
+                reference_price: Some(Decimal::new(2000, 0)),
+                reserve_price: Some(Decimal::ONE),
+            },
+        ] {
+            assert_eq!(
+                try_compute_clearing_price(&book, &config).unwrap(),
+                expected
+            );
+        }
+
+        // A market buy makes the book cross; the full sweep then runs.
+        book.insert_order(market_order(OrderSide::Buy, Decimal::ONE))
+            .unwrap();
+        let result = compute_clearing_price(&book);
+        assert_eq!(result.clearing_price, Some(Decimal::new(2001, 0)));
+        assert_eq!(result.candidates_evaluated, 4000);
+    }
+
     #[test]
     fn crossing_at_exact_price() {
-        let mut book = OrderBook::new(MarketPair::new("BTC", "USDT"));
+        let mut book = OrderBook::new(MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")));
         book.insert_order(make_order(
             OrderSide::Buy,
             Decimal::new(100, 0),
@@ -182,8 +566,8 @@ mod tests {
 
     #[test]
     fn crossing_with_spread() {
-        let mut book = OrderBook::new(MarketPair::new("BTC", "USDT"));
-        // Bid at 102, ask at 98 → crossing, clearing at midpoint = 100
+        let mut book = OrderBook::new(MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")));
+        // Bid at 102, ask at 98 → 98 and 102 tie, sellers get 102
         book.insert_order(make_order(
             OrderSide::Buy,
             Decimal::new(102, 0),
@@ -197,12 +581,12 @@ mod tests {
         ))
         .unwrap();
         let result = compute_clearing_price(&book);
-        assert_eq!(result.clearing_price, Some(Decimal::new(100, 0)));
+        assert_eq!(result.clearing_price, Some(Decimal::new(102, 0)));
     }
 
     #[test]
     fn matchable_volume_limited_by_smaller_side() {
-        let mut book = OrderBook::new(MarketPair::new("BTC", "USDT"));
+        let mut book = OrderBook::new(MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")));
         book.insert_order(make_order(
             OrderSide::Buy,
             Decimal::new(100, 0),
@@ -219,9 +603,431 @@ mod tests {
         assert_eq!(result.matchable_volume, Decimal::new(3, 0));
     }
 
+    fn tied_book() -> OrderBook {
+        // Bid 103, ask 98: both candidates clear 1 unit with zero imbalance.
+        let mut book = OrderBook::new(MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")));
+        book.insert_order(make_order(
+            OrderSide::Buy,
+            Decimal::new(103, 0),
+            Decimal::ONE,
+        ))
+        .unwrap();
+        book.insert_order(make_order(
+            OrderSide::Sell,
+            Decimal::new(98, 0),
+            Decimal::ONE,
+        ))
+        .unwrap();
+        book
+    }
+
+    #[test]
+    fn tie_break_favor_sellers_picks_high() {
+        let config = ClearingConfig {
+            tie_break: ClearingTieBreak::FavorSellers,
+            tick_size: None,
+            ..ClearingConfig::default()
+        };
+        let result = compute_clearing_price_with(&tied_book(), &config);
+        assert_eq!(result.clearing_price, Some(Decimal::new(103, 0)));
+    }
+
+    #[test]
+    fn tie_break_favor_buyers_picks_low() {
+        let config = ClearingConfig {
+            tie_break: ClearingTieBreak::FavorBuyers,
+            tick_size: None,
+            ..ClearingConfig::default()
+        };
+        let result = compute_clearing_price_with(&tied_book(), &config);
+        assert_eq!(result.clearing_price, Some(Decimal::new(98, 0)));
+    }
+
+    #[test]
+    fn tie_break_midpoint_rounds_to_tick() {
+        let unrounded = ClearingConfig {
+            tie_break: ClearingTieBreak::Midpoint,
+            tick_size: None,
+            ..ClearingConfig::default()
+        };
+        let result = compute_clearing_price_with(&tied_book(), &unrounded);
+        assert_eq!(result.clearing_price, Some(Decimal::new(1005, 1)));
+
+        // 100.5 on a tick of 2 → 100
+        let rounded = ClearingConfig {
+            tie_break: ClearingTieBreak::Midpoint,
+            tick_size: Some(Decimal::TWO),
+            ..ClearingConfig::default()
+        };
+        let result = compute_clearing_price_with(&tied_book(), &rounded);
+        assert_eq!(result.clearing_price, Some(Decimal::new(100, 0)));
+    }
+
+    #[test]
+    fn tie_break_ignored_without_tie() {
+        // Two bids at 100 and 102, one ask at 100 for 2 units:
+        // 100 clears 2 units, 102 only 1 → no tie, 100 wins for every mode.
+        let mut book = OrderBook::new(MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")));
+        book.insert_order(make_order(
+            OrderSide::Buy,
+            Decimal::new(102, 0),
+            Decimal::ONE,
+        ))
+        .unwrap();
+        book.insert_order(make_order(
+            OrderSide::Buy,
+            Decimal::new(100, 0),
+            Decimal::ONE,
+        ))
+        .unwrap();
+        book.insert_order(make_order(
+            OrderSide::Sell,
+            Decimal::new(100, 0),
+            Decimal::TWO,
+        ))
+        .unwrap();
+        for tie_break in [
+            ClearingTieBreak::FavorSellers,
+            ClearingTieBreak::FavorBuyers,
+            ClearingTieBreak::Midpoint,
+        ] {
+            let config = ClearingConfig {
+                tie_break,
+                tick_size: None,
+                ..ClearingConfig::default()
+            };
+            let result = compute_clearing_price_with(&book, &config);
+            assert_eq!(result.matchable_volume, Decimal::TWO);
+            assert_eq!(result.clearing_price, Some(Decimal::new(100, 0)));
+        }
+    }
+
+    #[test]
+    fn default_price_vectors_before_and_after_tie_break() {
+        // (bids, asks as (price, qty); price before `ClearingTieBreak`, i.e.
+        // the best-bid/best-ask midpoint; price under the default now)
+        type Levels = &'static [(i64, i64)];
+        let vectors: [(Levels, Levels, Decimal, Decimal); 5] = [
+            (
+                &[(100, 1)],
+                &[(100, 1)],
+                Decimal::new(100, 0),
+                Decimal::new(100, 0),
+            ),
+            (
+                &[(102, 1)],
+                &[(98, 1)],
+                Decimal::new(100, 0),
+                Decimal::new(102, 0),
+            ),
+            (
+                &[(101, 2), (98, 1)],
+                &[(99, 1), (100, 2)],
+                Decimal::new(100, 0),
+                Decimal::new(101, 0),
+            ),
+            (
+                &[(102, 1), (100, 1)],
+                &[(100, 2)],
+                Decimal::new(101, 0),
+                Decimal::new(100, 0),
+            ),
+            (
+                &[(103, 1), (100, 1)],
+                &[(98, 2)],
+                Decimal::new(1005, 1),
+                Decimal::new(100, 0),
+            ),
+        ];
+        assert_eq!(
+            ClearingConfig::default().tie_break,
+            ClearingTieBreak::FavorSellers
+        );
+        for (bids, asks, before, after) in vectors {
+            let mut book = OrderBook::new(MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")));
+            let side = |side, levels: Levels| {
+                levels.iter().map(move |&(price, qty)| {
+                    make_order(side, Decimal::new(price, 0), Decimal::new(qty, 0))
+                })
+            };
+            book.insert_batch(
+                side(OrderSide::Buy, bids)
+                    .chain(side(OrderSide::Sell, asks))
+                    .collect(),
+            )
+            .unwrap();
+            let result = compute_clearing_price(&book);
+            let midpoint = (result.best_bid.unwrap() + result.best_ask.unwrap()) / Decimal::TWO;
+            assert_eq!(midpoint, before, "{bids:?} / {asks:?}");
+            assert_eq!(result.clearing_price, Some(after), "{bids:?} / {asks:?}");
+        }
+    }
+
+    fn reserve_book() -> OrderBook {
+        // Bids 103 and 100, ask 2 @ 98: 98 and 100 both clear 2 units
+        // with zero imbalance.
+        let mut book = OrderBook::new(MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")));
+        book.insert_order(make_order(
+            OrderSide::Buy,
+            Decimal::new(103, 0),
+            Decimal::ONE,
+        ))
+        .unwrap();
+        book.insert_order(make_order(
+            OrderSide::Buy,
+            Decimal::new(100, 0),
+            Decimal::ONE,
+        ))
+        .unwrap();
+        book.insert_order(make_order(
+            OrderSide::Sell,
+            Decimal::new(98, 0),
+            Decimal::TWO,
+        ))
+        .unwrap();
+        book
+    }
+
+    #[test]
+    fn reserve_price_raises_clearing_price() {
+        let book = reserve_book();
+        let favor_buyers = ClearingConfig {
+            tie_break: ClearingTieBreak::FavorBuyers,
+            ..ClearingConfig::default()
+        };
+        assert_eq!(
+            compute_clearing_price_with(&book, &favor_buyers).clearing_price,
+            Some(Decimal::new(98, 0))
+        );
+
+        let config = ClearingConfig {
+            reserve_price: Some(Decimal::new(100, 0)),
+            ..favor_buyers
+        };
+        let result = compute_clearing_price_with(&book, &config);
+        assert_eq!(result.clearing_price, Some(Decimal::new(100, 0)));
+        assert_eq!(result.matchable_volume, Decimal::TWO);
+    }
+
+    #[test]
+    fn reserve_price_above_all_bids_prevents_clearing() {
+        let config = ClearingConfig {
+            reserve_price: Some(Decimal::new(104, 0)),
+            ..ClearingConfig::default()
+        };
+        let result = compute_clearing_price_with(&reserve_book(), &config);
+        assert_eq!(result.clearing_price, None);
+        assert_eq!(result.matchable_volume, Decimal::ZERO);
+    }
+
+    #[test]
+    fn shuffled_input_yields_byte_identical_result() {
+        use rand::{SeedableRng, rngs::StdRng, seq::SliceRandom};
+
+        // Same numeric prices/quantities with differing scales.
+        let mut orders = vec![
+            make_order(OrderSide::Buy, Decimal::new(101, 0), Decimal::new(2, 0)),
+            make_order(OrderSide::Buy, Decimal::new(10100, 2), Decimal::new(15, 1)),
+            make_order(OrderSide::Buy, Decimal::new(1000, 1), Decimal::new(300, 2)),
+            make_order(OrderSide::Buy, Decimal::new(100, 0), Decimal::ONE),
+            make_order(OrderSide::Sell, Decimal::new(99, 0), Decimal::new(25, 1)),
+            make_order(OrderSide::Sell, Decimal::new(9900, 2), Decimal::ONE),
+            make_order(OrderSide::Sell, Decimal::new(1000, 1), Decimal::new(2, 0)),
+            make_order(OrderSide::Sell, Decimal::new(100, 0), Decimal::new(50, 2)),
+        ];
+
+        let build = |orders: &[Order]| {
+            let mut book = OrderBook::new(MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")));
+            book.insert_batch(orders.to_vec()).unwrap();
+            compute_clearing_price(&book)
+        };
+
+        let reference = build(&orders);
+        let reference_repr = format!("{reference:?}");
+        for seed in 0..32 {
+            orders.shuffle(&mut StdRng::seed_from_u64(seed));
+            let result = build(&orders);
+            assert_eq!(result, reference);
+            assert_eq!(format!("{result:?}"), reference_repr, "seed {seed}");
+        }
+    }
+
+    fn market_order(side: OrderSide, qty: Decimal) -> Order {
+        let mut order = make_order(side, Decimal::ZERO, qty);
+        order.order_type = OrderType::Market;
+        order.price = None;
+        order
+    }
+
+    #[test]
+    fn market_only_book_clears_at_reference_price() {
+        let mut book = OrderBook::new(MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")));
+        book.insert_order(market_order(OrderSide::Buy, Decimal::TWO))
+            .unwrap();
+        book.insert_order(market_order(OrderSide::Sell, Decimal::ONE))
+            .unwrap();
+
+        let config = ClearingConfig {
+            reference_price: Some(Decimal::new(50_000, 0)),
+            ..ClearingConfig::default()
+        };
+        let result = try_compute_clearing_price(&book, &config).unwrap();
+        assert_eq!(result.clearing_price, Some(Decimal::new(50_000, 0)));
+        assert_eq!(result.matchable_volume, Decimal::ONE);
+    }
+
+    #[test]
+    fn market_only_book_respects_reserve_price() {
+        let mut book = OrderBook::new(MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")));
+        book.insert_order(market_order(OrderSide::Buy, Decimal::ONE))
+            .unwrap();
+        book.insert_order(market_order(OrderSide::Sell, Decimal::ONE))
+            .unwrap();
+
+        let config = ClearingConfig {
+            reference_price: Some(Decimal::new(50, 0)),
+            reserve_price: Some(Decimal::new(100, 0)),
+            ..ClearingConfig::default()
+        };
+        let result = try_compute_clearing_price(&book, &config).unwrap();
+        assert_eq!(result.clearing_price, Some(Decimal::new(100, 0)));
+        assert_eq!(result.matchable_volume, Decimal::ONE);
+
+        // An off-grid reserve lifts the price onto the next tick.
+        let config = ClearingConfig {
+            reserve_price: Some(Decimal::new(1003, 1)),
+            tick_size: Some(Decimal::new(5, 1)),
+            ..config
+        };
+        let result = try_compute_clearing_price(&book, &config).unwrap();
+        assert_eq!(result.clearing_price, Some(Decimal::new(1005, 1)));
+    }
+
+    #[test]
+    fn market_only_book_without_reference_is_an_error() {
+        let mut book = OrderBook::new(MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")));
+        book.insert_order(market_order(OrderSide::Buy, Decimal::ONE))
+            .unwrap();
+        book.insert_order(market_order(OrderSide::Sell, Decimal::ONE))
+            .unwrap();
+
+        let err = try_compute_clearing_price(&book, &ClearingConfig::default()).unwrap_err();
+        assert!(matches!(err, OpenmatchError::MatchingFailed { .. }));
+        // The infallible variant reports no crossing instead.
+        assert!(compute_clearing_price(&book).clearing_price.is_none());
+    }
+
+    #[test]
+    fn market_buy_clears_at_limit_sell_price() {
+        let mut book = OrderBook::new(MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")));
+        book.insert_order(market_order(OrderSide::Buy, Decimal::TWO))
+            .unwrap();
+        book.insert_order(make_order(
+            OrderSide::Sell,
+            Decimal::new(101, 0),
+            Decimal::ONE,
+        ))
+        .unwrap();
+
+        // The reference price is only a fallback; a limit price wins.
+        let config = ClearingConfig {
+            reference_price: Some(Decimal::new(50_000, 0)),
+            ..ClearingConfig::default()
+        };
+        let result = try_compute_clearing_price(&book, &config).unwrap();
+        assert_eq!(result.clearing_price, Some(Decimal::new(101, 0)));
+        assert_eq!(result.matchable_volume, Decimal::ONE);
+    }
+
+    #[test]
+    fn market_buy_against_limit_and_market_sells() {
+        let mut book = OrderBook::new(MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")));
+        book.insert_order(market_order(OrderSide::Buy, Decimal::new(3, 0)))
+            .unwrap();
+        book.insert_order(make_order(
+            OrderSide::Sell,
+            Decimal::new(101, 0),
+            Decimal::ONE,
+        ))
+        .unwrap();
+        book.insert_order(market_order(OrderSide::Sell, Decimal::ONE))
+            .unwrap();
+
+        let result = compute_clearing_price(&book);
+        assert_eq!(result.clearing_price, Some(Decimal::new(101, 0)));
+        assert_eq!(result.matchable_volume, Decimal::TWO);
+    }
+
+    #[test]
+    fn market_sell_does_not_drag_price_to_zero() {
+        let mut book = OrderBook::new(MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")));
+        book.insert_order(make_order(
+            OrderSide::Buy,
+            Decimal::new(100, 0),
+            Decimal::ONE,
+        ))
+        .unwrap();
+        book.insert_order(market_order(OrderSide::Sell, Decimal::ONE))
+            .unwrap();
+
+        let result = compute_clearing_price(&book);
+        assert_eq!(result.clearing_price, Some(Decimal::new(100, 0)));
+    }
+
+    #[test]
+    fn zero_remaining_orders_propose_no_candidate() {
+        let mut book = OrderBook::new(MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")));
+        book.insert_order(make_order(
+            OrderSide::Buy,
+            Decimal::new(100, 0),
+            Decimal::ONE,
+        ))
+        .unwrap();
+        book.insert_order(make_order(
+            OrderSide::Sell,
+            Decimal::new(100, 0),
+            Decimal::ONE,
+        ))
+        .unwrap();
+        let baseline = compute_clearing_price(&book);
+
+        // Fully-filled carryover on both sides, outside the live prices.
+        for (side, price) in [
+            (OrderSide::Buy, Decimal::new(105, 0)),
+            (OrderSide::Sell, Decimal::new(95, 0)),
+        ] {
+            let mut filled = make_order(side, price, Decimal::ONE);
+            filled.remaining_qty = Decimal::ZERO;
+            book.insert_order(filled).unwrap();
+        }
+
+        let result = compute_clearing_price(&book);
+        assert_eq!(result.clearing_price, baseline.clearing_price);
+        assert_eq!(result.matchable_volume, Decimal::ONE);
+        assert_eq!(result.candidates_evaluated, 1);
+    }
+
+    #[test]
+    fn only_zero_remaining_on_one_side_does_not_cross() {
+        let mut book = OrderBook::new(MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")));
+        let mut filled = make_order(OrderSide::Buy, Decimal::new(100, 0), Decimal::ONE);
+        filled.remaining_qty = Decimal::ZERO;
+        book.insert_order(filled).unwrap();
+        book.insert_order(make_order(
+            OrderSide::Sell,
+            Decimal::new(100, 0),
+            Decimal::ONE,
+        ))
+        .unwrap();
+
+        let result = compute_clearing_price(&book);
+        assert!(result.clearing_price.is_none());
+        assert_eq!(result.candidates_evaluated, 0);
+    }
+
     #[test]
     fn clearing_result_has_best_bid_ask() {
-        let mut book = OrderBook::new(MarketPair::new("BTC", "USDT"));
+        let mut book = OrderBook::new(MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")));
         book.insert_order(make_order(
             OrderSide::Buy,
             Decimal::new(100, 0),
@@ -238,4 +1044,173 @@ mod tests {
         assert_eq!(result.best_bid, Some(Decimal::new(100, 0)));
         assert_eq!(result.best_ask, Some(Decimal::new(100, 0)));
     }
+
+    #[test]
+    fn metrics_on_multi_level_book() {
+        let mut book = OrderBook::new(MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")));
+        let bid = |p, q| make_order(OrderSide::Buy, Decimal::new(p, 0), Decimal::new(q, 0));
+        let ask = |p, q| make_order(OrderSide::Sell, Decimal::new(p, 0), Decimal::new(q, 0));
+        let orders = vec![
+            bid(105, 1),
+            bid(102, 2),
+            bid(100, 1),
+            ask(97, 1),
+            ask(99, 1),
+            ask(102, 2),
+        ];
+        let rationed_ask = orders[5].id;
+        book.insert_batch(orders).unwrap();
+
+        // 102 clears 3 units: bids 105×1 + 102×2 vs asks 97, 99, 102×1 of 2.
+        let result = compute_clearing_price(&book);
+        assert_eq!(result.clearing_price, Some(Decimal::new(102, 0)));
+        assert_eq!(result.matchable_volume, Decimal::new(3, 0));
+
+        let metrics = compute_clearing_metrics(&book, &result);
+        assert_eq!(metrics.buyer_surplus, Decimal::new(3, 0));
+        assert_eq!(metrics.seller_surplus, Decimal::new(8, 0));
+        // Supply (4) exceeds demand (3) at 102: the partially filled ask
+        // at the clearing price set it.
+        assert_eq!(metrics.marginal_price_setter, Some(rationed_ask));
+    }
+
+    #[test]
+    fn metrics_without_price_setter_between_levels() {
+        let mut book = OrderBook::new(MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")));
+        book.insert_order(make_order(
+            OrderSide::Buy,
+            Decimal::new(102, 0),
+            Decimal::ONE,
+        ))
+        .unwrap();
+        book.insert_order(make_order(
+            OrderSide::Sell,
+            Decimal::new(98, 0),
+            Decimal::ONE,
+        ))
+        .unwrap();
+
+        // Midpoint 100: each side gains 2.
+        let config = ClearingConfig {
+            tie_break: ClearingTieBreak::Midpoint,
+            ..ClearingConfig::default()
+        };
+        let result = compute_clearing_price_with(&book, &config);
+        let metrics = compute_clearing_metrics(&book, &result);
+        assert_eq!(metrics.buyer_surplus, Decimal::TWO);
+        assert_eq!(metrics.seller_surplus, Decimal::TWO);
+        assert_eq!(metrics.marginal_price_setter, None);
+    }
+
+    #[test]
+    fn metrics_empty_without_crossing() {
+        let book = OrderBook::new(MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")));
+        let result = compute_clearing_price(&book);
+        assert_eq!(
+            compute_clearing_metrics(&book, &result),
+            ClearingMetrics::default()
+        );
+    }
+
+    #[test]
+    fn symmetric_tie_resolves_to_single_stable_price() {
+        use rand::{SeedableRng, rngs::StdRng, seq::SliceRandom};
+
+        // Every candidate in [98, 101] clears 1 unit with zero imbalance.
+        // Midpoint 99.5 sits exactly between ticks 99 and 100.
+        let mut orders = vec![
+            make_order(OrderSide::Buy, Decimal::new(101, 0), Decimal::ONE),
+            make_order(OrderSide::Buy, Decimal::new(10000, 2), Decimal::ZERO),
+            make_order(OrderSide::Sell, Decimal::new(98, 0), Decimal::ONE),
+            make_order(OrderSide::Sell, Decimal::new(990, 1), Decimal::ZERO),
+        ];
+        let config = ClearingConfig {
+            tie_break: ClearingTieBreak::Midpoint,
+            tick_size: Some(Decimal::ONE),
+            ..ClearingConfig::default()
+        };
+        for seed in 0..16 {
+            orders.shuffle(&mut StdRng::seed_from_u64(seed));
+            let mut book = OrderBook::new(MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")));
+            book.insert_batch(orders.clone()).unwrap();
+            let result = compute_clearing_price_with(&book, &config);
+            assert_eq!(result.matchable_volume, Decimal::ONE);
+            // Half-tick midpoint rounds to the even tick.
+            assert_eq!(
+                result.clearing_price,
+                Some(Decimal::new(100, 0)),
+                "seed {seed}"
+            );
+        }
+    }
+
+    #[test]
+    fn off_tick_midpoint_snaps_to_tick() {
+        // Bids 2 @ 101; asks 1 @ 98 and 1 @ 100. Candidates 100 and 101
+        // both clear 2 units with zero imbalance → midpoint 100.5.
+        let mut orders = vec![
+            make_order(OrderSide::Buy, Decimal::new(101, 0), Decimal::TWO),
+            make_order(OrderSide::Sell, Decimal::new(98, 0), Decimal::ONE),
+            make_order(OrderSide::Sell, Decimal::new(100, 0), Decimal::ONE),
+        ];
+        let cent_tick = ClearingConfig {
+            tie_break: ClearingTieBreak::Midpoint,
+            ..ClearingConfig::for_market(&MarketConfig::btc_usdt())
+        };
+        let unit_tick = ClearingConfig {
+            tick_size: Some(Decimal::ONE),
+            ..cent_tick
+        };
+        for seed in 0..8 {
+            use rand::{SeedableRng, rngs::StdRng, seq::SliceRandom};
+            orders.shuffle(&mut StdRng::seed_from_u64(seed));
+            let mut book = OrderBook::new(MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")));
+            book.insert_batch(orders.clone()).unwrap();
+
+            let result = compute_clearing_price_with(&book, &cent_tick);
+            assert_eq!(result.clearing_price, Some(Decimal::new(1005, 1)));
+            let result = compute_clearing_price_with(&book, &unit_tick);
+            assert_eq!(result.clearing_price, Some(Decimal::new(100, 0)), "{seed}");
+            assert_eq!(result.matchable_volume, Decimal::TWO);
+        }
+    }
+
+    #[test]
+    fn off_tick_reference_snaps_toward_imbalance() {
+        let market_only = |bid_qty: Decimal, ask_qty: Decimal| {
+            let mut bid = make_order(OrderSide::Buy, Decimal::ONE, bid_qty);
+            bid.order_type = OrderType::Market;
+            bid.price = None;
+            let mut ask = make_order(OrderSide::Sell, Decimal::ONE, ask_qty);
+            ask.order_type = OrderType::Market;
+            ask.price = None;
+            let mut book = OrderBook::new(MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")));
+            book.insert_batch(vec![bid, ask]).unwrap();
+            book
+        };
+        let config = ClearingConfig {
+            tick_size: Some(Decimal::ONE),
+            reference_price: Some(Decimal::new(1004, 1)),
+            ..ClearingConfig::default()
+        };
+        let price = |book: &OrderBook| {
+            try_compute_clearing_price(book, &config)
+                .unwrap()
+                .clearing_price
+        };
+
+        // Excess demand rounds up, excess supply down, balance to nearest.
+        assert_eq!(
+            price(&market_only(Decimal::TWO, Decimal::ONE)),
+            Some(Decimal::new(101, 0))
+        );
+        assert_eq!(
+            price(&market_only(Decimal::ONE, Decimal::TWO)),
+            Some(Decimal::new(100, 0))
+        );
+        assert_eq!(
+            price(&market_only(Decimal::ONE, Decimal::ONE)),
+            Some(Decimal::new(100, 0))
+        );
+    }
 }
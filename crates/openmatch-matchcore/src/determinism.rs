@@ -3,22 +3,28 @@
 //! Every node processing the same `SealedBatch` must produce the exact
 //! same `TradeBundle`. The `trade_root` is a Merkle-style hash over all
 //! trades that enables quick verification without comparing full payloads.
+//! The epoch ID is part of the preimage, so bundles from different epochs —
+//! including empty ones — never share a root.
 
-use openmatch_types::Trade;
+use openmatch_types::{EpochId, Trade};
 use sha2::{Digest, Sha256};
 
 /// Compute the trade root hash over a set of trades.
 ///
 /// This is a deterministic hash that depends on:
+/// - The epoch that produced the bundle
 /// - Trade IDs (in order)
 /// - Prices and quantities
 /// - Taker/maker user IDs
+/// - Quote dust, for trades that carry any
 ///
 /// The same set of trades in the same order always produces the same root.
+/// Decimals are normalized so `100` and `100.00` hash the same.
 #[must_use]
-pub fn compute_trade_root(trades: &[Trade]) -> [u8; 32] {
+pub fn compute_trade_root(epoch_id: EpochId, trades: &[Trade]) -> [u8; 32] {
     let mut hasher = Sha256::new();
-    hasher.update(b"openmatch:trade_root:v2:");
+    hasher.update(b"openmatch:trade_root:v4:");
+    hasher.update(epoch_id.0.to_le_bytes());
     hasher.update((trades.len() as u64).to_le_bytes());
 
     for trade in trades {
@@ -29,9 +35,15 @@ pub fn compute_trade_root(trades: &[Trade]) -> [u8; 32] {
         hasher.update(trade.maker_order_id.0.as_bytes());
         hasher.update(trade.taker_user_id.0.as_bytes());
         hasher.update(trade.maker_user_id.0.as_bytes());
-        hasher.update(trade.price.to_string().as_bytes());
-        hasher.update(trade.quantity.to_string().as_bytes());
-        hasher.update(trade.quote_amount.to_string().as_bytes());
+        for value in [trade.price, trade.quantity, trade.quote_amount] {
+            hasher.update(value.normalize().to_string().as_bytes());
+            hasher.update(b"\0");
+        }
+        if let Some(dust) = &trade.quote_dust {
+            hasher.update(dust.account.0.as_bytes());
+            hasher.update(dust.amount.normalize().to_string().as_bytes());
+            hasher.update(b"\0");
+        }
     }
 
     let result = hasher.finalize();
@@ -44,8 +56,8 @@ pub fn compute_trade_root(trades: &[Trade]) -> [u8; 32] {
 ///
 /// Recomputes the hash from the trades and compares with the expected root.
 #[must_use]
-pub fn verify_trade_root(trades: &[Trade], expected_root: &[u8; 32]) -> bool {
-    let actual = compute_trade_root(trades);
+pub fn verify_trade_root(epoch_id: EpochId, trades: &[Trade], expected_root: &[u8; 32]) -> bool {
+    let actual = compute_trade_root(epoch_id, trades);
     actual == *expected_root
 }
 
@@ -61,7 +73,7 @@ mod tests {
         Trade {
             id: TradeId::deterministic(epoch_id, fill_seq),
             epoch_id: EpochId(epoch_id),
-            market: MarketPair::new("BTC", "USDT"),
+            market: MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")),
             taker_order_id: OrderId::from_bytes([1; 16]),
             taker_user_id: UserId::from_bytes([2; 16]),
             maker_order_id: OrderId::from_bytes([3; 16]),
@@ -72,21 +84,37 @@ mod tests {
             taker_side: OrderSide::Buy,
             matcher_node: NodeId([0u8; 32]),
             executed_at: Utc::now(),
+            quote_dust: None,
         }
     }
 
     #[test]
     fn empty_trades_deterministic() {
-        let root1 = compute_trade_root(&[]);
-        let root2 = compute_trade_root(&[]);
+        let root1 = compute_trade_root(EpochId(1), &[]);
+        let root2 = compute_trade_root(EpochId(1), &[]);
         assert_eq!(root1, root2);
     }
 
+    #[test]
+    fn empty_roots_differ_by_epoch() {
+        let root1 = compute_trade_root(EpochId(1), &[]);
+        let root2 = compute_trade_root(EpochId(2), &[]);
+        assert_ne!(root1, root2);
+        assert_ne!(root1, [0u8; 32]);
+    }
+
+    #[test]
+    fn verify_rejects_wrong_epoch() {
+        let trades = vec![make_trade(1, 0)];
+        let root = compute_trade_root(EpochId(1), &trades);
+        assert!(!verify_trade_root(EpochId(2), &trades, &root));
+    }
+
     #[test]
     fn same_trades_same_root() {
         let trades = vec![make_trade(1, 0), make_trade(1, 1)];
-        let root1 = compute_trade_root(&trades);
-        let root2 = compute_trade_root(&trades);
+        let root1 = compute_trade_root(EpochId(1), &trades);
+        let root2 = compute_trade_root(EpochId(1), &trades);
         assert_eq!(root1, root2);
     }
 
@@ -94,8 +122,8 @@ mod tests {
     fn different_trades_different_root() {
         let trades_a = vec![make_trade(1, 0)];
         let trades_b = vec![make_trade(1, 1)];
-        let root_a = compute_trade_root(&trades_a);
-        let root_b = compute_trade_root(&trades_b);
+        let root_a = compute_trade_root(EpochId(1), &trades_a);
+        let root_b = compute_trade_root(EpochId(1), &trades_b);
         assert_ne!(root_a, root_b);
     }
 
@@ -103,28 +131,41 @@ mod tests {
     fn order_matters() {
         let t1 = make_trade(1, 0);
         let t2 = make_trade(1, 1);
-        let root_ab = compute_trade_root(&[t1.clone(), t2.clone()]);
-        let root_ba = compute_trade_root(&[t2, t1]);
+        let root_ab = compute_trade_root(EpochId(1), &[t1.clone(), t2.clone()]);
+        let root_ba = compute_trade_root(EpochId(1), &[t2, t1]);
         assert_ne!(root_ab, root_ba, "Order of trades must affect root hash");
     }
 
     #[test]
     fn verify_correct_root() {
         let trades = vec![make_trade(1, 0), make_trade(1, 1)];
-        let root = compute_trade_root(&trades);
-        assert!(verify_trade_root(&trades, &root));
+        let root = compute_trade_root(EpochId(1), &trades);
+        assert!(verify_trade_root(EpochId(1), &trades, &root));
     }
 
     #[test]
     fn verify_wrong_root() {
         let trades = vec![make_trade(1, 0)];
         let wrong_root = [0xAB; 32];
-        assert!(!verify_trade_root(&trades, &wrong_root));
+        assert!(!verify_trade_root(EpochId(1), &trades, &wrong_root));
     }
 
     #[test]
     fn root_is_32_bytes() {
-        let root = compute_trade_root(&[]);
+        let root = compute_trade_root(EpochId(1), &[]);
         assert_eq!(root.len(), 32);
     }
+
+    #[test]
+    fn decimal_scale_does_not_change_root() {
+        let trade = make_trade(1, 0);
+        let mut rescaled = trade.clone();
+        rescaled.price = Decimal::new(5_000_000, 2);
+        rescaled.quantity = Decimal::new(10, 1);
+        rescaled.quote_amount = Decimal::new(50_000_000, 3);
+        assert_eq!(
+            compute_trade_root(EpochId(1), &[trade]),
+            compute_trade_root(EpochId(1), &[rescaled])
+        );
+    }
 }
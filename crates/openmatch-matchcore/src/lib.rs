@@ -16,8 +16,13 @@ pub mod matcher;
 pub mod orderbook;
 pub mod price_level;
 
-pub use clearing::{ClearingResult, compute_clearing_price};
+pub use clearing::{
+    ClearingConfig, ClearingMetrics, ClearingResult, ClearingTieBreak, compute_clearing_metrics,
+    compute_clearing_price, compute_clearing_price_with, try_compute_clearing_price,
+};
 pub use determinism::{compute_trade_root, verify_trade_root};
-pub use matcher::match_sealed_batch;
+#[cfg(feature = "rayon")]
+pub use matcher::match_sealed_batch_parallel;
+pub use matcher::{BatchMatcher, MatchingMetrics, QuoteRounding, match_sealed_batch};
 pub use orderbook::OrderBook;
 pub use price_level::PriceLevel;
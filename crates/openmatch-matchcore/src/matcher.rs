@@ -5,192 +5,833 @@
 //! no side effects, no DB writes, no balance checks.
 //!
 //! ```text
-//! match_sealed_batch(SealedBatch) -> TradeBundle
+//! match_sealed_batch(SealedBatch) -> Result<TradeBundle>
 //! ```
 //!
+//! [`BatchMatcher`] wraps the same function with the node identity taken
+//! from [`NodeConfig`], so produced trades carry the matching node's ID.
+//! Every trade in a pass is stamped with one reading of the matcher's
+//! [`Clock`]; inject a [`FixedClock`](openmatch_types::FixedClock) with
+//! [`BatchMatcher::with_clock`] to make `executed_at` reproducible too.
+//!
 //! ## Self-Trade Prevention
 //!
 //! If a buy and sell order have the same `user_id`, the match is skipped
 //! (wash trading prevention). The aggressive order continues to match
-//! against the next passive order at that level.
+//! against the next passive order at that level. Each skipped pair is
+//! recorded as a [`WashTradeAttempt`] in `TradeBundle::wash_trade_attempts`
+//! for compliance review.
+//!
+//! ## Cancels
+//!
+//! A `Cancel { target }` order removes `target` from the batch before
+//! pricing, provided both belong to the same user. The removed order is
+//! reported in `TradeBundle::cancelled_orders` so its escrow can be
+//! released. A cancel aimed at another user's order has no effect.
+//!
+//! ## Quote Overflow
+//!
+//! A fill whose `price × quantity` overflows `Decimal` is skipped like a
+//! self-trade: no trade is produced and both orders rest.
+//!
+//! ## Single-Fill Cap
+//!
+//! With [`BatchMatcher::with_max_single_fill`], no single bid/ask pair
+//! trades more than the cap. The bid moves on to the next ask; the capped
+//! ask stays available to later bids. Whatever cannot be filled rests.
+//!
+//! ## All-or-None
+//!
+//! An all-or-none limit order that a pass would fill only partially is
+//! held back and the batch is matched again without it, repeating until
+//! no all-or-none order is partially filled. Held orders are returned
+//! unchanged in `TradeBundle::remaining_orders`.
+//!
+//! ## Multiple Markets
+//!
+//! [`BatchMatcher::match_batch`] prices a batch as one market. A batch
+//! holding several markets goes through
+//! [`BatchMatcher::match_batch_by_market`], which matches each market's
+//! orders on their own and merges the bundles in `MarketPair` order. Markets
+//! are independent, so with the `rayon` feature
+//! [`BatchMatcher::match_batch_by_market_parallel`] matches them
+//! concurrently on a thread pool and produces the same bytes.
+//!
+//! ## Quote Rounding
+//!
+//! With a [`QuoteRounding`], each fill's quote amount is rounded to the
+//! quote asset's scale and the residual is attached to the trade as
+//! [`QuoteDust`] for a reserved dust account, so the buyer still pays
+//! exactly `price × quantity`.
+//!
+//! ## Unfilled Reasons
+//!
+//! Every remaining order is paired with an [`UnfilledReason`] in
+//! `TradeBundle::unfilled_reasons`: the batch didn't clear, the order was
+//! out of the money, rationed at the margin, blocked by self-trade
+//! prevention, or held back as all-or-none.
+//!
+//! ## Metrics
+//!
+//! [`BatchMatcher::try_match_batch_with_metrics`] also returns a
+//! [`MatchingMetrics`] for monitoring. Metrics sit outside the bundle, so
+//! the wall time they carry never affects determinism.
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
-use chrono::Utc;
 use openmatch_types::{
-    NodeId, Order, OrderSide, OrderType, SealedBatch, Trade, TradeBundle, TradeId,
+    BatchId, Clock, MarketPair, NodeConfig, NodeId, OpenmatchError, Order, OrderId, OrderSide,
+    OrderType, QuoteDust, Result, SealedBatch, SystemClock, Trade, TradeBundle, TradeId,
+    UnfilledReason, UserId, WashTradeAttempt,
 };
-use rust_decimal::Decimal;
+use rust_decimal::{Decimal, RoundingStrategy};
+
+use crate::{
+    OrderBook,
+    clearing::{ClearingConfig, try_compute_clearing_price},
+    determinism::compute_trade_root,
+};
+
+/// Counters describing one matching run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MatchingMetrics {
+    /// Candidate prices the clearing sweep evaluated.
+    pub price_candidates: usize,
+    /// Bid/ask pairs skipped because both orders belong to one user. A
+    /// rising count points at wash-trading pressure.
+    pub self_trades_skipped: usize,
+    /// Trades produced.
+    pub fills: usize,
+    /// Wall time spent matching.
+    pub elapsed: Duration,
+}
+
+/// Rounding of each fill's quote amount to the quote asset's precision.
+///
+/// The seller receives `price × quantity` rounded to `scale` places with
+/// `bias`. The residual is routed to `dust_account` as the trade's
+/// [`QuoteDust`], so the buyer's escrow still pays the exact product and
+/// settlement conserves supply to the last minor unit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuoteRounding {
+    /// Decimal places the quote asset carries.
+    pub scale: u32,
+    /// Direction to round the seller's amount: rounding down accumulates
+    /// dust, rounding up draws on the dust account.
+    pub bias: RoundingStrategy,
+    /// Reserved account that absorbs every residual.
+    pub dust_account: UserId,
+}
 
-use crate::{OrderBook, clearing::compute_clearing_price, determinism::compute_trade_root};
+impl QuoteRounding {
+    /// Split an exact quote amount into the seller's rounded amount and
+    /// the dust, if rounding changed anything.
+    #[must_use]
+    pub fn split(&self, exact: Decimal) -> (Decimal, Option<QuoteDust>) {
+        let rounded = exact
+            .round_dp_with_strategy(self.scale, self.bias)
+            .normalize();
+        let residual = (exact - rounded).normalize();
+        let dust = (!residual.is_zero()).then_some(QuoteDust {
+            account: self.dust_account,
+            amount: residual,
+        });
+        (rounded, dust)
+    }
+}
 
 /// Pure deterministic matching: takes a sealed batch, produces a trade bundle.
 ///
-/// ## Algorithm
+/// Equivalent to [`BatchMatcher::match_batch`] on a matcher with an all-zero
+/// node identity. Nodes that stamp trades with their own identity should
+/// construct a [`BatchMatcher`] instead.
 ///
-/// 1. Insert all orders from the sealed batch into a fresh order book
-/// 2. Compute the uniform clearing price
-/// 3. Walk crossing orders and produce trades at the clearing price
-/// 4. Self-trade prevention: skip fills where buyer == seller
-/// 5. Compute trade_root hash for cross-node verification
-/// 6. Return the `TradeBundle`
+/// # Errors
+/// Same as [`BatchMatcher::match_batch`].
+pub fn match_sealed_batch(batch: &SealedBatch) -> Result<TradeBundle> {
+    BatchMatcher::new(NodeId([0u8; 32])).match_batch(batch)
+}
+
+/// Match a multi-market batch with its markets matched concurrently on
+/// `thread_pool`.
 ///
-/// ## Determinism Guarantee
+/// Equivalent to [`BatchMatcher::match_batch_by_market_parallel`] on a
+/// matcher with an all-zero node identity; the bundle is byte-identical to
+/// [`BatchMatcher::match_batch_by_market`].
+///
+/// # Errors
+/// Same as [`BatchMatcher::match_batch_by_market`].
+#[cfg(feature = "rayon")]
+pub fn match_sealed_batch_parallel(
+    batch: &SealedBatch,
+    thread_pool: &rayon::ThreadPool,
+) -> Result<TradeBundle> {
+    BatchMatcher::new(NodeId([0u8; 32])).match_batch_by_market_parallel(batch, thread_pool)
+}
+
+/// Deterministic batch matcher bound to a node identity.
 ///
-/// Given the same `SealedBatch` (same orders in same order with same
-/// `batch_hash`), this function produces the **exact same** `TradeBundle`
-/// on every node — same trades, same trade_root, same clearing price.
-#[must_use]
-#[allow(clippy::too_many_lines)]
-pub fn match_sealed_batch(batch: &SealedBatch) -> TradeBundle {
-    let Some(first) = batch.orders.first() else {
-        // Empty batch → empty bundle
-        return TradeBundle {
+/// The node identity is recorded as `matcher_node` on every trade produced.
+/// It does not influence which trades are produced.
+#[derive(Debug, Clone)]
+pub struct BatchMatcher {
+    /// This node's identity (included in trade metadata).
+    pub node_id: NodeId,
+    /// Clearing price selection parameters for markets without an entry
+    /// in `market_clearing`.
+    pub clearing: ClearingConfig,
+    /// Per-market clearing parameters, overriding `clearing`.
+    pub market_clearing: HashMap<MarketPair, ClearingConfig>,
+    /// Minimum distinct users among crossing bids for the batch to clear.
+    pub min_distinct_buyers: usize,
+    /// Minimum distinct users among crossing asks for the batch to clear.
+    pub min_distinct_sellers: usize,
+    /// Largest quantity a single bid may fill against a single ask.
+    pub max_single_fill: Option<Decimal>,
+    /// Shard this matcher runs on; part of every trade ID's [`BatchId`].
+    pub shard: u16,
+    /// Source of trades' `executed_at`.
+    pub clock: Arc<dyn Clock>,
+    /// Quote-amount rounding; `None` keeps the exact product.
+    pub quote_rounding: Option<QuoteRounding>,
+}
+
+impl BatchMatcher {
+    /// Create a new matcher for the given node.
+    #[must_use]
+    pub fn new(node_id: NodeId) -> Self {
+        Self {
+            node_id,
+            clearing: ClearingConfig::default(),
+            market_clearing: HashMap::new(),
+            min_distinct_buyers: 1,
+            min_distinct_sellers: 1,
+            max_single_fill: None,
+            shard: 0,
+            clock: SystemClock::shared(),
+            quote_rounding: None,
+        }
+    }
+
+    /// Run as `shard`. Trade IDs are derived from
+    /// `BatchId::from_epoch(epoch, shard)`, so shards matching the same
+    /// epoch never produce colliding IDs.
+    #[must_use]
+    pub fn with_shard(mut self, shard: u16) -> Self {
+        self.shard = shard;
+        self
+    }
+
+    /// Stamp trades' `executed_at` from `clock`.
+    #[must_use]
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Cap every fill between one bid and one ask at `max` (this matcher's
+    /// market), spreading large orders across several counterparties.
+    #[must_use]
+    pub fn with_max_single_fill(mut self, max: Decimal) -> Self {
+        self.max_single_fill = Some(max);
+        self
+    }
+
+    /// Round every fill's quote amount per `rounding` (this matcher's
+    /// market), routing the residual to its dust account.
+    #[must_use]
+    pub fn with_quote_rounding(mut self, rounding: QuoteRounding) -> Self {
+        self.quote_rounding = Some(rounding);
+        self
+    }
+
+    /// Require at least `buyers` / `sellers` distinct users on each side of
+    /// the crossing before a batch may clear.
+    ///
+    /// Batches below the threshold produce no trades and return every order
+    /// as remaining, so a single participant cannot set the price alone.
+    #[must_use]
+    pub fn with_min_participants(mut self, buyers: usize, sellers: usize) -> Self {
+        self.min_distinct_buyers = buyers;
+        self.min_distinct_sellers = sellers;
+        self
+    }
+
+    /// Use the given clearing configuration.
+    #[must_use]
+    pub fn with_clearing_config(mut self, clearing: ClearingConfig) -> Self {
+        self.clearing = clearing;
+        self
+    }
+
+    /// Use `clearing` for batches in `market` instead of the default.
+    #[must_use]
+    pub fn with_market_clearing_config(
+        mut self,
+        market: MarketPair,
+        clearing: ClearingConfig,
+    ) -> Self {
+        self.market_clearing.insert(market, clearing);
+        self
+    }
+
+    /// Clearing parameters in effect for `market`.
+    #[must_use]
+    pub fn clearing_for(&self, market: &MarketPair) -> &ClearingConfig {
+        self.market_clearing.get(market).unwrap_or(&self.clearing)
+    }
+
+    /// Create a matcher from the node's configuration.
+    #[must_use]
+    pub fn from_node_config(config: &NodeConfig) -> Self {
+        Self::new(config.node_id)
+    }
+
+    /// Pure deterministic matching: takes a sealed batch, produces a trade bundle.
+    ///
+    /// ## Algorithm
+    ///
+    /// 1. Insert all orders from the sealed batch into a fresh order book
+    ///    and apply same-user cancels
+    /// 2. Compute the uniform clearing price
+    /// 3. Check minimum participation on both sides of the crossing
+    /// 4. Walk crossing orders and produce trades at the clearing price
+    /// 5. Self-trade prevention: skip fills where buyer == seller
+    /// 6. Compute trade_root hash for cross-node verification
+    /// 7. Return the `TradeBundle`
+    ///
+    /// ## Determinism Guarantee
+    ///
+    /// Given the same `SealedBatch` (same orders in same order with same
+    /// `batch_hash`), this function produces the **exact same** `TradeBundle`
+    /// on every node — same trades, same trade_root, same clearing price.
+    ///
+    /// A batch that cannot be priced (see [`BatchMatcher::try_match_batch`])
+    /// produces no trades and returns every uncancelled order as remaining.
+    ///
+    /// # Errors
+    /// `DeterminismViolation` if the batch is not in canonical order (see
+    /// [`SealedBatch::verify_canonical_order`]), its epoch is too large to
+    /// number trades from (see [`BatchId::try_from_epoch`]), or the
+    /// produced bundle fails an integrity check (e.g. two trades share a
+    /// `TradeId`). None may reach settlement, so they are not downgraded
+    /// to an empty bundle.
+    pub fn match_batch(&self, batch: &SealedBatch) -> Result<TradeBundle> {
+        match self.try_match_batch(batch) {
+            Err(OpenmatchError::MatchingFailed { .. }) => Ok(Self::unpriced_bundle(batch)),
+            result => result,
+        }
+    }
+
+    /// Bundle for a batch that crossed without a price: no trades, every
+    /// uncancelled order remaining.
+    fn unpriced_bundle(batch: &SealedBatch) -> TradeBundle {
+        let cancelled_orders = batch
+            .orders
+            .first()
+            .map(|first| Self::build_book(first.market.clone(), &batch.orders).1)
+            .unwrap_or_default();
+        let mut remaining: Vec<Order> = batch
+            .orders
+            .iter()
+            .filter(|o| {
+                !o.order_type.is_cancel()
+                    && o.remaining_qty > Decimal::ZERO
+                    && !cancelled_orders.iter().any(|c| c.id == o.id)
+            })
+            .cloned()
+            .collect();
+        Order::sort_canonical(&mut remaining);
+        TradeBundle {
             epoch_id: batch.epoch_id,
             trades: vec![],
-            trade_root: compute_trade_root(&[]),
+            trade_root: compute_trade_root(batch.epoch_id, &[]),
             input_hash: batch.batch_hash,
             clearing_price: None,
-            remaining_orders: vec![],
-        };
-    };
-    let market = first.market.clone();
-
-    // 1. Build the order book from the sealed batch
-    let mut book = OrderBook::new(market);
-    for order in &batch.orders {
-        // Skip non-matchable orders (cancel orders)
-        if order.order_type == OrderType::Cancel {
-            continue;
+            unfilled_reasons: Self::all_unfilled(&remaining, UnfilledReason::NoCrossing),
+            wash_trade_attempts: vec![],
+            remaining_orders: remaining,
+            cancelled_orders,
+        }
+    }
+
+    /// Pair every order with the same `reason`.
+    fn all_unfilled(orders: &[Order], reason: UnfilledReason) -> Vec<(OrderId, UnfilledReason)> {
+        orders.iter().map(|o| (o.id, reason)).collect()
+    }
+
+    /// Insert the batch's orders into a fresh book, then apply its cancels
+    /// in batch order. Returns the book and the orders the cancels removed.
+    fn build_book(market: MarketPair, orders: &[Order]) -> (OrderBook, Vec<Order>) {
+        let mut book = OrderBook::new(market);
+        for order in orders.iter().filter(|o| !o.order_type.is_cancel()) {
+            // Ignore insert errors (duplicate order IDs in a sealed batch shouldn't happen)
+            let _ = book.insert_order(order.clone());
         }
-        // Ignore insert errors (duplicate order IDs in a sealed batch shouldn't happen)
-        let _ = book.insert_order(order.clone());
+        let cancelled = orders
+            .iter()
+            .filter_map(|o| match o.order_type {
+                // Missing or foreign targets are rejected by the book
+                OrderType::Cancel { target } => book.cancel_for_user(&target, &o.user_id).ok(),
+                _ => None,
+            })
+            .collect();
+        (book, cancelled)
+    }
+
+    /// Like [`BatchMatcher::match_batch`], but reports batches that cross
+    /// without any price to clear at.
+    ///
+    /// # Errors
+    /// `MatchingFailed` if only market orders cross and the clearing config
+    /// has no reference price; otherwise as [`BatchMatcher::match_batch`].
+    pub fn try_match_batch(&self, batch: &SealedBatch) -> Result<TradeBundle> {
+        self.match_counted(batch, &mut MatchingMetrics::default())
+    }
+
+    /// Like [`BatchMatcher::try_match_batch`], also returning the run's
+    /// [`MatchingMetrics`].
+    ///
+    /// # Errors
+    /// Same as [`BatchMatcher::try_match_batch`].
+    pub fn try_match_batch_with_metrics(
+        &self,
+        batch: &SealedBatch,
+    ) -> Result<(TradeBundle, MatchingMetrics)> {
+        let started = Instant::now();
+        let mut metrics = MatchingMetrics::default();
+        let bundle = self.match_counted(batch, &mut metrics)?;
+        metrics.fills = bundle.trades.len();
+        metrics.elapsed = started.elapsed();
+        Ok((bundle, metrics))
+    }
+
+    /// Match a batch that may hold several markets.
+    ///
+    /// The batch is split by market, keeping each market's orders in batch
+    /// order, and each part is matched with [`BatchMatcher::match_batch`].
+    /// The bundles are merged in `MarketPair` order: trades are concatenated
+    /// and renumbered so IDs stay unique, `trade_root` is recomputed over
+    /// the merged trades, and remaining orders are re-sorted canonically.
+    /// `clearing_price` is set only when exactly one market cleared; every
+    /// trade carries its own market's price. A single-market batch yields
+    /// the same bundle as [`BatchMatcher::match_batch`].
+    ///
+    /// # Errors
+    /// `DeterminismViolation` if the batch is not in canonical order or
+    /// the merged bundle fails an integrity check; otherwise the first
+    /// error a market's [`BatchMatcher::match_batch`] returns, in market
+    /// order.
+    pub fn match_batch_by_market(&self, batch: &SealedBatch) -> Result<TradeBundle> {
+        let markets = Self::split_by_market(batch)?;
+        let bundles = markets
+            .iter()
+            .map(|part| self.match_batch(part))
+            .collect::<Result<Vec<_>>>()?;
+        self.merge_market_bundles(batch, bundles)
     }
 
-    // 2. Compute the clearing price
-    let clearing = compute_clearing_price(&book);
+    /// [`BatchMatcher::match_batch_by_market`] with the markets matched
+    /// concurrently on `thread_pool`.
+    ///
+    /// Markets are merged in the same order as the serial path, so the
+    /// bundle is byte-identical to it regardless of scheduling.
+    ///
+    /// # Errors
+    /// Same as [`BatchMatcher::match_batch_by_market`].
+    #[cfg(feature = "rayon")]
+    pub fn match_batch_by_market_parallel(
+        &self,
+        batch: &SealedBatch,
+        thread_pool: &rayon::ThreadPool,
+    ) -> Result<TradeBundle> {
+        use rayon::prelude::*;
 
-    let Some(clearing_price) = clearing.clearing_price else {
-        // No crossing: all orders remain unmatched
-        let remaining = book.drain_all();
-        return TradeBundle {
+        let markets = Self::split_by_market(batch)?;
+        let bundles = thread_pool.install(|| {
+            markets
+                .par_iter()
+                .map(|part| self.match_batch(part))
+                .collect::<Result<Vec<_>>>()
+        })?;
+        self.merge_market_bundles(batch, bundles)
+    }
+
+    /// One sub-batch per market, in `MarketPair` order.
+    fn split_by_market(batch: &SealedBatch) -> Result<Vec<SealedBatch>> {
+        batch.verify_canonical_order()?;
+        let mut markets: BTreeMap<&MarketPair, Vec<Order>> = BTreeMap::new();
+        for order in &batch.orders {
+            markets
+                .entry(&order.market)
+                .or_default()
+                .push(order.clone());
+        }
+        Ok(markets
+            .into_values()
+            .map(|orders| SealedBatch {
+                epoch_id: batch.epoch_id,
+                orders,
+                batch_hash: batch.batch_hash,
+                sealed_at: batch.sealed_at,
+                sealer_node: batch.sealer_node,
+            })
+            .collect())
+    }
+
+    /// Merge per-market bundles, in market order, into one bundle for
+    /// `batch`.
+    fn merge_market_bundles(
+        &self,
+        batch: &SealedBatch,
+        bundles: Vec<TradeBundle>,
+    ) -> Result<TradeBundle> {
+        let prices: Vec<Decimal> = bundles.iter().filter_map(|b| b.clearing_price).collect();
+        let mut merged = TradeBundle {
             epoch_id: batch.epoch_id,
             trades: vec![],
-            trade_root: compute_trade_root(&[]),
+            trade_root: [0u8; 32],
             input_hash: batch.batch_hash,
-            clearing_price: None,
-            remaining_orders: remaining,
+            clearing_price: match prices[..] {
+                [price] => Some(price),
+                _ => None,
+            },
+            remaining_orders: vec![],
+            cancelled_orders: vec![],
+            unfilled_reasons: vec![],
+            wash_trade_attempts: vec![],
         };
-    };
-
-    // 3. Walk crossing orders and produce trades
-    let mut trades: Vec<Trade> = Vec::new();
-    let mut fill_seq: u64 = 0;
+        let mut reasons: HashMap<OrderId, UnfilledReason> = HashMap::new();
+        for bundle in bundles {
+            merged.trades.extend(bundle.trades);
+            merged.remaining_orders.extend(bundle.remaining_orders);
+            merged.cancelled_orders.extend(bundle.cancelled_orders);
+            merged
+                .wash_trade_attempts
+                .extend(bundle.wash_trade_attempts);
+            reasons.extend(bundle.unfilled_reasons);
+        }
 
-    // Collect bids and asks that cross at the clearing price
-    let mut bids: Vec<Order> = Vec::new();
-    for level in book.bid_levels() {
-        if level.price >= clearing_price {
-            bids.extend(level.orders.iter().cloned());
+        let batch_id = BatchId::try_from_epoch(batch.epoch_id, self.shard)?;
+        for (fill_seq, trade) in (0u64..).zip(&mut merged.trades) {
+            trade.id = TradeId::deterministic(batch_id.0, fill_seq);
         }
+        merged.trade_root = compute_trade_root(batch.epoch_id, &merged.trades);
+        Order::sort_canonical(&mut merged.remaining_orders);
+        merged.unfilled_reasons = merged
+            .remaining_orders
+            .iter()
+            .map(|o| (o.id, reasons[&o.id]))
+            .collect();
+        merged.assert_unique_trade_ids()?;
+        Ok(merged)
     }
-    // Sort bids by sequence (deterministic order)
-    bids.sort_by_key(|o| o.sequence);
 
-    let mut asks: Vec<Order> = Vec::new();
-    for level in book.ask_levels() {
-        if level.price <= clearing_price {
-            asks.extend(level.orders.iter().cloned());
+    /// Match `batch`, holding back all-or-none orders a pass would only
+    /// partially fill. Each retry holds back at least one more order, so
+    /// this terminates.
+    fn match_counted(
+        &self,
+        batch: &SealedBatch,
+        metrics: &mut MatchingMetrics,
+    ) -> Result<TradeBundle> {
+        batch.verify_canonical_order()?;
+        let mut bundle = self.match_pass(batch, metrics)?;
+        if !batch.orders.iter().any(|o| o.order_type.is_all_or_none()) {
+            return Ok(bundle);
         }
+
+        let mut held: HashSet<OrderId> = HashSet::new();
+        loop {
+            let partial = bundle.remaining_orders.iter().filter(|rest| {
+                rest.order_type.is_all_or_none()
+                    && batch
+                        .orders
+                        .iter()
+                        .any(|o| o.id == rest.id && o.remaining_qty != rest.remaining_qty)
+            });
+            let before = held.len();
+            held.extend(partial.map(|o| o.id));
+            if held.len() == before {
+                break;
+            }
+            let pass = SealedBatch {
+                epoch_id: batch.epoch_id,
+                orders: batch
+                    .orders
+                    .iter()
+                    .filter(|o| !held.contains(&o.id))
+                    .cloned()
+                    .collect(),
+                batch_hash: batch.batch_hash,
+                sealed_at: batch.sealed_at,
+                sealer_node: batch.sealer_node,
+            };
+            *metrics = MatchingMetrics::default();
+            bundle = self.match_pass(&pass, metrics)?;
+        }
+
+        if !held.is_empty() {
+            bundle.remaining_orders.extend(
+                batch
+                    .orders
+                    .iter()
+                    .filter(|o| held.contains(&o.id))
+                    .cloned(),
+            );
+            Order::sort_canonical(&mut bundle.remaining_orders);
+            let reasons: HashMap<OrderId, UnfilledReason> =
+                bundle.unfilled_reasons.drain(..).collect();
+            bundle.unfilled_reasons = bundle
+                .remaining_orders
+                .iter()
+                .map(|o| {
+                    let reason = reasons
+                        .get(&o.id)
+                        .copied()
+                        .unwrap_or(UnfilledReason::AllOrNoneHeld);
+                    (o.id, reason)
+                })
+                .collect();
+        }
+        Ok(bundle)
     }
-    // Sort asks by sequence (deterministic order)
-    asks.sort_by_key(|o| o.sequence);
 
-    // Match bids against asks at the clearing price
-    let mut ask_idx = 0;
-    for bid in &mut bids {
-        while ask_idx < asks.len() && bid.remaining_qty > Decimal::ZERO {
-            let ask = &mut asks[ask_idx];
+    #[allow(clippy::too_many_lines)]
+    fn match_pass(
+        &self,
+        batch: &SealedBatch,
+        metrics: &mut MatchingMetrics,
+    ) -> Result<TradeBundle> {
+        let Some(first) = batch.orders.first() else {
+            // Empty batch → empty bundle
+            return Ok(TradeBundle {
+                epoch_id: batch.epoch_id,
+                trades: vec![],
+                trade_root: compute_trade_root(batch.epoch_id, &[]),
+                input_hash: batch.batch_hash,
+                clearing_price: None,
+                remaining_orders: vec![],
+                cancelled_orders: vec![],
+                unfilled_reasons: vec![],
+                wash_trade_attempts: vec![],
+            });
+        };
 
-            if ask.remaining_qty.is_zero() {
-                ask_idx += 1;
-                continue;
-            }
+        // 1. Build the order book from the sealed batch, applying cancels
+        let (mut book, cancelled_orders) = Self::build_book(first.market.clone(), &batch.orders);
 
-            // Self-trade prevention: skip if same user
-            if bid.user_id == ask.user_id {
-                ask_idx += 1;
-                continue;
-            }
+        // 2. Compute the clearing price
+        let clearing = try_compute_clearing_price(&book, self.clearing_for(&book.market))?;
+        metrics.price_candidates = clearing.candidates_evaluated;
+
+        let Some(clearing_price) = clearing.clearing_price else {
+            // No crossing: all orders remain unmatched
+            let mut remaining = book.drain_all();
+            Order::sort_canonical(&mut remaining);
+            return Ok(TradeBundle {
+                epoch_id: batch.epoch_id,
+                trades: vec![],
+                trade_root: compute_trade_root(batch.epoch_id, &[]),
+                input_hash: batch.batch_hash,
+                clearing_price: None,
+                unfilled_reasons: Self::all_unfilled(&remaining, UnfilledReason::NoCrossing),
+                wash_trade_attempts: vec![],
+                remaining_orders: remaining,
+                cancelled_orders,
+            });
+        };
 
-            // Compute fill quantity
-            let fill_qty = bid.remaining_qty.min(ask.remaining_qty);
-            let quote_amount = clearing_price * fill_qty;
+        // 3. Walk crossing orders and produce trades
+        let mut trades: Vec<Trade> = Vec::new();
+        let batch_id = BatchId::try_from_epoch(batch.epoch_id, self.shard)?;
+        let executed_at = self.clock.now();
+        let mut fill_seq: u64 = 0;
+
+        // Move every order out of the book once; matching works on indices
+        // into this vector rather than on cloned copies. Drain order is
+        // book priority: bids best-first, then asks best-first, FIFO within
+        // a level.
+        let mut orders = book.drain_all();
+        let crossing: Vec<bool> = orders
+            .iter()
+            .map(|o| match o.side {
+                OrderSide::Buy => o.effective_price() >= clearing_price,
+                OrderSide::Sell => o.effective_price() <= clearing_price,
+            })
+            .collect();
 
-            // Create the trade
-            let trade = Trade {
-                id: TradeId::deterministic(batch.epoch_id.0, fill_seq),
+        // Crossing bids and asks, each in canonical order (price priority,
+        // then sequence, then id)
+        let crossing_side = |side: OrderSide| -> Vec<usize> {
+            let mut idx: Vec<usize> = (0..orders.len())
+                .filter(|&i| crossing[i] && orders[i].side == side)
+                .collect();
+            idx.sort_by(|&a, &b| orders[a].cmp_canonical(&orders[b]));
+            idx
+        };
+        let bids = crossing_side(OrderSide::Buy);
+        let asks = crossing_side(OrderSide::Sell);
+
+        // Minimum participation: too few distinct users on a side → no clearing
+        let distinct = |idx: &[usize]| {
+            idx.iter()
+                .map(|&i| orders[i].user_id)
+                .collect::<HashSet<_>>()
+                .len()
+        };
+        if distinct(&bids) < self.min_distinct_buyers || distinct(&asks) < self.min_distinct_sellers
+        {
+            Order::sort_canonical(&mut orders);
+            return Ok(TradeBundle {
                 epoch_id: batch.epoch_id,
-                market: bid.market.clone(),
-                taker_order_id: bid.id,
-                taker_user_id: bid.user_id,
-                maker_order_id: ask.id,
-                maker_user_id: ask.user_id,
-                price: clearing_price,
-                quantity: fill_qty,
-                quote_amount,
-                taker_side: OrderSide::Buy,
-                matcher_node: NodeId([0u8; 32]),
-                executed_at: Utc::now(),
-            };
+                trades: vec![],
+                trade_root: compute_trade_root(batch.epoch_id, &[]),
+                input_hash: batch.batch_hash,
+                clearing_price: None,
+                unfilled_reasons: Self::all_unfilled(&orders, UnfilledReason::NoCrossing),
+                wash_trade_attempts: vec![],
+                remaining_orders: orders,
+                cancelled_orders,
+            });
+        }
 
-            trades.push(trade);
-            fill_seq += 1;
+        // Match bids against asks at the clearing price. `ask_pos` is the
+        // first ask still open to every bid; asks before it are exhausted
+        // or were skipped for self-trade. `j` scans ahead of it for the
+        // current bid only (past asks this bid hit the fill cap on).
+        let mut has_fill = vec![false; orders.len()];
+        let mut wash_trade_attempts = Vec::new();
+        let mut ask_pos = 0;
+        for &b in &bids {
+            let mut j = ask_pos;
+            while j < asks.len() && orders[b].remaining_qty > Decimal::ZERO {
+                let a = asks[j];
+                let (bid, ask) = (&orders[b], &orders[a]);
 
-            bid.remaining_qty -= fill_qty;
-            ask.remaining_qty -= fill_qty;
+                // Compute fill quantity and its quote amount
+                let mut fill_qty = bid.remaining_qty.min(ask.remaining_qty);
+                if let Some(cap) = self.max_single_fill {
+                    fill_qty = fill_qty.min(cap);
+                }
+                let quote = Trade::checked_quote_amount(clearing_price, fill_qty);
 
-            if ask.remaining_qty.is_zero() {
-                ask_idx += 1;
+                // Skip exhausted asks; self-trade prevention: skip if same
+                // user; skip pairs whose quote amount overflows
+                let self_trade = bid.user_id == ask.user_id;
+                if self_trade && !ask.remaining_qty.is_zero() {
+                    metrics.self_trades_skipped += 1;
+                    wash_trade_attempts.push(WashTradeAttempt {
+                        user_id: bid.user_id,
+                        buy_order_id: bid.id,
+                        sell_order_id: ask.id,
+                        epoch_id: batch.epoch_id,
+                    });
+                }
+                let skip = ask.remaining_qty.is_zero() || self_trade || quote.is_none();
+                if let Some(exact) = quote.filter(|_| !skip) {
+                    let (quote_amount, quote_dust) = match &self.quote_rounding {
+                        Some(rounding) => rounding.split(exact),
+                        None => (exact, None),
+                    };
+                    // Create the trade
+                    let trade = Trade {
+                        id: TradeId::deterministic(batch_id.0, fill_seq),
+                        epoch_id: batch.epoch_id,
+                        market: bid.market.clone(),
+                        taker_order_id: bid.id,
+                        taker_user_id: bid.user_id,
+                        maker_order_id: ask.id,
+                        maker_user_id: ask.user_id,
+                        price: clearing_price,
+                        quantity: fill_qty,
+                        quote_amount,
+                        taker_side: OrderSide::Buy,
+                        matcher_node: self.node_id,
+                        executed_at,
+                        quote_dust,
+                    };
+
+                    trades.push(trade);
+                    fill_seq += 1;
+
+                    orders[b].remaining_qty -= fill_qty;
+                    orders[a].remaining_qty -= fill_qty;
+                    has_fill[b] = true;
+                    has_fill[a] = true;
+                }
+
+                if j == ask_pos && (skip || orders[a].remaining_qty.is_zero()) {
+                    ask_pos += 1;
+                }
+                j += 1;
             }
         }
-    }
 
-    // 4. Compute trade root for determinism verification
-    let trade_root = compute_trade_root(&trades);
+        // 4. Compute trade root for determinism verification
+        let trade_root = compute_trade_root(batch.epoch_id, &trades);
 
-    // 5. Collect remaining (unmatched or partially filled) orders
-    let mut remaining = Vec::new();
-    for order in bids.into_iter().chain(asks.into_iter()) {
-        if order.remaining_qty > Decimal::ZERO {
-            remaining.push(order);
+        // 5. Collect remaining orders: crossing orders with quantity left
+        // (bids, then asks, in sequence order), then every order that
+        // never traded (non-crossing side of the price) in book order.
+        // A crossing order left open while its own user still has open
+        // volume on the other side was stopped by self-trade prevention.
+        let open_users = |idx: &[usize]| -> HashSet<UserId> {
+            idx.iter()
+                .filter(|&&i| orders[i].remaining_qty > Decimal::ZERO)
+                .map(|&i| orders[i].user_id)
+                .collect()
+        };
+        let (open_buyers, open_sellers) = (open_users(&bids), open_users(&asks));
+        let mut reasons: HashMap<OrderId, UnfilledReason> = HashMap::new();
+        let mut slots: Vec<Option<Order>> = orders.into_iter().map(Some).collect();
+        let mut remaining = Vec::new();
+        for &i in bids.iter().chain(&asks) {
+            if let Some(order) = slots[i].take_if(|o| o.remaining_qty > Decimal::ZERO) {
+                let counterparties = match order.side {
+                    OrderSide::Buy => &open_sellers,
+                    OrderSide::Sell => &open_buyers,
+                };
+                let reason = if counterparties.contains(&order.user_id) {
+                    UnfilledReason::SelfTrade
+                } else {
+                    UnfilledReason::Rationed
+                };
+                reasons.insert(order.id, reason);
+                remaining.push(order);
+            }
         }
-    }
-    // Also collect orders that were completely on the non-crossing side
-    // (bids below clearing price, asks above clearing price)
-    let all_remaining = book.drain_all();
-    for order in all_remaining {
-        // Only add orders that weren't already included in bids/asks
-        if !remaining.iter().any(|o| o.id == order.id)
-            && !trades
-                .iter()
-                .any(|t| t.taker_order_id == order.id || t.maker_order_id == order.id)
-        {
-            remaining.push(order);
+        for (i, slot) in slots.into_iter().enumerate() {
+            if let Some(order) = slot.filter(|o| !has_fill[i] && o.remaining_qty > Decimal::ZERO) {
+                reasons.insert(order.id, UnfilledReason::OutOfMoney);
+                remaining.push(order);
+            }
         }
-    }
 
-    TradeBundle {
-        epoch_id: batch.epoch_id,
-        trades,
-        trade_root,
-        input_hash: batch.batch_hash,
-        clearing_price: Some(clearing_price),
-        remaining_orders: remaining,
+        Order::sort_canonical(&mut remaining);
+        let bundle = TradeBundle {
+            epoch_id: batch.epoch_id,
+            trades,
+            trade_root,
+            input_hash: batch.batch_hash,
+            clearing_price: Some(clearing_price),
+            unfilled_reasons: remaining.iter().map(|o| (o.id, reasons[&o.id])).collect(),
+            wash_trade_attempts,
+            remaining_orders: remaining,
+            cancelled_orders,
+        };
+        bundle.assert_unique_trade_ids()?;
+        Ok(bundle)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use chrono::Utc;
+    use chrono::{DateTime, Utc};
     use openmatch_types::*;
     use rust_decimal::Decimal;
 
@@ -206,10 +847,36 @@ mod tests {
         }
     }
 
+    fn make_node_config(node_id: NodeId) -> NodeConfig {
+        NodeConfig {
+            node_id,
+            listen_addr: "127.0.0.1:8080".parse().unwrap(),
+            data_dir: "/tmp/openmatch".to_string(),
+            epoch: EpochConfig::default(),
+            network: NetworkConfig::default(),
+            markets: vec![MarketConfig::btc_usdt()],
+        }
+    }
+
+    #[test]
+    fn matcher_from_node_config_uses_node_id() {
+        let node_id = NodeId([7u8; 32]);
+        let matcher = BatchMatcher::from_node_config(&make_node_config(node_id));
+        assert_eq!(matcher.node_id, node_id);
+
+        let batch = make_sealed_batch(vec![
+            Order::dummy_limit(OrderSide::Buy, Decimal::new(100, 0), Decimal::ONE),
+            Order::dummy_limit(OrderSide::Sell, Decimal::new(100, 0), Decimal::ONE),
+        ]);
+        let bundle = matcher.match_batch(&batch).unwrap();
+        assert_eq!(bundle.trades.len(), 1);
+        assert_eq!(bundle.trades[0].matcher_node, node_id);
+    }
+
     #[test]
     fn empty_batch_produces_no_trades() {
         let batch = make_sealed_batch(vec![]);
-        let bundle = match_sealed_batch(&batch);
+        let bundle = match_sealed_batch(&batch).unwrap();
         assert!(bundle.trades.is_empty());
         assert!(bundle.clearing_price.is_none());
         assert_eq!(bundle.epoch_id, EpochId(1));
@@ -221,19 +888,69 @@ mod tests {
             Order::dummy_limit(OrderSide::Buy, Decimal::new(99, 0), Decimal::ONE),
             Order::dummy_limit(OrderSide::Sell, Decimal::new(101, 0), Decimal::ONE),
         ]);
-        let bundle = match_sealed_batch(&batch);
+        let bundle = match_sealed_batch(&batch).unwrap();
         assert!(bundle.trades.is_empty());
         assert!(bundle.clearing_price.is_none());
         assert_eq!(bundle.remaining_orders.len(), 2);
     }
 
+    #[test]
+    fn non_canonical_batch_is_rejected() {
+        let sell = Order::dummy_limit(OrderSide::Sell, Decimal::new(100, 0), Decimal::ONE);
+        let buy = Order::dummy_limit(OrderSide::Buy, Decimal::new(100, 0), Decimal::ONE);
+        let batch = sequenced(vec![sell, buy]);
+        assert_eq!(match_sealed_batch(&batch).unwrap().trades.len(), 1);
+
+        let mut swapped = batch.clone();
+        swapped.orders.reverse();
+        assert!(matches!(
+            match_sealed_batch(&swapped),
+            Err(OpenmatchError::DeterminismViolation { .. })
+        ));
+        assert!(matches!(
+            BatchMatcher::new(NodeId([0u8; 32])).try_match_batch_with_metrics(&swapped),
+            Err(OpenmatchError::DeterminismViolation { .. })
+        ));
+    }
+
+    #[test]
+    fn zero_remaining_order_is_not_traded() {
+        let mut filled = Order::dummy_limit(OrderSide::Buy, Decimal::new(105, 0), Decimal::ONE);
+        filled.remaining_qty = Decimal::ZERO;
+        let filled_id = filled.id;
+        let live = vec![
+            Order::dummy_limit(OrderSide::Buy, Decimal::new(100, 0), Decimal::ONE),
+            Order::dummy_limit(OrderSide::Sell, Decimal::new(100, 0), Decimal::ONE),
+        ];
+        let baseline = match_sealed_batch(&make_sealed_batch(live.clone())).unwrap();
+
+        let mut orders = vec![filled];
+        orders.extend(live);
+        let bundle = match_sealed_batch(&make_sealed_batch(orders)).unwrap();
+        assert_eq!(bundle.clearing_price, baseline.clearing_price);
+        assert_eq!(bundle.trades.len(), 1);
+        assert!(
+            bundle
+                .trades
+                .iter()
+                .all(|t| { t.taker_order_id != filled_id && t.maker_order_id != filled_id })
+        );
+        assert!(bundle.remaining_orders.iter().all(|o| o.id != filled_id));
+        assert!(
+            bundle
+                .unfilled_reasons
+                .iter()
+                .all(|(id, _)| *id != filled_id)
+        );
+    }
+
     #[test]
     fn simple_crossing_produces_trade() {
         let batch = make_sealed_batch(vec![
             Order::dummy_limit(OrderSide::Buy, Decimal::new(100, 0), Decimal::ONE),
             Order::dummy_limit(OrderSide::Sell, Decimal::new(100, 0), Decimal::ONE),
         ]);
-        let bundle = match_sealed_batch(&batch);
+        let bundle = match_sealed_batch(&batch).unwrap();
         assert_eq!(bundle.trades.len(), 1);
         assert!(bundle.clearing_price.is_some());
 
@@ -251,17 +968,98 @@ mod tests {
         sell.user_id = user;
 
         let batch = make_sealed_batch(vec![buy, sell]);
-        let bundle = match_sealed_batch(&batch);
+        let bundle = match_sealed_batch(&batch).unwrap();
         assert!(bundle.trades.is_empty(), "Self-trade should be prevented");
     }
 
+    #[test]
+    fn self_crossing_pair_records_one_wash_trade_attempt() {
+        let user = UserId::new();
+        let mut buy = Order::dummy_limit(OrderSide::Buy, Decimal::new(100, 0), Decimal::ONE);
+        buy.user_id = user;
+        let mut sell = Order::dummy_limit(OrderSide::Sell, Decimal::new(100, 0), Decimal::ONE);
+        sell.user_id = user;
+        let (buy_id, sell_id) = (buy.id, sell.id);
+
+        let bundle = match_sealed_batch(&make_sealed_batch(vec![buy, sell])).unwrap();
+        assert_eq!(
+            bundle.wash_trade_attempts,
+            vec![WashTradeAttempt {
+                user_id: user,
+                buy_order_id: buy_id,
+                sell_order_id: sell_id,
+                epoch_id: EpochId(1),
+            }]
+        );
+        assert_eq!(bundle.wash_trade_counts()[&user], 1);
+
+        let clean = match_sealed_batch(&make_sealed_batch(vec![
+            Order::dummy_limit(OrderSide::Buy, Decimal::new(100, 0), Decimal::ONE),
+            Order::dummy_limit(OrderSide::Sell, Decimal::new(100, 0), Decimal::ONE),
+        ]))
+        .unwrap();
+        assert!(clean.wash_trade_attempts.is_empty());
+    }
+
+    #[test]
+    fn quote_rounding_routes_residual_to_dust_account() {
+        let dust_account = UserId::new();
+        let rounding = QuoteRounding {
+            scale: 2,
+            bias: RoundingStrategy::ToZero,
+            dust_account,
+        };
+        let (amount, dust) = rounding.split(Decimal::new(3_700_111, 5));
+        assert_eq!(amount, Decimal::new(3700, 2));
+        assert_eq!(
+            dust,
+            Some(QuoteDust {
+                account: dust_account,
+                amount: Decimal::new(111, 5),
+            })
+        );
+        assert_eq!(
+            rounding.split(Decimal::new(125, 1)),
+            (Decimal::new(125, 1), None)
+        );
+
+        let up = QuoteRounding {
+            bias: RoundingStrategy::AwayFromZero,
+            ..rounding
+        };
+        let (amount, dust) = up.split(Decimal::new(3_700_111, 5));
+        assert_eq!(amount, Decimal::new(3701, 2));
+        assert_eq!(dust.unwrap().amount, Decimal::new(-889, 5));
+
+        // Through the matcher: the buyer still pays the exact product.
+        let batch = make_sealed_batch(vec![
+            Order::dummy_limit(
+                OrderSide::Buy,
+                Decimal::new(100_003, 3),
+                Decimal::new(37, 2),
+            ),
+            Order::dummy_limit(
+                OrderSide::Sell,
+                Decimal::new(100_003, 3),
+                Decimal::new(37, 2),
+            ),
+        ]);
+        let bundle = BatchMatcher::new(NodeId([0u8; 32]))
+            .with_quote_rounding(rounding)
+            .match_batch(&batch)
+            .unwrap();
+        let trade = &bundle.trades[0];
+        assert_eq!(trade.quote_amount, Decimal::new(3700, 2));
+        assert_eq!(trade.buyer_quote_amount(), trade.price * trade.quantity);
+    }
+
     #[test]
     fn partial_fill() {
         let batch = make_sealed_batch(vec![
             Order::dummy_limit(OrderSide::Buy, Decimal::new(100, 0), Decimal::new(5, 0)),
             Order::dummy_limit(OrderSide::Sell, Decimal::new(100, 0), Decimal::new(3, 0)),
         ]);
-        let bundle = match_sealed_batch(&batch);
+        let bundle = match_sealed_batch(&batch).unwrap();
         assert_eq!(bundle.trades.len(), 1);
         assert_eq!(bundle.trades[0].quantity, Decimal::new(3, 0));
         // Buyer should have remaining 2
@@ -281,12 +1079,48 @@ mod tests {
             Order::dummy_limit(OrderSide::Sell, Decimal::new(100, 0), Decimal::ONE),
             Order::dummy_limit(OrderSide::Sell, Decimal::new(100, 0), Decimal::ONE),
         ]);
-        let bundle = match_sealed_batch(&batch);
+        let bundle = match_sealed_batch(&batch).unwrap();
         assert_eq!(bundle.trades.len(), 3);
         let total_qty: Decimal = bundle.trades.iter().map(|t| t.quantity).sum();
         assert_eq!(total_qty, Decimal::new(3, 0));
     }
 
+    #[test]
+    fn remaining_orders_are_canonically_ordered() {
+        // Bid 2 @ 101 fills against the 1 @ 100 ask; the rest rests.
+        let orders = vec![
+            Order::dummy_limit(OrderSide::Sell, Decimal::new(103, 0), Decimal::ONE),
+            Order::dummy_limit(OrderSide::Buy, Decimal::new(99, 0), Decimal::ONE),
+            Order::dummy_limit(OrderSide::Sell, Decimal::new(102, 0), Decimal::ONE),
+            Order::dummy_limit(OrderSide::Buy, Decimal::new(101, 0), Decimal::TWO),
+            Order::dummy_limit(OrderSide::Buy, Decimal::new(98, 0), Decimal::ONE),
+            Order::dummy_limit(OrderSide::Sell, Decimal::new(100, 0), Decimal::ONE),
+        ];
+        let mut reversed = orders.clone();
+        reversed.reverse();
+
+        let a = match_sealed_batch(&sequenced(orders)).unwrap();
+        let b = match_sealed_batch(&sequenced(reversed)).unwrap();
+        assert_eq!(a.trades.len(), 1);
+        let ids = |orders: &[Order]| orders.iter().map(|o| o.id).collect::<Vec<_>>();
+        assert_eq!(ids(&a.remaining_orders), ids(&b.remaining_orders));
+        let prices: Vec<_> = a
+            .remaining_orders
+            .iter()
+            .map(|o| (o.side, o.price.unwrap()))
+            .collect();
+        assert_eq!(
+            prices,
+            vec![
+                (OrderSide::Buy, Decimal::new(101, 0)),
+                (OrderSide::Buy, Decimal::new(99, 0)),
+                (OrderSide::Buy, Decimal::new(98, 0)),
+                (OrderSide::Sell, Decimal::new(102, 0)),
+                (OrderSide::Sell, Decimal::new(103, 0)),
+            ]
+        );
+    }
+
     #[test]
     fn trade_ids_are_deterministic() {
         let orders = vec![
@@ -308,8 +1142,8 @@ mod tests {
             sealer_node: NodeId([0u8; 32]),
         };
 
-        let bundle1 = match_sealed_batch(&batch1);
-        let bundle2 = match_sealed_batch(&batch2);
+        let bundle1 = match_sealed_batch(&batch1).unwrap();
+        let bundle2 = match_sealed_batch(&batch2).unwrap();
 
         // Trade IDs should be identical (deterministic from epoch_id + fill_seq)
         assert_eq!(bundle1.trades.len(), bundle2.trades.len());
@@ -324,32 +1158,85 @@ mod tests {
             Order::dummy_limit(OrderSide::Buy, Decimal::new(100, 0), Decimal::ONE),
             Order::dummy_limit(OrderSide::Sell, Decimal::new(100, 0), Decimal::ONE),
         ]);
-        let bundle = match_sealed_batch(&batch);
+        let bundle = match_sealed_batch(&batch).unwrap();
         assert_ne!(
             bundle.trade_root, [0u8; 32],
             "Trade root should not be zero"
         );
     }
 
+    #[test]
+    fn empty_bundles_from_different_epochs_differ() {
+        let mut batch = make_sealed_batch(vec![]);
+        let bundle1 = match_sealed_batch(&batch).unwrap();
+        batch.epoch_id = EpochId(2);
+        let bundle2 = match_sealed_batch(&batch).unwrap();
+        assert!(bundle1.trades.is_empty() && bundle2.trades.is_empty());
+        assert_ne!(bundle1.trade_root, bundle2.trade_root);
+    }
+
     #[test]
     fn input_hash_is_propagated() {
         let mut batch = make_sealed_batch(vec![]);
         batch.batch_hash = [42u8; 32];
-        let bundle = match_sealed_batch(&batch);
+        let bundle = match_sealed_batch(&batch).unwrap();
         assert_eq!(bundle.input_hash, [42u8; 32]);
     }
 
     #[test]
     fn cancel_orders_are_skipped() {
         let mut cancel = Order::dummy_limit(OrderSide::Buy, Decimal::new(100, 0), Decimal::ONE);
-        cancel.order_type = OrderType::Cancel;
+        cancel.order_type = OrderType::Cancel {
+            target: OrderId::new(),
+        };
 
         let batch = make_sealed_batch(vec![
             cancel,
             Order::dummy_limit(OrderSide::Sell, Decimal::new(100, 0), Decimal::ONE),
         ]);
-        let bundle = match_sealed_batch(&batch);
+        let bundle = match_sealed_batch(&batch).unwrap();
+        assert!(bundle.trades.is_empty());
+        assert!(bundle.cancelled_orders.is_empty());
+    }
+
+    fn cancel_of(user_id: UserId, target: OrderId) -> Order {
+        let mut cancel = Order::dummy_limit_for_user(
+            user_id,
+            OrderSide::Buy,
+            Decimal::new(100, 0),
+            Decimal::ONE,
+        );
+        cancel.order_type = OrderType::Cancel { target };
+        cancel
+    }
+
+    #[test]
+    fn self_cancel_removes_target_from_matching() {
+        let bid = Order::dummy_limit(OrderSide::Buy, Decimal::new(100, 0), Decimal::ONE);
+        let ask = Order::dummy_limit(OrderSide::Sell, Decimal::new(100, 0), Decimal::ONE);
+        let (bid_id, ask_id) = (bid.id, ask.id);
+        let cancel = cancel_of(bid.user_id, bid_id);
+
+        let bundle = match_sealed_batch(&sequenced(vec![bid, ask, cancel])).unwrap();
         assert!(bundle.trades.is_empty());
+        assert_eq!(bundle.cancelled_orders.len(), 1);
+        assert_eq!(bundle.cancelled_orders[0].id, bid_id);
+        let remaining: Vec<_> = bundle.remaining_orders.iter().map(|o| o.id).collect();
+        assert_eq!(remaining, vec![ask_id]);
+    }
+
+    #[test]
+    fn cross_user_cancel_is_rejected() {
+        let bid = Order::dummy_limit(OrderSide::Buy, Decimal::new(100, 0), Decimal::ONE);
+        let ask = Order::dummy_limit(OrderSide::Sell, Decimal::new(100, 0), Decimal::ONE);
+        let bid_id = bid.id;
+        // The seller tries to cancel the buyer's order
+        let cancel = cancel_of(ask.user_id, bid_id);
+
+        let bundle = match_sealed_batch(&sequenced(vec![bid, ask, cancel])).unwrap();
+        assert!(bundle.cancelled_orders.is_empty());
+        assert_eq!(bundle.trades.len(), 1);
+        assert_eq!(bundle.trades[0].taker_order_id, bid_id);
     }
 
     #[test]
@@ -371,7 +1258,7 @@ mod tests {
         buy_other.sequence = 2;
 
         let batch = make_sealed_batch(vec![sell, buy_self, buy_other]);
-        let bundle = match_sealed_batch(&batch);
+        let bundle = match_sealed_batch(&batch).unwrap();
 
         // Should have at least one trade (user_b buys from user_a)
         // User_a's self-trade should be skipped
@@ -382,4 +1269,723 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn metrics_report_skipped_self_trades() {
+        // A's bid skips A's own ask and fills against C's ask instead
+        let (user_a, user_b, user_c) = (UserId::new(), UserId::new(), UserId::new());
+        let price = Decimal::new(100, 0);
+        let mut sell = Order::dummy_limit(OrderSide::Sell, price, Decimal::ONE);
+        sell.user_id = user_a;
+        sell.sequence = 0;
+        let mut buy_self = Order::dummy_limit(OrderSide::Buy, price, Decimal::ONE);
+        buy_self.user_id = user_a;
+        buy_self.sequence = 1;
+        let mut buy_other = Order::dummy_limit(OrderSide::Buy, price, Decimal::ONE);
+        buy_other.user_id = user_b;
+        buy_other.sequence = 2;
+        let mut sell_other = Order::dummy_limit(OrderSide::Sell, price, Decimal::ONE);
+        sell_other.user_id = user_c;
+        sell_other.sequence = 3;
+
+        let batch = make_sealed_batch(vec![sell, buy_self, buy_other, sell_other]);
+        let matcher = BatchMatcher::new(NodeId([0u8; 32]));
+        let (bundle, metrics) = matcher.try_match_batch_with_metrics(&batch).unwrap();
+
+        assert_eq!(metrics.self_trades_skipped, 1);
+        assert_eq!(metrics.fills, bundle.trades.len());
+        assert_eq!(bundle.trades[0].maker_user_id, user_c);
+        assert_eq!(metrics.price_candidates, 1);
+        assert_eq!(
+            bundle.trade_root,
+            matcher.try_match_batch(&batch).unwrap().trade_root
+        );
+    }
+
+    #[test]
+    fn market_only_batch_clears_at_reference_price() {
+        let mut buy = Order::dummy_limit(OrderSide::Buy, Decimal::ZERO, Decimal::ONE);
+        buy.order_type = OrderType::Market;
+        buy.price = None;
+        buy.sequence = 0;
+        let mut sell = Order::dummy_limit(OrderSide::Sell, Decimal::ZERO, Decimal::ONE);
+        sell.order_type = OrderType::Market;
+        sell.price = None;
+        sell.sequence = 1;
+        let batch = make_sealed_batch(vec![buy, sell]);
+
+        // Without a reference price the batch cannot be priced.
+        let matcher = BatchMatcher::new(NodeId([0u8; 32]));
+        assert!(matches!(
+            matcher.try_match_batch(&batch),
+            Err(OpenmatchError::MatchingFailed { .. })
+        ));
+        let bundle = matcher.match_batch(&batch).unwrap();
+        assert!(bundle.trades.is_empty());
+        assert_eq!(bundle.remaining_orders.len(), 2);
+
+        let matcher = matcher.with_clearing_config(ClearingConfig {
+            reference_price: Some(Decimal::new(42_000, 0)),
+            ..ClearingConfig::default()
+        });
+        let bundle = matcher.try_match_batch(&batch).unwrap();
+        assert_eq!(bundle.clearing_price, Some(Decimal::new(42_000, 0)));
+        assert_eq!(bundle.trades.len(), 1);
+        assert_eq!(bundle.trades[0].price, Decimal::new(42_000, 0));
+        assert!(bundle.remaining_orders.is_empty());
+    }
+
+    fn participation_batch(buyers: &[UserId], seller: UserId) -> SealedBatch {
+        let mut orders: Vec<Order> = buyers
+            .iter()
+            .map(|&u| {
+                Order::dummy_limit_for_user(u, OrderSide::Buy, Decimal::new(100, 0), Decimal::ONE)
+            })
+            .collect();
+        orders.push(Order::dummy_limit_for_user(
+            seller,
+            OrderSide::Sell,
+            Decimal::new(100, 0),
+            Decimal::new(2, 0),
+        ));
+        for (seq, order) in orders.iter_mut().enumerate() {
+            order.sequence = seq as u64;
+        }
+        make_sealed_batch(orders)
+    }
+
+    #[test]
+    fn min_participation_met_clears() {
+        let batch = participation_batch(&[UserId::new(), UserId::new()], UserId::new());
+        let bundle = BatchMatcher::new(NodeId([0u8; 32]))
+            .with_min_participants(2, 1)
+            .match_batch(&batch)
+            .unwrap();
+        assert_eq!(bundle.clearing_price, Some(Decimal::new(100, 0)));
+        assert_eq!(bundle.trades.len(), 2);
+    }
+
+    #[test]
+    fn single_buyer_below_min_participation_does_not_clear() {
+        let buyer = UserId::new();
+        let batch = participation_batch(&[buyer, buyer], UserId::new());
+        let bundle = BatchMatcher::new(NodeId([0u8; 32]))
+            .with_min_participants(2, 1)
+            .match_batch(&batch)
+            .unwrap();
+        assert!(bundle.trades.is_empty());
+        assert_eq!(bundle.clearing_price, None);
+        assert_eq!(bundle.remaining_orders.len(), 3);
+        assert!(
+            bundle
+                .remaining_orders
+                .iter()
+                .all(|o| o.remaining_qty == o.quantity)
+        );
+    }
+
+    fn sequenced(mut orders: Vec<Order>) -> SealedBatch {
+        for (seq, order) in orders.iter_mut().enumerate() {
+            order.sequence = seq as u64;
+        }
+        make_sealed_batch(orders)
+    }
+
+    #[test]
+    fn marginal_unfilled_reports_rationed_buyers() {
+        let bid = |price, qty| Order::dummy_limit(OrderSide::Buy, Decimal::new(price, 0), qty);
+        // Demand 4 meets supply 2.5 at 100: b1 fills, b2 partially, b3 not
+        let orders = vec![
+            bid(101, Decimal::TWO),
+            bid(100, Decimal::ONE),
+            bid(100, Decimal::ONE),
+            bid(99, Decimal::ONE),
+            Order::dummy_limit(OrderSide::Sell, Decimal::new(100, 0), Decimal::new(25, 1)),
+        ];
+        let rationed = orders[2].id;
+        let bundle = match_sealed_batch(&sequenced(orders)).unwrap();
+        let clearing_price = bundle.clearing_price.unwrap();
+        assert_eq!(clearing_price, Decimal::new(100, 0));
+
+        let mut book = OrderBook::new(MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")));
+        book.insert_batch(bundle.remaining_orders).unwrap();
+        assert_eq!(book.order_count(), 3);
+        assert_eq!(book.marginal_unfilled(clearing_price), vec![rationed]);
+    }
+
+    #[test]
+    fn shards_in_same_epoch_have_disjoint_trade_ids() {
+        let batch = sequenced(vec![
+            Order::dummy_limit(OrderSide::Buy, Decimal::new(100, 0), Decimal::TWO),
+            Order::dummy_limit(OrderSide::Sell, Decimal::new(100, 0), Decimal::ONE),
+            Order::dummy_limit(OrderSide::Sell, Decimal::new(100, 0), Decimal::ONE),
+        ]);
+        let ids = |shard| -> HashSet<TradeId> {
+            BatchMatcher::new(NodeId([0u8; 32]))
+                .with_shard(shard)
+                .match_batch(&batch)
+                .unwrap()
+                .trades
+                .iter()
+                .map(|t| t.id)
+                .collect()
+        };
+
+        let (shard0, shard1) = (ids(0), ids(1));
+        assert_eq!(shard0.len(), 2);
+        assert_eq!(shard1.len(), 2);
+        assert!(shard0.is_disjoint(&shard1));
+        // Shard 0 keeps the epoch-numbered IDs
+        assert!(shard0.contains(&TradeId::deterministic(1, 0)));
+    }
+
+    #[test]
+    fn overflowing_quote_amount_skips_fill() {
+        // price × qty exceeds Decimal::MAX: the pair is skipped, not saturated
+        let price = Decimal::MAX / Decimal::new(4, 0);
+        let qty = Decimal::new(10, 0);
+        let batch = sequenced(vec![
+            Order::dummy_limit(OrderSide::Buy, price, qty),
+            Order::dummy_limit(OrderSide::Sell, price, qty),
+        ]);
+
+        let bundle = match_sealed_batch(&batch).unwrap();
+        assert_eq!(bundle.clearing_price, Some(price));
+        assert!(bundle.trades.is_empty());
+        assert_eq!(bundle.remaining_orders.len(), 2);
+        assert!(
+            bundle
+                .remaining_orders
+                .iter()
+                .all(|o| o.remaining_qty == qty)
+        );
+    }
+
+    #[test]
+    fn single_fill_cap_rests_remainder() {
+        let buy = Order::dummy_limit(OrderSide::Buy, Decimal::new(100, 0), Decimal::new(10, 0));
+        let sell = Order::dummy_limit(OrderSide::Sell, Decimal::new(100, 0), Decimal::new(50, 0));
+        let (buy_id, sell_id) = (buy.id, sell.id);
+        let batch = sequenced(vec![buy, sell]);
+
+        let bundle = BatchMatcher::new(NodeId([0u8; 32]))
+            .with_max_single_fill(Decimal::new(3, 0))
+            .match_batch(&batch)
+            .unwrap();
+        assert_eq!(bundle.trades.len(), 1);
+        assert_eq!(bundle.trades[0].quantity, Decimal::new(3, 0));
+
+        let rest = |id| {
+            bundle
+                .remaining_orders
+                .iter()
+                .find(|o| o.id == id)
+                .unwrap()
+                .remaining_qty
+        };
+        assert_eq!(rest(buy_id), Decimal::new(7, 0));
+        assert_eq!(rest(sell_id), Decimal::new(47, 0));
+    }
+
+    #[test]
+    fn single_fill_cap_spreads_across_makers() {
+        let batch = sequenced(vec![
+            Order::dummy_limit(OrderSide::Buy, Decimal::new(100, 0), Decimal::new(5, 0)),
+            Order::dummy_limit(OrderSide::Buy, Decimal::new(100, 0), Decimal::TWO),
+            Order::dummy_limit(OrderSide::Sell, Decimal::new(100, 0), Decimal::new(3, 0)),
+            Order::dummy_limit(OrderSide::Sell, Decimal::new(100, 0), Decimal::new(3, 0)),
+        ]);
+        let matcher = BatchMatcher::new(NodeId([0u8; 32])).with_max_single_fill(Decimal::TWO);
+        let bundle = matcher.match_batch(&batch).unwrap();
+
+        // Bid 1 takes 2 + 2, then bid 2 returns to the capped asks for 1 + 1.
+        let (bid1, bid2) = (batch.orders[0].id, batch.orders[1].id);
+        let (ask1, ask2) = (batch.orders[2].id, batch.orders[3].id);
+        let fills: Vec<_> = bundle
+            .trades
+            .iter()
+            .map(|t| (t.taker_order_id, t.maker_order_id, t.quantity))
+            .collect();
+        assert_eq!(
+            fills,
+            vec![
+                (bid1, ask1, Decimal::TWO),
+                (bid1, ask2, Decimal::TWO),
+                (bid2, ask1, Decimal::ONE),
+                (bid2, ask2, Decimal::ONE),
+            ]
+        );
+        assert_eq!(bundle.remaining_orders.len(), 1);
+        assert_eq!(bundle.remaining_orders[0].id, bid1);
+        assert_eq!(bundle.remaining_orders[0].remaining_qty, Decimal::ONE);
+
+        // Deterministic across runs
+        assert_eq!(
+            matcher.match_batch(&batch).unwrap().trade_root,
+            bundle.trade_root
+        );
+    }
+
+    /// The pre-index matcher: clones crossing orders into `Vec`s and
+    /// rebuilds the remainder with linear scans. Kept as an oracle for the
+    /// index-based implementation.
+    fn clone_based_match(batch: &SealedBatch) -> TradeBundle {
+        let mut book = OrderBook::new(batch.orders[0].market.clone());
+        for order in &batch.orders {
+            let _ = book.insert_order(order.clone());
+        }
+        let clearing_price = crate::compute_clearing_price(&book).clearing_price.unwrap();
+
+        let mut trades: Vec<Trade> = Vec::new();
+        let mut fill_seq: u64 = 0;
+        let mut bids: Vec<Order> = Vec::new();
+        for level in book.bid_levels() {
+            if level.price >= clearing_price {
+                bids.extend(level.orders.iter().cloned());
+            }
+        }
+        Order::sort_canonical(&mut bids);
+        let mut asks: Vec<Order> = Vec::new();
+        for level in book.ask_levels() {
+            if level.price <= clearing_price {
+                asks.extend(level.orders.iter().cloned());
+            }
+        }
+        Order::sort_canonical(&mut asks);
+
+        let mut ask_idx = 0;
+        for bid in &mut bids {
+            while ask_idx < asks.len() && bid.remaining_qty > Decimal::ZERO {
+                let ask = &mut asks[ask_idx];
+                if ask.remaining_qty.is_zero() || bid.user_id == ask.user_id {
+                    ask_idx += 1;
+                    continue;
+                }
+                let fill_qty = bid.remaining_qty.min(ask.remaining_qty);
+                trades.push(Trade {
+                    id: TradeId::deterministic(batch.epoch_id.0, fill_seq),
+                    epoch_id: batch.epoch_id,
+                    market: bid.market.clone(),
+                    taker_order_id: bid.id,
+                    taker_user_id: bid.user_id,
+                    maker_order_id: ask.id,
+                    maker_user_id: ask.user_id,
+                    price: clearing_price,
+                    quantity: fill_qty,
+                    quote_amount: clearing_price * fill_qty,
+                    taker_side: OrderSide::Buy,
+                    matcher_node: NodeId([0u8; 32]),
+                    executed_at: Utc::now(),
+                    quote_dust: None,
+                });
+                fill_seq += 1;
+                bid.remaining_qty -= fill_qty;
+                ask.remaining_qty -= fill_qty;
+                if ask.remaining_qty.is_zero() {
+                    ask_idx += 1;
+                }
+            }
+        }
+
+        let mut remaining = Vec::new();
+        for order in bids.into_iter().chain(asks) {
+            if order.remaining_qty > Decimal::ZERO {
+                remaining.push(order);
+            }
+        }
+        for order in book.drain_all() {
+            if !remaining.iter().any(|o| o.id == order.id)
+                && !trades
+                    .iter()
+                    .any(|t| t.taker_order_id == order.id || t.maker_order_id == order.id)
+            {
+                remaining.push(order);
+            }
+        }
+        Order::sort_canonical(&mut remaining);
+
+        TradeBundle {
+            epoch_id: batch.epoch_id,
+            trade_root: crate::compute_trade_root(batch.epoch_id, &trades),
+            trades,
+            input_hash: batch.batch_hash,
+            clearing_price: Some(clearing_price),
+            remaining_orders: remaining,
+            cancelled_orders: vec![],
+            unfilled_reasons: vec![],
+            wash_trade_attempts: vec![],
+        }
+    }
+
+    #[test]
+    fn large_batch_matches_clone_based_output() {
+        use rand::{Rng, SeedableRng, rngs::StdRng};
+
+        let mut rng = StdRng::seed_from_u64(884);
+        let users: Vec<UserId> = (0..40).map(|_| UserId::new()).collect();
+        let orders: Vec<Order> = (0..4_000u64)
+            .map(|i| {
+                let side = if rng.gen_bool(0.5) {
+                    OrderSide::Buy
+                } else {
+                    OrderSide::Sell
+                };
+                let price = Decimal::new(rng.gen_range(9_000..11_000), 2);
+                let qty = Decimal::new(rng.gen_range(1..500), 2);
+                let user = users[rng.gen_range(0..users.len())];
+                let mut order = Order::dummy_limit_for_user(user, side, price, qty);
+                // Pairs share a sequence number to exercise stable tie order
+                order.sequence = i / 2;
+                order
+            })
+            .collect();
+        let batch = make_sealed_batch(orders);
+
+        let expected = clone_based_match(&batch);
+        let actual = match_sealed_batch(&batch).unwrap();
+
+        assert!(!expected.trades.is_empty());
+        assert_eq!(actual.clearing_price, expected.clearing_price);
+        assert_eq!(actual.trade_root, expected.trade_root);
+        assert_eq!(actual.trades.len(), expected.trades.len());
+        let remaining_key = |b: &TradeBundle| -> Vec<(OrderId, Decimal)> {
+            b.remaining_orders
+                .iter()
+                .map(|o| (o.id, o.remaining_qty))
+                .collect()
+        };
+        assert_eq!(remaining_key(&actual), remaining_key(&expected));
+    }
+
+    fn make_aon_buy(price: Decimal, qty: Decimal) -> Order {
+        let mut order = Order::dummy_limit(OrderSide::Buy, price, qty);
+        order.order_type = OrderType::Limit { all_or_none: true };
+        order
+    }
+
+    #[test]
+    fn all_or_none_fills_completely_when_it_can() {
+        let price = Decimal::new(100, 0);
+        let aon = make_aon_buy(price, Decimal::TWO);
+        let aon_id = aon.id;
+        let mut orders = vec![aon];
+        for seq in 1..=2 {
+            let mut ask = Order::dummy_limit(OrderSide::Sell, price, Decimal::ONE);
+            ask.sequence = seq;
+            orders.push(ask);
+        }
+
+        let bundle = match_sealed_batch(&make_sealed_batch(orders)).unwrap();
+        let filled: Decimal = bundle
+            .trades
+            .iter()
+            .filter(|t| t.taker_order_id == aon_id)
+            .map(|t| t.quantity)
+            .sum();
+        assert_eq!(filled, Decimal::TWO);
+        assert!(bundle.remaining_orders.is_empty());
+    }
+
+    #[test]
+    fn all_or_none_rests_unchanged_when_it_cannot_fill() {
+        let price = Decimal::new(100, 0);
+        let aon = make_aon_buy(price, Decimal::new(3, 0));
+        let mut plain = Order::dummy_limit(OrderSide::Buy, price, Decimal::ONE);
+        plain.sequence = 1;
+        let mut ask = Order::dummy_limit(OrderSide::Sell, price, Decimal::ONE);
+        ask.sequence = 2;
+        let (aon_id, plain_id) = (aon.id, plain.id);
+
+        let bundle = match_sealed_batch(&make_sealed_batch(vec![aon.clone(), plain, ask])).unwrap();
+
+        // The plain bid behind it takes the liquidity instead
+        assert_eq!(bundle.trades.len(), 1);
+        assert_eq!(bundle.trades[0].taker_order_id, plain_id);
+        assert_eq!(bundle.remaining_orders.len(), 1);
+        let rest = &bundle.remaining_orders[0];
+        assert_eq!(rest.id, aon_id);
+        assert_eq!(rest.remaining_qty, aon.quantity);
+        assert_eq!(rest.status, aon.status);
+        assert_eq!(
+            bundle.unfilled_reasons,
+            vec![(aon_id, UnfilledReason::AllOrNoneHeld)]
+        );
+    }
+
+    #[test]
+    fn markets_use_their_own_clearing_config() {
+        let favor = |tie_break| ClearingConfig {
+            tie_break,
+            ..ClearingConfig::default()
+        };
+        let eth = MarketPair::new(Asset::lit("ETH"), Asset::lit("USDT"));
+        let matcher = BatchMatcher::new(NodeId([0u8; 32]))
+            .with_clearing_config(favor(crate::ClearingTieBreak::FavorBuyers))
+            .with_market_clearing_config(eth.clone(), favor(crate::ClearingTieBreak::FavorSellers));
+
+        // Bid 102 / ask 100 clear the same volume anywhere in [100, 102]
+        let batch_in = |market: &MarketPair| {
+            let mut bid = Order::dummy_limit(OrderSide::Buy, Decimal::new(102, 0), Decimal::ONE);
+            let mut ask = Order::dummy_limit(OrderSide::Sell, Decimal::new(100, 0), Decimal::ONE);
+            ask.sequence = 1;
+            bid.market = market.clone();
+            ask.market = market.clone();
+            make_sealed_batch(vec![bid, ask])
+        };
+        let btc = matcher
+            .match_batch(&batch_in(&MarketPair::new(
+                Asset::lit("BTC"),
+                Asset::lit("USDT"),
+            )))
+            .unwrap();
+        let eth = matcher.match_batch(&batch_in(&eth)).unwrap();
+
+        assert_eq!(btc.clearing_price, Some(Decimal::new(100, 0)));
+        assert_eq!(eth.clearing_price, Some(Decimal::new(102, 0)));
+    }
+
+    #[test]
+    fn fixed_clock_stamps_every_trade() {
+        let t0 = DateTime::UNIX_EPOCH + chrono::Duration::days(20_000);
+        let clock = FixedClock::new(t0);
+        let matcher = BatchMatcher::new(NodeId([0u8; 32])).with_clock(Arc::new(clock.clone()));
+        let mut ask = Order::dummy_limit(OrderSide::Sell, Decimal::new(100, 0), Decimal::TWO);
+        ask.sequence = 2;
+        let batch = make_sealed_batch(vec![
+            Order::dummy_limit(OrderSide::Buy, Decimal::new(100, 0), Decimal::ONE),
+            Order::dummy_limit(OrderSide::Buy, Decimal::new(101, 0), Decimal::ONE),
+            ask,
+        ]);
+
+        let first = matcher.match_batch(&batch).unwrap();
+        assert_eq!(first.trades.len(), 2);
+        assert!(first.trades.iter().all(|t| t.executed_at == t0));
+
+        clock.advance(chrono::Duration::seconds(1));
+        let second = matcher.match_batch(&batch).unwrap();
+        assert!(
+            second
+                .trades
+                .iter()
+                .all(|t| t.executed_at == t0 + chrono::Duration::seconds(1))
+        );
+        assert_eq!(second.trade_root, first.trade_root);
+
+        // Continuous fills are stamped from the same clock
+        let mut book = OrderBook::new(MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")));
+        book.insert_order(Order::dummy_limit(
+            OrderSide::Sell,
+            Decimal::new(100, 0),
+            Decimal::ONE,
+        ))
+        .unwrap();
+        clock.advance(chrono::Duration::seconds(1));
+        let taker = Order::dummy_limit(OrderSide::Buy, Decimal::new(100, 0), Decimal::ONE);
+        let (trades, _) = book.match_incoming(taker, matcher.clock.now());
+        assert_eq!(trades.len(), 1);
+        assert_eq!(trades[0].executed_at, t0 + chrono::Duration::seconds(2));
+    }
+
+    /// `per_market` random orders in each of `bases`, interleaved and
+    /// sequenced as one batch.
+    fn multi_market_batch(bases: &[&str], per_market: u64, seed: u64) -> SealedBatch {
+        use rand::{Rng, SeedableRng, rngs::StdRng};
+
+        let mut rng = StdRng::seed_from_u64(seed);
+        let users: Vec<UserId> = (0..8).map(|_| UserId::new()).collect();
+        let orders = (0..per_market)
+            .flat_map(|_| bases.iter())
+            .map(|base| {
+                let side = if rng.gen_bool(0.5) {
+                    OrderSide::Buy
+                } else {
+                    OrderSide::Sell
+                };
+                let price = Decimal::new(rng.gen_range(9_900..10_100), 2);
+                let qty = Decimal::new(rng.gen_range(1..100), 1);
+                let user = users[rng.gen_range(0..users.len())];
+                let mut order = Order::dummy_limit_for_user(user, side, price, qty);
+                order.market = MarketPair::new(Asset::lit(base), Asset::lit("USDT"));
+                order
+            })
+            .collect();
+        sequenced(orders)
+    }
+
+    #[test]
+    fn oversized_epoch_is_an_error_not_a_panic() {
+        let mut batch = sequenced(vec![
+            Order::dummy_limit(OrderSide::Buy, Decimal::new(100, 0), Decimal::ONE),
+            Order::dummy_limit(OrderSide::Sell, Decimal::new(100, 0), Decimal::ONE),
+        ]);
+        batch.epoch_id = EpochId(1 << 48);
+        assert!(matches!(
+            match_sealed_batch(&batch),
+            Err(OpenmatchError::DeterminismViolation { .. })
+        ));
+        assert!(matches!(
+            BatchMatcher::new(NodeId([0u8; 32])).match_batch_by_market(&batch),
+            Err(OpenmatchError::DeterminismViolation { .. })
+        ));
+    }
+
+    #[test]
+    fn markets_in_one_batch_match_independently() {
+        let batch = multi_market_batch(&["BTC", "ETH", "SOL"], 100, 954);
+        let matcher = BatchMatcher::new(NodeId([7u8; 32]))
+            .with_clock(Arc::new(FixedClock::new(DateTime::UNIX_EPOCH)));
+        let merged = matcher.match_batch_by_market(&batch).unwrap();
+
+        // Each market's fills are exactly what it produces on its own
+        let mut fills = 0;
+        for base in ["BTC", "ETH", "SOL"] {
+            let market = MarketPair::new(Asset::lit(base), Asset::lit("USDT"));
+            let alone = matcher
+                .match_batch(&make_sealed_batch(
+                    batch
+                        .orders
+                        .iter()
+                        .filter(|o| o.market == market)
+                        .cloned()
+                        .collect(),
+                ))
+                .unwrap();
+            let in_merged: Vec<_> = merged
+                .trades
+                .iter()
+                .filter(|t| t.market == market)
+                .map(|t| (t.taker_order_id, t.maker_order_id, t.quantity, t.price))
+                .collect();
+            let own: Vec<_> = alone
+                .trades
+                .iter()
+                .map(|t| (t.taker_order_id, t.maker_order_id, t.quantity, t.price))
+                .collect();
+            assert!(!own.is_empty(), "{base}");
+            assert_eq!(in_merged, own, "{base}");
+            fills += own.len();
+        }
+        assert_eq!(merged.trades.len(), fills);
+        assert_eq!(
+            merged.trade_root,
+            compute_trade_root(batch.epoch_id, &merged.trades)
+        );
+        assert_eq!(merged.clearing_price, None);
+        assert_eq!(merged.unfilled_reasons.len(), merged.remaining_orders.len());
+    }
+
+    #[test]
+    fn single_market_batch_by_market_matches_serial() {
+        let batch = multi_market_batch(&["BTC"], 200, 7);
+        let matcher = BatchMatcher::new(NodeId([7u8; 32]))
+            .with_clock(Arc::new(FixedClock::new(DateTime::UNIX_EPOCH)));
+        assert_eq!(
+            serde_json::to_vec(&matcher.match_batch_by_market(&batch).unwrap()).unwrap(),
+            serde_json::to_vec(&matcher.match_batch(&batch).unwrap()).unwrap()
+        );
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn parallel_markets_match_serial_bytes() {
+        let batch = multi_market_batch(&["BTC", "ETH", "SOL", "XRP", "ADA"], 200, 954);
+        let matcher = BatchMatcher::new(NodeId([7u8; 32]))
+            .with_clock(Arc::new(FixedClock::new(DateTime::UNIX_EPOCH)));
+        let serial = matcher.match_batch_by_market(&batch).unwrap();
+        assert!(!serial.trades.is_empty());
+        let serial = serde_json::to_vec(&serial).unwrap();
+
+        for threads in [1, 2, 3, 8] {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .unwrap();
+            let parallel = matcher
+                .match_batch_by_market_parallel(&batch, &pool)
+                .unwrap();
+            assert_eq!(
+                serde_json::to_vec(&parallel).unwrap(),
+                serial,
+                "{threads} threads"
+            );
+        }
+        let pool = rayon::ThreadPoolBuilder::new().build().unwrap();
+        assert!(
+            match_sealed_batch_parallel(&make_sealed_batch(vec![]), &pool)
+                .unwrap()
+                .trades
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn remaining_orders_carry_unfilled_reasons() {
+        let seq = |mut order: Order, sequence| {
+            order.sequence = sequence;
+            order
+        };
+        let first = seq(
+            Order::dummy_limit(OrderSide::Buy, Decimal::new(100, 0), Decimal::ONE),
+            0,
+        );
+        let rationed = seq(
+            Order::dummy_limit(OrderSide::Buy, Decimal::new(100, 0), Decimal::ONE),
+            1,
+        );
+        let out_of_money = seq(
+            Order::dummy_limit(OrderSide::Buy, Decimal::new(90, 0), Decimal::ONE),
+            2,
+        );
+        let ask = seq(
+            Order::dummy_limit(OrderSide::Sell, Decimal::new(100, 0), Decimal::ONE),
+            3,
+        );
+        let (rationed_id, out_of_money_id) = (rationed.id, out_of_money.id);
+
+        let bundle =
+            match_sealed_batch(&make_sealed_batch(vec![first, rationed, out_of_money, ask]))
+                .unwrap();
+        assert_eq!(bundle.trades.len(), 1);
+        assert_eq!(
+            bundle.unfilled_reasons,
+            vec![
+                (rationed_id, UnfilledReason::Rationed),
+                (out_of_money_id, UnfilledReason::OutOfMoney),
+            ]
+        );
+        let ids: Vec<OrderId> = bundle.remaining_orders.iter().map(|o| o.id).collect();
+        assert_eq!(ids, vec![rationed_id, out_of_money_id]);
+    }
+
+    #[test]
+    fn self_trade_and_no_crossing_reasons() {
+        let user = UserId::new();
+        let bid =
+            Order::dummy_limit_for_user(user, OrderSide::Buy, Decimal::new(100, 0), Decimal::ONE);
+        let mut ask =
+            Order::dummy_limit_for_user(user, OrderSide::Sell, Decimal::new(100, 0), Decimal::ONE);
+        ask.sequence = 1;
+        let bundle = match_sealed_batch(&make_sealed_batch(vec![bid, ask])).unwrap();
+        assert!(bundle.trades.is_empty());
+        assert!(
+            bundle
+                .unfilled_reasons
+                .iter()
+                .all(|(_, r)| *r == UnfilledReason::SelfTrade)
+        );
+        assert_eq!(bundle.unfilled_reasons.len(), 2);
+
+        let bundle = match_sealed_batch(&make_sealed_batch(vec![
+            Order::dummy_limit(OrderSide::Buy, Decimal::new(90, 0), Decimal::ONE),
+            Order::dummy_limit(OrderSide::Sell, Decimal::new(100, 0), Decimal::ONE),
+        ]))
+        .unwrap();
+        assert_eq!(bundle.unfilled_reasons.len(), 2);
+        assert!(
+            bundle
+                .unfilled_reasons
+                .iter()
+                .all(|(_, r)| *r == UnfilledReason::NoCrossing)
+        );
+    }
 }
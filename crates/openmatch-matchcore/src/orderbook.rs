@@ -4,15 +4,29 @@
 //! - **Bids** (buys): `BTreeMap<Reverse<Decimal>, PriceLevel>` -- highest price first
 //! - **Asks** (sells): `BTreeMap<Decimal, PriceLevel>` -- lowest price first
 //!
-//! An auxiliary `HashMap<OrderId, (Side, Price)>` enables O(log N) cancellation.
+//! An auxiliary `HashMap<OrderId, (Side, Price)>` enables O(log N) cancellation,
+//! and a `HashMap<UserId, Vec<OrderId>>` lets [`OrderBook::cancel_all_for_user`]
+//! touch only that user's orders.
+//!
+//! Besides feeding the batch matcher, the book supports continuous matching
+//! of a single aggressive order via [`OrderBook::match_incoming`].
+//!
+//! Derived prices ([`OrderBook::mid_price`], [`OrderBook::vwap`]) are
+//! rounded to a caller-supplied scale, half to even, so every node reports
+//! the same displayable value. [`OrderBook::liquidity_score`] condenses
+//! near-touch depth into one number for market-health monitoring.
 
 use std::{
     cmp::Reverse,
     collections::{BTreeMap, HashMap},
 };
 
-use openmatch_types::{MarketPair, OpenmatchError, Order, OrderId, OrderSide, Result};
-use rust_decimal::Decimal;
+use chrono::{DateTime, Utc};
+use openmatch_types::{
+    EpochId, MarketPair, OpenmatchError, Order, OrderId, OrderSide, OrderStatus, Result, Trade,
+    TradeId, UserId,
+};
+use rust_decimal::{Decimal, RoundingStrategy};
 
 use crate::price_level::PriceLevel;
 
@@ -27,6 +41,8 @@ pub struct OrderBook {
     asks: BTreeMap<Decimal, PriceLevel>,
     /// Fast lookup: `OrderId -> (side, price)` for O(log N) cancel.
     index: HashMap<OrderId, (OrderSide, Decimal)>,
+    /// Resting orders per user, in insertion order.
+    by_user: HashMap<UserId, Vec<OrderId>>,
 }
 
 impl OrderBook {
@@ -38,6 +54,7 @@ impl OrderBook {
             bids: BTreeMap::new(),
             asks: BTreeMap::new(),
             index: HashMap::new(),
+            by_user: HashMap::new(),
         }
     }
 
@@ -53,6 +70,10 @@ impl OrderBook {
 
         let price = order.effective_price();
         self.index.insert(order.id, (order.side, price));
+        self.by_user
+            .entry(order.user_id)
+            .or_default()
+            .push(order.id);
 
         match order.side {
             OrderSide::Buy => {
@@ -71,6 +92,66 @@ impl OrderBook {
         Ok(())
     }
 
+    /// Amend a resting order to `new_price` and `new_remaining` quantity.
+    ///
+    /// A size decrease at the same price keeps the order's place in its
+    /// level. Any price change or size increase loses time priority: the
+    /// order is re-stamped with `sequence` (when the amend arrived) and
+    /// moved to the back of its new level.
+    ///
+    /// # Errors
+    /// - `OrderNotFound` if the order isn't in the book
+    /// - `InvalidOrder` if `new_price` or `new_remaining` isn't positive,
+    ///   or the order isn't a limit order; the order stays in the book
+    pub fn amend_order(
+        &mut self,
+        order_id: &OrderId,
+        new_price: Decimal,
+        new_remaining: Decimal,
+        sequence: u64,
+    ) -> Result<()> {
+        if new_remaining <= Decimal::ZERO {
+            return Err(OpenmatchError::InvalidOrder {
+                reason: "amended quantity must be positive".to_string(),
+            });
+        }
+        if new_price <= Decimal::ZERO {
+            return Err(OpenmatchError::InvalidOrder {
+                reason: "amended price must be positive".to_string(),
+            });
+        }
+        let &(side, price) = self
+            .index
+            .get(order_id)
+            .ok_or(OpenmatchError::OrderNotFound(*order_id))?;
+        let level = match side {
+            OrderSide::Buy => self.bids.get_mut(&Reverse(price)),
+            OrderSide::Sell => self.asks.get_mut(&price),
+        };
+        let order = level
+            .and_then(|level| level.iter_mut().find(|o| o.id == *order_id))
+            .ok_or(OpenmatchError::OrderNotFound(*order_id))?;
+        if !order.order_type.is_limit() {
+            return Err(OpenmatchError::InvalidOrder {
+                reason: "only limit orders can be amended".to_string(),
+            });
+        }
+
+        if new_price == price && new_remaining <= order.remaining_qty {
+            // Size decrease: keep position
+            order.quantity -= order.remaining_qty - new_remaining;
+            order.remaining_qty = new_remaining;
+            return Ok(());
+        }
+
+        let mut order = self.cancel_order(order_id)?;
+        order.quantity += new_remaining - order.remaining_qty;
+        order.remaining_qty = new_remaining;
+        order.price = Some(new_price);
+        order.sequence = sequence;
+        self.insert_order(order)
+    }
+
     /// Insert a batch of orders from a sealed pending buffer.
     pub fn insert_batch(&mut self, orders: Vec<Order>) -> Result<()> {
         for order in orders {
@@ -79,6 +160,39 @@ impl OrderBook {
         Ok(())
     }
 
+    /// Atomically replace the book's contents with `orders`.
+    ///
+    /// The new book is built and checked on the side; `self` is only
+    /// swapped once every order is accepted, so there is no window where
+    /// the book is empty or half-built. Returns the IDs of the orders
+    /// displaced, in book priority.
+    ///
+    /// # Errors
+    /// - `InvalidOrder` if an order belongs to a different market
+    /// - `DuplicateOrder` if two orders share an ID
+    ///
+    /// On error the existing book is left untouched.
+    pub fn replace_batch(&mut self, orders: Vec<Order>) -> Result<Vec<OrderId>> {
+        let mut fresh = Self::new(self.market.clone());
+        for order in orders {
+            if order.market != self.market {
+                return Err(OpenmatchError::InvalidOrder {
+                    reason: format!(
+                        "order {} is for {}, book is {}",
+                        order.id, order.market, self.market
+                    ),
+                });
+            }
+            fresh.insert_order(order)?;
+        }
+        let old = std::mem::replace(self, fresh);
+        Ok(old
+            .bid_levels()
+            .chain(old.ask_levels())
+            .flat_map(|level| level.orders.iter().map(|o| o.id))
+            .collect())
+    }
+
     // =================================================================
     // Cancellation
     // =================================================================
@@ -118,10 +232,239 @@ impl OrderBook {
                 order
             }
         };
+        Self::forget_user_order(&mut self.by_user, &order.user_id, order_id);
 
         Ok(order)
     }
 
+    /// Cancel an order on behalf of `user_id`, who must own it.
+    ///
+    /// # Errors
+    /// - `OrderNotFound` if the order isn't in the book
+    /// - `OrderNotCancellable` if it belongs to another user
+    pub fn cancel_for_user(&mut self, order_id: &OrderId, user_id: &UserId) -> Result<Order> {
+        let owned = self
+            .by_user
+            .get(user_id)
+            .is_some_and(|ids| ids.contains(order_id));
+        if !owned {
+            return Err(if self.contains_order(order_id) {
+                OpenmatchError::OrderNotCancellable
+            } else {
+                OpenmatchError::OrderNotFound(*order_id)
+            });
+        }
+        self.cancel_order(order_id)
+    }
+
+    /// Cancel every resting order belonging to `user_id`, on both sides.
+    ///
+    /// Returns the removed orders in the order they were inserted. Empty
+    /// levels are dropped. Cost is proportional to the user's orders, not
+    /// the book.
+    pub fn cancel_all_for_user(&mut self, user_id: &UserId) -> Vec<Order> {
+        let ids = self.by_user.remove(user_id).unwrap_or_default();
+        ids.iter()
+            .filter_map(|id| self.cancel_order(id).ok())
+            .collect()
+    }
+
+    /// Remove every resting order whose `expires_at` is at or before
+    /// `now`, so the caller can release their escrow.
+    ///
+    /// Returns the removed orders in book priority: bids best to worst,
+    /// then asks best to worst, FIFO within a level. Empty levels are
+    /// dropped. This scans the whole book, so run it once per batch rather
+    /// than per order.
+    pub fn expire_orders(&mut self, now: DateTime<Utc>) -> Vec<Order> {
+        let expired: Vec<OrderId> = self
+            .bid_levels()
+            .chain(self.ask_levels())
+            .flat_map(PriceLevel::iter)
+            .filter(|order| order.is_expired_at(now))
+            .map(|order| order.id)
+            .collect();
+        expired
+            .iter()
+            .filter_map(|id| self.cancel_order(id).ok())
+            .collect()
+    }
+
+    /// Drop `order_id` from the user index.
+    fn forget_user_order(
+        by_user: &mut HashMap<UserId, Vec<OrderId>>,
+        user_id: &UserId,
+        order_id: &OrderId,
+    ) {
+        if let Some(ids) = by_user.get_mut(user_id) {
+            if let Some(pos) = ids.iter().position(|id| id == order_id) {
+                ids.remove(pos);
+            }
+            if ids.is_empty() {
+                by_user.remove(user_id);
+            }
+        }
+    }
+
+    // =================================================================
+    // Continuous matching
+    // =================================================================
+
+    /// Match a single aggressive order against the resting book.
+    ///
+    /// Unlike batch matching, each fill executes at the resting level's
+    /// price, walking levels from best to worst until the taker is filled
+    /// or its limit no longer crosses. Resting market orders have no price
+    /// of their own and fill at the taker's limit; a market taker skips
+    /// them. Within a level, makers fill in FIFO
+    /// order; makers owned by the taker's user are skipped (self-trade
+    /// prevention) and stay on the book.
+    ///
+    /// Returns the trades and the unfilled remainder of the taker, if any.
+    /// The remainder is **not** inserted — the caller decides whether it
+    /// rests (e.g. via [`OrderBook::insert_order`]) or is discarded, as for
+    /// market orders. Cancel orders are not matchable and come back as-is.
+    ///
+    /// Continuous fills are not part of a sealed batch, so trade IDs are
+    /// random rather than derived from the epoch fill sequence. Every trade
+    /// is stamped `executed_at = now`; callers read it from their
+    /// [`Clock`](openmatch_types::Clock), as the batch matcher does.
+    pub fn match_incoming(
+        &mut self,
+        mut taker: Order,
+        now: DateTime<Utc>,
+    ) -> (Vec<Trade>, Option<Order>) {
+        let mut trades = Vec::new();
+        if taker.order_type.is_cancel() {
+            return (trades, Some(taker));
+        }
+
+        let limit = taker.effective_price();
+        match taker.side {
+            OrderSide::Buy => {
+                let mut emptied = Vec::new();
+                for (&price, level) in &mut self.asks {
+                    if taker.remaining_qty.is_zero() || price > limit {
+                        break;
+                    }
+                    // Resting market sells sit at 0: they trade at the
+                    // taker's limit, and not at all against a market buy.
+                    let fill_price = if price.is_zero() { limit } else { price };
+                    if fill_price == Decimal::MAX {
+                        continue;
+                    }
+                    Self::fill_against_level(
+                        level,
+                        fill_price,
+                        now,
+                        &mut taker,
+                        &mut trades,
+                        &mut self.index,
+                        &mut self.by_user,
+                    );
+                    if level.is_empty() {
+                        emptied.push(price);
+                    }
+                }
+                for price in emptied {
+                    self.asks.remove(&price);
+                }
+            }
+            OrderSide::Sell => {
+                let mut emptied = Vec::new();
+                for (&key, level) in &mut self.bids {
+                    if taker.remaining_qty.is_zero() || key.0 < limit {
+                        break;
+                    }
+                    // Resting market buys sit at `Decimal::MAX`: they trade
+                    // at the taker's limit, and not at all against a
+                    // market sell.
+                    let fill_price = if key.0 == Decimal::MAX { limit } else { key.0 };
+                    if fill_price.is_zero() {
+                        continue;
+                    }
+                    Self::fill_against_level(
+                        level,
+                        fill_price,
+                        now,
+                        &mut taker,
+                        &mut trades,
+                        &mut self.index,
+                        &mut self.by_user,
+                    );
+                    if level.is_empty() {
+                        emptied.push(key);
+                    }
+                }
+                for key in emptied {
+                    self.bids.remove(&key);
+                }
+            }
+        }
+
+        if taker.remaining_qty.is_zero() {
+            taker.status = OrderStatus::Filled;
+            return (trades, None);
+        }
+        if !trades.is_empty() {
+            taker.status = OrderStatus::PartiallyFilled;
+        }
+        (trades, Some(taker))
+    }
+
+    /// Fill `taker` against one level at `price`, removing fully filled
+    /// makers from the level and both indexes.
+    fn fill_against_level(
+        level: &mut PriceLevel,
+        price: Decimal,
+        now: DateTime<Utc>,
+        taker: &mut Order,
+        trades: &mut Vec<Trade>,
+        index: &mut HashMap<OrderId, (OrderSide, Decimal)>,
+        by_user: &mut HashMap<UserId, Vec<OrderId>>,
+    ) {
+        let mut i = 0;
+        while i < level.orders.len() && !taker.remaining_qty.is_zero() {
+            let maker = &mut level.orders[i];
+            let fill_qty = taker.remaining_qty.min(maker.remaining_qty);
+            // Skip self-trades and fills whose quote amount overflows
+            let quote_amount = Trade::checked_quote_amount(price, fill_qty);
+            let Some(quote_amount) = quote_amount.filter(|_| maker.user_id != taker.user_id) else {
+                i += 1;
+                continue;
+            };
+
+            trades.push(Trade {
+                id: TradeId::new(),
+                epoch_id: taker.epoch_id.unwrap_or(EpochId(0)),
+                market: taker.market.clone(),
+                taker_order_id: taker.id,
+                taker_user_id: taker.user_id,
+                maker_order_id: maker.id,
+                maker_user_id: maker.user_id,
+                price,
+                quantity: fill_qty,
+                quote_amount,
+                taker_side: taker.side,
+                matcher_node: taker.origin_node,
+                executed_at: now,
+                quote_dust: None,
+            });
+            taker.remaining_qty -= fill_qty;
+            maker.remaining_qty -= fill_qty;
+
+            if maker.remaining_qty.is_zero() {
+                let (maker_id, maker_user) = (maker.id, maker.user_id);
+                level.orders.remove(i);
+                index.remove(&maker_id);
+                Self::forget_user_order(by_user, &maker_user, &maker_id);
+            } else {
+                maker.status = OrderStatus::PartiallyFilled;
+                i += 1;
+            }
+        }
+    }
+
     // =================================================================
     // Queries
     // =================================================================
@@ -147,15 +490,81 @@ impl OrderBook {
         }
     }
 
-    /// Mid price = (best_bid + best_ask) / 2. `None` if either side is empty.
+    /// Mid price = (best_bid + best_ask) / 2, rounded to `scale` decimal
+    /// places (half to even). `None` if either side is empty.
     #[must_use]
-    pub fn mid_price(&self) -> Option<Decimal> {
+    pub fn mid_price(&self, scale: u32) -> Option<Decimal> {
         match (self.best_bid(), self.best_ask()) {
-            (Some(bid), Some(ask)) => Some((bid + ask) / Decimal::TWO),
+            (Some(bid), Some(ask)) => Some(Self::round_price((bid + ask) / Decimal::TWO, scale)),
             _ => None,
         }
     }
 
+    /// Volume-weighted average price of taking `quantity` from the resting
+    /// `side` (asks for [`OrderSide::Sell`]), best levels first, rounded to
+    /// `scale` decimal places (half to even).
+    ///
+    /// `None` if `quantity` isn't positive or the side holds less than
+    /// `quantity`.
+    #[must_use]
+    pub fn vwap(&self, side: OrderSide, quantity: Decimal, scale: u32) -> Option<Decimal> {
+        if quantity <= Decimal::ZERO {
+            return None;
+        }
+        let levels: Box<dyn Iterator<Item = &PriceLevel>> = match side {
+            OrderSide::Buy => Box::new(self.bid_levels()),
+            OrderSide::Sell => Box::new(self.ask_levels()),
+        };
+        let (mut left, mut notional) = (quantity, Decimal::ZERO);
+        for level in levels {
+            let take = left.min(level.total_quantity());
+            notional = notional.checked_add(level.price.checked_mul(take)?)?;
+            left -= take;
+            if left.is_zero() {
+                return Some(Self::round_price(notional / quantity, scale));
+            }
+        }
+        None
+    }
+
+    /// Total resting quantity, bids plus asks, priced within `band_pct`
+    /// percent of the mid price (e.g. `2` counts bids down to 98% and asks
+    /// up to 102% of mid).
+    ///
+    /// Market orders carry no price and are left out, including from the
+    /// mid. Zero if either side has no limit order or `band_pct` is
+    /// negative.
+    #[must_use]
+    pub fn liquidity_score(&self, band_pct: Decimal) -> Decimal {
+        let mut bids = self.bid_levels().filter(|l| l.price != Decimal::MAX);
+        let mut asks = self.ask_levels().filter(|l| !l.price.is_zero());
+        let (Some(best_bid), Some(best_ask)) = (bids.next(), asks.next()) else {
+            return Decimal::ZERO;
+        };
+        if band_pct.is_sign_negative() {
+            return Decimal::ZERO;
+        }
+        let mid = (best_bid.price + best_ask.price) / Decimal::TWO;
+        let half_width = mid * band_pct / Decimal::ONE_HUNDRED;
+        let (floor, ceiling) = (mid - half_width, mid + half_width);
+
+        let bid_depth: Decimal = std::iter::once(best_bid)
+            .chain(bids)
+            .take_while(|l| l.price >= floor)
+            .map(PriceLevel::total_quantity)
+            .sum();
+        let ask_depth: Decimal = std::iter::once(best_ask)
+            .chain(asks)
+            .take_while(|l| l.price <= ceiling)
+            .map(PriceLevel::total_quantity)
+            .sum();
+        bid_depth + ask_depth
+    }
+
+    fn round_price(price: Decimal, scale: u32) -> Decimal {
+        price.round_dp_with_strategy(scale, RoundingStrategy::MidpointNearestEven)
+    }
+
     /// Total number of orders currently in the book.
     #[must_use]
     pub fn order_count(&self) -> usize {
@@ -186,6 +595,27 @@ impl OrderBook {
         self.index.contains_key(order_id)
     }
 
+    /// Orders eligible at `clearing_price` that received no fill: bids at
+    /// or above it and asks at or below it with their full quantity left.
+    ///
+    /// Run on the post-match book, these are the orders the short side
+    /// rationed out. Bids come first, best price and time priority first.
+    #[must_use]
+    pub fn marginal_unfilled(&self, clearing_price: Decimal) -> Vec<OrderId> {
+        let bids = self
+            .bids
+            .range(..=Reverse(clearing_price))
+            .flat_map(|(_, level)| level.orders.iter());
+        let asks = self
+            .asks
+            .range(..=clearing_price)
+            .flat_map(|(_, level)| level.orders.iter());
+        bids.chain(asks)
+            .filter(|order| order.remaining_qty == order.quantity)
+            .map(|order| order.id)
+            .collect()
+    }
+
     // =================================================================
     // Iteration (for the matcher)
     // =================================================================
@@ -217,6 +647,7 @@ impl OrderBook {
     /// Drain all orders from the book (used during settlement reset).
     pub fn drain_all(&mut self) -> Vec<Order> {
         self.index.clear();
+        self.by_user.clear();
         let mut all = Vec::new();
         for level in self.bids.values_mut() {
             all.extend(level.orders.drain(..));
@@ -241,9 +672,50 @@ mod tests {
         Order::dummy_limit(side, price, qty)
     }
 
+    #[test]
+    fn replace_batch_swaps_contents() {
+        let mut book = OrderBook::new(MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")));
+        let old = make_order(OrderSide::Buy, Decimal::new(100, 0), Decimal::ONE);
+        let old_id = old.id;
+        book.insert_order(old).unwrap();
+
+        let displaced = book
+            .replace_batch(vec![
+                make_order(OrderSide::Buy, Decimal::new(99, 0), Decimal::ONE),
+                make_order(OrderSide::Sell, Decimal::new(101, 0), Decimal::ONE),
+            ])
+            .unwrap();
+        assert_eq!(displaced, vec![old_id]);
+        assert!(!book.contains_order(&old_id));
+        assert_eq!(book.order_count(), 2);
+        assert_eq!(book.best_bid(), Some(Decimal::new(99, 0)));
+        assert_eq!(book.best_ask(), Some(Decimal::new(101, 0)));
+    }
+
+    #[test]
+    fn replace_batch_rejects_without_mutation() {
+        let mut book = OrderBook::new(MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")));
+        let old = make_order(OrderSide::Buy, Decimal::new(100, 0), Decimal::ONE);
+        let old_id = old.id;
+        book.insert_order(old).unwrap();
+
+        let dup = make_order(OrderSide::Sell, Decimal::new(101, 0), Decimal::ONE);
+        let err = book.replace_batch(vec![dup.clone(), dup]).unwrap_err();
+        assert!(matches!(err, OpenmatchError::DuplicateOrder(_)));
+
+        let mut foreign = make_order(OrderSide::Sell, Decimal::new(101, 0), Decimal::ONE);
+        foreign.market = MarketPair::new(Asset::lit("ETH"), Asset::lit("USDT"));
+        let err = book.replace_batch(vec![foreign]).unwrap_err();
+        assert!(matches!(err, OpenmatchError::InvalidOrder { .. }));
+
+        assert!(book.contains_order(&old_id));
+        assert_eq!(book.order_count(), 1);
+        assert_eq!(book.best_ask(), None);
+    }
+
     #[test]
     fn insert_and_query_best_bid_ask() {
-        let mut book = OrderBook::new(MarketPair::new("BTC", "USDT"));
+        let mut book = OrderBook::new(MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")));
 
         book.insert_order(make_order(
             OrderSide::Buy,
@@ -278,7 +750,7 @@ mod tests {
 
     #[test]
     fn cancel_order_removes_from_book() {
-        let mut book = OrderBook::new(MarketPair::new("BTC", "USDT"));
+        let mut book = OrderBook::new(MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")));
         let order = make_order(OrderSide::Buy, Decimal::new(100, 0), Decimal::ONE);
         let id = order.id;
 
@@ -293,14 +765,14 @@ mod tests {
 
     #[test]
     fn cancel_nonexistent_order() {
-        let mut book = OrderBook::new(MarketPair::new("BTC", "USDT"));
+        let mut book = OrderBook::new(MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")));
         let result = book.cancel_order(&OrderId::new());
         assert!(result.is_err());
     }
 
     #[test]
     fn cancel_removes_empty_level() {
-        let mut book = OrderBook::new(MarketPair::new("BTC", "USDT"));
+        let mut book = OrderBook::new(MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")));
         let order = make_order(OrderSide::Buy, Decimal::new(100, 0), Decimal::ONE);
         let id = order.id;
 
@@ -313,7 +785,7 @@ mod tests {
 
     #[test]
     fn duplicate_order_rejected() {
-        let mut book = OrderBook::new(MarketPair::new("BTC", "USDT"));
+        let mut book = OrderBook::new(MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")));
         let order = make_order(OrderSide::Buy, Decimal::new(100, 0), Decimal::ONE);
         let dup = order.clone();
 
@@ -324,7 +796,7 @@ mod tests {
 
     #[test]
     fn insert_batch() {
-        let mut book = OrderBook::new(MarketPair::new("BTC", "USDT"));
+        let mut book = OrderBook::new(MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")));
         let orders = vec![
             make_order(OrderSide::Buy, Decimal::new(100, 0), Decimal::ONE),
             make_order(OrderSide::Sell, Decimal::new(101, 0), Decimal::ONE),
@@ -338,7 +810,7 @@ mod tests {
 
     #[test]
     fn drain_all_empties_book() {
-        let mut book = OrderBook::new(MarketPair::new("BTC", "USDT"));
+        let mut book = OrderBook::new(MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")));
         book.insert_order(make_order(
             OrderSide::Buy,
             Decimal::new(100, 0),
@@ -361,7 +833,7 @@ mod tests {
 
     #[test]
     fn bid_levels_iterate_highest_first() {
-        let mut book = OrderBook::new(MarketPair::new("BTC", "USDT"));
+        let mut book = OrderBook::new(MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")));
         book.insert_order(make_order(
             OrderSide::Buy,
             Decimal::new(90, 0),
@@ -394,7 +866,7 @@ mod tests {
 
     #[test]
     fn ask_levels_iterate_lowest_first() {
-        let mut book = OrderBook::new(MarketPair::new("BTC", "USDT"));
+        let mut book = OrderBook::new(MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")));
         book.insert_order(make_order(
             OrderSide::Sell,
             Decimal::new(110, 0),
@@ -427,7 +899,7 @@ mod tests {
 
     #[test]
     fn mid_price_calculation() {
-        let mut book = OrderBook::new(MarketPair::new("BTC", "USDT"));
+        let mut book = OrderBook::new(MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")));
         book.insert_order(make_order(
             OrderSide::Buy,
             Decimal::new(100, 0),
@@ -440,16 +912,462 @@ mod tests {
             Decimal::ONE,
         ))
         .unwrap();
-        assert_eq!(book.mid_price(), Some(Decimal::new(101, 0)));
+        assert_eq!(book.mid_price(2), Some(Decimal::new(101, 0)));
+    }
+
+    #[test]
+    fn half_tick_mid_price_rounds_half_to_even() {
+        let mut book = OrderBook::new(MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")));
+        book.insert_order(make_order(
+            OrderSide::Buy,
+            Decimal::new(10001, 2),
+            Decimal::ONE,
+        ))
+        .unwrap();
+        let ask = make_order(OrderSide::Sell, Decimal::new(10002, 2), Decimal::ONE);
+        let ask_id = ask.id;
+        book.insert_order(ask).unwrap();
+
+        // 100.015 → 100.02 (2 is even); exact at a finer scale
+        assert_eq!(book.mid_price(2), Some(Decimal::new(10002, 2)));
+        assert_eq!(book.mid_price(3), Some(Decimal::new(100_015, 3)));
+
+        // 100.025 → 100.02, not 100.03
+        book.cancel_order(&ask_id).unwrap();
+        book.insert_order(make_order(
+            OrderSide::Sell,
+            Decimal::new(10004, 2),
+            Decimal::ONE,
+        ))
+        .unwrap();
+        assert_eq!(book.mid_price(2), Some(Decimal::new(10002, 2)));
+    }
+
+    #[test]
+    fn vwap_walks_levels_and_rounds() {
+        let mut book = OrderBook::new(MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")));
+        for (price, qty) in [(100, 1), (101, 2)] {
+            book.insert_order(make_order(
+                OrderSide::Sell,
+                Decimal::new(price, 0),
+                Decimal::new(qty, 0),
+            ))
+            .unwrap();
+        }
+
+        // (100 + 2 × 101) / 3 = 100.6666…
+        let qty = Decimal::new(3, 0);
+        assert_eq!(
+            book.vwap(OrderSide::Sell, qty, 2),
+            Some(Decimal::new(10067, 2))
+        );
+        assert_eq!(
+            book.vwap(OrderSide::Sell, Decimal::ONE, 2),
+            Some(Decimal::new(100, 0))
+        );
+        assert_eq!(book.vwap(OrderSide::Sell, Decimal::new(4, 0), 2), None);
+        assert_eq!(book.vwap(OrderSide::Buy, Decimal::ONE, 2), None);
     }
 
     #[test]
     fn empty_book() {
-        let book = OrderBook::new(MarketPair::new("BTC", "USDT"));
+        let book = OrderBook::new(MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")));
         assert!(book.is_empty());
         assert_eq!(book.best_bid(), None);
         assert_eq!(book.best_ask(), None);
         assert_eq!(book.spread(), None);
-        assert_eq!(book.mid_price(), None);
+        assert_eq!(book.mid_price(2), None);
+    }
+
+    #[test]
+    fn match_incoming_full_fill_walks_levels() {
+        let mut book = OrderBook::new(MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")));
+        book.insert_order(make_order(
+            OrderSide::Sell,
+            Decimal::new(100, 0),
+            Decimal::ONE,
+        ))
+        .unwrap();
+        book.insert_order(make_order(
+            OrderSide::Sell,
+            Decimal::new(101, 0),
+            Decimal::TWO,
+        ))
+        .unwrap();
+
+        let taker = make_order(OrderSide::Buy, Decimal::new(101, 0), Decimal::TWO);
+        let (trades, rest) = book.match_incoming(taker, DateTime::UNIX_EPOCH);
+
+        assert!(rest.is_none());
+        assert_eq!(trades.len(), 2);
+        assert_eq!(trades[0].price, Decimal::new(100, 0));
+        assert_eq!(trades[1].price, Decimal::new(101, 0));
+        assert_eq!(trades[1].quote_amount, Decimal::new(101, 0));
+        assert!(trades.iter().all(|t| t.taker_side == OrderSide::Buy));
+        // 100 level consumed; 1 left at 101
+        assert_eq!(book.best_ask(), Some(Decimal::new(101, 0)));
+        assert_eq!(
+            book.ask_levels().next().unwrap().total_quantity(),
+            Decimal::ONE
+        );
+        assert_eq!(book.order_count(), 1);
+    }
+
+    #[test]
+    fn match_incoming_partial_fill_rests_remainder() {
+        let mut book = OrderBook::new(MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")));
+        book.insert_order(make_order(
+            OrderSide::Buy,
+            Decimal::new(100, 0),
+            Decimal::ONE,
+        ))
+        .unwrap();
+        book.insert_order(make_order(
+            OrderSide::Buy,
+            Decimal::new(98, 0),
+            Decimal::ONE,
+        ))
+        .unwrap();
+
+        let taker = make_order(OrderSide::Sell, Decimal::new(99, 0), Decimal::new(3, 0));
+        let taker_id = taker.id;
+        let (trades, rest) = book.match_incoming(taker, DateTime::UNIX_EPOCH);
+
+        assert_eq!(trades.len(), 1);
+        assert_eq!(trades[0].price, Decimal::new(100, 0));
+        let rest = rest.unwrap();
+        assert_eq!(rest.id, taker_id);
+        assert_eq!(rest.remaining_qty, Decimal::TWO);
+        assert_eq!(rest.status, OrderStatus::PartiallyFilled);
+
+        book.insert_order(rest).unwrap();
+        assert_eq!(book.best_ask(), Some(Decimal::new(99, 0)));
+        assert_eq!(book.best_bid(), Some(Decimal::new(98, 0)));
+    }
+
+    #[test]
+    fn match_incoming_skips_self_trade_and_continues() {
+        let mut book = OrderBook::new(MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")));
+        let user = UserId::new();
+        let own =
+            Order::dummy_limit_for_user(user, OrderSide::Sell, Decimal::new(100, 0), Decimal::ONE);
+        let own_id = own.id;
+        book.insert_order(own).unwrap();
+        let other = make_order(OrderSide::Sell, Decimal::new(102, 0), Decimal::ONE);
+        let other_user = other.user_id;
+        book.insert_order(other).unwrap();
+
+        let taker =
+            Order::dummy_limit_for_user(user, OrderSide::Buy, Decimal::new(105, 0), Decimal::ONE);
+        let (trades, rest) = book.match_incoming(taker, DateTime::UNIX_EPOCH);
+
+        assert!(rest.is_none());
+        assert_eq!(trades.len(), 1);
+        assert_eq!(trades[0].price, Decimal::new(102, 0));
+        assert_eq!(trades[0].maker_user_id, other_user);
+        // The taker's own resting order is untouched
+        assert!(book.contains_order(&own_id));
+        assert_eq!(book.best_ask(), Some(Decimal::new(100, 0)));
+    }
+
+    fn market(side: OrderSide, qty: Decimal) -> Order {
+        let mut order = make_order(side, Decimal::ZERO, qty);
+        order.order_type = OrderType::Market;
+        order.price = None;
+        order
+    }
+
+    #[test]
+    fn match_incoming_prices_resting_market_sell_at_taker_limit() {
+        let mut book = OrderBook::new(MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")));
+        book.insert_order(market(OrderSide::Sell, Decimal::ONE))
+            .unwrap();
+        book.insert_order(make_order(
+            OrderSide::Sell,
+            Decimal::new(100, 0),
+            Decimal::ONE,
+        ))
+        .unwrap();
+
+        let taker = make_order(OrderSide::Buy, Decimal::new(101, 0), Decimal::TWO);
+        let (trades, rest) = book.match_incoming(taker, DateTime::UNIX_EPOCH);
+        assert!(rest.is_none());
+        assert_eq!(trades.len(), 2);
+        assert_eq!(trades[0].price, Decimal::new(101, 0));
+        assert_eq!(trades[0].quote_amount, Decimal::new(101, 0));
+        assert_eq!(trades[1].price, Decimal::new(100, 0));
+
+        // A market buy has no price to trade a resting market sell at.
+        book.insert_order(market(OrderSide::Sell, Decimal::ONE))
+            .unwrap();
+        let (trades, rest) =
+            book.match_incoming(market(OrderSide::Buy, Decimal::ONE), DateTime::UNIX_EPOCH);
+        assert!(trades.is_empty());
+        assert_eq!(rest.unwrap().remaining_qty, Decimal::ONE);
+    }
+
+    #[test]
+    fn match_incoming_prices_resting_market_buy_at_taker_limit() {
+        let mut book = OrderBook::new(MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")));
+        book.insert_order(market(OrderSide::Buy, Decimal::ONE))
+            .unwrap();
+        book.insert_order(make_order(
+            OrderSide::Buy,
+            Decimal::new(100, 0),
+            Decimal::ONE,
+        ))
+        .unwrap();
+
+        let taker = make_order(OrderSide::Sell, Decimal::new(99, 0), Decimal::TWO);
+        let (trades, rest) = book.match_incoming(taker, DateTime::UNIX_EPOCH);
+        assert!(rest.is_none());
+        assert_eq!(trades.len(), 2);
+        assert_eq!(trades[0].price, Decimal::new(99, 0));
+        assert_eq!(trades[0].quote_amount, Decimal::new(99, 0));
+        assert_eq!(trades[1].price, Decimal::new(100, 0));
+
+        book.insert_order(market(OrderSide::Buy, Decimal::ONE))
+            .unwrap();
+        let (trades, rest) =
+            book.match_incoming(market(OrderSide::Sell, Decimal::ONE), DateTime::UNIX_EPOCH);
+        assert!(trades.is_empty());
+        assert_eq!(rest.unwrap().remaining_qty, Decimal::ONE);
+    }
+
+    #[test]
+    fn cancel_all_for_user_removes_only_that_user() {
+        let mut book = OrderBook::new(MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")));
+        let (alice, bob) = (UserId::new(), UserId::new());
+        let alice_orders = [
+            Order::dummy_limit_for_user(alice, OrderSide::Buy, Decimal::new(100, 0), Decimal::ONE),
+            Order::dummy_limit_for_user(alice, OrderSide::Buy, Decimal::new(99, 0), Decimal::ONE),
+            Order::dummy_limit_for_user(alice, OrderSide::Sell, Decimal::new(105, 0), Decimal::ONE),
+        ];
+        let alice_ids: Vec<OrderId> = alice_orders.iter().map(|o| o.id).collect();
+        let bob_order =
+            Order::dummy_limit_for_user(bob, OrderSide::Buy, Decimal::new(100, 0), Decimal::ONE);
+        let bob_id = bob_order.id;
+        book.insert_batch(alice_orders.to_vec()).unwrap();
+        book.insert_order(bob_order).unwrap();
+
+        let removed = book.cancel_all_for_user(&alice);
+        assert_eq!(removed.iter().map(|o| o.id).collect::<Vec<_>>(), alice_ids);
+        assert_eq!(book.order_count(), 1);
+        assert!(book.contains_order(&bob_id));
+        // The 99 bid and the only ask level are gone; the shared 100 level stays.
+        assert_eq!(book.bid_depth(), 1);
+        assert_eq!(book.ask_depth(), 0);
+        assert!(book.cancel_all_for_user(&alice).is_empty());
+    }
+
+    #[test]
+    fn expire_orders_removes_only_expired() {
+        let mut book = OrderBook::new(MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")));
+        let now = Utc::now();
+        let with_expiry = |side, price, expires_at| {
+            let mut order = make_order(side, Decimal::new(price, 0), Decimal::ONE);
+            order.expires_at = expires_at;
+            order
+        };
+        let gone = [
+            with_expiry(OrderSide::Buy, 99, Some(now - chrono::Duration::seconds(1))),
+            with_expiry(OrderSide::Sell, 105, Some(now)),
+        ];
+        let live = [
+            with_expiry(OrderSide::Buy, 99, Some(now + chrono::Duration::seconds(1))),
+            with_expiry(OrderSide::Buy, 98, None),
+            with_expiry(OrderSide::Sell, 101, None),
+        ];
+        let gone_ids: Vec<OrderId> = gone.iter().map(|o| o.id).collect();
+        let user = gone[1].user_id;
+        book.insert_batch(gone.into_iter().chain(live.clone()).collect())
+            .unwrap();
+
+        let expired = book.expire_orders(now);
+        assert_eq!(expired.iter().map(|o| o.id).collect::<Vec<_>>(), gone_ids);
+        assert_eq!(book.order_count(), 3);
+        assert!(live.iter().all(|o| book.contains_order(&o.id)));
+        // The 105 ask level emptied; the 99 bid level still holds a live order.
+        assert_eq!(book.ask_depth(), 1);
+        assert_eq!(book.bid_depth(), 2);
+        assert!(book.cancel_all_for_user(&user).is_empty());
+        assert!(book.expire_orders(now).is_empty());
+    }
+
+    #[test]
+    fn user_index_tracks_filled_makers() {
+        let mut book = OrderBook::new(MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")));
+        let maker = UserId::new();
+        book.insert_order(Order::dummy_limit_for_user(
+            maker,
+            OrderSide::Sell,
+            Decimal::new(100, 0),
+            Decimal::ONE,
+        ))
+        .unwrap();
+        let resting =
+            Order::dummy_limit_for_user(maker, OrderSide::Sell, Decimal::new(101, 0), Decimal::ONE);
+        let resting_id = resting.id;
+        book.insert_order(resting).unwrap();
+
+        let taker = make_order(OrderSide::Buy, Decimal::new(100, 0), Decimal::ONE);
+        let (trades, _) = book.match_incoming(taker, DateTime::UNIX_EPOCH);
+        assert_eq!(trades.len(), 1);
+
+        let removed = book.cancel_all_for_user(&maker);
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].id, resting_id);
+        assert!(book.is_empty());
+    }
+
+    #[test]
+    fn cancel_for_user_requires_ownership() {
+        let mut book = OrderBook::new(MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")));
+        let order = make_order(OrderSide::Buy, Decimal::new(100, 0), Decimal::ONE);
+        let (id, owner) = (order.id, order.user_id);
+        book.insert_order(order).unwrap();
+
+        assert!(matches!(
+            book.cancel_for_user(&id, &UserId::new()),
+            Err(OpenmatchError::OrderNotCancellable)
+        ));
+        assert!(book.contains_order(&id));
+
+        assert_eq!(book.cancel_for_user(&id, &owner).unwrap().id, id);
+        assert!(matches!(
+            book.cancel_for_user(&id, &owner),
+            Err(OpenmatchError::OrderNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn amend_priority_follows_price_and_size() {
+        let mut book = OrderBook::new(MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")));
+        let mut ids = Vec::new();
+        for (seq, price) in [(0, 100), (1, 100), (2, 101)] {
+            let mut order = make_order(OrderSide::Buy, Decimal::new(price, 0), Decimal::TWO);
+            order.sequence = seq;
+            ids.push(order.id);
+            book.insert_order(order).unwrap();
+        }
+        let level_ids = |book: &OrderBook, price| -> Vec<OrderId> {
+            book.bid_levels()
+                .find(|l| l.price == Decimal::new(price, 0))
+                .map(|l| l.iter().map(|o| o.id).collect())
+                .unwrap_or_default()
+        };
+
+        // Size decrease keeps the head of the 100 level
+        book.amend_order(&ids[0], Decimal::new(100, 0), Decimal::ONE, 3)
+            .unwrap();
+        assert_eq!(level_ids(&book, 100), vec![ids[0], ids[1]]);
+        let head = book.bid_levels().nth(1).unwrap().front().unwrap();
+        assert_eq!((head.quantity, head.sequence), (Decimal::ONE, 0));
+
+        // Price change tails the order at its new level
+        book.amend_order(&ids[0], Decimal::new(101, 0), Decimal::ONE, 4)
+            .unwrap();
+        assert_eq!(level_ids(&book, 101), vec![ids[2], ids[0]]);
+        assert_eq!(level_ids(&book, 100), vec![ids[1]]);
+
+        // Size increase loses priority too
+        book.amend_order(&ids[2], Decimal::new(101, 0), Decimal::new(3, 0), 5)
+            .unwrap();
+        assert_eq!(level_ids(&book, 101), vec![ids[0], ids[2]]);
+        assert!(book.bid_levels().all(PriceLevel::is_fifo_ordered));
+
+        assert!(
+            book.amend_order(&ids[1], Decimal::new(100, 0), Decimal::ZERO, 6)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn rejected_amend_leaves_order_in_book() {
+        let mut book = OrderBook::new(MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")));
+        let limit = make_order(OrderSide::Buy, Decimal::new(100, 0), Decimal::TWO);
+        let limit_id = limit.id;
+        book.insert_order(limit).unwrap();
+        let resting_market = market(OrderSide::Sell, Decimal::ONE);
+        let market_id = resting_market.id;
+        book.insert_order(resting_market).unwrap();
+
+        // Non-positive price
+        for price in [Decimal::ZERO, Decimal::new(-1, 0)] {
+            assert!(matches!(
+                book.amend_order(&limit_id, price, Decimal::ONE, 1),
+                Err(OpenmatchError::InvalidOrder { .. })
+            ));
+        }
+        assert!(book.contains_order(&limit_id));
+        assert_eq!(book.best_bid(), Some(Decimal::new(100, 0)));
+
+        // Non-limit order
+        assert!(matches!(
+            book.amend_order(&market_id, Decimal::new(101, 0), Decimal::ONE, 2),
+            Err(OpenmatchError::InvalidOrder { .. })
+        ));
+        assert!(book.contains_order(&market_id));
+        assert_eq!(book.order_count(), 2);
+    }
+
+    fn banded_book(levels: &[(OrderSide, i64, i64)]) -> OrderBook {
+        let mut book = OrderBook::new(MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")));
+        for &(side, price, qty) in levels {
+            book.insert_order(make_order(
+                side,
+                Decimal::new(price, 0),
+                Decimal::new(qty, 0),
+            ))
+            .unwrap();
+        }
+        book
+    }
+
+    #[test]
+    fn liquidity_score_counts_depth_near_mid() {
+        use OrderSide::{Buy, Sell};
+
+        // Mid 100: a 2% band spans [98, 102]
+        let symmetric = banded_book(&[
+            (Buy, 99, 5),
+            (Buy, 98, 5),
+            (Buy, 90, 100),
+            (Sell, 101, 5),
+            (Sell, 102, 5),
+            (Sell, 110, 100),
+        ]);
+        assert_eq!(symmetric.liquidity_score(Decimal::TWO), Decimal::new(20, 0));
+        assert_eq!(symmetric.liquidity_score(Decimal::ONE), Decimal::new(10, 0));
+        assert_eq!(
+            symmetric.liquidity_score(Decimal::TEN),
+            Decimal::new(220, 0)
+        );
+
+        // Thin bids, deep asks: only the near-touch asks count
+        let asymmetric = banded_book(&[(Buy, 99, 1), (Sell, 101, 10), (Sell, 130, 50)]);
+        assert_eq!(
+            asymmetric.liquidity_score(Decimal::TWO),
+            Decimal::new(11, 0)
+        );
+        assert!(asymmetric.liquidity_score(Decimal::TWO) < symmetric.liquidity_score(Decimal::TWO));
+    }
+
+    #[test]
+    fn liquidity_score_zero_for_one_sided_book() {
+        let book = banded_book(&[(OrderSide::Buy, 99, 5)]);
+        assert_eq!(book.liquidity_score(Decimal::TEN), Decimal::ZERO);
+
+        let mut market_only = banded_book(&[(OrderSide::Buy, 99, 5)]);
+        let mut sell = make_order(OrderSide::Sell, Decimal::ZERO, Decimal::ONE);
+        sell.order_type = OrderType::Market;
+        sell.price = None;
+        market_only.insert_order(sell).unwrap();
+        assert_eq!(market_only.liquidity_score(Decimal::TEN), Decimal::ZERO);
+        assert_eq!(
+            banded_book(&[(OrderSide::Buy, 99, 5), (OrderSide::Sell, 101, 5)])
+                .liquidity_score(-Decimal::ONE),
+            Decimal::ZERO
+        );
     }
 }
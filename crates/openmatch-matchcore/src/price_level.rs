@@ -69,6 +69,42 @@ impl PriceLevel {
     pub fn len(&self) -> usize {
         self.orders.len()
     }
+
+    /// Returns `true` if sequence numbers never decrease from front to
+    /// back, i.e. the level is in strict insertion (time-priority) order.
+    ///
+    /// A level rotated with [`PriceLevel::rotate_left`] is deliberately
+    /// out of FIFO order and reports `false`.
+    #[must_use]
+    pub fn is_fifo_ordered(&self) -> bool {
+        self.orders
+            .iter()
+            .zip(self.orders.iter().skip(1))
+            .all(|(a, b)| a.sequence <= b.sequence)
+    }
+
+    /// Iterate orders in time-priority order without cloning.
+    pub fn iter(&self) -> impl Iterator<Item = &Order> {
+        self.orders.iter()
+    }
+
+    /// Mutably iterate orders in time-priority order.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Order> {
+        self.orders.iter_mut()
+    }
+
+    /// Rotate the level left by `n` positions.
+    ///
+    /// The first `n` orders move to the back, preserving the relative order
+    /// of all orders. Used for round-robin fairness across batches: rotating
+    /// by one after each batch gives every order a turn at the head.
+    /// `n` is taken modulo the level length; an empty level is a no-op.
+    pub fn rotate_left(&mut self, n: usize) {
+        if !self.orders.is_empty() {
+            let len = self.orders.len();
+            self.orders.rotate_left(n % len);
+        }
+    }
 }
 
 #[cfg(test)]
@@ -132,6 +168,54 @@ mod tests {
         assert!(level.remove_order(&fake_id).is_none());
     }
 
+    #[test]
+    fn rotate_advances_head_preserving_order() {
+        let mut level = PriceLevel::new(Decimal::new(100, 0));
+        for seq in 0..4 {
+            level.push_back(make_order(Decimal::new(100, 0), Decimal::ONE, seq));
+        }
+
+        level.rotate_left(1);
+        let seqs: Vec<u64> = level.iter().map(|o| o.sequence).collect();
+        assert_eq!(seqs, vec![1, 2, 3, 0]);
+
+        // Rotating by the full length is a no-op.
+        level.rotate_left(level.len());
+        let seqs: Vec<u64> = level.iter().map(|o| o.sequence).collect();
+        assert_eq!(seqs, vec![1, 2, 3, 0]);
+    }
+
+    #[test]
+    fn fifo_order_detects_rotation() {
+        let mut level = PriceLevel::new(Decimal::new(100, 0));
+        assert!(level.is_fifo_ordered());
+        for seq in [0, 1, 1, 3] {
+            level.push_back(make_order(Decimal::new(100, 0), Decimal::ONE, seq));
+        }
+        assert!(level.is_fifo_ordered());
+
+        level.rotate_left(1);
+        assert!(!level.is_fifo_ordered());
+    }
+
+    #[test]
+    fn iter_mut_updates_in_place() {
+        let mut level = PriceLevel::new(Decimal::new(100, 0));
+        level.push_back(make_order(Decimal::new(100, 0), Decimal::new(5, 0), 0));
+        level.push_back(make_order(Decimal::new(100, 0), Decimal::new(3, 0), 1));
+        for order in level.iter_mut() {
+            order.remaining_qty = Decimal::ONE;
+        }
+        assert_eq!(level.total_quantity(), Decimal::TWO);
+    }
+
+    #[test]
+    fn rotate_empty_level_is_noop() {
+        let mut level = PriceLevel::new(Decimal::new(100, 0));
+        level.rotate_left(1);
+        assert!(level.is_empty());
+    }
+
     #[test]
     fn empty_level() {
         let level = PriceLevel::new(Decimal::new(100, 0));
@@ -6,7 +6,8 @@
 //! ## Architecture
 //!
 //! The Finality Plane receives a [`TradeBundle`] from MatchCore and:
-//! 1. Validates idempotency (no double-settlement)
+//! 1. Validates idempotency (no double-settlement) and `SpendRight`
+//!    nonces (no replayed SRs, see [`NonceTracker`])
 //! 2. Consumes SpendRights (ACTIVE → SPENT)
 //! 3. Executes balance transfers (frozen → counterparty available)
 //! 4. Generates cryptographic receipts for audit trail
@@ -17,13 +18,26 @@
 //! - **Tier 1**: Local atomic (within same node) — instant
 //! - **Tier 2**: Cross-node gossip settlement — sub-second
 //! - **Tier 3**: On-chain finality — minutes/blocks
+//!
+//! [`SettlementRouter`] picks the tier for each trade.
+//!
+//! Settled trades are published to market-data consumers through the
+//! [`TradeTape`], which numbers them for gap detection.
 
 pub mod idempotency;
+pub mod nonce_tracker;
+pub mod plan;
+pub mod router;
 pub mod supply_conservation;
+pub mod tape;
 pub mod tier1;
 pub mod withdraw_lock;
 
-pub use idempotency::IdempotencyGuard;
-pub use supply_conservation::SupplyConservation;
+pub use idempotency::{IdempotencyGuard, SettlementIdempotencyGuard};
+pub use nonce_tracker::NonceTracker;
+pub use plan::{BalanceMutation, SettlementPlan};
+pub use router::{SettlementRouter, SettlementTier};
+pub use supply_conservation::{SupplyCheckCadence, SupplyConservation};
+pub use tape::TradeTape;
 pub use tier1::Tier1Settler;
 pub use withdraw_lock::WithdrawLock;
@@ -0,0 +1,264 @@
+//! `SpendRight` nonce replay prevention.
+//!
+//! Every SR carries its issuing node and a nonce. A captured SR replayed
+//! to this node carries a nonce already seen from that issuer, so
+//! settlement records `(issuer_node, nonce)` before honoring a foreign SR.
+//!
+//! Memory is bounded either way: by a per-node quota, or — in monotonic
+//! mode — by keeping only the highest accepted nonce per node.
+
+use std::collections::{HashMap, HashSet};
+
+use openmatch_types::{NodeId, OpenmatchError, Result, SpendRight};
+
+/// Tracks used nonces per issuing node.
+///
+/// Each node's nonce set is bounded. When the limit is reached, new
+/// nonces from that node are refused until the set is cleared (typically
+/// at the epoch boundary), so a flood of SRs can't exhaust memory.
+///
+/// With [`NonceTracker::require_monotonic`], each node's nonces must be
+/// strictly increasing (like Ethereum account nonces). Only the highest
+/// accepted nonce is kept per node, so memory is O(1) per node and the
+/// quota does not apply. High-water marks survive `clear_*`.
+#[derive(Debug, Default)]
+pub struct NonceTracker {
+    /// Used nonces per issuing node.
+    used_nonces: HashMap<NodeId, HashSet<u64>>,
+    /// Maximum nonces per node before rejection.
+    max_per_node: usize,
+    /// Reject any nonce `<=` the node's high-water mark.
+    monotonic: bool,
+    /// Highest accepted nonce per node (monotonic mode only).
+    high_water: HashMap<NodeId, u64>,
+}
+
+impl NonceTracker {
+    /// Create a tracker allowing `max_per_node` nonces per issuing node,
+    /// typically `constants::MAX_NONCE_ENTRIES_PER_NODE`.
+    #[must_use]
+    pub fn new(max_per_node: usize) -> Self {
+        Self {
+            used_nonces: HashMap::new(),
+            max_per_node,
+            monotonic: false,
+            high_water: HashMap::new(),
+        }
+    }
+
+    /// Switch to monotonic mode: only nonces above the node's high-water
+    /// mark are accepted.
+    #[must_use]
+    pub fn require_monotonic(mut self) -> Self {
+        self.monotonic = true;
+        self
+    }
+
+    /// Check and record a nonce from `node_id`.
+    ///
+    /// # Errors
+    /// - `NonceReplay` if the nonce was already used, or in monotonic mode
+    ///   is at or below the node's high-water mark
+    /// - `RateLimitExceeded` if the node has used up its nonce quota
+    pub fn check_and_record(&mut self, node_id: &NodeId, nonce: u64) -> Result<()> {
+        let replay = || OpenmatchError::NonceReplay {
+            node_hex: hex::encode(node_id.0),
+            nonce,
+        };
+        if self.monotonic {
+            if self.high_water.get(node_id).is_some_and(|&hw| nonce <= hw) {
+                return Err(replay());
+            }
+            self.high_water.insert(*node_id, nonce);
+            return Ok(());
+        }
+
+        let nonces = self.used_nonces.entry(*node_id).or_default();
+        if nonces.contains(&nonce) {
+            return Err(replay());
+        }
+        if nonces.len() >= self.max_per_node {
+            return Err(OpenmatchError::RateLimitExceeded {
+                reason: format!(
+                    "Node {} exceeded nonce quota ({})",
+                    hex::encode(node_id.0),
+                    self.max_per_node
+                ),
+            });
+        }
+        nonces.insert(nonce);
+        Ok(())
+    }
+
+    /// Check and record `sr`'s nonce under its issuing node.
+    ///
+    /// # Errors
+    /// Same as [`NonceTracker::check_and_record`].
+    pub fn check_spend_right(&mut self, sr: &SpendRight) -> Result<()> {
+        self.check_and_record(&sr.issuer_node, sr.nonce)
+    }
+
+    /// Clear all nonces for a given node (e.g., at epoch boundary).
+    pub fn clear_node(&mut self, node_id: &NodeId) {
+        self.used_nonces.remove(node_id);
+    }
+
+    /// Clear all tracked nonces (e.g., at epoch boundary).
+    pub fn clear_all(&mut self) {
+        self.used_nonces.clear();
+    }
+
+    /// Total nonces tracked across all nodes.
+    #[must_use]
+    pub fn total_nonces(&self) -> usize {
+        self.used_nonces.values().map(HashSet::len).sum()
+    }
+
+    /// Highest nonce accepted from `node_id` in monotonic mode.
+    #[must_use]
+    pub fn high_water_mark(&self, node_id: &NodeId) -> Option<u64> {
+        self.high_water.get(node_id).copied()
+    }
+
+    /// Quota usage for `node_id` as `(used, max)`.
+    ///
+    /// In monotonic mode no nonce set is kept and the quota doesn't apply,
+    /// so `used` is always 0.
+    #[must_use]
+    pub fn node_usage(&self, node_id: &NodeId) -> (usize, usize) {
+        let used = self.used_nonces.get(node_id).map_or(0, HashSet::len);
+        (used, self.max_per_node)
+    }
+
+    /// Nodes whose quota utilization is at or above `pct` (0.0–1.0),
+    /// sorted by `NodeId`. Alert on these before `RateLimitExceeded` fires.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn nodes_near_capacity(&self, pct: f64) -> Vec<NodeId> {
+        if self.max_per_node == 0 {
+            return Vec::new();
+        }
+        let mut nodes: Vec<NodeId> = self
+            .used_nonces
+            .iter()
+            .filter(|(_, nonces)| nonces.len() as f64 / self.max_per_node as f64 >= pct)
+            .map(|(node_id, _)| *node_id)
+            .collect();
+        nodes.sort_unstable();
+        nodes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use openmatch_types::*;
+    use rust_decimal::Decimal;
+
+    use super::*;
+
+    #[test]
+    fn blocks_replay_per_node() {
+        let mut tracker = NonceTracker::new(100);
+        let (a, b) = (NodeId([1u8; 32]), NodeId([2u8; 32]));
+        tracker.check_and_record(&a, 42).unwrap();
+        assert!(matches!(
+            tracker.check_and_record(&a, 42),
+            Err(OpenmatchError::NonceReplay { nonce: 42, .. })
+        ));
+        // Same nonce from another node is fine
+        tracker.check_and_record(&b, 42).unwrap();
+    }
+
+    #[test]
+    fn rejects_at_capacity_until_cleared() {
+        let mut tracker = NonceTracker::new(3);
+        let node = NodeId([1u8; 32]);
+        for nonce in 1..=3 {
+            tracker.check_and_record(&node, nonce).unwrap();
+        }
+        assert!(matches!(
+            tracker.check_and_record(&node, 4),
+            Err(OpenmatchError::RateLimitExceeded { .. })
+        ));
+
+        tracker.clear_node(&node);
+        tracker.check_and_record(&node, 1).unwrap();
+        assert_eq!(tracker.total_nonces(), 1);
+    }
+
+    #[test]
+    fn reports_nodes_near_capacity() {
+        let mut tracker = NonceTracker::new(10);
+        let (busy, quiet) = (NodeId([1u8; 32]), NodeId([2u8; 32]));
+        for nonce in 0..8 {
+            tracker.check_and_record(&busy, nonce).unwrap();
+        }
+        tracker.check_and_record(&quiet, 0).unwrap();
+
+        assert_eq!(tracker.node_usage(&busy), (8, 10));
+        assert_eq!(tracker.node_usage(&NodeId([3u8; 32])), (0, 10));
+        assert_eq!(tracker.nodes_near_capacity(0.75), vec![busy]);
+        assert!(tracker.nodes_near_capacity(0.9).is_empty());
+    }
+
+    #[test]
+    fn spend_right_replay_is_caught() {
+        let mut tracker = NonceTracker::new(100);
+        let sr = SpendRight::dummy(
+            OrderId::new(),
+            UserId::new(),
+            "USDT",
+            Decimal::ONE,
+            EpochId(1),
+        );
+        tracker.check_spend_right(&sr).unwrap();
+        assert!(matches!(
+            tracker.check_spend_right(&sr),
+            Err(OpenmatchError::NonceReplay { .. })
+        ));
+    }
+
+    #[test]
+    fn monotonic_accepts_increasing_nonces() {
+        let mut tracker = NonceTracker::new(100).require_monotonic();
+        let node = NodeId([1u8; 32]);
+        for nonce in [1, 5, 6] {
+            tracker.check_and_record(&node, nonce).unwrap();
+        }
+        assert_eq!(tracker.high_water_mark(&node), Some(6));
+    }
+
+    #[test]
+    fn monotonic_rejects_stale_nonces() {
+        let mut tracker = NonceTracker::new(100).require_monotonic();
+        let node = NodeId([1u8; 32]);
+        tracker.check_and_record(&node, 10).unwrap();
+        for stale in [10, 9, 0] {
+            assert!(matches!(
+                tracker.check_and_record(&node, stale),
+                Err(OpenmatchError::NonceReplay { .. })
+            ));
+        }
+        assert_eq!(tracker.high_water_mark(&node), Some(10));
+
+        // Other nodes have their own mark
+        tracker.check_and_record(&NodeId([2u8; 32]), 1).unwrap();
+    }
+
+    #[test]
+    fn monotonic_memory_is_constant_per_node() {
+        let mut tracker = NonceTracker::new(3).require_monotonic();
+        let node = NodeId([1u8; 32]);
+
+        // Far beyond the per-node quota: nothing is stored per nonce
+        for nonce in 1..=10_000 {
+            tracker.check_and_record(&node, nonce).unwrap();
+        }
+        assert_eq!(tracker.total_nonces(), 0);
+        assert_eq!(tracker.high_water.len(), 1);
+
+        // Clearing the epoch's nonce sets does not reopen old nonces
+        tracker.clear_all();
+        assert!(tracker.check_and_record(&node, 10_000).is_err());
+    }
+}
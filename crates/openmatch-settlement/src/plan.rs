@@ -0,0 +1,451 @@
+//! Settlement plans — inspectable, dry-runnable balance mutations.
+//!
+//! A [`SettlementPlan`] is computed from a [`TradeBundle`] before any
+//! balance is touched. It nets every trade into per-(user, asset) frozen
+//! debits and available credits, so the whole epoch can be checked
+//! against any [`BalanceLedger`] first and applied afterwards.
+//!
+//! With a [`FeeSchedule`], fees are netted exactly as
+//! [`Tier1Settler`](crate::Tier1Settler) charges them, and the fee pool's
+//! net change is reported alongside the user mutations.
+
+use std::collections::BTreeMap;
+
+use openmatch_types::{
+    Asset, BalanceLedger, EpochId, FeeSchedule, OpenmatchError, Result, TradeBundle, TradeId,
+    UserId,
+};
+use rust_decimal::Decimal;
+
+/// One balance mutation in a settlement plan.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BalanceMutation {
+    /// Remove `amount` from the user's frozen balance.
+    ConsumeFrozen {
+        user_id: UserId,
+        asset: Asset,
+        amount: Decimal,
+    },
+    /// Remove `amount` from the user's available balance: a dust account
+    /// funding fills whose quote was rounded up.
+    DebitAvailable {
+        user_id: UserId,
+        asset: Asset,
+        amount: Decimal,
+    },
+    /// Add `amount` to the user's available balance.
+    Credit {
+        user_id: UserId,
+        asset: Asset,
+        amount: Decimal,
+    },
+}
+
+/// Precomputed balance mutations for one epoch's trades.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SettlementPlan {
+    /// The epoch the bundle came from.
+    pub epoch_id: EpochId,
+    /// Idempotency keys: every trade the plan settles, in bundle order.
+    pub trade_ids: Vec<TradeId>,
+    /// Mutations in application order: all frozen debits, then all
+    /// available debits, then all credits, each sorted by (user, asset).
+    pub mutations: Vec<BalanceMutation>,
+    /// Net change to the fee pool per quote asset: fees collected minus
+    /// rebates paid. The pool is not a ledger account, so whoever holds it
+    /// applies this and must fund a negative entry. Zero entries are
+    /// omitted.
+    pub fee_pool_deltas: BTreeMap<Asset, Decimal>,
+}
+
+impl SettlementPlan {
+    /// Net a bundle's trades into a plan, without fees.
+    ///
+    /// Per trade, the seller's frozen base pays the buyer's available base
+    /// and the buyer's frozen quote pays the seller's available quote. Quote
+    /// dust moves between the buyer's frozen quote and the dust account; a
+    /// dust account that nets negative is debited from available.
+    #[must_use]
+    pub fn from_bundle(bundle: &TradeBundle) -> Self {
+        Self::from_bundle_with_fees(bundle, &FeeSchedule::default())
+    }
+
+    /// Net a bundle's trades into a plan, charging `fees`.
+    ///
+    /// Fees follow [`FeeSchedule::buyer_seller_fees`], as in
+    /// [`Tier1Settler`](crate::Tier1Settler): a buyer's fee is taken from
+    /// frozen quote on top of the trade's quote amount, a seller's from its
+    /// quote proceeds, and rebates are credited to available. What the
+    /// users pay net lands in [`SettlementPlan::fee_pool_deltas`].
+    #[must_use]
+    pub fn from_bundle_with_fees(bundle: &TradeBundle, fees: &FeeSchedule) -> Self {
+        let mut debits: BTreeMap<(UserId, Asset), Decimal> = BTreeMap::new();
+        let mut credits: BTreeMap<(UserId, Asset), Decimal> = BTreeMap::new();
+        let mut fee_pool_deltas: BTreeMap<Asset, Decimal> = BTreeMap::new();
+        for trade in &bundle.trades {
+            let (buyer_id, seller_id) = if trade.taker_is_buyer() {
+                (trade.taker_user_id, trade.maker_user_id)
+            } else {
+                (trade.maker_user_id, trade.taker_user_id)
+            };
+            let (base, quote) = (&trade.market.base, &trade.market.quote);
+
+            *debits.entry((seller_id, base.clone())).or_default() += trade.quantity;
+            *credits.entry((buyer_id, base.clone())).or_default() += trade.quantity;
+            let (buyer_fee, seller_fee) = fees.buyer_seller_fees(trade);
+            *debits.entry((buyer_id, quote.clone())).or_default() +=
+                trade.buyer_quote_amount() + buyer_fee.max(Decimal::ZERO);
+            *credits.entry((buyer_id, quote.clone())).or_default() -= buyer_fee.min(Decimal::ZERO);
+            *credits.entry((seller_id, quote.clone())).or_default() +=
+                trade.quote_amount - seller_fee;
+            *fee_pool_deltas.entry(quote.clone()).or_default() += buyer_fee + seller_fee;
+            if let Some(dust) = &trade.quote_dust {
+                *credits.entry((dust.account, quote.clone())).or_default() += dust.amount;
+            }
+        }
+        let (credits, available_debits): (BTreeMap<_, _>, BTreeMap<_, _>) = credits
+            .into_iter()
+            .filter(|(_, amount)| !amount.is_zero())
+            .partition(|(_, amount)| *amount > Decimal::ZERO);
+
+        let consume =
+            debits.into_iter().map(
+                |((user_id, asset), amount)| BalanceMutation::ConsumeFrozen {
+                    user_id,
+                    asset,
+                    amount,
+                },
+            );
+        let debit_available = available_debits
+            .into_iter()
+            .map(
+                |((user_id, asset), amount)| BalanceMutation::DebitAvailable {
+                    user_id,
+                    asset,
+                    amount: -amount,
+                },
+            );
+        let credit =
+            credits
+                .into_iter()
+                .map(|((user_id, asset), amount)| BalanceMutation::Credit {
+                    user_id,
+                    asset,
+                    amount,
+                });
+
+        Self {
+            epoch_id: bundle.epoch_id,
+            trade_ids: bundle.trades.iter().map(|t| t.id).collect(),
+            mutations: consume.chain(debit_available).chain(credit).collect(),
+            fee_pool_deltas: fee_pool_deltas
+                .into_iter()
+                .filter(|(_, amount)| !amount.is_zero())
+                .collect(),
+        }
+    }
+
+    /// Net change per (user, asset): credits minus frozen debits.
+    ///
+    /// Settlement only moves value between users and the fee pool, so the
+    /// deltas of each asset plus its fee pool delta sum to zero.
+    #[must_use]
+    pub fn net_deltas(&self) -> BTreeMap<(UserId, Asset), Decimal> {
+        let mut net: BTreeMap<(UserId, Asset), Decimal> = BTreeMap::new();
+        for mutation in &self.mutations {
+            match mutation {
+                BalanceMutation::ConsumeFrozen {
+                    user_id,
+                    asset,
+                    amount,
+                }
+                | BalanceMutation::DebitAvailable {
+                    user_id,
+                    asset,
+                    amount,
+                } => *net.entry((*user_id, asset.clone())).or_default() -= *amount,
+                BalanceMutation::Credit {
+                    user_id,
+                    asset,
+                    amount,
+                } => *net.entry((*user_id, asset.clone())).or_default() += *amount,
+            }
+        }
+        net
+    }
+
+    /// Dry run: check every debit is covered, without mutating.
+    ///
+    /// # Errors
+    /// `InsufficientFrozen` if any user's frozen balance is below its
+    /// planned debit, `InsufficientBalance` if a dust account's available
+    /// balance is.
+    pub fn validate(&self, balances: &impl BalanceLedger) -> Result<()> {
+        for mutation in &self.mutations {
+            match mutation {
+                BalanceMutation::ConsumeFrozen {
+                    user_id,
+                    asset,
+                    amount,
+                } => {
+                    if balances.balance_of(*user_id, asset).frozen < *amount {
+                        return Err(OpenmatchError::InsufficientFrozen);
+                    }
+                }
+                BalanceMutation::DebitAvailable {
+                    user_id,
+                    asset,
+                    amount,
+                } => {
+                    let available = balances.balance_of(*user_id, asset).available;
+                    if available < *amount {
+                        return Err(OpenmatchError::InsufficientBalance {
+                            needed: *amount,
+                            available,
+                        });
+                    }
+                }
+                BalanceMutation::Credit { .. } => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Validate, then apply every mutation in order.
+    ///
+    /// Idempotency is the caller's concern: mark [`SettlementPlan::trade_ids`]
+    /// settled before applying.
+    ///
+    /// # Errors
+    /// Same as [`SettlementPlan::validate`]; on error nothing is applied.
+    pub fn apply(&self, balances: &mut impl BalanceLedger) -> Result<()> {
+        self.validate(balances)?;
+        for mutation in &self.mutations {
+            match mutation {
+                BalanceMutation::ConsumeFrozen {
+                    user_id,
+                    asset,
+                    amount,
+                } => balances.consume_frozen_funds(*user_id, asset, *amount)?,
+                BalanceMutation::DebitAvailable {
+                    user_id,
+                    asset,
+                    amount,
+                } => {
+                    balances.freeze_funds(*user_id, asset, *amount)?;
+                    balances.consume_frozen_funds(*user_id, asset, *amount)?;
+                }
+                BalanceMutation::Credit {
+                    user_id,
+                    asset,
+                    amount,
+                } => balances.credit_funds(*user_id, asset, *amount),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use openmatch_ingress::BalanceManager;
+    use openmatch_types::*;
+
+    use super::*;
+
+    fn make_trade(seq: u64, buyer: UserId, seller: UserId, qty: Decimal) -> Trade {
+        let price = Decimal::new(100, 0);
+        Trade {
+            id: TradeId::deterministic(1, seq),
+            epoch_id: EpochId(1),
+            market: MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")),
+            taker_order_id: OrderId::new(),
+            taker_user_id: buyer,
+            maker_order_id: OrderId::new(),
+            maker_user_id: seller,
+            price,
+            quantity: qty,
+            quote_amount: price * qty,
+            taker_side: OrderSide::Buy,
+            matcher_node: NodeId([0u8; 32]),
+            executed_at: Utc::now(),
+            quote_dust: None,
+        }
+    }
+
+    fn make_bundle(trades: Vec<Trade>) -> TradeBundle {
+        TradeBundle {
+            epoch_id: EpochId(1),
+            trades,
+            trade_root: [0u8; 32],
+            input_hash: [0u8; 32],
+            clearing_price: Some(Decimal::new(100, 0)),
+            remaining_orders: vec![],
+            cancelled_orders: vec![],
+            unfilled_reasons: vec![],
+            wash_trade_attempts: vec![],
+        }
+    }
+
+    #[test]
+    fn net_deltas_sum_to_zero_per_asset() {
+        let (alice, bob, carol) = (UserId::new(), UserId::new(), UserId::new());
+        let bundle = make_bundle(vec![
+            make_trade(0, alice, bob, Decimal::ONE),
+            make_trade(1, carol, bob, Decimal::TWO),
+            make_trade(2, bob, alice, Decimal::new(5, 1)),
+        ]);
+        let plan = SettlementPlan::from_bundle(&bundle);
+        assert_eq!(
+            plan.trade_ids,
+            bundle.trades.iter().map(|t| t.id).collect::<Vec<_>>()
+        );
+
+        let net = plan.net_deltas();
+        for asset in ["BTC", "USDT"] {
+            let sum: Decimal = net
+                .iter()
+                .filter(|((_, a), _)| a == asset)
+                .map(|(_, d)| *d)
+                .sum();
+            assert_eq!(sum, Decimal::ZERO, "{asset}");
+        }
+        // Bob sold 3 BTC and bought back 0.5
+        assert_eq!(net[&(bob, Asset::lit("BTC"))], Decimal::new(-25, 1));
+        assert!(plan.fee_pool_deltas.is_empty());
+    }
+
+    #[test]
+    fn fee_deltas_match_tier1_settler() {
+        use crate::Tier1Settler;
+
+        // Taker pays 0.2%, maker earns a 0.1% rebate.
+        let fees = FeeSchedule::new(Decimal::new(-1, 3), Decimal::new(2, 3));
+        let (alice, bob) = (UserId::new(), UserId::new());
+        let mut seller_taker = make_trade(1, bob, alice, Decimal::new(5, 1));
+        seller_taker.taker_side = OrderSide::Sell;
+        std::mem::swap(
+            &mut seller_taker.taker_user_id,
+            &mut seller_taker.maker_user_id,
+        );
+        let bundle = make_bundle(vec![
+            make_trade(0, alice, bob, Decimal::ONE),
+            seller_taker,
+            make_trade(2, alice, bob, Decimal::TWO),
+        ]);
+        let plan = SettlementPlan::from_bundle_with_fees(&bundle, &fees);
+
+        let mut settler = Tier1Settler::new(100).with_fee_schedule(fees).unwrap();
+        let mut bm = BalanceManager::new();
+        for user in [alice, bob] {
+            for (asset, amount) in [("USDT", Decimal::new(1_000, 0)), ("BTC", Decimal::TEN)] {
+                settler.deposit(user, Asset::lit(asset), amount);
+                settler.freeze(user, asset, amount).unwrap();
+                bm.deposit(user, Asset::lit(asset), amount);
+                bm.freeze(user, asset, amount).unwrap();
+            }
+        }
+        let before: BTreeMap<(UserId, Asset), Decimal> = [alice, bob]
+            .into_iter()
+            .flat_map(|user| ["USDT", "BTC"].map(|asset| (user, Asset::lit(asset))))
+            .map(|(user, asset)| {
+                let total = settler.balance(user, &asset).total();
+                ((user, asset), total)
+            })
+            .collect();
+        settler.settle_all(&bundle.trades).unwrap();
+
+        let net = plan.net_deltas();
+        for ((user, asset), total) in &before {
+            let settled = settler.balance(*user, asset).total() - total;
+            let planned = net
+                .get(&(*user, asset.clone()))
+                .copied()
+                .unwrap_or_default();
+            assert_eq!(planned, settled, "{asset}");
+        }
+        assert_eq!(
+            plan.fee_pool_deltas.get("USDT").copied(),
+            Some(settler.fee_pool("USDT"))
+        );
+        assert_ne!(settler.fee_pool("USDT"), Decimal::ZERO);
+
+        // Applied to a ledger, the plan lands on the settler's balances.
+        plan.apply(&mut bm).unwrap();
+        for (user, asset) in before.keys() {
+            assert_eq!(bm.balance(*user, asset), settler.balance(*user, asset));
+        }
+    }
+
+    #[test]
+    fn validate_and_apply_against_frozen_balances() {
+        let (buyer, seller) = (UserId::new(), UserId::new());
+        let plan = SettlementPlan::from_bundle(&make_bundle(vec![
+            make_trade(0, buyer, seller, Decimal::ONE),
+            make_trade(1, buyer, seller, Decimal::ONE),
+        ]));
+
+        let mut bm = BalanceManager::new();
+        bm.deposit(buyer, Asset::lit("USDT"), Decimal::new(200, 0));
+        bm.freeze(buyer, "USDT", Decimal::new(200, 0)).unwrap();
+        bm.deposit(seller, Asset::lit("BTC"), Decimal::TWO);
+        bm.freeze(seller, "BTC", Decimal::ONE).unwrap();
+
+        // Seller froze only one of the two BTC the plan consumes.
+        assert!(matches!(
+            plan.validate(&bm),
+            Err(OpenmatchError::InsufficientFrozen)
+        ));
+        assert!(plan.apply(&mut bm).is_err());
+        assert_eq!(bm.balance(buyer, "USDT").frozen, Decimal::new(200, 0));
+
+        bm.freeze(seller, "BTC", Decimal::ONE).unwrap();
+        plan.validate(&bm).unwrap();
+        plan.apply(&mut bm).unwrap();
+        assert_eq!(bm.balance(buyer, "BTC").available, Decimal::TWO);
+        assert_eq!(bm.balance(seller, "USDT").available, Decimal::new(200, 0));
+        assert_eq!(bm.balance(seller, "BTC"), BalanceEntry::default());
+        assert_eq!(bm.total_supply("BTC").unwrap(), Decimal::TWO);
+    }
+
+    #[test]
+    fn quote_dust_nets_into_dust_account() {
+        let (buyer, seller, dust) = (UserId::new(), UserId::new(), UserId::new());
+        let mut down = make_trade(0, buyer, seller, Decimal::ONE);
+        down.quote_amount = Decimal::new(99, 0);
+        down.quote_dust = Some(QuoteDust {
+            account: dust,
+            amount: Decimal::ONE,
+        });
+        let mut up = make_trade(1, buyer, seller, Decimal::ONE);
+        up.quote_amount = Decimal::new(103, 0);
+        up.quote_dust = Some(QuoteDust {
+            account: dust,
+            amount: Decimal::new(-3, 0),
+        });
+        let plan = SettlementPlan::from_bundle(&make_bundle(vec![down, up]));
+        assert!(plan.mutations.contains(&BalanceMutation::DebitAvailable {
+            user_id: dust,
+            asset: Asset::lit("USDT"),
+            amount: Decimal::TWO,
+        }));
+
+        let mut bm = BalanceManager::new();
+        bm.deposit(buyer, Asset::lit("USDT"), Decimal::new(200, 0));
+        bm.freeze(buyer, "USDT", Decimal::new(200, 0)).unwrap();
+        bm.deposit(seller, Asset::lit("BTC"), Decimal::TWO);
+        bm.freeze(seller, "BTC", Decimal::TWO).unwrap();
+        assert!(matches!(
+            plan.apply(&mut bm),
+            Err(OpenmatchError::InsufficientBalance { .. })
+        ));
+
+        bm.deposit(dust, Asset::lit("USDT"), Decimal::TWO);
+        plan.apply(&mut bm).unwrap();
+        assert_eq!(bm.balance(seller, "USDT").available, Decimal::new(202, 0));
+        assert_eq!(bm.balance(buyer, "USDT"), BalanceEntry::default());
+        assert_eq!(bm.balance(dust, "USDT"), BalanceEntry::default());
+        assert_eq!(bm.total_supply("USDT").unwrap(), Decimal::new(202, 0));
+    }
+}
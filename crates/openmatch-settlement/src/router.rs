@@ -0,0 +1,148 @@
+//! Settlement tier routing.
+//!
+//! Each trade settles on the cheapest tier that can reach both
+//! counterparties' escrow:
+//!
+//! - **Tier 1** when buyer and seller are escrowed on this node
+//! - **Tier 2** when either is escrowed elsewhere (or unknown)
+//! - **Tier 3** when the market involves an asset that requires on-chain
+//!   finality, regardless of where the escrow lives
+
+use std::collections::{HashMap, HashSet};
+
+use openmatch_types::{Asset, NodeId, Trade, UserId};
+
+/// Settlement tier chosen for a trade.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SettlementTier {
+    /// Local atomic settlement on this node.
+    Tier1,
+    /// Cross-node gossip settlement.
+    Tier2,
+    /// On-chain finality.
+    Tier3,
+}
+
+/// Routes trades to a settlement tier by counterparty escrow location.
+#[derive(Debug, Clone)]
+pub struct SettlementRouter {
+    /// The node doing the routing.
+    local_node: NodeId,
+    /// Node holding each user's escrow.
+    escrow_nodes: HashMap<UserId, NodeId>,
+    /// Assets whose trades must settle on-chain.
+    onchain_assets: HashSet<Asset>,
+}
+
+impl SettlementRouter {
+    /// Create a router for `local_node` from the escrow location map.
+    #[must_use]
+    pub fn new(local_node: NodeId, escrow_nodes: HashMap<UserId, NodeId>) -> Self {
+        Self {
+            local_node,
+            escrow_nodes,
+            onchain_assets: HashSet::new(),
+        }
+    }
+
+    /// Require on-chain finality (Tier 3) for every trade touching `asset`.
+    #[must_use]
+    pub fn with_onchain_asset(mut self, asset: impl Into<Asset>) -> Self {
+        self.onchain_assets.insert(asset.into());
+        self
+    }
+
+    /// Record (or move) a user's escrow location.
+    pub fn set_escrow_node(&mut self, user_id: UserId, node_id: NodeId) {
+        self.escrow_nodes.insert(user_id, node_id);
+    }
+
+    /// Pick the settlement tier for `trade`.
+    ///
+    /// A counterparty with no known escrow location is treated as remote.
+    #[must_use]
+    pub fn route(&self, trade: &Trade) -> SettlementTier {
+        if self.onchain_assets.contains(&trade.market.base)
+            || self.onchain_assets.contains(&trade.market.quote)
+        {
+            return SettlementTier::Tier3;
+        }
+        if self.is_local(&trade.taker_user_id) && self.is_local(&trade.maker_user_id) {
+            SettlementTier::Tier1
+        } else {
+            SettlementTier::Tier2
+        }
+    }
+
+    fn is_local(&self, user_id: &UserId) -> bool {
+        self.escrow_nodes.get(user_id) == Some(&self.local_node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use openmatch_types::*;
+    use rust_decimal::Decimal;
+
+    use super::*;
+
+    const LOCAL: NodeId = NodeId([1u8; 32]);
+    const REMOTE: NodeId = NodeId([2u8; 32]);
+
+    fn make_trade(taker: UserId, maker: UserId) -> Trade {
+        Trade {
+            id: TradeId::deterministic(1, 0),
+            epoch_id: EpochId(1),
+            market: MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")),
+            taker_order_id: OrderId::new(),
+            taker_user_id: taker,
+            maker_order_id: OrderId::new(),
+            maker_user_id: maker,
+            price: Decimal::new(100, 0),
+            quantity: Decimal::ONE,
+            quote_amount: Decimal::new(100, 0),
+            taker_side: OrderSide::Buy,
+            matcher_node: LOCAL,
+            executed_at: Utc::now(),
+            quote_dust: None,
+        }
+    }
+
+    fn make_router(users: &[(UserId, NodeId)]) -> SettlementRouter {
+        SettlementRouter::new(LOCAL, users.iter().copied().collect())
+    }
+
+    #[test]
+    fn both_local_routes_to_tier1() {
+        let (alice, bob) = (UserId::new(), UserId::new());
+        let router = make_router(&[(alice, LOCAL), (bob, LOCAL)]);
+        assert_eq!(router.route(&make_trade(alice, bob)), SettlementTier::Tier1);
+    }
+
+    #[test]
+    fn cross_node_or_unknown_routes_to_tier2() {
+        let (alice, bob) = (UserId::new(), UserId::new());
+        let mut router = make_router(&[(alice, LOCAL), (bob, REMOTE)]);
+        assert_eq!(router.route(&make_trade(alice, bob)), SettlementTier::Tier2);
+        assert_eq!(
+            router.route(&make_trade(alice, UserId::new())),
+            SettlementTier::Tier2
+        );
+
+        router.set_escrow_node(bob, LOCAL);
+        assert_eq!(router.route(&make_trade(alice, bob)), SettlementTier::Tier1);
+    }
+
+    #[test]
+    fn onchain_asset_routes_to_tier3() {
+        let (alice, bob) = (UserId::new(), UserId::new());
+        let router =
+            make_router(&[(alice, LOCAL), (bob, LOCAL)]).with_onchain_asset(Asset::lit("BTC"));
+        assert_eq!(router.route(&make_trade(alice, bob)), SettlementTier::Tier3);
+
+        let mut eth = make_trade(alice, bob);
+        eth.market = MarketPair::new(Asset::lit("ETH"), Asset::lit("USDT"));
+        assert_eq!(router.route(&eth), SettlementTier::Tier1);
+    }
+}
@@ -2,18 +2,42 @@
 //!
 //! Mathematical invariant enforced after every settlement:
 //! ```text
-//! ∀ asset: Σ(available + frozen) == Σ(deposits) - Σ(withdrawals)
+//! ∀ asset: Σ(available + frozen + withdraw_pending) == Σ(deposits) - Σ(withdrawals)
 //! ```
 //!
+//! A two-step withdrawal counts as a withdrawal only once it is confirmed;
+//! while pending, the reserved funds remain part of supply.
+//!
 //! If this invariant ever breaks, the system halts with a critical alert.
 //! This is the ultimate safety net — if supply is not conserved, something
 //! has gone catastrophically wrong.
+//!
+//! With per-user tracking enabled, each user's net flows (deposits,
+//! withdrawals, settlement credits and debits) are also recorded, so a
+//! broken invariant can be traced to the accounts whose balance diverges
+//! ([`SupplyConservation::locate_leaks`]).
+//!
+//! The global sum can still balance while one account is negative and
+//! another holds the excess, so [`SupplyConservation::verify_no_negative`]
+//! checks each balance on its own.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
-use openmatch_types::{Asset, OpenmatchError, Result};
+use openmatch_types::{Asset, BalanceEntry, EpochPhase, OpenmatchError, Result, UserId};
 use rust_decimal::Decimal;
 
+/// When a settler runs its supply conservation check on its own.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SupplyCheckCadence {
+    /// Only when called explicitly.
+    #[default]
+    Manual,
+    /// After every N balance-changing operations. `EveryNOps(0)` never fires.
+    EveryNOps(u64),
+    /// Whenever the settler enters this epoch phase.
+    OnPhase(EpochPhase),
+}
+
 /// Tracks per-asset supply totals and validates conservation after every
 /// settlement cycle.
 pub struct SupplyConservation {
@@ -21,6 +45,9 @@ pub struct SupplyConservation {
     deposits: HashMap<Asset, Decimal>,
     /// Total withdrawals per asset since genesis.
     withdrawals: HashMap<Asset, Decimal>,
+    /// Net recorded flow per (user, asset); `None` unless per-user
+    /// tracking is enabled.
+    user_flows: Option<HashMap<(UserId, Asset), Decimal>>,
 }
 
 impl SupplyConservation {
@@ -30,22 +57,70 @@ impl SupplyConservation {
         Self {
             deposits: HashMap::new(),
             withdrawals: HashMap::new(),
+            user_flows: None,
         }
     }
 
+    /// Enable per-user subaccounting for leak localization.
+    #[must_use]
+    pub fn with_user_tracking(mut self) -> Self {
+        self.user_flows = Some(HashMap::new());
+        self
+    }
+
+    /// Whether per-user tracking is enabled.
+    #[must_use]
+    pub fn tracks_users(&self) -> bool {
+        self.user_flows.is_some()
+    }
+
+    /// Record a signed balance change for one user (positive = credit).
+    /// No-op unless per-user tracking is enabled.
+    pub fn record_user_flow(&mut self, user_id: UserId, asset: impl Into<Asset>, delta: Decimal) {
+        if let Some(flows) = &mut self.user_flows {
+            *flows
+                .entry((user_id, asset.into()))
+                .or_insert(Decimal::ZERO) += delta;
+        }
+    }
+
+    /// Compare each user's balance to their recorded net flow.
+    ///
+    /// Returns `(user, asset, actual − expected)` for every account that
+    /// diverges, sorted by user then asset. Empty when per-user tracking is
+    /// disabled.
+    #[must_use]
+    pub fn locate_leaks(
+        &self,
+        balances: &HashMap<(UserId, Asset), BalanceEntry>,
+    ) -> Vec<(UserId, Asset, Decimal)> {
+        let Some(flows) = &self.user_flows else {
+            return Vec::new();
+        };
+        let mut diffs: BTreeMap<(UserId, Asset), Decimal> = BTreeMap::new();
+        for (key, entry) in balances {
+            *diffs.entry(key.clone()).or_insert(Decimal::ZERO) += entry.saturating_total();
+        }
+        for (key, expected) in flows {
+            *diffs.entry(key.clone()).or_insert(Decimal::ZERO) -= *expected;
+        }
+        diffs
+            .into_iter()
+            .filter(|(_, diff)| !diff.is_zero())
+            .map(|((user_id, asset), diff)| (user_id, asset, diff))
+            .collect()
+    }
+
     /// Record a deposit.
-    pub fn record_deposit(&mut self, asset: &str, amount: Decimal) {
-        *self
-            .deposits
-            .entry(asset.to_string())
-            .or_insert(Decimal::ZERO) += amount;
+    pub fn record_deposit(&mut self, asset: impl Into<Asset>, amount: Decimal) {
+        *self.deposits.entry(asset.into()).or_insert(Decimal::ZERO) += amount;
     }
 
     /// Record a withdrawal.
-    pub fn record_withdrawal(&mut self, asset: &str, amount: Decimal) {
+    pub fn record_withdrawal(&mut self, asset: impl Into<Asset>, amount: Decimal) {
         *self
             .withdrawals
-            .entry(asset.to_string())
+            .entry(asset.into())
             .or_insert(Decimal::ZERO) += amount;
     }
 
@@ -84,10 +159,38 @@ impl SupplyConservation {
         Ok(())
     }
 
+    /// Verify that no individual balance has a negative component
+    /// (available, frozen or withdraw pending).
+    ///
+    /// # Errors
+    /// Returns [`OpenmatchError::BalanceUnderflow`] naming the offending
+    /// account; with several, the lowest by user then asset.
+    pub fn verify_no_negative(
+        &self,
+        per_user: &HashMap<(UserId, Asset), BalanceEntry>,
+    ) -> Result<()> {
+        let offender = per_user
+            .iter()
+            .filter(|(_, entry)| {
+                entry.available.is_sign_negative()
+                    || entry.frozen.is_sign_negative()
+                    || entry.withdraw_pending.is_sign_negative()
+            })
+            .map(|(key, _)| key)
+            .min();
+        match offender {
+            Some((user_id, asset)) => Err(OpenmatchError::BalanceUnderflow {
+                user_id: *user_id,
+                asset: asset.clone(),
+            }),
+            None => Ok(()),
+        }
+    }
+
     /// Get all tracked assets.
     #[must_use]
-    pub fn tracked_assets(&self) -> Vec<String> {
-        let mut assets: std::collections::HashSet<String> = self.deposits.keys().cloned().collect();
+    pub fn tracked_assets(&self) -> Vec<Asset> {
+        let mut assets: std::collections::HashSet<Asset> = self.deposits.keys().cloned().collect();
         assets.extend(self.withdrawals.keys().cloned());
         assets.into_iter().collect()
     }
@@ -128,31 +231,31 @@ mod tests {
     #[test]
     fn deposits_increase_expected() {
         let mut sc = SupplyConservation::new();
-        sc.record_deposit("USDT", Decimal::new(1000, 0));
-        sc.record_deposit("USDT", Decimal::new(500, 0));
+        sc.record_deposit(Asset::lit("USDT"), Decimal::new(1000, 0));
+        sc.record_deposit(Asset::lit("USDT"), Decimal::new(500, 0));
         assert_eq!(sc.expected_supply("USDT"), Decimal::new(1500, 0));
     }
 
     #[test]
     fn withdrawals_decrease_expected() {
         let mut sc = SupplyConservation::new();
-        sc.record_deposit("USDT", Decimal::new(1000, 0));
-        sc.record_withdrawal("USDT", Decimal::new(300, 0));
+        sc.record_deposit(Asset::lit("USDT"), Decimal::new(1000, 0));
+        sc.record_withdrawal(Asset::lit("USDT"), Decimal::new(300, 0));
         assert_eq!(sc.expected_supply("USDT"), Decimal::new(700, 0));
     }
 
     #[test]
     fn verify_passes_when_balanced() {
         let mut sc = SupplyConservation::new();
-        sc.record_deposit("BTC", Decimal::new(10, 0));
-        sc.record_withdrawal("BTC", Decimal::new(3, 0));
+        sc.record_deposit(Asset::lit("BTC"), Decimal::new(10, 0));
+        sc.record_withdrawal(Asset::lit("BTC"), Decimal::new(3, 0));
         assert!(sc.verify("BTC", Decimal::new(7, 0)).is_ok());
     }
 
     #[test]
     fn verify_fails_when_imbalanced() {
         let mut sc = SupplyConservation::new();
-        sc.record_deposit("BTC", Decimal::new(10, 0));
+        sc.record_deposit(Asset::lit("BTC"), Decimal::new(10, 0));
         let err = sc.verify("BTC", Decimal::new(11, 0)).unwrap_err();
         assert!(matches!(
             err,
@@ -163,8 +266,8 @@ mod tests {
     #[test]
     fn multiple_assets_independent() {
         let mut sc = SupplyConservation::new();
-        sc.record_deposit("BTC", Decimal::new(5, 0));
-        sc.record_deposit("USDT", Decimal::new(50000, 0));
+        sc.record_deposit(Asset::lit("BTC"), Decimal::new(5, 0));
+        sc.record_deposit(Asset::lit("USDT"), Decimal::new(50000, 0));
         assert_eq!(sc.expected_supply("BTC"), Decimal::new(5, 0));
         assert_eq!(sc.expected_supply("USDT"), Decimal::new(50000, 0));
         assert!(sc.verify("BTC", Decimal::new(5, 0)).is_ok());
@@ -175,12 +278,79 @@ mod tests {
     fn settlement_does_not_change_supply() {
         // After settlement: funds move between users but total supply is unchanged.
         let mut sc = SupplyConservation::new();
-        sc.record_deposit("USDT", Decimal::new(1000, 0));
-        sc.record_deposit("BTC", Decimal::new(1, 0));
+        sc.record_deposit(Asset::lit("USDT"), Decimal::new(1000, 0));
+        sc.record_deposit(Asset::lit("BTC"), Decimal::new(1, 0));
 
         // Settlement: buyer gets BTC, seller gets USDT — no deposits/withdrawals.
         // Total supply must remain the same.
         assert!(sc.verify("USDT", Decimal::new(1000, 0)).is_ok());
         assert!(sc.verify("BTC", Decimal::new(1, 0)).is_ok());
     }
+
+    #[test]
+    fn locate_leaks_flags_corrupted_user() {
+        let mut sc = SupplyConservation::new().with_user_tracking();
+        let (alice, bob) = (UserId::new(), UserId::new());
+        let mut balances = HashMap::new();
+        for user in [alice, bob] {
+            sc.record_user_flow(user, Asset::lit("USDT"), Decimal::new(100, 0));
+            balances.insert(
+                (user, Asset::lit("USDT")),
+                BalanceEntry {
+                    available: Decimal::new(60, 0),
+                    frozen: Decimal::new(40, 0),
+                    withdraw_pending: Decimal::ZERO,
+                },
+            );
+        }
+        assert!(sc.locate_leaks(&balances).is_empty());
+
+        balances
+            .get_mut(&(bob, Asset::lit("USDT")))
+            .unwrap()
+            .available += Decimal::new(5, 0);
+        assert_eq!(
+            sc.locate_leaks(&balances),
+            vec![(bob, Asset::lit("USDT"), Decimal::new(5, 0))]
+        );
+    }
+
+    #[test]
+    fn negative_balance_caught_despite_conserved_supply() {
+        let mut sc = SupplyConservation::new();
+        sc.record_deposit(Asset::lit("USDT"), Decimal::new(100, 0));
+        let (alice, bob) = (UserId::new(), UserId::new());
+        let entry = |available| BalanceEntry {
+            available,
+            frozen: Decimal::ZERO,
+            withdraw_pending: Decimal::ZERO,
+        };
+        let mut balances = HashMap::from([
+            ((alice, Asset::lit("USDT")), entry(Decimal::new(100, 0))),
+            ((bob, Asset::lit("USDT")), entry(Decimal::ZERO)),
+        ]);
+        sc.verify_no_negative(&balances).unwrap();
+
+        // Alice overdraws by 30 and Bob holds the 30: the sum still nets out
+        balances.insert((alice, Asset::lit("USDT")), entry(Decimal::new(-30, 0)));
+        balances.insert((bob, Asset::lit("USDT")), entry(Decimal::new(130, 0)));
+        let total: Decimal = balances.values().map(BalanceEntry::saturating_total).sum();
+        sc.verify("USDT", total).unwrap();
+
+        let err = sc.verify_no_negative(&balances).unwrap_err();
+        assert!(matches!(
+            err,
+            OpenmatchError::BalanceUnderflow { user_id, ref asset }
+                if user_id == alice && asset == "USDT"
+        ));
+    }
+
+    #[test]
+    fn locate_leaks_disabled_by_default() {
+        let mut sc = SupplyConservation::new();
+        let user = UserId::new();
+        sc.record_user_flow(user, Asset::lit("BTC"), Decimal::ONE);
+        assert!(!sc.tracks_users());
+        assert!(sc.locate_leaks(&HashMap::new()).is_empty());
+    }
 }
@@ -0,0 +1,100 @@
+//! Trade tape — the public market-data feed of executed trades.
+//!
+//! Every published trade gets the next `tape_seq`, starting at 1 and
+//! never reused. Sequences are contiguous across bundles, so a consumer
+//! that sees a gap knows it missed a bundle and can re-request it.
+
+use openmatch_types::{Trade, TradeBundle};
+
+/// Assigns monotonic tape sequence numbers to published trades.
+#[derive(Debug, Default)]
+pub struct TradeTape {
+    /// Sequence of the last published trade (0 before the first).
+    last_seq: u64,
+}
+
+impl TradeTape {
+    /// Create a tape with nothing published.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Publish a bundle's trades in bundle order, pairing each with its
+    /// tape sequence.
+    pub fn publish<'a>(&mut self, bundle: &'a TradeBundle) -> Vec<(u64, &'a Trade)> {
+        bundle
+            .trades
+            .iter()
+            .map(|trade| {
+                self.last_seq += 1;
+                (self.last_seq, trade)
+            })
+            .collect()
+    }
+
+    /// Sequence of the last published trade, or 0 if none has been.
+    #[must_use]
+    pub fn last_seq(&self) -> u64 {
+        self.last_seq
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use openmatch_types::*;
+    use rust_decimal::Decimal;
+
+    use super::*;
+
+    fn make_bundle(epoch: u64, trades: u64) -> TradeBundle {
+        let trades = (0..trades)
+            .map(|seq| Trade {
+                id: TradeId::deterministic(epoch, seq),
+                epoch_id: EpochId(epoch),
+                market: MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")),
+                taker_order_id: OrderId::new(),
+                taker_user_id: UserId::new(),
+                maker_order_id: OrderId::new(),
+                maker_user_id: UserId::new(),
+                price: Decimal::new(100, 0),
+                quantity: Decimal::ONE,
+                quote_amount: Decimal::new(100, 0),
+                taker_side: OrderSide::Buy,
+                matcher_node: NodeId([0u8; 32]),
+                executed_at: Utc::now(),
+                quote_dust: None,
+            })
+            .collect();
+        TradeBundle {
+            epoch_id: EpochId(epoch),
+            trades,
+            trade_root: [0u8; 32],
+            input_hash: [0u8; 32],
+            clearing_price: Some(Decimal::new(100, 0)),
+            remaining_orders: vec![],
+            cancelled_orders: vec![],
+            unfilled_reasons: vec![],
+            wash_trade_attempts: vec![],
+        }
+    }
+
+    #[test]
+    fn sequences_are_contiguous_across_bundles() {
+        let mut tape = TradeTape::new();
+        assert_eq!(tape.last_seq(), 0);
+
+        let (first, second) = (make_bundle(1, 2), make_bundle(2, 3));
+        let published: Vec<(u64, TradeId)> = [&first, &make_bundle(3, 0), &second]
+            .into_iter()
+            .flat_map(|bundle| tape.publish(bundle))
+            .map(|(seq, trade)| (seq, trade.id))
+            .collect();
+
+        let seqs: Vec<u64> = published.iter().map(|(seq, _)| *seq).collect();
+        assert_eq!(seqs, vec![1, 2, 3, 4, 5]);
+        assert_eq!(published[2].1, second.trades[0].id);
+        assert_eq!(tape.last_seq(), 5);
+    }
+}
@@ -7,13 +7,46 @@
 //! 4. Transfer frozen balance from buyer → seller (quote asset)
 //! 5. Mark SpendRights as SPENT
 //! 6. Generate settlement receipts
+//!
+//! Steps 2 and 5 run when a bundle is settled through
+//! [`Tier1Settler::settle_bundle_with_escrow`], which pays each fill out
+//! of the order's SR and frozen escrow balance, and spends the SR once
+//! the order is fully filled. The escrow is reached through the
+//! [`SpendRightLedger`] and [`BalanceLedger`] traits, so this crate does
+//! not depend on the Security Envelope.
+//!
+//! [`Tier1Settler::settle_all`] applies an epoch's trades all-or-nothing:
+//! every debit is validated before any balance moves.
+//!
+//! With a [`FeeSchedule`], fees are charged in the quote asset into a
+//! per-asset fee pool, and maker rebates are paid out of it. The pool
+//! counts as an account for supply conservation.
+//!
+//! A trade carrying [`QuoteDust`](openmatch_types::QuoteDust) debits the
+//! buyer the exact `price × quantity` and credits the rounding residual to
+//! the dust account named in it. The dust account is an ordinary balance,
+//! so supply conservation covers it like any other.
+//!
+//! With a [`SupplyCheckCadence`], the settler also verifies supply on its
+//! own — every N operations or on entering a phase — instead of relying
+//! on callers to invoke [`Tier1Settler::verify_all_supply`].
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 
-use openmatch_types::{Asset, BalanceEntry, OpenmatchError, Result, Trade, UserId};
+use chrono::Utc;
+use openmatch_types::{
+    Asset, BalanceEntry, BalanceLedger, EpochId, EpochPhase, FeeSchedule, IntoAsset, NodeId,
+    OpenmatchError, OrderId, Receipt, ReceiptChain, ReceiptType, Result, SpendRightLedger, Trade,
+    TradeBundle, TradeId, UserId,
+};
 use rust_decimal::Decimal;
+use sha2::{Digest, Sha256};
 
-use crate::{idempotency::IdempotencyGuard, supply_conservation::SupplyConservation};
+use crate::{
+    idempotency::IdempotencyGuard,
+    supply_conservation::{SupplyCheckCadence, SupplyConservation},
+    withdraw_lock::WithdrawLock,
+};
 
 /// Local atomic settler for Tier 1 (same-node) settlement.
 ///
@@ -26,6 +59,25 @@ pub struct Tier1Settler {
     idempotency: IdempotencyGuard,
     /// Supply conservation tracker.
     supply: SupplyConservation,
+    /// Phase- and asset-aware withdraw lock.
+    withdraw_lock: WithdrawLock,
+    /// Settled trades per user, in settlement order.
+    user_trades: HashMap<UserId, Vec<TradeId>>,
+    /// Epoch stamped on deposit/withdrawal receipts.
+    epoch_id: EpochId,
+    /// Receipts for deposits and withdrawals that carried a chain reference,
+    /// hash-linked for tamper evidence.
+    audit_receipts: ReceiptChain,
+    /// Maker/taker fee rates.
+    fees: FeeSchedule,
+    /// Collected fees net of rebates, per asset.
+    fee_pool: HashMap<Asset, Decimal>,
+    /// When supply is verified automatically.
+    supply_check_cadence: SupplyCheckCadence,
+    /// Balance-changing operations applied so far.
+    ops_count: u64,
+    /// Automatic supply checks run so far.
+    supply_checks_run: u64,
 }
 
 impl Tier1Settler {
@@ -36,22 +88,153 @@ impl Tier1Settler {
             balances: HashMap::new(),
             idempotency: IdempotencyGuard::new(idempotency_cache_size),
             supply: SupplyConservation::new(),
+            withdraw_lock: WithdrawLock::new(),
+            user_trades: HashMap::new(),
+            epoch_id: EpochId(0),
+            audit_receipts: ReceiptChain::new(),
+            fees: FeeSchedule::default(),
+            fee_pool: HashMap::new(),
+            supply_check_cadence: SupplyCheckCadence::default(),
+            ops_count: 0,
+            supply_checks_run: 0,
+        }
+    }
+
+    /// Charge fees per `fees` on every settled trade.
+    ///
+    /// A buyer with a positive fee pays it from frozen quote on top of the
+    /// trade's quote amount, so escrow must cover both; a seller's fee is
+    /// taken from its quote proceeds. Rebates are credited to available.
+    ///
+    /// # Errors
+    /// Returns `Configuration` if the schedule fails
+    /// [`FeeSchedule::validate`].
+    pub fn with_fee_schedule(mut self, fees: FeeSchedule) -> Result<Self> {
+        fees.validate()?;
+        self.fees = fees;
+        Ok(self)
+    }
+
+    /// Collected fees (net of rebates paid) held for `asset`.
+    #[must_use]
+    pub fn fee_pool(&self, asset: &str) -> Decimal {
+        self.fee_pool.get(asset).copied().unwrap_or(Decimal::ZERO)
+    }
+
+    /// Enable per-user supply subaccounting so a broken invariant can be
+    /// localized with [`Tier1Settler::locate_leaks`].
+    #[must_use]
+    pub fn with_leak_tracking(mut self) -> Self {
+        self.supply = std::mem::take(&mut self.supply).with_user_tracking();
+        self
+    }
+
+    /// Verify supply automatically per `cadence`.
+    ///
+    /// Deposits, withdrawals, freezes and [`Tier1Settler::settle_all`]
+    /// calls each count as one operation. A fallible operation that trips
+    /// the check returns `SupplyInvariantViolation` with its effect already
+    /// applied; a deposit can't fail, so its failed check is logged.
+    #[must_use]
+    pub fn with_supply_check_cadence(mut self, cadence: SupplyCheckCadence) -> Self {
+        self.supply_check_cadence = cadence;
+        self
+    }
+
+    /// Balance-changing operations applied so far.
+    #[must_use]
+    pub fn ops_count(&self) -> u64 {
+        self.ops_count
+    }
+
+    /// Automatic supply checks run so far (passed or failed).
+    #[must_use]
+    pub fn supply_checks_run(&self) -> u64 {
+        self.supply_checks_run
+    }
+
+    /// Count one applied operation and run the supply check if the
+    /// cadence is due.
+    fn record_op(&mut self) -> Result<()> {
+        self.ops_count += 1;
+        match self.supply_check_cadence {
+            SupplyCheckCadence::EveryNOps(n) if n > 0 && self.ops_count % n == 0 => {
+                self.run_supply_check()
+            }
+            _ => Ok(()),
         }
     }
 
+    fn run_supply_check(&mut self) -> Result<()> {
+        self.supply_checks_run += 1;
+        self.verify_all_supply().inspect_err(|err| {
+            tracing::error!(ops = self.ops_count, %err, "supply conservation check failed");
+        })
+    }
+
     /// Deposit funds for a user. Creates the balance entry if it doesn't exist.
-    pub fn deposit(&mut self, user_id: UserId, asset: &str, amount: Decimal) {
-        let entry = self
-            .balances
-            .entry((user_id, asset.to_string()))
-            .or_default();
+    pub fn deposit(&mut self, user_id: UserId, asset: impl Into<Asset>, amount: Decimal) {
+        self.deposit_with_ref(user_id, asset, amount, None);
+    }
+
+    /// Deposit funds credited by an on-chain transaction.
+    ///
+    /// With a `chain_ref`, a [`ReceiptType::Deposit`] receipt is stored for
+    /// audit (see [`Tier1Settler::audit_receipts`]).
+    pub fn deposit_with_ref(
+        &mut self,
+        user_id: UserId,
+        asset: impl Into<Asset>,
+        amount: Decimal,
+        chain_ref: Option<String>,
+    ) {
+        let asset = asset.into();
+        let entry = self.balances.entry((user_id, asset.clone())).or_default();
         entry.available += amount;
-        self.supply.record_deposit(asset, amount);
+        self.supply.record_deposit(&asset, amount);
+        self.supply.record_user_flow(user_id, &asset, amount);
+        if let Some(chain_ref) = chain_ref {
+            self.record_chain_receipt(ReceiptType::Deposit { chain_ref }, user_id, &asset, amount);
+        }
+        // Logged by `run_supply_check`; a deposit has no error to return.
+        let _ = self.record_op();
     }
 
-    /// Freeze funds for an order (available → frozen).
-    pub fn freeze(&mut self, user_id: UserId, asset: &str, amount: Decimal) -> Result<()> {
-        let entry = self.balances.get_mut(&(user_id, asset.to_string())).ok_or(
+    /// Withdraw available funds for a user.
+    ///
+    /// # Errors
+    /// - `WithdrawLockedDuringSettle` during MATCH/FINALIZE
+    /// - `AssetWithdrawLocked` if the asset is frozen
+    /// - `InsufficientBalance` if available < amount
+    /// - `SupplyInvariantViolation` if a scheduled supply check fails
+    ///   (the withdrawal stays applied)
+    pub fn withdraw(
+        &mut self,
+        user_id: UserId,
+        asset: impl IntoAsset,
+        amount: Decimal,
+    ) -> Result<()> {
+        self.withdraw_with_ref(user_id, asset, amount, None)
+    }
+
+    /// Withdraw funds released to an on-chain transaction.
+    ///
+    /// With a `chain_ref`, a successful withdrawal stores a
+    /// [`ReceiptType::Withdrawal`] receipt for audit.
+    ///
+    /// # Errors
+    /// Same as [`Tier1Settler::withdraw`].
+    pub fn withdraw_with_ref(
+        &mut self,
+        user_id: UserId,
+        asset: impl IntoAsset,
+        amount: Decimal,
+        chain_ref: Option<String>,
+    ) -> Result<()> {
+        let asset = asset.into_asset()?;
+        self.withdraw_lock.check_withdraw_asset(&asset)?;
+
+        let entry = self.balances.get_mut(&(user_id, asset.clone())).ok_or(
             OpenmatchError::InsufficientBalance {
                 needed: amount,
                 available: Decimal::ZERO,
@@ -66,10 +249,110 @@ impl Tier1Settler {
         }
 
         entry.available -= amount;
-        entry.frozen += amount;
+        self.supply.record_withdrawal(&asset, amount);
+        self.supply.record_user_flow(user_id, &asset, -amount);
+        if let Some(chain_ref) = chain_ref {
+            self.record_chain_receipt(
+                ReceiptType::Withdrawal { chain_ref },
+                user_id,
+                &asset,
+                amount,
+            );
+        }
+        self.record_op()
+    }
+
+    /// Set the epoch stamped on subsequent deposit/withdrawal receipts.
+    pub fn set_epoch(&mut self, epoch_id: EpochId) {
+        self.epoch_id = epoch_id;
+    }
+
+    /// Deposit and withdrawal receipts, in the order they were issued.
+    #[must_use]
+    pub fn audit_receipts(&self) -> &[Receipt] {
+        self.audit_receipts.receipts()
+    }
+
+    /// The hash-linked log behind [`Tier1Settler::audit_receipts`].
+    #[must_use]
+    pub fn audit_chain(&self) -> &ReceiptChain {
+        &self.audit_receipts
+    }
+
+    /// Unsigned receipt whose payload is `user|asset|amount`.
+    fn record_chain_receipt(
+        &mut self,
+        receipt_type: ReceiptType,
+        user_id: UserId,
+        asset: &str,
+        amount: Decimal,
+    ) {
+        let payload = format!("{}|{asset}|{}", user_id.0, amount.normalize()).into_bytes();
+        let payload_hash = Sha256::digest(&payload).into();
+        self.audit_receipts.append(Receipt {
+            receipt_type,
+            epoch_id: self.epoch_id,
+            trade_id: None,
+            payload,
+            payload_hash,
+            signature: Vec::new(),
+            issuer_node: NodeId([0u8; 32]),
+            issued_at: Utc::now(),
+            prev_hash: [0u8; 32],
+        });
+    }
+
+    /// Advance the epoch phase (controls the withdraw lock).
+    ///
+    /// # Errors
+    /// - `WrongEpochPhase` if `phase` skips ahead or goes backwards
+    /// - `SupplyInvariantViolation` if the cadence checks supply on
+    ///   entering `phase` and it doesn't reconcile (the phase still changes)
+    pub fn set_phase(&mut self, phase: EpochPhase) -> Result<()> {
+        self.withdraw_lock.set_phase(phase)?;
+        if self.supply_check_cadence == SupplyCheckCadence::OnPhase(phase) {
+            self.run_supply_check()?;
+        }
         Ok(())
     }
 
+    /// Freeze or unfreeze withdrawals of a single asset.
+    pub fn set_asset_lock(&mut self, asset: impl Into<Asset>, locked: bool) {
+        self.withdraw_lock.set_asset_lock(asset, locked);
+    }
+
+    /// Freeze funds for an order (available → frozen).
+    ///
+    /// # Errors
+    /// - `InsufficientBalance` if available < amount
+    /// - `SupplyInvariantViolation` if a scheduled supply check fails
+    ///   (the funds stay frozen)
+    pub fn freeze(
+        &mut self,
+        user_id: UserId,
+        asset: impl IntoAsset,
+        amount: Decimal,
+    ) -> Result<()> {
+        let entry = self
+            .balances
+            .get_mut(&(user_id, asset.into_asset()?))
+            .ok_or(OpenmatchError::InsufficientBalance {
+                needed: amount,
+                available: Decimal::ZERO,
+            })?;
+
+        if entry.available < amount {
+            return Err(OpenmatchError::InsufficientBalance {
+                needed: amount,
+                available: entry.available,
+            });
+        }
+
+        entry.available -= amount;
+        entry.frozen += amount;
+        self.record_op()
+    }
+
     /// Settle a single trade atomically.
     ///
     /// Transfers frozen balance from seller → buyer (base asset) and
@@ -79,89 +362,368 @@ impl Tier1Settler {
     /// - `TradeAlreadySettled` if idempotency check fails
     /// - `InsufficientFrozen` if frozen balance is insufficient
     pub fn settle_trade(&mut self, trade: &Trade) -> Result<()> {
-        // 1. Idempotency check
-        self.idempotency.mark_settled(trade.id)?;
-
-        let (buyer_id, seller_id) = if trade.taker_is_buyer() {
-            (trade.taker_user_id, trade.maker_user_id)
-        } else {
-            (trade.maker_user_id, trade.taker_user_id)
-        };
-
-        let base_asset = &trade.market.base;
-        let quote_asset = &trade.market.quote;
+        self.settle_all(std::slice::from_ref(trade)).map(|_| ())
+    }
 
-        // 2. Transfer base asset: seller's frozen → buyer's available
-        {
-            let seller_base = self
-                .balances
-                .get_mut(&(seller_id, base_asset.clone()))
-                .ok_or(OpenmatchError::InsufficientFrozen)?;
-            if seller_base.frozen < trade.quantity {
+    /// Settle a set of trades as one unit (two-phase).
+    ///
+    /// **Prepare** checks every trade for idempotency and sums each
+    /// (user, asset) frozen debit across the whole set, so trades drawing
+    /// on the same frozen balance are validated together. **Commit** runs
+    /// only if every check passes; on any failure no balance moves and no
+    /// trade is marked settled.
+    ///
+    /// Returns one unsigned `SettlementCompleted` receipt per trade, in
+    /// input order; the payload is the trade's [`Trade::fingerprint`].
+    ///
+    /// Fees are applied in input order; within a trade both fees are
+    /// collected before a rebate is paid, and the pool may never go
+    /// negative.
+    ///
+    /// # Errors
+    /// - `TradeAlreadySettled` if a trade was settled before or appears
+    ///   twice in `trades`
+    /// - `InsufficientFrozen` if any frozen balance can't cover its debits
+    /// - `SettlementFailed` if the fee pool can't fund a maker rebate, or
+    ///   a dust account's available balance can't fund a rounded-up fill
+    /// - `SupplyInvariantViolation` if a scheduled supply check fails
+    ///   (the trades stay settled)
+    pub fn settle_all(&mut self, trades: &[Trade]) -> Result<Vec<Receipt>> {
+        // Phase 1: prepare — validate everything, mutate nothing.
+        let mut seen = HashSet::with_capacity(trades.len());
+        let mut debits: HashMap<(UserId, Asset), Decimal> = HashMap::new();
+        let mut pool = self.fee_pool.clone();
+        let mut dust_available: HashMap<(UserId, Asset), Decimal> = HashMap::new();
+        for trade in trades {
+            if self.idempotency.is_settled(&trade.id) || !seen.insert(trade.id) {
+                return Err(OpenmatchError::TradeAlreadySettled(trade.id));
+            }
+            let (buyer_id, seller_id) = Self::buyer_seller(trade);
+            let (buyer_fee, seller_fee) = self.fees.buyer_seller_fees(trade);
+            let balance = pool.entry(trade.market.quote.clone()).or_default();
+            *balance += buyer_fee + seller_fee;
+            if *balance < Decimal::ZERO {
+                return Err(OpenmatchError::SettlementFailed {
+                    reason: format!(
+                        "{} fee pool cannot fund rebate for trade {}",
+                        trade.market.quote, trade.id
+                    ),
+                });
+            }
+            if let Some(dust) = &trade.quote_dust {
+                let key = (dust.account, trade.market.quote.clone());
+                let available = dust_available
+                    .entry(key)
+                    .or_insert_with_key(|(account, asset)| self.balance(*account, asset).available);
+                *available += dust.amount;
+                if *available < Decimal::ZERO {
+                    return Err(OpenmatchError::SettlementFailed {
+                        reason: format!(
+                            "{} dust account cannot fund rounding for trade {}",
+                            trade.market.quote, trade.id
+                        ),
+                    });
+                }
+            }
+            for (key, amount) in [
+                ((seller_id, trade.market.base.clone()), trade.quantity),
+                (
+                    (buyer_id, trade.market.quote.clone()),
+                    trade.buyer_quote_amount() + buyer_fee.max(Decimal::ZERO),
+                ),
+            ] {
+                let total = debits.entry(key).or_insert(Decimal::ZERO);
+                *total = total
+                    .checked_add(amount)
+                    .ok_or(OpenmatchError::InsufficientFrozen)?;
+            }
+        }
+        for (key, needed) in &debits {
+            let frozen = self.balances.get(key).map_or(Decimal::ZERO, |b| b.frozen);
+            if frozen < *needed {
                 return Err(OpenmatchError::InsufficientFrozen);
             }
-            seller_base.frozen -= trade.quantity;
         }
-        {
-            let buyer_base = self
-                .balances
-                .entry((buyer_id, base_asset.clone()))
-                .or_default();
-            buyer_base.available += trade.quantity;
+
+        // Phase 2: commit — every debit was covered above, nothing can fail.
+        let mut receipts = Vec::with_capacity(trades.len());
+        for trade in trades {
+            self.idempotency.mark_settled(trade.id)?;
+            let (buyer_id, seller_id) = Self::buyer_seller(trade);
+            let base_asset = &trade.market.base;
+            let quote_asset = &trade.market.quote;
+
+            // Base asset: seller's frozen → buyer's available
+            self.entry(seller_id, base_asset).frozen -= trade.quantity;
+            self.entry(buyer_id, base_asset).available += trade.quantity;
+
+            // Quote asset: buyer's frozen → seller's available, fees → pool
+            let (buyer_fee, seller_fee) = self.fees.buyer_seller_fees(trade);
+            let buyer_quote = trade.buyer_quote_amount();
+            let buyer = self.entry(buyer_id, quote_asset);
+            buyer.frozen -= buyer_quote + buyer_fee.max(Decimal::ZERO);
+            buyer.available -= buyer_fee.min(Decimal::ZERO);
+            self.entry(seller_id, quote_asset).available += trade.quote_amount - seller_fee;
+            *self.fee_pool.entry(quote_asset.clone()).or_default() += buyer_fee + seller_fee;
+            if let Some(dust) = &trade.quote_dust {
+                // Rounding residual → dust account (negative: it pays)
+                self.entry(dust.account, quote_asset).available += dust.amount;
+                self.supply
+                    .record_user_flow(dust.account, quote_asset, dust.amount);
+            }
+
+            self.supply
+                .record_user_flow(seller_id, base_asset, -trade.quantity);
+            self.supply
+                .record_user_flow(buyer_id, base_asset, trade.quantity);
+            self.supply
+                .record_user_flow(buyer_id, quote_asset, -(buyer_quote + buyer_fee));
+            self.supply
+                .record_user_flow(seller_id, quote_asset, trade.quote_amount - seller_fee);
+
+            self.user_trades.entry(buyer_id).or_default().push(trade.id);
+            if seller_id != buyer_id {
+                self.user_trades
+                    .entry(seller_id)
+                    .or_default()
+                    .push(trade.id);
+            }
+
+            receipts.push(Self::settlement_receipt(trade));
         }
 
-        // 3. Transfer quote asset: buyer's frozen → seller's available
-        {
-            let buyer_quote = self
-                .balances
-                .get_mut(&(buyer_id, quote_asset.clone()))
-                .ok_or(OpenmatchError::InsufficientFrozen)?;
-            if buyer_quote.frozen < trade.quote_amount {
+        self.record_op()?;
+        Ok(receipts)
+    }
+
+    /// [`Tier1Settler::settle_bundle`], paying each fill out of the
+    /// escrow that funds its order.
+    ///
+    /// `escrow` holds the orders' `SpendRight`s and `escrow_balances` the
+    /// funds they froze. Each fill draws its escrowed side (the seller's
+    /// base, the buyer's quote plus any fee) from the SR and from its
+    /// frozen balance. Once an order is fully filled (it traded and isn't
+    /// among the bundle's remaining orders), its SR is marked SPENT and
+    /// whatever the fills didn't use is unfrozen; a partially filled
+    /// order's SR stays ACTIVE with the rest still escrowed.
+    ///
+    /// Two-phase like [`Tier1Settler::settle_all`]: every SR and frozen
+    /// balance is checked before anything moves, so a rejected bundle
+    /// leaves the settler and the escrow untouched.
+    ///
+    /// # Errors
+    /// - `InvalidSpendRight` if a filled order has no ACTIVE SR in
+    ///   `escrow`, or its fills draw more than the SR has remaining
+    /// - `InsufficientFrozen` if `escrow_balances` doesn't hold the
+    ///   frozen funds the SRs claim
+    /// - any error from [`Tier1Settler::settle_bundle`]
+    pub fn settle_bundle_with_escrow(
+        &mut self,
+        bundle: &TradeBundle,
+        escrow: &mut impl SpendRightLedger,
+        escrow_balances: &mut impl BalanceLedger,
+    ) -> Result<Vec<Receipt>> {
+        // Phase 1: prepare — sum each order's draw on its SR.
+        let mut draws: BTreeMap<OrderId, Decimal> = BTreeMap::new();
+        for trade in &bundle.trades {
+            let (buyer_fee, _) = self.fees.buyer_seller_fees(trade);
+            let (buy_order, sell_order) = if trade.taker_is_buyer() {
+                (trade.taker_order_id, trade.maker_order_id)
+            } else {
+                (trade.maker_order_id, trade.taker_order_id)
+            };
+            *draws.entry(sell_order).or_default() += trade.quantity;
+            *draws.entry(buy_order).or_default() +=
+                trade.buyer_quote_amount() + buyer_fee.max(Decimal::ZERO);
+        }
+        let still_open: HashSet<OrderId> = bundle.remaining_orders.iter().map(|o| o.id).collect();
+        let mut moves = Vec::with_capacity(draws.len());
+        let mut frozen_needed: HashMap<(UserId, Asset), Decimal> = HashMap::new();
+        for (order_id, draw) in draws {
+            let sr = escrow.spend_right_for_order(&order_id).ok_or_else(|| {
+                OpenmatchError::InvalidSpendRight {
+                    reason: format!("no SpendRight for order {order_id}"),
+                }
+            })?;
+            if !escrow.is_active(&sr.id) {
+                return Err(OpenmatchError::InvalidSpendRight {
+                    reason: format!("SpendRight {} is not ACTIVE", sr.id),
+                });
+            }
+            if draw > sr.remaining() {
+                return Err(OpenmatchError::InvalidSpendRight {
+                    reason: format!(
+                        "SpendRight {} has {} remaining, fills need {draw}",
+                        sr.id,
+                        sr.remaining()
+                    ),
+                });
+            }
+            let filled = !still_open.contains(&order_id);
+            let leftover = if filled {
+                sr.remaining() - draw
+            } else {
+                Decimal::ZERO
+            };
+            *frozen_needed
+                .entry((sr.user_id, sr.asset.clone()))
+                .or_default() += draw + leftover;
+            moves.push((sr.id, sr.user_id, sr.asset.clone(), draw, leftover, filled));
+        }
+        for ((user_id, asset), needed) in &frozen_needed {
+            if escrow_balances.balance_of(*user_id, asset).frozen < *needed {
                 return Err(OpenmatchError::InsufficientFrozen);
             }
-            buyer_quote.frozen -= trade.quote_amount;
         }
-        {
-            let seller_quote = self
-                .balances
-                .entry((seller_id, quote_asset.clone()))
-                .or_default();
-            seller_quote.available += trade.quote_amount;
+
+        // Phase 2: commit — settle, then move escrow; every escrow step
+        // was covered above.
+        let receipts = self.settle_bundle(bundle)?;
+        for (sr_id, user_id, asset, draw, leftover, filled) in moves {
+            escrow_balances.consume_frozen_funds(user_id, &asset, draw)?;
+            escrow.consume(sr_id, draw)?;
+            if filled {
+                if !leftover.is_zero() {
+                    escrow_balances.unfreeze_funds(user_id, &asset, leftover)?;
+                }
+                escrow.mark_spent(sr_id)?;
+            }
         }
+        Ok(receipts)
+    }
 
-        Ok(())
+    /// Settle a matcher bundle with supply conservation checked around it.
+    ///
+    /// Every asset the bundle touches is verified before settling, so an
+    /// already-inconsistent ledger is refused with nothing applied. The
+    /// trades then go through [`Tier1Settler::settle_all`] and the same
+    /// assets are verified again.
+    ///
+    /// # Errors
+    /// - `SupplyInvariantViolation` if an asset doesn't reconcile before
+    ///   settling (nothing applied) or after (trades stay settled; the
+    ///   settlement logic itself is suspect)
+    /// - any error from [`Tier1Settler::settle_all`]
+    pub fn settle_bundle(&mut self, bundle: &TradeBundle) -> Result<Vec<Receipt>> {
+        let assets: BTreeSet<&str> = bundle
+            .trades
+            .iter()
+            .flat_map(|t| [t.market.base.as_str(), t.market.quote.as_str()])
+            .collect();
+        for asset in &assets {
+            self.verify_supply(asset)?;
+        }
+        let receipts = self.settle_all(&bundle.trades)?;
+        for asset in &assets {
+            self.verify_supply(asset)?;
+        }
+        Ok(receipts)
+    }
+
+    fn buyer_seller(trade: &Trade) -> (UserId, UserId) {
+        if trade.taker_is_buyer() {
+            (trade.taker_user_id, trade.maker_user_id)
+        } else {
+            (trade.maker_user_id, trade.taker_user_id)
+        }
+    }
+
+    fn entry(&mut self, user_id: UserId, asset: &Asset) -> &mut BalanceEntry {
+        self.balances.entry((user_id, asset.clone())).or_default()
+    }
+
+    /// Unsigned receipt for a settled trade; the node signs it downstream.
+    fn settlement_receipt(trade: &Trade) -> Receipt {
+        let payload = trade.fingerprint().to_vec();
+        let payload_hash = Sha256::digest(&payload).into();
+        Receipt {
+            receipt_type: ReceiptType::SettlementCompleted,
+            epoch_id: trade.epoch_id,
+            trade_id: Some(trade.id),
+            payload,
+            payload_hash,
+            signature: Vec::new(),
+            issuer_node: trade.matcher_node,
+            issued_at: Utc::now(),
+            prev_hash: [0u8; 32],
+        }
     }
 
     /// Get the balance for a (user, asset) pair.
     #[must_use]
     pub fn balance(&self, user_id: UserId, asset: &str) -> BalanceEntry {
-        self.balances
-            .get(&(user_id, asset.to_string()))
+        // An invalid symbol can never have been credited.
+        Asset::new(asset)
+            .ok()
+            .and_then(|asset| self.balances.get(&(user_id, asset)))
             .cloned()
             .unwrap_or_default()
     }
 
+    /// Trades settled for `user_id` (as buyer or seller), in settlement order.
+    #[must_use]
+    pub fn user_trades(&self, user_id: &UserId) -> &[TradeId] {
+        self.user_trades.get(user_id).map_or(&[], Vec::as_slice)
+    }
+
     /// Verify supply conservation for a given asset.
+    ///
+    /// The fee pool counts as an account alongside user balances.
+    /// Balances are summed with checked arithmetic: an entry whose total
+    /// overflows `Decimal` is reported as a supply violation, not a panic.
     pub fn verify_supply(&self, asset: &str) -> Result<()> {
-        let actual: Decimal = self
+        let mut actual = self.fee_pool(asset);
+        for ((user_id, _), entry) in self
             .balances
             .iter()
-            .filter(|((_, a), _)| a == asset)
-            .map(|(_, entry)| entry.total())
-            .sum();
+            .filter(|((_, a), _)| a.as_str() == asset)
+        {
+            actual = entry
+                .checked_total()
+                .and_then(|total| actual.checked_add(total))
+                .ok_or_else(|| OpenmatchError::SupplyInvariantViolation {
+                    reason: format!("Asset {asset}: balance overflow summing user {user_id}"),
+                })?;
+        }
         self.supply.verify(asset, actual)
     }
 
+    /// Verify supply conservation for every asset ever deposited or
+    /// withdrawn, stopping at the first violation.
+    ///
+    /// # Errors
+    /// Same as [`Tier1Settler::verify_supply`].
+    pub fn verify_all_supply(&self) -> Result<()> {
+        self.supply
+            .tracked_assets()
+            .iter()
+            .try_for_each(|asset| self.verify_supply(asset))
+    }
+
+    /// Accounts whose balance diverges from their recorded flows, as
+    /// `(user, asset, actual − expected)`. Empty unless leak tracking is on.
+    #[must_use]
+    pub fn locate_leaks(&self) -> Vec<(UserId, Asset, Decimal)> {
+        self.supply.locate_leaks(&self.balances)
+    }
+
     /// Access the idempotency guard.
     #[must_use]
     pub fn idempotency(&self) -> &IdempotencyGuard {
         &self.idempotency
     }
+
+    /// Access the withdraw lock.
+    #[must_use]
+    pub fn withdraw_lock(&self) -> &WithdrawLock {
+        &self.withdraw_lock
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use chrono::Utc;
+    use openmatch_ingress::{BalanceManager, EscrowManager};
     use openmatch_types::*;
 
     use super::*;
@@ -170,7 +732,7 @@ mod tests {
         Trade {
             id: TradeId::deterministic(1, 0),
             epoch_id: EpochId(1),
-            market: MarketPair::new("BTC", "USDT"),
+            market: MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")),
             taker_order_id: OrderId::new(),
             taker_user_id: buyer,
             maker_order_id: OrderId::new(),
@@ -181,6 +743,7 @@ mod tests {
             taker_side: OrderSide::Buy,
             matcher_node: NodeId([0u8; 32]),
             executed_at: Utc::now(),
+            quote_dust: None,
         }
     }
 
@@ -188,7 +751,7 @@ mod tests {
     fn deposit_and_freeze() {
         let mut settler = Tier1Settler::new(100);
         let user = UserId::new();
-        settler.deposit(user, "USDT", Decimal::new(100_000, 0));
+        settler.deposit(user, Asset::lit("USDT"), Decimal::new(100_000, 0));
 
         let bal = settler.balance(user, "USDT");
         assert_eq!(bal.available, Decimal::new(100_000, 0));
@@ -206,7 +769,7 @@ mod tests {
     fn freeze_insufficient_balance() {
         let mut settler = Tier1Settler::new(100);
         let user = UserId::new();
-        settler.deposit(user, "USDT", Decimal::new(100, 0));
+        settler.deposit(user, Asset::lit("USDT"), Decimal::new(100, 0));
 
         let err = settler
             .freeze(user, "USDT", Decimal::new(200, 0))
@@ -221,11 +784,11 @@ mod tests {
         let seller = UserId::new();
 
         // Setup: buyer has USDT frozen, seller has BTC frozen
-        settler.deposit(buyer, "USDT", Decimal::new(50000, 0));
+        settler.deposit(buyer, Asset::lit("USDT"), Decimal::new(50000, 0));
         settler
             .freeze(buyer, "USDT", Decimal::new(50000, 0))
             .unwrap();
-        settler.deposit(seller, "BTC", Decimal::ONE);
+        settler.deposit(seller, Asset::lit("BTC"), Decimal::ONE);
         settler.freeze(seller, "BTC", Decimal::ONE).unwrap();
 
         let trade = make_trade(buyer, seller);
@@ -246,17 +809,215 @@ mod tests {
         assert_eq!(seller_btc.frozen, Decimal::ZERO);
     }
 
+    #[test]
+    fn rounded_up_fill_draws_on_dust_account() {
+        let (buyer, seller, dust) = (UserId::new(), UserId::new(), UserId::new());
+        let mut trade = make_trade(buyer, seller);
+        trade.quote_amount = Decimal::new(50_001, 0);
+        trade.quote_dust = Some(QuoteDust {
+            account: dust,
+            amount: -Decimal::ONE,
+        });
+
+        let mut settler = Tier1Settler::new(100);
+        settler.deposit(buyer, Asset::lit("USDT"), Decimal::new(50_000, 0));
+        settler
+            .freeze(buyer, "USDT", Decimal::new(50_000, 0))
+            .unwrap();
+        settler.deposit(seller, Asset::lit("BTC"), Decimal::ONE);
+        settler.freeze(seller, "BTC", Decimal::ONE).unwrap();
+
+        // An unfunded dust account refuses the fill with nothing applied.
+        let err = settler
+            .settle_all(std::slice::from_ref(&trade))
+            .unwrap_err();
+        assert!(matches!(err, OpenmatchError::SettlementFailed { .. }));
+        assert!(!settler.idempotency().is_settled(&trade.id));
+
+        settler.deposit(dust, Asset::lit("USDT"), Decimal::ONE);
+        settler.settle_all(&[trade]).unwrap();
+        assert_eq!(
+            settler.balance(seller, "USDT").available,
+            Decimal::new(50_001, 0)
+        );
+        assert_eq!(settler.balance(buyer, "USDT"), BalanceEntry::default());
+        assert_eq!(settler.balance(dust, "USDT"), BalanceEntry::default());
+        settler.verify_all_supply().unwrap();
+    }
+
+    #[test]
+    fn settle_all_applies_nothing_if_any_trade_underfrozen() {
+        let mut settler = Tier1Settler::new(100);
+        let buyer = UserId::new();
+        let seller = UserId::new();
+
+        // Enough frozen for two trades, not three.
+        settler.deposit(buyer, Asset::lit("USDT"), Decimal::new(150_000, 0));
+        settler
+            .freeze(buyer, "USDT", Decimal::new(100_000, 0))
+            .unwrap();
+        settler.deposit(seller, Asset::lit("BTC"), Decimal::new(3, 0));
+        settler.freeze(seller, "BTC", Decimal::new(3, 0)).unwrap();
+
+        let trades: Vec<Trade> = (0..3)
+            .map(|seq| Trade {
+                id: TradeId::deterministic(1, seq),
+                ..make_trade(buyer, seller)
+            })
+            .collect();
+
+        let err = settler.settle_all(&trades).unwrap_err();
+        assert!(matches!(err, OpenmatchError::InsufficientFrozen));
+        for trade in &trades {
+            assert!(!settler.idempotency().is_settled(&trade.id));
+        }
+        assert_eq!(settler.balance(buyer, "BTC"), BalanceEntry::default());
+        assert_eq!(
+            settler.balance(buyer, "USDT").frozen,
+            Decimal::new(100_000, 0)
+        );
+        assert_eq!(settler.balance(seller, "BTC").frozen, Decimal::new(3, 0));
+        assert_eq!(settler.balance(seller, "USDT"), BalanceEntry::default());
+
+        // The first two settle once the set fits.
+        let receipts = settler.settle_all(&trades[..2]).unwrap();
+        assert_eq!(receipts.len(), 2);
+        assert_eq!(receipts[1].trade_id, Some(trades[1].id));
+        assert_eq!(receipts[1].receipt_type, ReceiptType::SettlementCompleted);
+        assert_eq!(settler.balance(buyer, "BTC").available, Decimal::new(2, 0));
+        settler.verify_supply("USDT").unwrap();
+        settler.verify_supply("BTC").unwrap();
+    }
+
+    #[test]
+    fn settle_all_rejects_duplicate_trade_in_set() {
+        let mut settler = Tier1Settler::new(100);
+        let buyer = UserId::new();
+        let seller = UserId::new();
+        settler.deposit(buyer, Asset::lit("USDT"), Decimal::new(100_000, 0));
+        settler
+            .freeze(buyer, "USDT", Decimal::new(100_000, 0))
+            .unwrap();
+        settler.deposit(seller, Asset::lit("BTC"), Decimal::new(2, 0));
+        settler.freeze(seller, "BTC", Decimal::new(2, 0)).unwrap();
+
+        let trade = make_trade(buyer, seller);
+        let err = settler
+            .settle_all(&[trade.clone(), trade.clone()])
+            .unwrap_err();
+        assert!(matches!(err, OpenmatchError::TradeAlreadySettled(_)));
+        assert!(settler.idempotency().is_empty());
+    }
+
+    #[test]
+    fn user_trades_indexed_for_both_sides() {
+        let mut settler = Tier1Settler::new(100);
+        let buyer = UserId::new();
+        let seller = UserId::new();
+        settler.deposit(buyer, Asset::lit("USDT"), Decimal::new(50000, 0));
+        settler
+            .freeze(buyer, "USDT", Decimal::new(50000, 0))
+            .unwrap();
+        settler.deposit(seller, Asset::lit("BTC"), Decimal::ONE);
+        settler.freeze(seller, "BTC", Decimal::ONE).unwrap();
+
+        let trade = make_trade(buyer, seller);
+        settler.settle_trade(&trade).unwrap();
+
+        assert_eq!(settler.user_trades(&buyer), &[trade.id]);
+        assert_eq!(settler.user_trades(&seller), &[trade.id]);
+        assert!(settler.user_trades(&UserId::new()).is_empty());
+    }
+
+    #[test]
+    fn leak_tracking_localizes_corrupted_balance() {
+        let mut settler = Tier1Settler::new(100).with_leak_tracking();
+        let buyer = UserId::new();
+        let seller = UserId::new();
+        settler.deposit(buyer, Asset::lit("USDT"), Decimal::new(50000, 0));
+        settler
+            .freeze(buyer, "USDT", Decimal::new(50000, 0))
+            .unwrap();
+        settler.deposit(seller, Asset::lit("BTC"), Decimal::ONE);
+        settler.freeze(seller, "BTC", Decimal::ONE).unwrap();
+        settler.settle_trade(&make_trade(buyer, seller)).unwrap();
+        assert!(settler.locate_leaks().is_empty());
+
+        settler
+            .balances
+            .get_mut(&(seller, Asset::lit("USDT")))
+            .unwrap()
+            .available -= Decimal::new(7, 0);
+        assert!(settler.verify_supply("USDT").is_err());
+        assert_eq!(
+            settler.locate_leaks(),
+            vec![(seller, Asset::lit("USDT"), Decimal::new(-7, 0))]
+        );
+    }
+
+    #[test]
+    fn chain_ref_deposit_and_withdrawal_produce_receipts() {
+        let mut settler = Tier1Settler::new(100);
+        let user = UserId::new();
+        settler.set_epoch(EpochId(4));
+        settler.deposit(user, Asset::lit("BTC"), Decimal::ONE);
+        assert!(settler.audit_receipts().is_empty());
+
+        settler.deposit_with_ref(
+            user,
+            Asset::lit("BTC"),
+            Decimal::TWO,
+            Some("btc:tx1".to_string()),
+        );
+        assert!(
+            settler
+                .withdraw_with_ref(
+                    user,
+                    "BTC",
+                    Decimal::new(10, 0),
+                    Some("btc:tx2".to_string())
+                )
+                .is_err()
+        );
+        settler
+            .withdraw_with_ref(user, "BTC", Decimal::ONE, Some("btc:tx3".to_string()))
+            .unwrap();
+
+        let receipts = settler.audit_receipts();
+        assert_eq!(receipts.len(), 2);
+        assert_eq!(
+            receipts[0].receipt_type,
+            ReceiptType::Deposit {
+                chain_ref: "btc:tx1".to_string()
+            }
+        );
+        assert_eq!(receipts[0].epoch_id, EpochId(4));
+        assert_eq!(
+            receipts[0].payload_hash,
+            <[u8; 32]>::from(Sha256::digest(&receipts[0].payload))
+        );
+        assert_eq!(
+            receipts[1].receipt_type,
+            ReceiptType::Withdrawal {
+                chain_ref: "btc:tx3".to_string()
+            }
+        );
+        assert_eq!(receipts[1].prev_hash, receipts[0].chain_hash());
+        settler.audit_chain().verify_chain().unwrap();
+        assert_eq!(settler.balance(user, "BTC").available, Decimal::TWO);
+    }
+
     #[test]
     fn double_settlement_blocked() {
         let mut settler = Tier1Settler::new(100);
         let buyer = UserId::new();
         let seller = UserId::new();
 
-        settler.deposit(buyer, "USDT", Decimal::new(100_000, 0));
+        settler.deposit(buyer, Asset::lit("USDT"), Decimal::new(100_000, 0));
         settler
             .freeze(buyer, "USDT", Decimal::new(50000, 0))
             .unwrap();
-        settler.deposit(seller, "BTC", Decimal::new(2, 0));
+        settler.deposit(seller, Asset::lit("BTC"), Decimal::new(2, 0));
         settler.freeze(seller, "BTC", Decimal::ONE).unwrap();
 
         let trade = make_trade(buyer, seller);
@@ -272,11 +1033,11 @@ mod tests {
         let buyer = UserId::new();
         let seller = UserId::new();
 
-        settler.deposit(buyer, "USDT", Decimal::new(50000, 0));
+        settler.deposit(buyer, Asset::lit("USDT"), Decimal::new(50000, 0));
         settler
             .freeze(buyer, "USDT", Decimal::new(50000, 0))
             .unwrap();
-        settler.deposit(seller, "BTC", Decimal::ONE);
+        settler.deposit(seller, Asset::lit("BTC"), Decimal::ONE);
         settler.freeze(seller, "BTC", Decimal::ONE).unwrap();
 
         let trade = make_trade(buyer, seller);
@@ -286,4 +1047,377 @@ mod tests {
         settler.verify_supply("USDT").unwrap();
         settler.verify_supply("BTC").unwrap();
     }
+
+    #[test]
+    fn maker_rebate_is_funded_by_taker_fee() {
+        // Taker pays 0.2%, maker earns a 0.1% rebate.
+        let fees = FeeSchedule::new(Decimal::new(-1, 3), Decimal::new(2, 3));
+        let mut settler = Tier1Settler::new(100).with_fee_schedule(fees).unwrap();
+        let (buyer, seller) = (UserId::new(), UserId::new());
+        settler.deposit(buyer, Asset::lit("USDT"), Decimal::new(50_100, 0));
+        settler
+            .freeze(buyer, "USDT", Decimal::new(50_100, 0))
+            .unwrap();
+        settler.deposit(seller, Asset::lit("BTC"), Decimal::ONE);
+        settler.freeze(seller, "BTC", Decimal::ONE).unwrap();
+
+        // Buyer is the taker: 50,000 + 100 fee from frozen quote.
+        settler.settle_trade(&make_trade(buyer, seller)).unwrap();
+        assert!(settler.balance(buyer, "USDT").frozen.is_zero());
+        assert_eq!(settler.balance(buyer, "BTC").available, Decimal::ONE);
+        assert_eq!(
+            settler.balance(seller, "USDT").available,
+            Decimal::new(50_050, 0)
+        );
+        assert_eq!(settler.fee_pool("USDT"), Decimal::new(50, 0));
+        settler.verify_all_supply().unwrap();
+    }
+
+    #[test]
+    fn rebate_without_pool_funds_is_refused() {
+        let fees = FeeSchedule::new(Decimal::new(-1, 3), Decimal::ZERO);
+        let mut settler = Tier1Settler::new(100).with_fee_schedule(fees).unwrap();
+        let (buyer, seller) = (UserId::new(), UserId::new());
+        settler.deposit(buyer, Asset::lit("USDT"), Decimal::new(50_000, 0));
+        settler
+            .freeze(buyer, "USDT", Decimal::new(50_000, 0))
+            .unwrap();
+        settler.deposit(seller, Asset::lit("BTC"), Decimal::ONE);
+        settler.freeze(seller, "BTC", Decimal::ONE).unwrap();
+
+        let trade = make_trade(buyer, seller);
+        let err = settler.settle_trade(&trade).unwrap_err();
+        assert!(matches!(err, OpenmatchError::SettlementFailed { .. }));
+        assert!(!settler.idempotency().is_settled(&trade.id));
+        assert_eq!(settler.balance(seller, "BTC").frozen, Decimal::ONE);
+        assert!(settler.fee_pool("USDT").is_zero());
+    }
+
+    #[test]
+    fn settle_bundle_checks_supply_around_settlement() {
+        let mut settler = Tier1Settler::new(100);
+        let buyer = UserId::new();
+        let seller = UserId::new();
+        settler.deposit(buyer, Asset::lit("USDT"), Decimal::new(100_000, 0));
+        settler
+            .freeze(buyer, "USDT", Decimal::new(100_000, 0))
+            .unwrap();
+        settler.deposit(seller, Asset::lit("BTC"), Decimal::TWO);
+        settler.freeze(seller, "BTC", Decimal::TWO).unwrap();
+
+        let mut second = make_trade(buyer, seller);
+        second.id = TradeId::deterministic(1, 1);
+        let bundle = TradeBundle {
+            epoch_id: EpochId(1),
+            trades: vec![make_trade(buyer, seller), second],
+            trade_root: [0u8; 32],
+            input_hash: [0u8; 32],
+            clearing_price: Some(Decimal::new(50000, 0)),
+            remaining_orders: vec![],
+            cancelled_orders: vec![],
+            unfilled_reasons: vec![],
+            wash_trade_attempts: vec![],
+        };
+        let receipts = settler.settle_bundle(&bundle).unwrap();
+        assert_eq!(receipts.len(), 2);
+        assert_eq!(settler.balance(buyer, "BTC").available, Decimal::TWO);
+
+        // A ledger that no longer reconciles is refused before any trade moves.
+        let mut fresh = Tier1Settler::new(100);
+        fresh.deposit(buyer, Asset::lit("USDT"), Decimal::new(100_000, 0));
+        fresh
+            .freeze(buyer, "USDT", Decimal::new(100_000, 0))
+            .unwrap();
+        fresh.deposit(seller, Asset::lit("BTC"), Decimal::TWO);
+        fresh.freeze(seller, "BTC", Decimal::TWO).unwrap();
+        fresh
+            .balances
+            .get_mut(&(seller, Asset::lit("BTC")))
+            .unwrap()
+            .frozen += Decimal::ONE;
+        let err = fresh.settle_bundle(&bundle).unwrap_err();
+        assert!(matches!(
+            err,
+            OpenmatchError::SupplyInvariantViolation { .. }
+        ));
+        assert!(!fresh.idempotency().is_settled(&bundle.trades[0].id));
+        assert_eq!(fresh.balance(buyer, "BTC"), BalanceEntry::default());
+    }
+
+    #[test]
+    fn asset_lock_blocks_withdrawal_of_that_asset_only() {
+        let mut settler = Tier1Settler::new(100);
+        let user = UserId::new();
+        settler.deposit(user, Asset::lit("BTC"), Decimal::ONE);
+        settler.deposit(user, Asset::lit("USDT"), Decimal::new(1000, 0));
+
+        settler.set_asset_lock(Asset::lit("BTC"), true);
+        let err = settler.withdraw(user, "BTC", Decimal::ONE).unwrap_err();
+        assert!(matches!(err, OpenmatchError::AssetWithdrawLocked { .. }));
+        assert_eq!(settler.balance(user, "BTC").available, Decimal::ONE);
+
+        settler
+            .withdraw(user, "USDT", Decimal::new(400, 0))
+            .unwrap();
+        assert_eq!(
+            settler.balance(user, "USDT").available,
+            Decimal::new(600, 0)
+        );
+
+        settler.set_asset_lock(Asset::lit("BTC"), false);
+        settler.withdraw(user, "BTC", Decimal::ONE).unwrap();
+
+        // Withdrawals are recorded for supply conservation.
+        settler.verify_supply("BTC").unwrap();
+        settler.verify_supply("USDT").unwrap();
+    }
+
+    #[test]
+    fn withdraw_blocked_during_match_phase() {
+        let mut settler = Tier1Settler::new(100);
+        let user = UserId::new();
+        settler.deposit(user, Asset::lit("USDT"), Decimal::new(1000, 0));
+
+        settler.set_phase(EpochPhase::Seal).unwrap();
+        settler.set_phase(EpochPhase::Match).unwrap();
+        let err = settler
+            .withdraw(user, "USDT", Decimal::new(1, 0))
+            .unwrap_err();
+        assert!(matches!(err, OpenmatchError::WithdrawLockedDuringSettle));
+    }
+
+    #[test]
+    fn overflowing_balance_reports_supply_violation() {
+        let mut settler = Tier1Settler::new(100);
+        // Poisoned entry: available + frozen exceeds `Decimal::MAX`.
+        settler.balances.insert(
+            (UserId::new(), Asset::lit("USDT")),
+            BalanceEntry {
+                available: Decimal::MAX,
+                frozen: Decimal::MAX,
+                withdraw_pending: Decimal::ZERO,
+            },
+        );
+
+        let err = settler.verify_supply("USDT").unwrap_err();
+        assert!(matches!(
+            err,
+            OpenmatchError::SupplyInvariantViolation { .. }
+        ));
+    }
+
+    #[test]
+    fn cadence_checks_supply_every_n_ops() {
+        let mut settler =
+            Tier1Settler::new(100).with_supply_check_cadence(SupplyCheckCadence::EveryNOps(3));
+        let user = UserId::new();
+        settler.deposit(user, Asset::lit("USDT"), Decimal::new(100, 0));
+        settler.freeze(user, "USDT", Decimal::new(10, 0)).unwrap();
+        assert_eq!(settler.supply_checks_run(), 0);
+        settler.withdraw(user, "USDT", Decimal::new(5, 0)).unwrap();
+        assert_eq!((settler.ops_count(), settler.supply_checks_run()), (3, 1));
+
+        // Phantom supply: recorded as deposited but credited to no one.
+        settler
+            .supply
+            .record_deposit(Asset::lit("USDT"), Decimal::ONE);
+        settler.freeze(user, "USDT", Decimal::ONE).unwrap();
+        settler.freeze(user, "USDT", Decimal::ONE).unwrap();
+        let err = settler.freeze(user, "USDT", Decimal::ONE).unwrap_err();
+        assert!(matches!(
+            err,
+            OpenmatchError::SupplyInvariantViolation { .. }
+        ));
+        assert_eq!(settler.supply_checks_run(), 2);
+        // The operation that tripped the check was still applied.
+        assert_eq!(settler.balance(user, "USDT").frozen, Decimal::new(13, 0));
+    }
+
+    #[test]
+    fn cadence_checks_supply_on_phase() {
+        let mut settler = Tier1Settler::new(100)
+            .with_supply_check_cadence(SupplyCheckCadence::OnPhase(EpochPhase::Finalize));
+        settler.deposit(UserId::new(), Asset::lit("USDT"), Decimal::new(100, 0));
+        settler.set_phase(EpochPhase::Seal).unwrap();
+        settler.set_phase(EpochPhase::Match).unwrap();
+        assert_eq!(settler.supply_checks_run(), 0);
+
+        settler
+            .supply
+            .record_deposit(Asset::lit("USDT"), Decimal::ONE);
+        assert!(matches!(
+            settler.set_phase(EpochPhase::Finalize),
+            Err(OpenmatchError::SupplyInvariantViolation { .. })
+        ));
+        assert_eq!(settler.supply_checks_run(), 1);
+        assert_eq!(
+            settler.withdraw_lock().current_phase(),
+            EpochPhase::Finalize
+        );
+    }
+
+    #[test]
+    fn manual_cadence_never_checks() {
+        let mut settler = Tier1Settler::new(100);
+        let user = UserId::new();
+        settler.deposit(user, Asset::lit("USDT"), Decimal::new(100, 0));
+        settler
+            .supply
+            .record_deposit(Asset::lit("USDT"), Decimal::ONE);
+        settler.freeze(user, "USDT", Decimal::ONE).unwrap();
+        assert_eq!((settler.ops_count(), settler.supply_checks_run()), (2, 0));
+    }
+
+    fn bundle_of(trades: Vec<Trade>, remaining_orders: Vec<Order>) -> TradeBundle {
+        TradeBundle {
+            epoch_id: EpochId(1),
+            trades,
+            trade_root: [0u8; 32],
+            input_hash: [0u8; 32],
+            clearing_price: Some(Decimal::new(50000, 0)),
+            remaining_orders,
+            cancelled_orders: vec![],
+            unfilled_reasons: vec![],
+            wash_trade_attempts: vec![],
+        }
+    }
+
+    /// A settler and an escrow ledger both funding `buyer` with
+    /// `buy_escrow` USDT and `seller` with `sell_escrow` BTC, with the
+    /// escrow's SRs minted for `trade`'s orders.
+    fn escrowed(
+        trade: &Trade,
+        buy_escrow: Decimal,
+        sell_escrow: Decimal,
+    ) -> (
+        Tier1Settler,
+        EscrowManager,
+        BalanceManager,
+        SpendRightId,
+        SpendRightId,
+    ) {
+        let (buyer, seller) = (trade.taker_user_id, trade.maker_user_id);
+        let mut settler = Tier1Settler::new(100);
+        settler.deposit(buyer, Asset::lit("USDT"), buy_escrow);
+        settler.freeze(buyer, "USDT", buy_escrow).unwrap();
+        settler.deposit(seller, Asset::lit("BTC"), sell_escrow);
+        settler.freeze(seller, "BTC", sell_escrow).unwrap();
+
+        let mut ledger = BalanceManager::new();
+        ledger.deposit(buyer, Asset::lit("USDT"), buy_escrow);
+        ledger.deposit(seller, Asset::lit("BTC"), sell_escrow);
+        let mut escrow = EscrowManager::new(NodeId([0u8; 32]));
+        let buy_sr = escrow
+            .mint(
+                &mut ledger,
+                trade.taker_order_id,
+                buyer,
+                "USDT",
+                buy_escrow,
+                EpochId(1),
+            )
+            .unwrap();
+        let sell_sr = escrow
+            .mint(
+                &mut ledger,
+                trade.maker_order_id,
+                seller,
+                "BTC",
+                sell_escrow,
+                EpochId(1),
+            )
+            .unwrap();
+        (settler, escrow, ledger, buy_sr, sell_sr)
+    }
+
+    #[test]
+    fn settle_with_escrow_spends_filled_srs_once() {
+        let (buyer, seller) = (UserId::new(), UserId::new());
+        let trade = make_trade(buyer, seller);
+        // The buy escrowed at its 50,500 limit and clears at 50,000.
+        let (mut settler, mut escrow, mut ledger, buy_sr, sell_sr) =
+            escrowed(&trade, Decimal::new(50500, 0), Decimal::ONE);
+
+        settler
+            .settle_bundle_with_escrow(
+                &bundle_of(vec![trade.clone()], vec![]),
+                &mut escrow,
+                &mut ledger,
+            )
+            .unwrap();
+        for sr_id in [buy_sr, sell_sr] {
+            assert_eq!(escrow.get(&sr_id).unwrap().state, SpendRightState::Spent);
+        }
+        // The fills drew on the escrowed funds; the unused 500 is unfrozen.
+        let buyer_escrow = ledger.balance(buyer, "USDT");
+        assert_eq!(buyer_escrow.frozen, Decimal::ZERO);
+        assert_eq!(buyer_escrow.available, Decimal::new(500, 0));
+        assert_eq!(ledger.balance(seller, "BTC"), BalanceEntry::default());
+
+        // A second settlement of the same fill is refused by the SRs,
+        // even under a fresh trade ID that idempotency wouldn't catch
+        let mut replay = trade;
+        replay.id = TradeId::deterministic(1, 1);
+        let err = settler
+            .settle_bundle_with_escrow(&bundle_of(vec![replay], vec![]), &mut escrow, &mut ledger)
+            .unwrap_err();
+        assert!(matches!(err, OpenmatchError::InvalidSpendRight { .. }));
+        assert_eq!(settler.balance(seller, "BTC").frozen, Decimal::ZERO);
+        assert_eq!(settler.balance(buyer, "USDT").frozen, Decimal::new(500, 0));
+    }
+
+    #[test]
+    fn partial_fill_keeps_sr_active_with_the_rest_escrowed() {
+        let (buyer, seller) = (UserId::new(), UserId::new());
+        let trade = make_trade(buyer, seller);
+        let (mut settler, mut escrow, mut ledger, buy_sr, sell_sr) =
+            escrowed(&trade, Decimal::new(50000, 0), Decimal::TWO);
+        // The seller offered 2 BTC; one is still resting.
+        let mut rest = Order::dummy_limit(OrderSide::Sell, Decimal::new(50000, 0), Decimal::ONE);
+        rest.id = trade.maker_order_id;
+        rest.user_id = seller;
+
+        settler
+            .settle_bundle_with_escrow(
+                &bundle_of(vec![trade], vec![rest]),
+                &mut escrow,
+                &mut ledger,
+            )
+            .unwrap();
+        assert_eq!(escrow.get(&buy_sr).unwrap().state, SpendRightState::Spent);
+        let sell = escrow.get(&sell_sr).unwrap();
+        assert_eq!(sell.state, SpendRightState::Active);
+        assert_eq!(sell.remaining(), Decimal::ONE);
+        assert_eq!(ledger.balance(seller, "BTC").frozen, Decimal::ONE);
+
+        // Cancelling the rest releases exactly what is still escrowed.
+        escrow.release(&mut ledger, sell_sr).unwrap();
+        let seller_escrow = ledger.balance(seller, "BTC");
+        assert_eq!(seller_escrow.frozen, Decimal::ZERO);
+        assert_eq!(seller_escrow.available, Decimal::ONE);
+    }
+
+    #[test]
+    fn escrow_without_frozen_funds_rejects_bundle_untouched() {
+        let (buyer, seller) = (UserId::new(), UserId::new());
+        let trade = make_trade(buyer, seller);
+        let (mut settler, mut escrow, _, buy_sr, _) =
+            escrowed(&trade, Decimal::new(50000, 0), Decimal::ONE);
+
+        // A ledger that never froze the SRs' funds
+        let err = settler
+            .settle_bundle_with_escrow(
+                &bundle_of(vec![trade.clone()], vec![]),
+                &mut escrow,
+                &mut BalanceManager::new(),
+            )
+            .unwrap_err();
+        assert!(matches!(err, OpenmatchError::InsufficientFrozen));
+        assert!(escrow.is_active(&buy_sr));
+        assert_eq!(
+            settler.balance(buyer, "USDT").frozen,
+            Decimal::new(50000, 0)
+        );
+        assert_eq!(settler.balance(buyer, "BTC"), BalanceEntry::default());
+    }
 }
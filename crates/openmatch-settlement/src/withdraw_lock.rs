@@ -3,8 +3,13 @@
 //! Blocks withdrawals during MATCH and FINALIZE phases to prevent
 //! balance manipulation while trades are being settled. During COLLECT
 //! and SEAL phases, withdrawals are allowed.
+//!
+//! Individual assets can additionally be frozen (e.g., a compromised token)
+//! without affecting withdrawals of other assets.
+
+use std::collections::HashSet;
 
-use openmatch_types::{EpochPhase, OpenmatchError, Result};
+use openmatch_types::{Asset, EpochPhase, OpenmatchError, Result};
 
 /// Phase-aware lock that blocks withdrawals during critical epoch phases.
 ///
@@ -13,6 +18,8 @@ use openmatch_types::{EpochPhase, OpenmatchError, Result};
 pub struct WithdrawLock {
     /// The current epoch phase.
     current_phase: EpochPhase,
+    /// Assets whose withdrawals are frozen regardless of phase.
+    locked_assets: HashSet<Asset>,
 }
 
 impl WithdrawLock {
@@ -21,12 +28,28 @@ impl WithdrawLock {
     pub fn new() -> Self {
         Self {
             current_phase: EpochPhase::Collect,
+            locked_assets: HashSet::new(),
         }
     }
 
-    /// Update the current epoch phase.
-    pub fn set_phase(&mut self, phase: EpochPhase) {
+    /// Advance to the next epoch phase.
+    ///
+    /// Re-setting the current phase is a no-op. Skipping a phase (e.g.
+    /// COLLECT → MATCH) would open or close the withdraw window out of turn,
+    /// so it is rejected and the phase is left unchanged.
+    ///
+    /// # Errors
+    /// [`OpenmatchError::WrongEpochPhase`] if `phase` can't follow the
+    /// current phase; `expected` is the legal next phase.
+    pub fn set_phase(&mut self, phase: EpochPhase) -> Result<()> {
+        if phase != self.current_phase && !phase.can_follow(self.current_phase) {
+            return Err(OpenmatchError::WrongEpochPhase {
+                expected: self.current_phase.next(),
+                actual: phase,
+            });
+        }
         self.current_phase = phase;
+        Ok(())
     }
 
     /// Get the current epoch phase.
@@ -37,10 +60,11 @@ impl WithdrawLock {
 
     /// Check if withdrawals are currently allowed.
     ///
-    /// Withdrawals are only permitted during COLLECT and SEAL phases.
+    /// Withdrawals are only permitted during COLLECT and SEAL phases
+    /// (see [`EpochPhase::allows_withdrawals`]).
     #[must_use]
     pub fn withdrawals_allowed(&self) -> bool {
-        matches!(self.current_phase, EpochPhase::Collect | EpochPhase::Seal)
+        self.current_phase.allows_withdrawals()
     }
 
     /// Guard a withdrawal attempt. Returns `Ok(())` if allowed,
@@ -52,6 +76,39 @@ impl WithdrawLock {
             Err(OpenmatchError::WithdrawLockedDuringSettle)
         }
     }
+
+    /// Freeze (`true`) or unfreeze (`false`) withdrawals of a single asset.
+    pub fn set_asset_lock(&mut self, asset: impl Into<Asset>, locked: bool) {
+        let asset = asset.into();
+        if locked {
+            self.locked_assets.insert(asset);
+        } else {
+            self.locked_assets.remove(&asset);
+        }
+    }
+
+    /// Whether withdrawals of `asset` are frozen by an asset lock.
+    #[must_use]
+    pub fn is_asset_locked(&self, asset: &str) -> bool {
+        self.locked_assets.contains(asset)
+    }
+
+    /// Guard a withdrawal of a specific asset.
+    ///
+    /// Applies the phase lock first, then the per-asset lock.
+    ///
+    /// # Errors
+    /// - [`OpenmatchError::WithdrawLockedDuringSettle`] during MATCH/FINALIZE
+    /// - [`OpenmatchError::AssetWithdrawLocked`] if the asset is frozen
+    pub fn check_withdraw_asset(&self, asset: &str) -> Result<()> {
+        self.check_withdraw()?;
+        if self.is_asset_locked(asset) {
+            return Err(OpenmatchError::AssetWithdrawLocked {
+                asset: asset.to_string(),
+            });
+        }
+        Ok(())
+    }
 }
 
 impl Default for WithdrawLock {
@@ -64,6 +121,14 @@ impl Default for WithdrawLock {
 mod tests {
     use super::*;
 
+    fn lock_in(phase: EpochPhase) -> WithdrawLock {
+        let mut lock = WithdrawLock::new();
+        while lock.current_phase() != phase {
+            lock.set_phase(lock.current_phase().next()).unwrap();
+        }
+        lock
+    }
+
     #[test]
     fn collect_phase_allows_withdraw() {
         let lock = WithdrawLock::new();
@@ -73,44 +138,88 @@ mod tests {
 
     #[test]
     fn seal_phase_allows_withdraw() {
-        let mut lock = WithdrawLock::new();
-        lock.set_phase(EpochPhase::Seal);
+        let lock = lock_in(EpochPhase::Seal);
         assert!(lock.withdrawals_allowed());
         assert!(lock.check_withdraw().is_ok());
     }
 
     #[test]
     fn match_phase_blocks_withdraw() {
-        let mut lock = WithdrawLock::new();
-        lock.set_phase(EpochPhase::Match);
+        let lock = lock_in(EpochPhase::Match);
         assert!(!lock.withdrawals_allowed());
         assert!(lock.check_withdraw().is_err());
     }
 
     #[test]
     fn finalize_phase_blocks_withdraw() {
-        let mut lock = WithdrawLock::new();
-        lock.set_phase(EpochPhase::Finalize);
+        let lock = lock_in(EpochPhase::Finalize);
         assert!(!lock.withdrawals_allowed());
         let err = lock.check_withdraw().unwrap_err();
         assert!(matches!(err, OpenmatchError::WithdrawLockedDuringSettle));
     }
 
+    #[test]
+    fn asset_lock_blocks_only_that_asset() {
+        let mut lock = WithdrawLock::new();
+        lock.set_asset_lock(Asset::lit("BTC"), true);
+
+        let err = lock.check_withdraw_asset("BTC").unwrap_err();
+        assert!(matches!(err, OpenmatchError::AssetWithdrawLocked { ref asset } if asset == "BTC"));
+        assert!(lock.check_withdraw_asset("USDT").is_ok());
+
+        lock.set_asset_lock(Asset::lit("BTC"), false);
+        assert!(lock.check_withdraw_asset("BTC").is_ok());
+    }
+
+    #[test]
+    fn phase_lock_applies_to_unlocked_assets() {
+        let lock = lock_in(EpochPhase::Match);
+        let err = lock.check_withdraw_asset("USDT").unwrap_err();
+        assert!(matches!(err, OpenmatchError::WithdrawLockedDuringSettle));
+    }
+
     #[test]
     fn phase_transitions_update_lock() {
         let mut lock = WithdrawLock::new();
         assert!(lock.withdrawals_allowed());
 
-        lock.set_phase(EpochPhase::Seal);
+        lock.set_phase(EpochPhase::Seal).unwrap();
         assert!(lock.withdrawals_allowed());
 
-        lock.set_phase(EpochPhase::Match);
+        lock.set_phase(EpochPhase::Match).unwrap();
         assert!(!lock.withdrawals_allowed());
 
-        lock.set_phase(EpochPhase::Finalize);
+        lock.set_phase(EpochPhase::Finalize).unwrap();
         assert!(!lock.withdrawals_allowed());
 
-        lock.set_phase(EpochPhase::Collect);
+        lock.set_phase(EpochPhase::Collect).unwrap();
+        assert!(lock.withdrawals_allowed());
+    }
+
+    #[test]
+    fn phase_skip_rejected() {
+        let mut lock = WithdrawLock::new();
+        let err = lock.set_phase(EpochPhase::Match).unwrap_err();
+        assert!(matches!(
+            err,
+            OpenmatchError::WrongEpochPhase {
+                expected: EpochPhase::Seal,
+                actual: EpochPhase::Match
+            }
+        ));
+        assert_eq!(lock.current_phase(), EpochPhase::Collect);
         assert!(lock.withdrawals_allowed());
+
+        // Backwards is a skip too.
+        let mut lock = lock_in(EpochPhase::Match);
+        assert!(lock.set_phase(EpochPhase::Seal).is_err());
+        assert!(!lock.withdrawals_allowed());
+    }
+
+    #[test]
+    fn same_phase_is_noop() {
+        let mut lock = lock_in(EpochPhase::Seal);
+        assert!(lock.set_phase(EpochPhase::Seal).is_ok());
+        assert_eq!(lock.current_phase(), EpochPhase::Seal);
     }
 }
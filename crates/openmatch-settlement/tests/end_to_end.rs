@@ -7,13 +7,11 @@
 //! scenarios: multi-user trading, partial fills, self-trade prevention,
 //! supply conservation, and idempotency.
 
-#![allow(clippy::too_many_arguments)]
-
 use openmatch_ingress::{BalanceManager, BatchSealer, EscrowManager, PendingBuffer, RiskKernel};
-use openmatch_matchcore::match_sealed_batch;
+use openmatch_matchcore::{BatchMatcher, QuoteRounding, match_sealed_batch};
 use openmatch_settlement::Tier1Settler;
 use openmatch_types::*;
-use rust_decimal::Decimal;
+use rust_decimal::{Decimal, RoundingStrategy};
 
 /// Helper: full epoch pipeline — collect, seal, match, settle.
 struct EpochPipeline {
@@ -39,7 +37,7 @@ impl EpochPipeline {
     }
 
     fn deposit(&mut self, user: UserId, asset: &str, amount: Decimal) {
-        self.balance_mgr.deposit(user, asset, amount);
+        self.balance_mgr.deposit(user, Asset::lit(asset), amount);
     }
 
     fn submit_order(
@@ -48,30 +46,20 @@ impl EpochPipeline {
         side: OrderSide,
         price: Decimal,
         qty: Decimal,
-        escrow_asset: &str,
-        escrow_amount: Decimal,
         seq: u64,
     ) -> OrderId {
-        // 1. Mint SpendRight (escrow funds)
-        let order_id = OrderId::new();
-        let _sr_id = self
-            .escrow_mgr
-            .mint(
-                &mut self.balance_mgr,
-                order_id,
-                user,
-                escrow_asset,
-                escrow_amount,
-                self.epoch,
-            )
-            .expect("Escrow mint should succeed");
-
-        // 2. Create order
+        // 1. Create order
         let mut order = Order::dummy_limit(side, price, qty);
-        order.id = order_id;
+        let order_id = order.id;
         order.user_id = user;
         order.sequence = seq;
 
+        // 2. Mint SpendRight (escrow funds on the order's side)
+        order.sr_id = self
+            .escrow_mgr
+            .mint_for_order(&mut self.balance_mgr, &order, None, self.epoch)
+            .expect("Escrow mint should succeed");
+
         // 3. Validate through risk kernel
         self.risk_kernel
             .validate(&order)
@@ -99,7 +87,7 @@ impl EpochPipeline {
         );
 
         // MATCH phase
-        match_sealed_batch(&sealed_batch)
+        match_sealed_batch(&sealed_batch).unwrap()
     }
 }
 
@@ -123,8 +111,6 @@ fn e2e_simple_trade() {
         OrderSide::Buy,
         Decimal::new(50_000, 0),
         Decimal::ONE,
-        "USDT",
-        Decimal::new(50_000, 0),
         0,
     );
 
@@ -134,8 +120,6 @@ fn e2e_simple_trade() {
         OrderSide::Sell,
         Decimal::new(50_000, 0),
         Decimal::ONE,
-        "BTC",
-        Decimal::ONE,
         1,
     );
 
@@ -152,11 +136,11 @@ fn e2e_simple_trade() {
 
     // FINALIZE: Settle
     let mut settler = Tier1Settler::new(100);
-    settler.deposit(alice, "USDT", Decimal::new(50_000, 0));
+    settler.deposit(alice, Asset::lit("USDT"), Decimal::new(50_000, 0));
     settler
         .freeze(alice, "USDT", Decimal::new(50_000, 0))
         .unwrap();
-    settler.deposit(bob, "BTC", Decimal::ONE);
+    settler.deposit(bob, Asset::lit("BTC"), Decimal::ONE);
     settler.freeze(bob, "BTC", Decimal::ONE).unwrap();
 
     for trade in &bundle.trades {
@@ -202,8 +186,6 @@ fn e2e_partial_fills() {
         OrderSide::Buy,
         Decimal::new(50_000, 0),
         Decimal::new(5, 0),
-        "USDT",
-        Decimal::new(250_000, 0),
         0,
     );
 
@@ -213,8 +195,6 @@ fn e2e_partial_fills() {
         OrderSide::Sell,
         Decimal::new(50_000, 0),
         Decimal::new(3, 0),
-        "BTC",
-        Decimal::new(3, 0),
         1,
     );
 
@@ -224,8 +204,6 @@ fn e2e_partial_fills() {
         OrderSide::Sell,
         Decimal::new(50_000, 0),
         Decimal::new(2, 0),
-        "BTC",
-        Decimal::new(2, 0),
         2,
     );
 
@@ -239,13 +217,13 @@ fn e2e_partial_fills() {
 
     // FINALIZE
     let mut settler = Tier1Settler::new(100);
-    settler.deposit(buyer, "USDT", Decimal::new(250_000, 0));
+    settler.deposit(buyer, Asset::lit("USDT"), Decimal::new(250_000, 0));
     settler
         .freeze(buyer, "USDT", Decimal::new(250_000, 0))
         .unwrap();
-    settler.deposit(seller1, "BTC", Decimal::new(3, 0));
+    settler.deposit(seller1, Asset::lit("BTC"), Decimal::new(3, 0));
     settler.freeze(seller1, "BTC", Decimal::new(3, 0)).unwrap();
-    settler.deposit(seller2, "BTC", Decimal::new(2, 0));
+    settler.deposit(seller2, Asset::lit("BTC"), Decimal::new(2, 0));
     settler.freeze(seller2, "BTC", Decimal::new(2, 0)).unwrap();
 
     for trade in &bundle.trades {
@@ -289,8 +267,6 @@ fn e2e_self_trade_prevention() {
         OrderSide::Buy,
         Decimal::new(50_000, 0),
         Decimal::ONE,
-        "USDT",
-        Decimal::new(50_000, 0),
         0,
     );
 
@@ -299,8 +275,6 @@ fn e2e_self_trade_prevention() {
         OrderSide::Sell,
         Decimal::new(50_000, 0),
         Decimal::ONE,
-        "BTC",
-        Decimal::ONE,
         1,
     );
 
@@ -333,8 +307,6 @@ fn e2e_no_crossing() {
         OrderSide::Buy,
         Decimal::new(48_000, 0),
         Decimal::ONE,
-        "USDT",
-        Decimal::new(48_000, 0),
         0,
     );
 
@@ -343,8 +315,6 @@ fn e2e_no_crossing() {
         OrderSide::Sell,
         Decimal::new(52_000, 0),
         Decimal::ONE,
-        "BTC",
-        Decimal::ONE,
         1,
     );
 
@@ -380,8 +350,6 @@ fn e2e_settlement_idempotency() {
         OrderSide::Buy,
         Decimal::new(50_000, 0),
         Decimal::ONE,
-        "USDT",
-        Decimal::new(50_000, 0),
         0,
     );
 
@@ -390,8 +358,6 @@ fn e2e_settlement_idempotency() {
         OrderSide::Sell,
         Decimal::new(50_000, 0),
         Decimal::ONE,
-        "BTC",
-        Decimal::ONE,
         1,
     );
 
@@ -400,11 +366,11 @@ fn e2e_settlement_idempotency() {
 
     // Settle once
     let mut settler = Tier1Settler::new(100);
-    settler.deposit(alice, "USDT", Decimal::new(50_000, 0));
+    settler.deposit(alice, Asset::lit("USDT"), Decimal::new(50_000, 0));
     settler
         .freeze(alice, "USDT", Decimal::new(50_000, 0))
         .unwrap();
-    settler.deposit(bob, "BTC", Decimal::ONE);
+    settler.deposit(bob, Asset::lit("BTC"), Decimal::ONE);
     settler.freeze(bob, "BTC", Decimal::ONE).unwrap();
 
     settler.settle_trade(&bundle.trades[0]).unwrap();
@@ -452,8 +418,8 @@ fn e2e_deterministic_matching() {
     );
 
     // Match both batches
-    let bundle1 = match_sealed_batch(&batch1);
-    let bundle2 = match_sealed_batch(&batch2);
+    let bundle1 = match_sealed_batch(&batch1).unwrap();
+    let bundle2 = match_sealed_batch(&batch2).unwrap();
 
     // Same trade count
     assert_eq!(bundle1.trades.len(), bundle2.trades.len());
@@ -625,8 +591,6 @@ fn e2e_multi_user_auction() {
         OrderSide::Buy,
         Decimal::new(52_000, 0),
         Decimal::ONE,
-        "USDT",
-        Decimal::new(52_000, 0),
         0,
     );
 
@@ -636,8 +600,6 @@ fn e2e_multi_user_auction() {
         OrderSide::Buy,
         Decimal::new(49_000, 0),
         Decimal::ONE,
-        "USDT",
-        Decimal::new(49_000, 0),
         1,
     );
 
@@ -647,8 +609,6 @@ fn e2e_multi_user_auction() {
         OrderSide::Sell,
         Decimal::new(50_000, 0),
         Decimal::ONE,
-        "BTC",
-        Decimal::ONE,
         2,
     );
 
@@ -658,8 +618,6 @@ fn e2e_multi_user_auction() {
         OrderSide::Sell,
         Decimal::new(53_000, 0),
         Decimal::ONE,
-        "BTC",
-        Decimal::ONE,
         3,
     );
 
@@ -732,23 +690,23 @@ fn e2e_withdraw_lock_phases() {
     let mut lock = WithdrawLock::new();
 
     // COLLECT phase — withdrawals allowed
-    lock.set_phase(EpochPhase::Collect);
+    lock.set_phase(EpochPhase::Collect).unwrap();
     assert!(lock.check_withdraw().is_ok());
 
     // SEAL phase — withdrawals allowed
-    lock.set_phase(EpochPhase::Seal);
+    lock.set_phase(EpochPhase::Seal).unwrap();
     assert!(lock.check_withdraw().is_ok());
 
     // MATCH phase — withdrawals BLOCKED
-    lock.set_phase(EpochPhase::Match);
+    lock.set_phase(EpochPhase::Match).unwrap();
     assert!(lock.check_withdraw().is_err());
 
     // FINALIZE phase — withdrawals BLOCKED
-    lock.set_phase(EpochPhase::Finalize);
+    lock.set_phase(EpochPhase::Finalize).unwrap();
     assert!(lock.check_withdraw().is_err());
 
     // Back to COLLECT — allowed again
-    lock.set_phase(EpochPhase::Collect);
+    lock.set_phase(EpochPhase::Collect).unwrap();
     assert!(lock.check_withdraw().is_ok());
 }
 
@@ -784,8 +742,6 @@ fn e2e_sealed_buffer_rejects_late_orders() {
         OrderSide::Buy,
         Decimal::new(50_000, 0),
         Decimal::ONE,
-        "USDT",
-        Decimal::new(50_000, 0),
         0,
     );
 
@@ -797,3 +753,197 @@ fn e2e_sealed_buffer_rejects_late_orders() {
     let err = pipeline.pending_buf.push(late_order).unwrap_err();
     assert!(matches!(err, OpenmatchError::BufferAlreadySealed));
 }
+
+// =============================================================================
+// Test: In-batch cancel removes the order and releases its escrow
+// =============================================================================
+#[test]
+fn e2e_cancel_releases_escrow() {
+    let mut pipeline = EpochPipeline::new(EpochId(11));
+
+    let alice = UserId::new();
+    pipeline.deposit(alice, "USDT", Decimal::new(100_000, 0));
+
+    let order_id = pipeline.submit_order(
+        alice,
+        OrderSide::Buy,
+        Decimal::new(50_000, 0),
+        Decimal::ONE,
+        0,
+    );
+    assert_eq!(
+        pipeline.balance_mgr.balance(alice, "USDT").frozen,
+        Decimal::new(50_000, 0)
+    );
+
+    let mut cancel = Order::dummy_limit(OrderSide::Buy, Decimal::new(50_000, 0), Decimal::ONE);
+    cancel.user_id = alice;
+    cancel.order_type = OrderType::Cancel { target: order_id };
+    cancel.sequence = 1;
+    pipeline.risk_kernel.validate(&cancel).unwrap();
+    pipeline.pending_buf.push(cancel).unwrap();
+
+    let bundle = pipeline.seal_and_match();
+    assert!(bundle.remaining_orders.is_empty());
+    assert_eq!(bundle.cancelled_orders.len(), 1);
+
+    for order in &bundle.cancelled_orders {
+        pipeline
+            .escrow_mgr
+            .release_by_order(&mut pipeline.balance_mgr, order.id)
+            .unwrap();
+    }
+    let balance = pipeline.balance_mgr.balance(alice, "USDT");
+    assert_eq!(balance.available, Decimal::new(100_000, 0));
+    assert!(balance.frozen.is_zero());
+}
+
+// =============================================================================
+// Test: Epoch summary assembled at FINALIZE
+// =============================================================================
+#[test]
+fn e2e_epoch_summary_counts_and_clearing_price() {
+    let mut pipeline = EpochPipeline::new(EpochId(12));
+    let mut summary = EpochSummary::new(EpochId(12));
+
+    let alice = UserId::new();
+    let bob = UserId::new();
+    pipeline.deposit(alice, "USDT", Decimal::new(100_000, 0));
+    pipeline.deposit(bob, "BTC", Decimal::new(10, 0));
+
+    pipeline.submit_order(
+        alice,
+        OrderSide::Buy,
+        Decimal::new(50_000, 0),
+        Decimal::TWO,
+        0,
+    );
+    pipeline.submit_order(
+        bob,
+        OrderSide::Sell,
+        Decimal::new(50_000, 0),
+        Decimal::ONE,
+        1,
+    );
+    // An oversized order is refused at ingress.
+    let oversized = Order::dummy_limit(
+        OrderSide::Sell,
+        Decimal::new(50_000, 0),
+        Decimal::new(1_000, 0),
+    );
+    summary.record_rejection(&pipeline.risk_kernel.validate(&oversized).unwrap_err());
+    summary.record_received(pipeline.pending_buf.len());
+
+    let bundle = pipeline.seal_and_match();
+    summary.record_bundle(&bundle);
+
+    let mut settler = Tier1Settler::new(100);
+    settler.deposit(alice, Asset::lit("USDT"), Decimal::new(100_000, 0));
+    settler
+        .freeze(alice, "USDT", Decimal::new(100_000, 0))
+        .unwrap();
+    settler.deposit(bob, Asset::lit("BTC"), Decimal::ONE);
+    settler.freeze(bob, "BTC", Decimal::ONE).unwrap();
+    settler.settle_bundle(&bundle).unwrap();
+    summary.record_supply_check(&settler.verify_all_supply());
+
+    assert_eq!(summary.orders_received, 2);
+    assert_eq!(summary.rejections.len(), 1);
+    assert_eq!(summary.trade_count(), 1);
+    let market = &summary.markets["BTC/USDT"];
+    assert_eq!(market.clearing_price, Some(Decimal::new(50_000, 0)));
+    assert_eq!(market.base_volume, Decimal::ONE);
+    assert_eq!(market.quote_volume, Decimal::new(50_000, 0));
+    assert_eq!(market.remaining_orders, 1);
+    assert!(summary.supply_conserved());
+
+    let json = serde_json::to_string(&summary).unwrap();
+    let back: EpochSummary = serde_json::from_str(&json).unwrap();
+    assert_eq!(back, summary);
+}
+
+// =============================================================================
+// Test: Quote rounding dust accumulates in the dust account
+// =============================================================================
+#[test]
+fn e2e_quote_rounding_dust_is_conserved() {
+    let node_id = NodeId([0u8; 32]);
+    let dust = UserId::new();
+    let buyer = UserId::new();
+    let price = Decimal::new(100_003, 3); // 100.003: off the 2-dp USDT grid
+    let lot = Decimal::new(37, 2);
+    let sellers: Vec<UserId> = (0..25).map(|_| UserId::new()).collect();
+
+    let mut bid = Order::dummy_limit(OrderSide::Buy, price, lot * Decimal::from(25));
+    bid.user_id = buyer;
+    let mut orders = vec![bid];
+    for (seq, seller) in (1..).zip(&sellers) {
+        let mut ask = Order::dummy_limit(OrderSide::Sell, price, lot);
+        ask.user_id = *seller;
+        ask.sequence = seq;
+        orders.push(ask);
+    }
+    let batch = BatchSealer::new(node_id).seal(EpochId(1), orders);
+    let bundle = BatchMatcher::new(node_id)
+        .with_quote_rounding(QuoteRounding {
+            scale: 2,
+            bias: RoundingStrategy::ToZero,
+            dust_account: dust,
+        })
+        .match_batch(&batch)
+        .unwrap();
+    assert_eq!(bundle.trades.len(), 25);
+
+    let residual: Decimal = bundle
+        .trades
+        .iter()
+        .map(|t| t.price * t.quantity - t.quote_amount)
+        .sum();
+    assert!(residual > Decimal::ZERO);
+    assert!(bundle.trades.iter().all(|t| t.quote_amount.scale() <= 2));
+
+    let mut settler = Tier1Settler::new(100).with_leak_tracking();
+    let escrowed = price * lot * Decimal::from(25);
+    settler.deposit(buyer, Asset::lit("USDT"), escrowed);
+    settler.freeze(buyer, "USDT", escrowed).unwrap();
+    for seller in &sellers {
+        settler.deposit(*seller, Asset::lit("BTC"), lot);
+        settler.freeze(*seller, "BTC", lot).unwrap();
+    }
+    settler.settle_bundle(&bundle).unwrap();
+
+    assert_eq!(settler.balance(dust, "USDT").available, residual);
+    assert_eq!(settler.balance(buyer, "USDT"), BalanceEntry::default());
+    settler.verify_all_supply().unwrap();
+    assert!(settler.locate_leaks().is_empty());
+}
+
+// =============================================================================
+// Test: Fills free open-order slots, so the cap never locks a user out
+// =============================================================================
+#[test]
+fn e2e_open_order_cap_frees_slots_on_fill() {
+    let cap = constants::DEFAULT_MAX_ORDERS_PER_USER;
+    let rounds = cap + 50;
+    let mut pipeline = EpochPipeline::new(EpochId(1));
+    pipeline.risk_kernel = RiskKernel::with_limits(usize::MAX, Decimal::new(100, 0), Decimal::TEN)
+        .with_max_open_orders(cap);
+
+    let alice = UserId::new();
+    let bob = UserId::new();
+    let price = Decimal::new(100, 0);
+    pipeline.deposit(alice, "USDT", price * Decimal::from(rounds));
+    pipeline.deposit(bob, "BTC", Decimal::from(rounds));
+
+    for seq in 0..rounds as u64 {
+        pipeline.submit_order(alice, OrderSide::Buy, price, Decimal::ONE, 2 * seq);
+        pipeline.submit_order(bob, OrderSide::Sell, price, Decimal::ONE, 2 * seq + 1);
+        let bundle = pipeline.seal_and_match();
+        assert_eq!(bundle.trades.len(), 1);
+        pipeline.risk_kernel.apply_bundle(&bundle);
+        pipeline.pending_buf.reset();
+
+        assert_eq!(pipeline.risk_kernel.open_order_count(&alice), 0);
+        assert_eq!(pipeline.risk_kernel.open_order_count(&bob), 0);
+    }
+}
@@ -0,0 +1,135 @@
+//! Frozen hash test vectors.
+//!
+//! Nodes agree on a batch by comparing `batch_hash` and `trade_root`. If
+//! either preimage changes, a deployed node and an upgraded one stop
+//! agreeing, so these tests pin the exact bytes for a fixed input. A
+//! failure here means the hashing scheme changed: bump the domain tag
+//! version and coordinate the rollout before updating the vectors.
+
+use chrono::{DateTime, Utc};
+use openmatch_ingress::BatchSealer;
+use openmatch_matchcore::{compute_trade_root, match_sealed_batch};
+use openmatch_types::*;
+use rust_decimal::Decimal;
+
+const NODE: NodeId = NodeId([7u8; 32]);
+
+fn fixed_time() -> DateTime<Utc> {
+    DateTime::from_timestamp(1_700_000_000, 0).expect("valid timestamp")
+}
+
+fn make_order(seed: u64, user: u64, side: OrderSide, price: i64, qty: i64) -> Order {
+    Order {
+        id: OrderId::deterministic(seed),
+        user_id: UserId::deterministic(user),
+        market: MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")),
+        side,
+        order_type: OrderType::Limit { all_or_none: false },
+        status: OrderStatus::Active,
+        price: Some(Decimal::new(price, 0)),
+        quantity: Decimal::new(qty, 0),
+        remaining_qty: Decimal::new(qty, 0),
+        sr_id: SpendRightId::deterministic(seed),
+        epoch_id: Some(EpochId(42)),
+        origin_node: NODE,
+        sequence: seed,
+        created_at: fixed_time(),
+        updated_at: fixed_time(),
+        expires_at: None,
+    }
+}
+
+fn vector_orders() -> Vec<Order> {
+    vec![
+        make_order(0, 1, OrderSide::Buy, 101, 2),
+        make_order(1, 2, OrderSide::Sell, 99, 1),
+        make_order(2, 3, OrderSide::Sell, 100, 2),
+        make_order(3, 4, OrderSide::Buy, 98, 1),
+    ]
+}
+
+#[test]
+fn batch_hash_vector() {
+    let batch = BatchSealer::new(NODE).seal(EpochId(42), vector_orders());
+    assert_eq!(
+        hex::encode(batch.batch_hash),
+        "c20d75a746d7ee5ba0b13995e2038c97d5ee72289ca061d9c103d35a07cc6ea2"
+    );
+}
+
+#[test]
+fn empty_batch_hash_vector() {
+    let batch = BatchSealer::new(NODE).seal(EpochId(42), vec![]);
+    assert_eq!(
+        hex::encode(batch.batch_hash),
+        "b293c54a0aebd7f32e937ae56ccdf91d1e6ee896db18afbe5e01495c7940d66a"
+    );
+}
+
+#[test]
+fn expiring_batch_hash_vector() {
+    let mut orders = vector_orders();
+    orders[0].expires_at = Some(fixed_time() + chrono::Duration::hours(1));
+    let batch = BatchSealer::new(NODE).seal(EpochId(42), orders);
+    assert_eq!(
+        hex::encode(batch.batch_hash),
+        "22e0a91625f0f237d625b4c5363df197bc62df2e0e9821e6a3e896f2b07ac10a"
+    );
+}
+
+#[test]
+fn matched_trade_root_vector() {
+    let batch = BatchSealer::new(NODE).seal(EpochId(42), vector_orders());
+    let bundle = match_sealed_batch(&batch).unwrap();
+    assert_eq!(bundle.trades.len(), 2);
+    assert_eq!(bundle.clearing_price, Some(Decimal::new(101, 0)));
+    assert_eq!(bundle.input_hash, batch.batch_hash);
+    assert_eq!(
+        hex::encode(bundle.trade_root),
+        "72d6fc4318383ccccfcec0d692d4503bd338c4f9dba9304eb46d9e4c76875b43"
+    );
+}
+
+#[test]
+fn trade_root_vector() {
+    let trade = Trade {
+        id: TradeId::deterministic(42, 0),
+        epoch_id: EpochId(42),
+        market: MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")),
+        taker_order_id: OrderId::deterministic(0),
+        taker_user_id: UserId::deterministic(1),
+        maker_order_id: OrderId::deterministic(1),
+        maker_user_id: UserId::deterministic(2),
+        price: Decimal::new(10_000, 2),
+        quantity: Decimal::new(15, 1),
+        quote_amount: Decimal::new(150, 0),
+        taker_side: OrderSide::Buy,
+        matcher_node: NODE,
+        executed_at: fixed_time(),
+        quote_dust: None,
+    };
+    assert_eq!(
+        hex::encode(compute_trade_root(EpochId(42), &[trade])),
+        "8b6fcfb120b227df8f8c674f17ae2041c83f344f532ff221419e5e8d9bcd8a13"
+    );
+    assert_eq!(
+        hex::encode(compute_trade_root(EpochId(42), &[])),
+        "5ceed8ad437fb91f02ecb3763257dd32da4fa919a217532ff139dc49a2432ac2"
+    );
+}
+
+#[test]
+fn id_vectors() {
+    assert_eq!(
+        TradeId::deterministic(42, 0).to_string(),
+        "57241a6c-031a-206c-27a5-c27b479fdf5b"
+    );
+    assert_eq!(
+        OrderId::deterministic(0).to_string(),
+        "b32b574c-bb1d-c979-4aec-33263e161805"
+    );
+    assert_eq!(
+        UserId::deterministic(0).to_string(),
+        "cb06350a-dacc-25fb-525b-9104e066a02b"
+    );
+}
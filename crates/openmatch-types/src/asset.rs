@@ -0,0 +1,237 @@
+//! Asset display metadata.
+//!
+//! Balances and markets refer to assets by bare symbol ([`Asset`]). The
+//! [`AssetRegistry`] attaches a decimal precision and display name to each
+//! symbol so UIs can format amounts and ingress can reject amounts finer
+//! than the asset supports.
+
+use std::collections::BTreeMap;
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::{Asset, MarketConfig, OpenmatchError, Result};
+
+/// Display metadata for one asset.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssetInfo {
+    /// Ticker symbol, as used in balances and markets (e.g., "BTC").
+    pub symbol: Asset,
+    /// Maximum number of decimal places an amount may carry.
+    pub decimals: u32,
+    /// Human-readable name (e.g., "Bitcoin").
+    pub name: String,
+}
+
+impl AssetInfo {
+    /// Create asset metadata.
+    #[must_use]
+    pub fn new(symbol: impl Into<Asset>, decimals: u32, name: impl Into<String>) -> Self {
+        Self {
+            symbol: symbol.into(),
+            decimals,
+            name: name.into(),
+        }
+    }
+
+    /// Format `amount` with exactly `decimals` places (banker's rounding).
+    #[must_use]
+    pub fn format_amount(&self, amount: Decimal) -> String {
+        let mut rounded = amount.round_dp(self.decimals);
+        rounded.rescale(self.decimals);
+        rounded.to_string()
+    }
+
+    /// Returns `true` if `amount` needs no more than `decimals` places.
+    /// Trailing zeros don't count: `1.50` fits an asset with 1 decimal.
+    #[must_use]
+    pub fn fits_scale(&self, amount: Decimal) -> bool {
+        amount.normalize().scale() <= self.decimals
+    }
+}
+
+/// Registry of known assets, keyed by symbol.
+#[derive(Debug, Clone, Default)]
+pub struct AssetRegistry {
+    assets: BTreeMap<Asset, AssetInfo>,
+}
+
+impl AssetRegistry {
+    /// Create an empty registry.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registry with the assets used by the default market configs
+    /// (BTC, ETH, USDT).
+    #[must_use]
+    pub fn with_defaults() -> Self {
+        let mut registry = Self::new();
+        for info in [
+            AssetInfo::new(Asset::known("BTC"), 8, "Bitcoin"),
+            AssetInfo::new(Asset::known("ETH"), 18, "Ether"),
+            AssetInfo::new(Asset::known("USDT"), 6, "Tether USD"),
+        ] {
+            registry.assets.insert(info.symbol.clone(), info);
+        }
+        registry
+    }
+
+    /// Register an asset.
+    ///
+    /// # Errors
+    /// Returns `Configuration` if the symbol is already registered.
+    pub fn register(&mut self, info: AssetInfo) -> Result<()> {
+        if self.assets.contains_key(&info.symbol) {
+            return Err(OpenmatchError::Configuration(format!(
+                "asset {} is already registered",
+                info.symbol
+            )));
+        }
+        self.assets.insert(info.symbol.clone(), info);
+        Ok(())
+    }
+
+    /// Look up an asset's metadata.
+    #[must_use]
+    pub fn get(&self, symbol: &str) -> Option<&AssetInfo> {
+        self.assets.get(symbol)
+    }
+
+    /// Format `amount` to the asset's decimals, or `None` if the asset is
+    /// unknown.
+    #[must_use]
+    pub fn format_amount(&self, symbol: &str, amount: Decimal) -> Option<String> {
+        self.get(symbol).map(|info| info.format_amount(amount))
+    }
+
+    /// Check that `amount` is representable in the asset's precision.
+    ///
+    /// # Errors
+    /// `Configuration` if the asset is unknown, `InvalidOrder` if the
+    /// amount carries more decimal places than the asset allows.
+    pub fn check_scale(&self, symbol: &str, amount: Decimal) -> Result<()> {
+        let info = self.lookup(symbol)?;
+        if info.fits_scale(amount) {
+            Ok(())
+        } else {
+            Err(OpenmatchError::InvalidOrder {
+                reason: format!("{amount} {symbol} exceeds {} decimal places", info.decimals),
+            })
+        }
+    }
+
+    /// Check a market against the registry: both assets must be known,
+    /// the tick size must fit the quote precision and the lot size the
+    /// base precision.
+    ///
+    /// # Errors
+    /// Returns `Configuration` describing the first mismatch.
+    pub fn check_market(&self, config: &MarketConfig) -> Result<()> {
+        let base = self.lookup(&config.base)?;
+        let quote = self.lookup(&config.quote)?;
+        if !quote.fits_scale(config.tick_size) {
+            return Err(OpenmatchError::Configuration(format!(
+                "{}: tick size {} is finer than {} decimals",
+                config.symbol(),
+                config.tick_size,
+                quote.decimals
+            )));
+        }
+        if !base.fits_scale(config.lot_size) {
+            return Err(OpenmatchError::Configuration(format!(
+                "{}: lot size {} is finer than {} decimals",
+                config.symbol(),
+                config.lot_size,
+                base.decimals
+            )));
+        }
+        Ok(())
+    }
+
+    /// Number of registered assets.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.assets.len()
+    }
+
+    /// Returns `true` if no assets are registered.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.assets.is_empty()
+    }
+
+    fn lookup(&self, symbol: &str) -> Result<&AssetInfo> {
+        self.get(symbol)
+            .ok_or_else(|| OpenmatchError::Configuration(format!("unknown asset {symbol}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn register_and_lookup() {
+        let mut registry = AssetRegistry::new();
+        registry
+            .register(AssetInfo::new(Asset::lit("SOL"), 9, "Solana"))
+            .unwrap();
+        assert_eq!(registry.get("SOL").unwrap().name, "Solana");
+        assert!(registry.get("DOGE").is_none());
+
+        let err = registry
+            .register(AssetInfo::new(Asset::lit("SOL"), 6, "Other"))
+            .unwrap_err();
+        assert!(matches!(err, OpenmatchError::Configuration(_)));
+        assert_eq!(registry.len(), 1);
+    }
+
+    #[test]
+    fn format_amount_pads_and_rounds_to_decimals() {
+        let registry = AssetRegistry::with_defaults();
+        assert_eq!(
+            registry.format_amount("USDT", Decimal::new(15, 1)).unwrap(),
+            "1.500000"
+        );
+        assert_eq!(
+            registry
+                .format_amount("BTC", Decimal::new(123_456_789_125, 11))
+                .unwrap(),
+            "1.23456789"
+        );
+        assert!(registry.format_amount("DOGE", Decimal::ONE).is_none());
+    }
+
+    #[test]
+    fn check_scale_ignores_trailing_zeros() {
+        let registry = AssetRegistry::with_defaults();
+        assert!(
+            registry
+                .check_scale("USDT", Decimal::new(1_500_000, 6))
+                .is_ok()
+        );
+        assert!(
+            registry
+                .check_scale("USDT", Decimal::new(1_000_000_000, 9))
+                .is_ok()
+        );
+        assert!(matches!(
+            registry.check_scale("USDT", Decimal::new(1, 7)),
+            Err(OpenmatchError::InvalidOrder { .. })
+        ));
+        assert!(registry.check_scale("DOGE", Decimal::ONE).is_err());
+    }
+
+    #[test]
+    fn default_markets_fit_default_assets() {
+        let registry = AssetRegistry::with_defaults();
+        registry.check_market(&MarketConfig::btc_usdt()).unwrap();
+        registry.check_market(&MarketConfig::eth_usdt()).unwrap();
+
+        let mut cfg = MarketConfig::btc_usdt();
+        cfg.lot_size = Decimal::new(1, 9);
+        assert!(registry.check_market(&cfg).is_err());
+    }
+}
@@ -1,18 +1,70 @@
 //! Balance tracking types for the OpenMatch escrow model.
 //!
 //! Every user has an `available` balance (usable for new orders)
-//! and a `frozen` balance (locked by active orders' escrow).
+//! and a `frozen` balance (locked by active orders' escrow). Funds
+//! reserved for an external withdrawal sit in `withdraw_pending` until
+//! custody confirms or cancels it; they still count toward supply.
+//!
+//! Amounts serialize as decimal strings so web clients, whose JSON
+//! numbers are doubles, round-trip them without losing precision.
+
+use std::borrow::Borrow;
+use std::fmt;
+use std::ops::Deref;
 
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
+use crate::{OpenmatchError, Result, UserId};
+
+/// The per-(user, asset) balance operations settlement applies.
+///
+/// Implemented by the Security Envelope's balance manager, so the Finality
+/// Plane can validate and apply settlement against it without depending
+/// on it.
+pub trait BalanceLedger {
+    /// The balance of a (user, asset) pair; zero if never credited.
+    fn balance_of(&self, user_id: UserId, asset: &Asset) -> BalanceEntry;
+
+    /// Move `amount` from available to frozen.
+    ///
+    /// # Errors
+    /// `InsufficientBalance` if available < amount.
+    fn freeze_funds(&mut self, user_id: UserId, asset: &Asset, amount: Decimal) -> Result<()>;
+
+    /// Move `amount` from frozen back to available.
+    ///
+    /// # Errors
+    /// `InsufficientFrozen` if frozen < amount.
+    fn unfreeze_funds(&mut self, user_id: UserId, asset: &Asset, amount: Decimal) -> Result<()>;
+
+    /// Remove `amount` from frozen (paid out by settlement).
+    ///
+    /// # Errors
+    /// `InsufficientFrozen` if frozen < amount.
+    fn consume_frozen_funds(
+        &mut self,
+        user_id: UserId,
+        asset: &Asset,
+        amount: Decimal,
+    ) -> Result<()>;
+
+    /// Add `amount` to available.
+    fn credit_funds(&mut self, user_id: UserId, asset: &Asset, amount: Decimal);
+}
+
 /// A single balance entry for a (user, asset) pair.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct BalanceEntry {
     /// Available for new orders / withdrawal.
+    #[serde(with = "rust_decimal::serde::str")]
     pub available: Decimal,
     /// Frozen / escrowed for active orders awaiting matching or settlement.
+    #[serde(with = "rust_decimal::serde::str")]
     pub frozen: Decimal,
+    /// Reserved for a withdrawal awaiting custody confirmation.
+    #[serde(default, with = "rust_decimal::serde::str")]
+    pub withdraw_pending: Decimal,
 }
 
 impl BalanceEntry {
@@ -22,19 +74,40 @@ impl BalanceEntry {
         Self {
             available: Decimal::ZERO,
             frozen: Decimal::ZERO,
+            withdraw_pending: Decimal::ZERO,
         }
     }
 
-    /// Total balance (available + frozen).
+    /// Total balance (available + frozen + withdraw pending).
+    ///
+    /// # Panics
+    /// Panics if the sum overflows `Decimal`. Use [`Self::checked_total`]
+    /// or [`Self::saturating_total`] where entries may hold extreme values.
     #[must_use]
     pub fn total(&self) -> Decimal {
-        self.available + self.frozen
+        self.available + self.frozen + self.withdraw_pending
+    }
+
+    /// Total balance, or `None` on overflow.
+    #[must_use]
+    pub fn checked_total(&self) -> Option<Decimal> {
+        self.available
+            .checked_add(self.frozen)?
+            .checked_add(self.withdraw_pending)
+    }
+
+    /// Total balance, clamped to `Decimal::MAX` / `Decimal::MIN`.
+    #[must_use]
+    pub fn saturating_total(&self) -> Decimal {
+        self.available
+            .saturating_add(self.frozen)
+            .saturating_add(self.withdraw_pending)
     }
 
     /// Whether this entry has no balance at all.
     #[must_use]
     pub fn is_zero(&self) -> bool {
-        self.available.is_zero() && self.frozen.is_zero()
+        self.available.is_zero() && self.frozen.is_zero() && self.withdraw_pending.is_zero()
     }
 }
 
@@ -44,8 +117,159 @@ impl Default for BalanceEntry {
     }
 }
 
-/// Type alias for asset identifiers (e.g., "BTC", "USDT", "ETH").
-pub type Asset = String;
+/// Asset symbol (e.g., "BTC", "USDT", "ETH").
+///
+/// A validated, non-empty, uppercase ticker: ASCII uppercase letters and
+/// digits, plus `.`, `-` and `_` as separators. Being a distinct type
+/// keeps asset symbols from being confused with other strings in balance
+/// and settlement signatures; it derefs to `str` so read-only code keeps
+/// working unchanged.
+///
+/// Serializes as a bare string; deserialization validates.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Ord, PartialOrd, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Asset(String);
+
+impl Asset {
+    /// Validate and wrap an asset symbol.
+    ///
+    /// # Errors
+    /// Returns `Configuration` if the symbol is empty or contains anything
+    /// other than uppercase ASCII letters, digits, `.`, `-` or `_`.
+    pub fn new(symbol: impl Into<String>) -> Result<Self> {
+        let symbol = symbol.into();
+        if Self::is_valid(&symbol) {
+            Ok(Self(symbol))
+        } else {
+            Err(OpenmatchError::Configuration(format!(
+                "invalid asset symbol {symbol:?}"
+            )))
+        }
+    }
+
+    /// Wrap a built-in symbol known to be valid, such as the defaults in
+    /// [`crate::MarketConfig::btc_usdt`].
+    pub(crate) fn known(symbol: &'static str) -> Self {
+        debug_assert!(Self::is_valid(symbol), "invalid built-in asset {symbol:?}");
+        Self(symbol.to_string())
+    }
+
+    fn is_valid(symbol: &str) -> bool {
+        !symbol.is_empty()
+            && symbol.chars().all(|c| {
+                c.is_ascii_uppercase() || c.is_ascii_digit() || matches!(c, '.' | '-' | '_')
+            })
+    }
+
+    /// The symbol as a string slice.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Test helpers.
+#[cfg(any(test, feature = "test-helpers"))]
+impl Asset {
+    /// Wrap a symbol literal such as `"BTC"`.
+    ///
+    /// # Panics
+    /// Panics if the symbol is invalid.
+    #[must_use]
+    pub fn lit(symbol: &str) -> Self {
+        Self::new(symbol).unwrap_or_else(|e| panic!("{e}"))
+    }
+}
+
+impl TryFrom<&str> for Asset {
+    type Error = OpenmatchError;
+
+    fn try_from(symbol: &str) -> Result<Self> {
+        Self::new(symbol)
+    }
+}
+
+impl TryFrom<&String> for Asset {
+    type Error = OpenmatchError;
+
+    fn try_from(symbol: &String) -> Result<Self> {
+        Self::new(symbol.as_str())
+    }
+}
+
+impl From<&Asset> for Asset {
+    fn from(asset: &Asset) -> Self {
+        asset.clone()
+    }
+}
+
+impl TryFrom<String> for Asset {
+    type Error = OpenmatchError;
+
+    fn try_from(symbol: String) -> Result<Self> {
+        Self::new(symbol)
+    }
+}
+
+/// Anything a fallible API accepts as an asset: an [`Asset`], an `&Asset`,
+/// or a symbol string that is validated on conversion.
+pub trait IntoAsset {
+    /// Convert into an [`Asset`].
+    ///
+    /// # Errors
+    /// Returns `Configuration` if a symbol string is invalid.
+    fn into_asset(self) -> Result<Asset>;
+}
+
+impl<T: TryInto<Asset, Error: Into<OpenmatchError>>> IntoAsset for T {
+    fn into_asset(self) -> Result<Asset> {
+        self.try_into().map_err(Into::into)
+    }
+}
+
+impl From<Asset> for String {
+    fn from(asset: Asset) -> Self {
+        asset.0
+    }
+}
+
+impl Deref for Asset {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for Asset {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Borrow<str> for Asset {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl PartialEq<str> for Asset {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<&str> for Asset {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+impl fmt::Display for Asset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -64,19 +288,113 @@ mod tests {
         let entry = BalanceEntry {
             available: Decimal::new(100, 0),
             frozen: Decimal::new(50, 0),
+            withdraw_pending: Decimal::new(25, 0),
         };
-        assert_eq!(entry.total(), Decimal::new(150, 0));
+        assert_eq!(entry.total(), Decimal::new(175, 0));
         assert!(!entry.is_zero());
     }
 
+    #[test]
+    fn balance_entry_near_max_total_does_not_panic() {
+        let entry = BalanceEntry {
+            available: Decimal::MAX,
+            frozen: Decimal::MAX,
+            withdraw_pending: Decimal::ZERO,
+        };
+        assert_eq!(entry.checked_total(), None);
+        assert_eq!(entry.saturating_total(), Decimal::MAX);
+
+        let entry = BalanceEntry {
+            available: Decimal::MAX - Decimal::ONE,
+            frozen: Decimal::ONE,
+            withdraw_pending: Decimal::ZERO,
+        };
+        assert_eq!(entry.checked_total(), Some(Decimal::MAX));
+    }
+
     #[test]
     fn balance_entry_serde_roundtrip() {
         let entry = BalanceEntry {
             available: Decimal::new(12345, 2), // 123.45
             frozen: Decimal::new(678, 1),      // 67.8
+            withdraw_pending: Decimal::ZERO,
         };
         let json = serde_json::to_string(&entry).unwrap();
         let back: BalanceEntry = serde_json::from_str(&json).unwrap();
         assert_eq!(entry, back);
     }
+
+    #[test]
+    fn balance_entry_serializes_amounts_as_strings() {
+        // 28 significant digits: far beyond an f64's 15-17
+        let precise = Decimal::from_str_exact("1234567890.123456789012345678").unwrap();
+        let entry = BalanceEntry {
+            available: precise,
+            frozen: Decimal::new(1, 18),
+            withdraw_pending: Decimal::ZERO,
+        };
+        let json = serde_json::to_string(&entry).unwrap();
+        assert!(
+            json.contains(r#""available":"1234567890.123456789012345678""#),
+            "{json}"
+        );
+
+        let back: BalanceEntry = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, entry);
+        assert_eq!(back.available.to_string(), precise.to_string());
+
+        // Entries written before `withdraw_pending` existed still load
+        let legacy: BalanceEntry =
+            serde_json::from_str(r#"{"available":"1.5","frozen":"0"}"#).unwrap();
+        assert_eq!(legacy.withdraw_pending, Decimal::ZERO);
+    }
+
+    #[test]
+    fn asset_construction_validates_symbol() {
+        assert_eq!(Asset::new("BTC").unwrap(), "BTC");
+        assert_eq!(Asset::new("USDC.E").unwrap().as_str(), "USDC.E");
+        for bad in ["", "btc", "B TC", "BTC/USDT"] {
+            assert!(
+                matches!(Asset::new(bad), Err(OpenmatchError::Configuration(_))),
+                "{bad:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn asset_from_literal_behaves_like_str() {
+        let asset = Asset::lit("BTC");
+        assert_eq!(asset, Asset::new("BTC").unwrap());
+        assert_eq!(asset.len(), 3);
+        assert!(asset.starts_with('B'));
+        assert_eq!(asset.to_string(), "BTC");
+
+        let mut by_asset = std::collections::HashMap::new();
+        by_asset.insert(asset, Decimal::ONE);
+        assert_eq!(by_asset.get("BTC"), Some(&Decimal::ONE));
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid asset symbol")]
+    fn asset_from_invalid_literal_panics() {
+        let _ = Asset::lit("btc");
+    }
+
+    #[test]
+    fn asset_try_from_str_rejects_invalid_symbol() {
+        assert_eq!(Asset::try_from("ETH").unwrap(), "ETH");
+        assert!(matches!(
+            Asset::try_from("eth"),
+            Err(OpenmatchError::Configuration(_))
+        ));
+    }
+
+    #[test]
+    fn asset_serializes_as_bare_validated_string() {
+        let json = serde_json::to_string(&Asset::lit("USDT")).unwrap();
+        assert_eq!(json, r#""USDT""#);
+        let back: Asset = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, "USDT");
+        assert!(serde_json::from_str::<Asset>(r#""usdt""#).is_err());
+    }
 }
@@ -0,0 +1,91 @@
+//! Time sources.
+//!
+//! Components that stamp or compare wall-clock times take an
+//! `Arc<dyn Clock>` instead of calling `Utc::now()` directly. Production
+//! code uses [`SystemClock`]; tests and replays inject a [`FixedClock`] so
+//! timestamps such as `created_at`, `expires_at` and `executed_at` are
+//! reproducible.
+
+use std::{
+    fmt,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use chrono::{DateTime, Duration, Utc};
+
+/// A source of the current time.
+pub trait Clock: fmt::Debug + Send + Sync {
+    /// The current time according to this clock.
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// The operating system's wall clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl SystemClock {
+    /// A shared handle, ready to inject.
+    #[must_use]
+    pub fn shared() -> Arc<dyn Clock> {
+        Arc::new(Self)
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock that only moves when told to.
+///
+/// Clones share the same time, so a test can keep one handle and advance
+/// the clock it injected elsewhere.
+#[derive(Debug, Clone)]
+pub struct FixedClock {
+    now: Arc<Mutex<DateTime<Utc>>>,
+}
+
+impl FixedClock {
+    /// A clock frozen at `now`.
+    #[must_use]
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self {
+            now: Arc::new(Mutex::new(now)),
+        }
+    }
+
+    /// Move the clock to `now` (backwards is allowed).
+    pub fn set(&self, now: DateTime<Utc>) {
+        *self.now.lock().unwrap_or_else(PoisonError::into_inner) = now;
+    }
+
+    /// Move the clock forward by `by`.
+    pub fn advance(&self, by: Duration) {
+        *self.now.lock().unwrap_or_else(PoisonError::into_inner) += by;
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_clock_clones_share_time() {
+        let t0 = DateTime::UNIX_EPOCH;
+        let clock = FixedClock::new(t0);
+        let injected: Arc<dyn Clock> = Arc::new(clock.clone());
+        assert_eq!(injected.now(), t0);
+
+        clock.advance(Duration::seconds(5));
+        assert_eq!(injected.now(), t0 + Duration::seconds(5));
+        clock.set(t0);
+        assert_eq!(injected.now(), t0);
+    }
+}
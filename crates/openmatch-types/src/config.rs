@@ -5,7 +5,7 @@ use std::net::SocketAddr;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
-use crate::{EpochConfig, NodeId, constants};
+use crate::{Asset, EpochConfig, NodeId, OpenmatchError, Result, Trade, constants};
 
 /// Configuration for a single OpenMatch node.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -49,9 +49,9 @@ impl Default for NetworkConfig {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MarketConfig {
     /// Base asset (e.g., "BTC").
-    pub base: String,
+    pub base: Asset,
     /// Quote asset (e.g., "USDT").
-    pub quote: String,
+    pub quote: Asset,
     /// Minimum order size in base asset.
     pub min_order_size: Decimal,
     /// Tick size (price granularity).
@@ -60,6 +60,15 @@ pub struct MarketConfig {
     pub lot_size: Decimal,
     /// Maximum number of open orders per user for this market.
     pub max_orders_per_user: usize,
+    /// Whether new orders are accepted. When `false` the market is halted:
+    /// only cancels pass ingress. Configs written before halts existed
+    /// omit the field and load as enabled.
+    #[serde(default = "default_trading_enabled")]
+    pub trading_enabled: bool,
+}
+
+fn default_trading_enabled() -> bool {
+    true
 }
 
 impl MarketConfig {
@@ -67,12 +76,13 @@ impl MarketConfig {
     #[must_use]
     pub fn btc_usdt() -> Self {
         Self {
-            base: "BTC".to_string(),
-            quote: "USDT".to_string(),
+            base: Asset::known("BTC"),
+            quote: Asset::known("USDT"),
             min_order_size: Decimal::new(1, 5), // 0.00001 BTC
             tick_size: Decimal::new(1, 2),      // 0.01 USDT
             lot_size: Decimal::new(1, 5),       // 0.00001 BTC
             max_orders_per_user: constants::DEFAULT_MAX_ORDERS_PER_USER,
+            trading_enabled: true,
         }
     }
 
@@ -80,12 +90,13 @@ impl MarketConfig {
     #[must_use]
     pub fn eth_usdt() -> Self {
         Self {
-            base: "ETH".to_string(),
-            quote: "USDT".to_string(),
+            base: Asset::known("ETH"),
+            quote: Asset::known("USDT"),
             min_order_size: Decimal::new(1, 4), // 0.0001 ETH
             tick_size: Decimal::new(1, 2),      // 0.01 USDT
             lot_size: Decimal::new(1, 4),       // 0.0001 ETH
             max_orders_per_user: constants::DEFAULT_MAX_ORDERS_PER_USER,
+            trading_enabled: true,
         }
     }
 
@@ -96,6 +107,63 @@ impl MarketConfig {
     }
 }
 
+/// Per-trade fee rates, applied to the trade's quote amount and settled in
+/// the quote asset. A negative `maker_rate` is a rebate paid to the maker
+/// out of the collected-fee pool.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeSchedule {
+    /// Fraction of the quote amount charged to the maker (negative = rebate).
+    pub maker_rate: Decimal,
+    /// Fraction of the quote amount charged to the taker.
+    pub taker_rate: Decimal,
+}
+
+impl FeeSchedule {
+    /// Create a fee schedule.
+    #[must_use]
+    pub fn new(maker_rate: Decimal, taker_rate: Decimal) -> Self {
+        Self {
+            maker_rate,
+            taker_rate,
+        }
+    }
+
+    /// Check the rates are usable: the taker rate is non-negative and both
+    /// rates are strictly between -1 and 1.
+    ///
+    /// # Errors
+    /// Returns `Configuration` describing the offending rate.
+    pub fn validate(&self) -> Result<()> {
+        if self.taker_rate < Decimal::ZERO {
+            return Err(OpenmatchError::Configuration(format!(
+                "taker fee rate {} is negative",
+                self.taker_rate
+            )));
+        }
+        for rate in [self.maker_rate, self.taker_rate] {
+            if rate.abs() >= Decimal::ONE {
+                return Err(OpenmatchError::Configuration(format!(
+                    "fee rate {rate} is not within (-1, 1)"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Quote-asset fees owed by `(buyer, seller)` for `trade`; negative is
+    /// a rebate.
+    #[must_use]
+    pub fn buyer_seller_fees(&self, trade: &Trade) -> (Decimal, Decimal) {
+        let taker = (self.taker_rate * trade.quote_amount).normalize();
+        let maker = (self.maker_rate * trade.quote_amount).normalize();
+        if trade.taker_is_buyer() {
+            (taker, maker)
+        } else {
+            (maker, taker)
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -106,6 +174,24 @@ mod tests {
         assert_eq!(cfg.symbol(), "BTC/USDT");
         assert!(cfg.min_order_size > Decimal::ZERO);
         assert!(cfg.tick_size > Decimal::ZERO);
+        assert!(cfg.trading_enabled);
+    }
+
+    #[test]
+    fn fee_schedule_allows_maker_rebate_only() {
+        let rebate = FeeSchedule::new(Decimal::new(-1, 3), Decimal::new(2, 3));
+        assert!(rebate.validate().is_ok());
+        assert!(FeeSchedule::default().validate().is_ok());
+        assert!(
+            FeeSchedule::new(Decimal::ZERO, Decimal::new(-1, 3))
+                .validate()
+                .is_err()
+        );
+        assert!(
+            FeeSchedule::new(Decimal::NEGATIVE_ONE, Decimal::ZERO)
+                .validate()
+                .is_err()
+        );
     }
 
     #[test]
@@ -124,5 +210,17 @@ mod tests {
         assert_eq!(cfg.base, back.base);
         assert_eq!(cfg.quote, back.quote);
         assert_eq!(cfg.tick_size, back.tick_size);
+        assert_eq!(cfg.trading_enabled, back.trading_enabled);
+    }
+
+    #[test]
+    fn market_config_without_trading_enabled_defaults_to_enabled() {
+        let mut json = serde_json::to_value(MarketConfig::btc_usdt()).unwrap();
+        json.as_object_mut()
+            .unwrap()
+            .remove("trading_enabled")
+            .unwrap();
+        let cfg: MarketConfig = serde_json::from_value(json).unwrap();
+        assert!(cfg.trading_enabled);
     }
 }
@@ -9,13 +9,20 @@
 //! During FINALIZE, trades are settled via the 3-tier settlement engine and
 //! SpendRights are consumed (ACTIVE → SPENT).
 
-use std::{fmt, time::Duration};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt,
+    time::Duration,
+};
 
 use chrono::{DateTime, Utc};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
-use crate::{EpochId, NodeId, Order, Trade, constants};
+use crate::{
+    EpochId, MarketPair, NodeId, OpenmatchError, Order, OrderId, Result, Trade, UserId, constants,
+};
 
 /// The four non-overlapping phases of an epoch.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -52,6 +59,42 @@ impl EpochPhase {
             Self::Finalize => Self::Collect,
         }
     }
+
+    /// Returns `true` if moving from `prev` to `self` is a legal transition
+    /// (exactly one step along COLLECT → SEAL → MATCH → FINALIZE → COLLECT).
+    #[must_use]
+    pub fn can_follow(self, prev: Self) -> bool {
+        prev.next() == self
+    }
+
+    /// Returns `true` if balances are stable enough to withdraw from
+    /// (COLLECT and SEAL). During MATCH and FINALIZE balances are in flux.
+    #[must_use]
+    pub fn allows_withdrawals(self) -> bool {
+        matches!(self, Self::Collect | Self::Seal)
+    }
+
+    /// Returns `true` if new orders may enter the pending buffer (COLLECT).
+    #[must_use]
+    pub fn allows_new_orders(self) -> bool {
+        matches!(self, Self::Collect)
+    }
+
+    /// Returns `true` if cancel orders may enter the pending buffer.
+    ///
+    /// Cancels are batch input like any other order, so they are only
+    /// accepted while collecting; once sealed, the batch is fixed.
+    #[must_use]
+    pub fn allows_cancels(self) -> bool {
+        matches!(self, Self::Collect)
+    }
+
+    /// Returns `true` for the last phase of an epoch (FINALIZE); the next
+    /// transition starts a new epoch.
+    #[must_use]
+    pub fn is_terminal(self) -> bool {
+        self.next() == Self::Collect
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -77,6 +120,39 @@ pub struct SealedBatch {
     pub sealer_node: NodeId,
 }
 
+impl SealedBatch {
+    /// Check that `orders` are in the canonical sealing order: ascending
+    /// `(sequence, id)`, as produced by `BatchSealer::seal`.
+    ///
+    /// The matcher ranks crossing orders itself (price, then `(sequence,
+    /// id)`), but it applies cancels in batch order and `batch_hash` commits
+    /// to this order, so a reordered batch is not the batch that was sealed.
+    /// `BatchMatcher` runs this check before matching.
+    ///
+    /// # Errors
+    /// `DeterminismViolation` naming the first pair of orders out of order
+    /// (a repeated `(sequence, id)` counts as out of order).
+    pub fn verify_canonical_order(&self) -> Result<()> {
+        for (i, pair) in self.orders.windows(2).enumerate() {
+            let (prev, next) = (&pair[0], &pair[1]);
+            if (prev.sequence, prev.id) >= (next.sequence, next.id) {
+                return Err(OpenmatchError::DeterminismViolation {
+                    expected: "orders sorted by (sequence, id)".to_string(),
+                    actual: format!(
+                        "order {} (seq {}) at index {} follows order {} (seq {})",
+                        next.id,
+                        next.sequence,
+                        i + 1,
+                        prev.id,
+                        prev.sequence
+                    ),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
 // ---------------------------------------------------------------------------
 // TradeBundle — the deterministic output from MatchCore
 // ---------------------------------------------------------------------------
@@ -99,6 +175,243 @@ pub struct TradeBundle {
     pub clearing_price: Option<Decimal>,
     /// Orders that remain unmatched (partially filled or no crossing).
     pub remaining_orders: Vec<Order>,
+    /// Orders removed by a same-user cancel in this batch. Their escrow
+    /// should be released.
+    #[serde(default)]
+    pub cancelled_orders: Vec<Order>,
+    /// Why each remaining order didn't (fully) fill, in the same order as
+    /// `remaining_orders`.
+    #[serde(default)]
+    pub unfilled_reasons: Vec<(OrderId, UnfilledReason)>,
+    /// Crossing pairs that self-trade prevention refused to fill, in
+    /// matching order. Each is a wash-trade attempt for compliance review.
+    #[serde(default)]
+    pub wash_trade_attempts: Vec<WashTradeAttempt>,
+}
+
+/// Why an order was returned in `TradeBundle::remaining_orders`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum UnfilledReason {
+    /// The batch didn't clear at all: the book didn't cross, too few
+    /// users crossed, or only market orders crossed with no reference
+    /// price.
+    NoCrossing,
+    /// The order's limit price is on the wrong side of the clearing price.
+    OutOfMoney,
+    /// Eligible at the clearing price, but the other side ran out (or a
+    /// fill cap applied) before this order was filled.
+    Rationed,
+    /// Eligible, and the opposite volume still open belongs to the same
+    /// user, so self-trade prevention blocked the fill.
+    SelfTrade,
+    /// An all-or-none order the batch could only have filled partially.
+    AllOrNoneHeld,
+}
+
+/// A buy and a sell from the same user that crossed at the clearing price
+/// and were kept apart by self-trade prevention.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct WashTradeAttempt {
+    /// The user on both sides.
+    pub user_id: UserId,
+    /// The buy order that would have taken.
+    pub buy_order_id: OrderId,
+    /// The sell order that would have been hit.
+    pub sell_order_id: OrderId,
+    /// The epoch whose batch contained the pair.
+    pub epoch_id: EpochId,
+}
+
+impl TradeBundle {
+    /// Returns `true` if matching produced no trades. Remaining and
+    /// cancelled orders don't count.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.trades.is_empty()
+    }
+
+    /// Sum of base quantity over all trades.
+    ///
+    /// Spans every market in the bundle; filter by market first if they
+    /// don't share a base asset.
+    #[must_use]
+    pub fn total_volume(&self) -> Decimal {
+        self.trades.iter().map(|t| t.quantity).sum()
+    }
+
+    /// Sum of quote amount over all trades. Like
+    /// [`TradeBundle::total_volume`], this mixes quote assets if the
+    /// bundle spans markets with different quotes.
+    #[must_use]
+    pub fn total_quote(&self) -> Decimal {
+        self.trades.iter().map(|t| t.quote_amount).sum()
+    }
+
+    /// Every market that traded in this bundle.
+    #[must_use]
+    pub fn markets(&self) -> HashSet<MarketPair> {
+        self.trades.iter().map(|t| t.market.clone()).collect()
+    }
+
+    /// Trades in which `user` is either the taker or the maker, in bundle order.
+    #[must_use]
+    pub fn trades_for_user(&self, user: &UserId) -> Vec<&Trade> {
+        self.trades
+            .iter()
+            .filter(|t| t.taker_user_id == *user || t.maker_user_id == *user)
+            .collect()
+    }
+
+    /// Group trades by every user they touch.
+    ///
+    /// A trade appears under both the taker and the maker key (once if they
+    /// are the same user), so each settlement shard sees all of its fills.
+    #[must_use]
+    pub fn partition_by_user(&self) -> HashMap<UserId, Vec<&Trade>> {
+        let mut out: HashMap<UserId, Vec<&Trade>> = HashMap::new();
+        for trade in &self.trades {
+            out.entry(trade.taker_user_id).or_default().push(trade);
+            if trade.maker_user_id != trade.taker_user_id {
+                out.entry(trade.maker_user_id).or_default().push(trade);
+            }
+        }
+        out
+    }
+
+    /// Number of [`WashTradeAttempt`]s per user in this bundle.
+    #[must_use]
+    pub fn wash_trade_counts(&self) -> BTreeMap<UserId, usize> {
+        let mut counts = BTreeMap::new();
+        for attempt in &self.wash_trade_attempts {
+            *counts.entry(attempt.user_id).or_default() += 1;
+        }
+        counts
+    }
+
+    /// Check that no two trades share a `TradeId`.
+    ///
+    /// Deterministic IDs are unique only while the fill sequence is; a
+    /// duplicate would make settlement's idempotency guard silently drop
+    /// the second trade.
+    ///
+    /// # Errors
+    /// Returns `DeterminismViolation` naming the first repeated ID.
+    pub fn assert_unique_trade_ids(&self) -> Result<()> {
+        let mut seen = HashSet::with_capacity(self.trades.len());
+        match self.trades.iter().find(|t| !seen.insert(t.id)) {
+            Some(dup) => Err(OpenmatchError::DeterminismViolation {
+                expected: "unique trade ids".to_string(),
+                actual: format!("duplicate trade id {}", dup.id),
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// EpochSummary — operator-facing record of one epoch
+// ---------------------------------------------------------------------------
+
+/// Per-market figures in an [`EpochSummary`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MarketSummary {
+    /// Uniform clearing price, or `None` if the book didn't cross.
+    pub clearing_price: Option<Decimal>,
+    /// Number of trades.
+    pub trade_count: usize,
+    /// Matched volume in the base asset.
+    pub base_volume: Decimal,
+    /// Matched volume in the quote asset.
+    pub quote_volume: Decimal,
+    /// Orders left unmatched after matching.
+    pub remaining_orders: usize,
+    /// Orders removed by a cancel in the batch.
+    pub cancelled_orders: usize,
+}
+
+/// Structured summary of one epoch, assembled at FINALIZE for logging and
+/// metrics.
+///
+/// Fill it from the pending buffer ([`EpochSummary::record_received`],
+/// [`EpochSummary::record_rejection`]), each market's [`TradeBundle`] and
+/// the settlement supply check.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EpochSummary {
+    /// The epoch summarized.
+    pub epoch_id: EpochId,
+    /// Orders accepted into the pending buffer.
+    pub orders_received: usize,
+    /// Orders refused at ingress, as their error messages.
+    pub rejections: Vec<String>,
+    /// Per-market results, keyed by market symbol.
+    pub markets: BTreeMap<String, MarketSummary>,
+    /// Supply conservation after settlement: `None` until checked, then
+    /// `Ok(())` or the violation message.
+    pub supply_check: Option<std::result::Result<(), String>>,
+}
+
+impl EpochSummary {
+    /// Start an empty summary for `epoch_id`.
+    #[must_use]
+    pub fn new(epoch_id: EpochId) -> Self {
+        Self {
+            epoch_id,
+            orders_received: 0,
+            rejections: Vec::new(),
+            markets: BTreeMap::new(),
+            supply_check: None,
+        }
+    }
+
+    /// Count `count` orders accepted into the buffer.
+    pub fn record_received(&mut self, count: usize) {
+        self.orders_received += count;
+    }
+
+    /// Record an order refused at ingress.
+    pub fn record_rejection(&mut self, err: &OpenmatchError) {
+        self.rejections.push(err.to_string());
+    }
+
+    /// Fold in one market's matching output. A bundle with no orders at
+    /// all names no market and is ignored.
+    pub fn record_bundle(&mut self, bundle: &TradeBundle) {
+        let market = bundle
+            .trades
+            .first()
+            .map(|t| &t.market)
+            .or_else(|| bundle.remaining_orders.first().map(|o| &o.market))
+            .or_else(|| bundle.cancelled_orders.first().map(|o| &o.market));
+        let Some(market) = market else {
+            return;
+        };
+        let entry = self.markets.entry(market.symbol()).or_default();
+        entry.clearing_price = bundle.clearing_price;
+        entry.trade_count += bundle.trades.len();
+        for trade in &bundle.trades {
+            entry.base_volume += trade.quantity;
+            entry.quote_volume += trade.quote_amount;
+        }
+        entry.remaining_orders += bundle.remaining_orders.len();
+        entry.cancelled_orders += bundle.cancelled_orders.len();
+    }
+
+    /// Record the outcome of the post-settlement supply check.
+    pub fn record_supply_check(&mut self, result: &Result<()>) {
+        self.supply_check = Some(result.as_ref().copied().map_err(ToString::to_string));
+    }
+
+    /// Total trades across all markets.
+    #[must_use]
+    pub fn trade_count(&self) -> usize {
+        self.markets.values().map(|m| m.trade_count).sum()
+    }
+
+    /// Returns `true` once supply has been checked and held.
+    #[must_use]
+    pub fn supply_conserved(&self) -> bool {
+        matches!(self.supply_check, Some(Ok(())))
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -120,10 +433,44 @@ pub struct BatchDigest {
     pub order_count: usize,
     /// The node that signed this digest.
     pub signer_node: NodeId,
-    /// Ed25519 signature over (epoch_id || batch_hash || order_count).
+    /// Ed25519 signature over (batch_hash || epoch_id || order_count).
     pub signature: Vec<u8>,
 }
 
+impl BatchDigest {
+    /// Canonical bytes covered by the signature:
+    /// `batch_hash || epoch_id (u64 LE) || order_count (u64 LE)`.
+    #[must_use]
+    pub fn signing_payload(&self) -> Vec<u8> {
+        let mut payload = Vec::with_capacity(48);
+        payload.extend_from_slice(&self.batch_hash);
+        payload.extend_from_slice(&self.epoch_id.0.to_le_bytes());
+        payload.extend_from_slice(&(self.order_count as u64).to_le_bytes());
+        payload
+    }
+
+    /// Sign the digest with the node's ed25519 key.
+    ///
+    /// Also sets `signer_node` to the key's public half so peers know
+    /// which key to verify against.
+    pub fn sign(&mut self, key: &SigningKey) {
+        self.signer_node = NodeId::from_pubkey(key.verifying_key().to_bytes());
+        self.signature = key.sign(&self.signing_payload()).to_bytes().to_vec();
+    }
+
+    /// Verify the signature against `pubkey`.
+    ///
+    /// Returns `false` for a malformed signature or if any signed field
+    /// has been altered since signing.
+    #[must_use]
+    pub fn verify(&self, pubkey: &VerifyingKey) -> bool {
+        let Ok(sig) = Signature::from_slice(&self.signature) else {
+            return false;
+        };
+        pubkey.verify(&self.signing_payload(), &sig).is_ok()
+    }
+}
+
 /// Configuration for epoch timing.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EpochConfig {
@@ -163,7 +510,213 @@ impl EpochConfig {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::EpochId;
+    use crate::Asset;
+    use crate::{OrderSide, TradeId};
+
+    #[test]
+    fn phase_predicate_truth_table() {
+        // (phase, withdrawals, new orders, cancels, terminal)
+        let table = [
+            (EpochPhase::Collect, true, true, true, false),
+            (EpochPhase::Seal, true, false, false, false),
+            (EpochPhase::Match, false, false, false, false),
+            (EpochPhase::Finalize, false, false, false, true),
+        ];
+        for (phase, withdrawals, new_orders, cancels, terminal) in table {
+            assert_eq!(phase.allows_withdrawals(), withdrawals, "{phase}");
+            assert_eq!(phase.allows_new_orders(), new_orders, "{phase}");
+            assert_eq!(phase.allows_cancels(), cancels, "{phase}");
+            assert_eq!(phase.is_terminal(), terminal, "{phase}");
+        }
+    }
+
+    fn make_sealed(orders: Vec<Order>) -> SealedBatch {
+        SealedBatch {
+            epoch_id: EpochId(1),
+            orders,
+            batch_hash: [0u8; 32],
+            sealed_at: Utc::now(),
+            sealer_node: NodeId([0u8; 32]),
+        }
+    }
+
+    #[test]
+    fn canonical_order_verified() {
+        let mut orders: Vec<Order> = (0..5)
+            .map(|seq| {
+                let mut o = Order::dummy_limit(OrderSide::Buy, Decimal::new(100, 0), Decimal::ONE);
+                o.sequence = seq;
+                o
+            })
+            .collect();
+        // Two orders sharing a sequence number tie-break by id
+        orders[4].sequence = 3;
+        orders.sort_by_key(|o| (o.sequence, o.id));
+        make_sealed(orders.clone())
+            .verify_canonical_order()
+            .unwrap();
+        make_sealed(vec![]).verify_canonical_order().unwrap();
+
+        orders.swap(0, 2);
+        assert!(matches!(
+            make_sealed(orders).verify_canonical_order(),
+            Err(OpenmatchError::DeterminismViolation { .. })
+        ));
+    }
+
+    #[test]
+    fn repeated_order_is_not_canonical() {
+        let order = Order::dummy_limit(OrderSide::Sell, Decimal::new(100, 0), Decimal::ONE);
+        let batch = make_sealed(vec![order.clone(), order]);
+        assert!(batch.verify_canonical_order().is_err());
+    }
+
+    fn make_trade(seq: u64, taker: UserId, maker: UserId) -> Trade {
+        Trade {
+            id: TradeId::deterministic(1, seq),
+            epoch_id: EpochId(1),
+            market: MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")),
+            taker_order_id: OrderId::new(),
+            taker_user_id: taker,
+            maker_order_id: OrderId::new(),
+            maker_user_id: maker,
+            price: Decimal::new(100, 0),
+            quantity: Decimal::ONE,
+            quote_amount: Decimal::new(100, 0),
+            taker_side: OrderSide::Buy,
+            matcher_node: NodeId([0u8; 32]),
+            executed_at: Utc::now(),
+            quote_dust: None,
+        }
+    }
+
+    fn make_bundle(trades: Vec<Trade>) -> TradeBundle {
+        TradeBundle {
+            epoch_id: EpochId(1),
+            trades,
+            trade_root: [0u8; 32],
+            input_hash: [0u8; 32],
+            clearing_price: None,
+            remaining_orders: vec![],
+            cancelled_orders: vec![],
+            unfilled_reasons: vec![],
+            wash_trade_attempts: vec![],
+        }
+    }
+
+    #[test]
+    fn trade_bundle_user_slices() {
+        let (alice, bob, carol) = (UserId::new(), UserId::new(), UserId::new());
+        let bundle = make_bundle(vec![
+            make_trade(0, alice, bob),
+            make_trade(1, bob, carol),
+            make_trade(2, carol, alice),
+        ]);
+        let ids = |trades: Vec<&Trade>| trades.iter().map(|t| t.id).collect::<Vec<_>>();
+        let t = &bundle.trades;
+
+        assert_eq!(ids(bundle.trades_for_user(&alice)), vec![t[0].id, t[2].id]);
+        assert_eq!(ids(bundle.trades_for_user(&bob)), vec![t[0].id, t[1].id]);
+        assert_eq!(ids(bundle.trades_for_user(&carol)), vec![t[1].id, t[2].id]);
+        assert!(bundle.trades_for_user(&UserId::new()).is_empty());
+
+        let parts = bundle.partition_by_user();
+        assert_eq!(parts.len(), 3);
+        for user in [alice, bob, carol] {
+            assert_eq!(
+                ids(parts[&user].clone()),
+                ids(bundle.trades_for_user(&user))
+            );
+        }
+    }
+
+    #[test]
+    fn duplicate_trade_id_detected() {
+        let (alice, bob) = (UserId::new(), UserId::new());
+        let mut bundle = make_bundle(vec![make_trade(0, alice, bob), make_trade(1, bob, alice)]);
+        bundle.assert_unique_trade_ids().unwrap();
+
+        bundle.trades.push(make_trade(1, alice, bob));
+        assert!(matches!(
+            bundle.assert_unique_trade_ids(),
+            Err(OpenmatchError::DeterminismViolation { .. })
+        ));
+    }
+
+    #[test]
+    fn trade_bundle_aggregates_across_markets() {
+        let (alice, bob) = (UserId::new(), UserId::new());
+        let empty = make_bundle(vec![]);
+        assert!(empty.is_empty());
+        assert_eq!(empty.total_volume(), Decimal::ZERO);
+        assert!(empty.markets().is_empty());
+
+        let mut eth = make_trade(2, bob, alice);
+        eth.market = MarketPair::new(Asset::lit("ETH"), Asset::lit("USDT"));
+        eth.quantity = Decimal::new(5, 1);
+        eth.quote_amount = Decimal::new(1500, 0);
+        let bundle = make_bundle(vec![
+            make_trade(0, alice, bob),
+            make_trade(1, bob, alice),
+            eth,
+        ]);
+
+        assert!(!bundle.is_empty());
+        assert_eq!(bundle.total_volume(), Decimal::new(25, 1));
+        assert_eq!(bundle.total_quote(), Decimal::new(1700, 0));
+        assert_eq!(
+            bundle.markets(),
+            HashSet::from([
+                MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")),
+                MarketPair::new(Asset::lit("ETH"), Asset::lit("USDT"))
+            ])
+        );
+    }
+
+    #[test]
+    fn partition_self_trade_listed_once() {
+        let alice = UserId::new();
+        let bundle = make_bundle(vec![make_trade(0, alice, alice)]);
+        let parts = bundle.partition_by_user();
+        assert_eq!(parts[&alice].len(), 1);
+    }
+
+    fn make_digest() -> BatchDigest {
+        BatchDigest {
+            epoch_id: EpochId(7),
+            batch_hash: [0xAB; 32],
+            order_count: 3,
+            signer_node: NodeId([0u8; 32]),
+            signature: vec![],
+        }
+    }
+
+    #[test]
+    fn batch_digest_sign_verify() {
+        let key = SigningKey::from_bytes(&[1u8; 32]);
+        let mut digest = make_digest();
+        digest.sign(&key);
+        assert_eq!(digest.signer_node.0, key.verifying_key().to_bytes());
+        assert!(digest.verify(&key.verifying_key()));
+
+        let other = SigningKey::from_bytes(&[2u8; 32]);
+        assert!(!digest.verify(&other.verifying_key()));
+    }
+
+    #[test]
+    fn batch_digest_tampered_order_count_rejected() {
+        let key = SigningKey::from_bytes(&[1u8; 32]);
+        let mut digest = make_digest();
+        digest.sign(&key);
+        digest.order_count += 1;
+        assert!(!digest.verify(&key.verifying_key()));
+    }
+
+    #[test]
+    fn batch_digest_unsigned_rejected() {
+        let key = SigningKey::from_bytes(&[1u8; 32]);
+        assert!(!make_digest().verify(&key.verifying_key()));
+    }
 
     #[test]
     fn epoch_phase_cycle() {
@@ -173,6 +726,21 @@ mod tests {
         assert_eq!(EpochPhase::Finalize.next(), EpochPhase::Collect);
     }
 
+    #[test]
+    fn epoch_phase_can_follow() {
+        let cycle = [
+            EpochPhase::Collect,
+            EpochPhase::Seal,
+            EpochPhase::Match,
+            EpochPhase::Finalize,
+        ];
+        for (i, &prev) in cycle.iter().enumerate() {
+            for (j, &next) in cycle.iter().enumerate() {
+                assert_eq!(next.can_follow(prev), j == (i + 1) % 4, "{prev} -> {next}");
+            }
+        }
+    }
+
     #[test]
     fn epoch_phase_display() {
         assert_eq!(format!("{}", EpochPhase::Collect), "COLLECT");
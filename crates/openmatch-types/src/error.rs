@@ -15,7 +15,7 @@
 use rust_decimal::Decimal;
 use thiserror::Error;
 
-use crate::{EpochPhase, NodeId, OrderId};
+use crate::{Asset, EpochPhase, NodeId, OrderId, RiskRejectionReason, UserId};
 
 /// Central error enum for all OpenMatch operations.
 #[derive(Debug, Error)]
@@ -54,9 +54,9 @@ pub enum OpenmatchError {
     #[error("OM_ERR_201: Insufficient frozen balance")]
     InsufficientFrozen,
 
-    /// A balance operation would produce a negative value.
-    #[error("OM_ERR_202: Balance underflow")]
-    BalanceUnderflow,
+    /// A balance operation would produce (or found) a negative value.
+    #[error("OM_ERR_202: Balance underflow: {asset} for user {user_id}")]
+    BalanceUnderflow { user_id: UserId, asset: Asset },
 
     // =================================================================
     // SpendRight / Escrow Errors (3xx)
@@ -133,6 +133,10 @@ pub enum OpenmatchError {
     #[error("OM_ERR_603: Withdrawals locked during settlement")]
     WithdrawLockedDuringSettle,
 
+    /// Withdrawals of this asset are frozen by an emergency asset lock.
+    #[error("OM_ERR_604: Withdrawals frozen for asset {asset}")]
+    AssetWithdrawLocked { asset: String },
+
     // =================================================================
     // Security Errors (8xx)
     // =================================================================
@@ -156,6 +160,15 @@ pub enum OpenmatchError {
     #[error("OM_ERR_804: Suspicious price: {reason}")]
     SuspiciousPrice { reason: String },
 
+    /// The risk gate rejected the action.
+    #[error("OM_ERR_805: Risk check failed: {0}")]
+    RiskRejected(RiskRejectionReason),
+
+    /// A receipt in a hash-linked audit log was altered, removed or
+    /// reordered.
+    #[error("OM_ERR_806: Receipt chain broken at index {index}")]
+    ReceiptChainBroken { index: usize },
+
     // =================================================================
     // Network Errors (7xx)
     // =================================================================
@@ -194,6 +207,14 @@ pub enum OpenmatchError {
 /// Crate-wide `Result` alias.
 pub type Result<T> = std::result::Result<T, OpenmatchError>;
 
+// Conversion from an infallible conversion's error, so APIs bounded on
+// `TryInto<Asset, Error: Into<OpenmatchError>>` also accept an `Asset`.
+impl From<std::convert::Infallible> for OpenmatchError {
+    fn from(never: std::convert::Infallible) -> Self {
+        match never {}
+    }
+}
+
 // Conversion from std::io::Error
 impl From<std::io::Error> for OpenmatchError {
     fn from(err: std::io::Error) -> Self {
@@ -201,6 +222,34 @@ impl From<std::io::Error> for OpenmatchError {
     }
 }
 
+// Conversion from rust_decimal::Error: out-of-range values are bad input,
+// anything else is a malformed string.
+impl From<rust_decimal::Error> for OpenmatchError {
+    fn from(err: rust_decimal::Error) -> Self {
+        match err {
+            rust_decimal::Error::ExceedsMaximumPossibleValue
+            | rust_decimal::Error::LessThanMinimumPossibleValue
+            | rust_decimal::Error::Underflow
+            | rust_decimal::Error::ScaleExceedsMaximumPrecision(_) => Self::InvalidOrder {
+                reason: format!("decimal out of range: {err}"),
+            },
+            _ => Self::Serialization(format!("invalid decimal: {err}")),
+        }
+    }
+}
+
+/// Parse a decimal from external input (API payloads, config).
+///
+/// Unlike `Decimal::from_str`, more fractional digits than a `Decimal`
+/// can hold are rejected rather than silently rounded.
+///
+/// # Errors
+/// `Serialization` for a malformed string, `InvalidOrder` for a value
+/// outside the representable range or precision.
+pub fn parse_decimal(s: &str) -> Result<Decimal> {
+    Ok(Decimal::from_str_exact(s)?)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -236,6 +285,19 @@ mod tests {
         assert!(msg.contains("MATCH"));
     }
 
+    #[test]
+    fn parse_decimal_maps_errors() {
+        assert_eq!(parse_decimal("1.25").unwrap(), Decimal::new(125, 2));
+        assert_eq!(parse_decimal("-0.5").unwrap(), Decimal::new(-5, 1));
+
+        let err = parse_decimal("12abc").unwrap_err();
+        assert!(matches!(err, OpenmatchError::Serialization(_)), "{err}");
+        assert!(parse_decimal("").is_err());
+
+        let err = parse_decimal("0.00000000000000000000000000001").unwrap_err();
+        assert!(matches!(err, OpenmatchError::InvalidOrder { .. }), "{err}");
+    }
+
     #[test]
     fn all_errors_have_om_err_prefix() {
         let errors: Vec<Box<dyn std::error::Error>> = vec![
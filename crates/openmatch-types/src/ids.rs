@@ -5,9 +5,12 @@
 
 use std::fmt;
 
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::{Asset, OpenmatchError, OrderSide, Result};
+
 // ---------------------------------------------------------------------------
 // OrderId
 // ---------------------------------------------------------------------------
@@ -37,6 +40,16 @@ impl OrderId {
     }
 }
 
+#[cfg(any(test, feature = "test-helpers"))]
+impl OrderId {
+    /// Reproducible `OrderId` derived from `seed`, for frozen test vectors.
+    /// Not time-ordered like [`OrderId::new`].
+    #[must_use]
+    pub fn deterministic(seed: u64) -> Self {
+        Self(seeded_uuid(b"openmatch:order_id:test:", seed))
+    }
+}
+
 impl Default for OrderId {
     fn default() -> Self {
         Self::new()
@@ -69,6 +82,15 @@ impl UserId {
     }
 }
 
+#[cfg(any(test, feature = "test-helpers"))]
+impl UserId {
+    /// Reproducible `UserId` derived from `seed`, for frozen test vectors.
+    #[must_use]
+    pub fn deterministic(seed: u64) -> Self {
+        Self(seeded_uuid(b"openmatch:user_id:test:", seed))
+    }
+}
+
 impl Default for UserId {
     fn default() -> Self {
         Self::new()
@@ -81,6 +103,18 @@ impl fmt::Display for UserId {
     }
 }
 
+/// First 16 bytes of `SHA-256(domain || seed)` as a UUID.
+#[cfg(any(test, feature = "test-helpers"))]
+fn seeded_uuid(domain: &[u8], seed: u64) -> Uuid {
+    use sha2::{Digest, Sha256};
+    let mut hasher = Sha256::new();
+    hasher.update(domain);
+    hasher.update(seed.to_le_bytes());
+    let hash = hasher.finalize();
+    let bytes: [u8; 16] = hash[..16].try_into().expect("SHA-256 produces 32 bytes");
+    Uuid::from_bytes(bytes)
+}
+
 // ---------------------------------------------------------------------------
 // NodeId
 // ---------------------------------------------------------------------------
@@ -128,6 +162,16 @@ impl SpendRightId {
     }
 }
 
+#[cfg(any(test, feature = "test-helpers"))]
+impl SpendRightId {
+    /// Reproducible `SpendRightId` derived from `seed`, for frozen test
+    /// vectors.
+    #[must_use]
+    pub fn deterministic(seed: u64) -> Self {
+        Self(seeded_uuid(b"openmatch:sr_id:test:", seed))
+    }
+}
+
 impl Default for SpendRightId {
     fn default() -> Self {
         Self::new()
@@ -170,13 +214,13 @@ impl fmt::Display for EpochId {
 /// A trading pair (e.g., BTC/USDT).
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Ord, PartialOrd, Serialize, Deserialize)]
 pub struct MarketPair {
-    pub base: String,
-    pub quote: String,
+    pub base: Asset,
+    pub quote: Asset,
 }
 
 impl MarketPair {
     #[must_use]
-    pub fn new(base: impl Into<String>, quote: impl Into<String>) -> Self {
+    pub fn new(base: impl Into<Asset>, quote: impl Into<Asset>) -> Self {
         Self {
             base: base.into(),
             quote: quote.into(),
@@ -187,6 +231,28 @@ impl MarketPair {
     pub fn symbol(&self) -> String {
         format!("{}/{}", self.base, self.quote)
     }
+
+    /// The asset an order on `side` locks in escrow: the quote asset it
+    /// pays with for a buy, the base asset it delivers for a sell.
+    #[must_use]
+    pub fn escrow_asset(&self, side: OrderSide) -> &Asset {
+        match side {
+            OrderSide::Buy => &self.quote,
+            OrderSide::Sell => &self.base,
+        }
+    }
+
+    /// Amount of [`MarketPair::escrow_asset`] an order of `qty` at `price`
+    /// locks: `price × qty` for a buy, `qty` for a sell. A buy whose
+    /// notional overflows saturates to `Decimal::MAX`, which escrow
+    /// minting refuses.
+    #[must_use]
+    pub fn escrow_amount(&self, side: OrderSide, price: Decimal, qty: Decimal) -> Decimal {
+        match side {
+            OrderSide::Buy => price.saturating_mul(qty),
+            OrderSide::Sell => qty,
+        }
+    }
 }
 
 impl fmt::Display for MarketPair {
@@ -209,10 +275,12 @@ impl TradeId {
         Self(Uuid::now_v7())
     }
 
-    /// Deterministic `TradeId` from epoch ID and fill sequence.
+    /// Deterministic `TradeId` from a batch number and fill sequence.
     ///
     /// Every node generates the **exact same** `TradeId` for the same fill
-    /// within the same epoch — critical for cross-node determinism.
+    /// within the same epoch — critical for cross-node determinism. Pass
+    /// [`BatchId::from_epoch`]`(..).0` so sharded matchers stay disjoint;
+    /// for shard 0 that is the bare epoch number.
     #[must_use]
     pub fn deterministic(epoch_id: u64, fill_sequence: u64) -> Self {
         use sha2::{Digest, Sha256};
@@ -238,8 +306,74 @@ impl fmt::Display for TradeId {
     }
 }
 
-/// Legacy alias. Prefer [`EpochId`] in new code.
-pub type BatchId = EpochId;
+// ---------------------------------------------------------------------------
+// BatchId
+// ---------------------------------------------------------------------------
+
+/// Identifier of one matched batch: an epoch on a given shard.
+///
+/// This is the numbering space fed to [`TradeId::deterministic`]. The
+/// shard occupies the top 16 bits and the epoch the low 48, so shard 0 of
+/// an epoch encodes to the bare epoch number and every `(epoch, shard)`
+/// pair gets a distinct value — trade IDs never collide across shards or
+/// epochs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Ord, PartialOrd, Serialize, Deserialize)]
+pub struct BatchId(pub u64);
+
+impl BatchId {
+    /// Bits reserved for the epoch number.
+    pub const EPOCH_BITS: u32 = 48;
+
+    /// Encode `epoch` on `shard`.
+    ///
+    /// # Panics
+    /// Panics if the epoch number needs more than [`Self::EPOCH_BITS`]
+    /// bits; use [`BatchId::try_from_epoch`] for epochs from the wire.
+    #[must_use]
+    pub fn from_epoch(epoch: EpochId, shard: u16) -> Self {
+        Self::try_from_epoch(epoch, shard).expect("epoch fits the batch encoding")
+    }
+
+    /// Encode `epoch` on `shard`, refusing epochs that don't fit.
+    ///
+    /// # Errors
+    /// `DeterminismViolation` if the epoch number needs more than
+    /// [`Self::EPOCH_BITS`] bits; it would alias another shard's IDs.
+    pub fn try_from_epoch(epoch: EpochId, shard: u16) -> Result<Self> {
+        if epoch.0 >> Self::EPOCH_BITS != 0 {
+            return Err(OpenmatchError::DeterminismViolation {
+                expected: format!("epoch below 2^{}", Self::EPOCH_BITS),
+                actual: format!("{epoch}"),
+            });
+        }
+        Ok(Self(u64::from(shard) << Self::EPOCH_BITS | epoch.0))
+    }
+
+    /// The epoch this batch belongs to.
+    #[must_use]
+    pub fn epoch(self) -> EpochId {
+        EpochId(self.0 & ((1 << Self::EPOCH_BITS) - 1))
+    }
+
+    /// The shard that matched this batch.
+    #[must_use]
+    pub fn shard(self) -> u16 {
+        u16::try_from(self.0 >> Self::EPOCH_BITS).expect("shard occupies the top 16 bits")
+    }
+}
+
+impl From<EpochId> for BatchId {
+    /// The epoch on shard 0.
+    fn from(epoch: EpochId) -> Self {
+        Self::from_epoch(epoch, 0)
+    }
+}
+
+impl fmt::Display for BatchId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "batch:{}/{}", self.epoch().0, self.shard())
+    }
+}
 
 // ---------------------------------------------------------------------------
 // Tests
@@ -306,10 +440,30 @@ mod tests {
 
     #[test]
     fn market_pair_symbol() {
-        let pair = MarketPair::new("BTC", "USDT");
+        let pair = MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT"));
         assert_eq!(pair.symbol(), "BTC/USDT");
     }
 
+    #[test]
+    fn escrow_asset_and_amount_by_side() {
+        let pair = MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT"));
+        let (price, qty) = (Decimal::new(50_000, 0), Decimal::new(15, 1));
+
+        assert_eq!(pair.escrow_asset(OrderSide::Buy), "USDT");
+        assert_eq!(
+            pair.escrow_amount(OrderSide::Buy, price, qty),
+            Decimal::new(75_000, 0)
+        );
+
+        assert_eq!(pair.escrow_asset(OrderSide::Sell), "BTC");
+        assert_eq!(pair.escrow_amount(OrderSide::Sell, price, qty), qty);
+
+        assert_eq!(
+            pair.escrow_amount(OrderSide::Buy, Decimal::MAX, Decimal::TWO),
+            Decimal::MAX
+        );
+    }
+
     #[test]
     fn serde_roundtrips() {
         let oid = OrderId::new();
@@ -322,4 +476,25 @@ mod tests {
         let back: SpendRightId = serde_json::from_str(&json).unwrap();
         assert_eq!(srid, back);
     }
+
+    #[test]
+    fn batch_id_encodes_epoch_and_shard() {
+        let id = BatchId::from_epoch(EpochId(42), 7);
+        assert_eq!(id.epoch(), EpochId(42));
+        assert_eq!(id.shard(), 7);
+        assert_eq!(BatchId::from(EpochId(42)).0, 42);
+        assert_ne!(id, BatchId::from_epoch(EpochId(42), 8));
+        assert_ne!(id, BatchId::from_epoch(EpochId(43), 7));
+        assert_eq!(id.to_string(), "batch:42/7");
+    }
+
+    #[test]
+    fn oversized_epoch_is_rejected() {
+        let max = EpochId((1 << BatchId::EPOCH_BITS) - 1);
+        assert_eq!(BatchId::try_from_epoch(max, 3).unwrap().epoch(), max);
+        assert!(matches!(
+            BatchId::try_from_epoch(EpochId(1 << BatchId::EPOCH_BITS), 0),
+            Err(OpenmatchError::DeterminismViolation { .. })
+        ));
+    }
 }
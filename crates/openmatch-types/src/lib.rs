@@ -5,23 +5,29 @@
 //! This crate is the leaf dependency of the workspace — every other crate
 //! depends on it. It defines:
 //!
-//! - **Identifiers**: [`OrderId`], [`UserId`], [`NodeId`], [`TradeId`], [`EpochId`], [`SpendRightId`], [`MarketPair`]
+//! - **Identifiers**: [`OrderId`], [`UserId`], [`NodeId`], [`TradeId`], [`EpochId`], [`BatchId`], [`SpendRightId`], [`MarketPair`]
 //! - **Order model**: [`Order`], [`OrderSide`], [`OrderType`], [`OrderStatus`]
 //! - **Trade model**: [`Trade`]
 //! - **SpendRight model**: [`SpendRight`], [`SpendRightState`]
 //! - **Receipt model**: [`Receipt`], [`ReceiptType`]
 //! - **Epoch model**: [`EpochPhase`], [`EpochConfig`], [`SealedBatch`], [`TradeBundle`], [`BatchDigest`]
 //! - **Balance model**: [`BalanceEntry`], [`Asset`]
+//! - **Asset metadata**: [`AssetInfo`], [`AssetRegistry`]
+//! - **Time**: [`Clock`], [`SystemClock`], [`FixedClock`]
 //! - **Configuration**: [`NodeConfig`], [`NetworkConfig`], [`MarketConfig`]
 //! - **Errors**: [`OpenmatchError`] with `OM_ERR_` prefix codes
 //! - **Risk management**: [`RiskLimits`], [`RiskDecision`], [`AgentId`]
+//! - **Settlement safety**: [`IdempotencyGuard`] (alias [`SettlementIdempotencyGuard`])
 //! - **Constants**: system-wide limits and defaults
 
+pub mod asset;
 pub mod balance;
+pub mod clock;
 pub mod config;
 pub mod constants;
 pub mod epoch;
 pub mod error;
+pub mod idempotency;
 pub mod ids;
 pub mod order;
 pub mod receipt;
@@ -32,10 +38,13 @@ pub mod trade;
 // Re-export all primary types at crate root for ergonomic imports:
 //   use openmatch_types::{Order, OrderSide, Trade, SpendRight, ...};
 
+pub use asset::*;
 pub use balance::*;
+pub use clock::*;
 pub use config::*;
 pub use epoch::*;
 pub use error::*;
+pub use idempotency::*;
 pub use ids::*;
 pub use order::*;
 pub use receipt::*;
@@ -3,11 +3,14 @@
 //! Every order entering MatchCore **must** have a valid SpendRight (sr_id).
 //! The Security Envelope validates this before the order enters the batch.
 
-use chrono::{DateTime, Utc};
+use std::cmp::Ordering;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
-use crate::{EpochId, MarketPair, NodeId, OrderId, SpendRightId, UserId};
+use crate::{EpochId, MarketPair, NodeId, OpenmatchError, OrderId, Result, SpendRightId, UserId};
 
 /// Which side of the book this order is on.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Ord, PartialOrd, Serialize, Deserialize)]
@@ -27,18 +30,81 @@ impl std::fmt::Display for OrderSide {
 
 /// The type of order.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Ord, PartialOrd, Serialize, Deserialize)]
+#[serde(from = "OrderTypeRepr", into = "OrderTypeRepr")]
 pub enum OrderType {
+    /// Rests at `price`. An `all_or_none` order fills completely within
+    /// a batch or not at all; unlike fill-or-kill it then rests intact
+    /// for the next batch rather than being dropped.
+    Limit {
+        all_or_none: bool,
+    },
+    Market,
+    /// Cancel the caller's own order `target`. Never matched.
+    Cancel {
+        target: OrderId,
+    },
+}
+
+impl OrderType {
+    /// Returns `true` for cancel requests.
+    #[must_use]
+    pub fn is_cancel(self) -> bool {
+        matches!(self, Self::Cancel { .. })
+    }
+
+    /// Returns `true` for limit orders, all-or-none or not.
+    #[must_use]
+    pub fn is_limit(self) -> bool {
+        matches!(self, Self::Limit { .. })
+    }
+
+    /// Returns `true` for all-or-none limit orders.
+    #[must_use]
+    pub fn is_all_or_none(self) -> bool {
+        matches!(self, Self::Limit { all_or_none: true })
+    }
+}
+
+/// Wire form of [`OrderType`]. Plain limits keep the original `"Limit"`
+/// encoding, so order signatures and stored orders predating all-or-none
+/// stay valid.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+enum OrderTypeRepr {
     Limit,
+    LimitAllOrNone,
     Market,
-    Cancel,
+    Cancel { target: OrderId },
+}
+
+impl From<OrderTypeRepr> for OrderType {
+    fn from(repr: OrderTypeRepr) -> Self {
+        match repr {
+            OrderTypeRepr::Limit => Self::Limit { all_or_none: false },
+            OrderTypeRepr::LimitAllOrNone => Self::Limit { all_or_none: true },
+            OrderTypeRepr::Market => Self::Market,
+            OrderTypeRepr::Cancel { target } => Self::Cancel { target },
+        }
+    }
+}
+
+impl From<OrderType> for OrderTypeRepr {
+    fn from(order_type: OrderType) -> Self {
+        match order_type {
+            OrderType::Limit { all_or_none: false } => Self::Limit,
+            OrderType::Limit { all_or_none: true } => Self::LimitAllOrNone,
+            OrderType::Market => Self::Market,
+            OrderType::Cancel { target } => Self::Cancel { target },
+        }
+    }
 }
 
 impl std::fmt::Display for OrderType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::Limit => write!(f, "LIMIT"),
+            Self::Limit { all_or_none: false } => write!(f, "LIMIT"),
+            Self::Limit { all_or_none: true } => write!(f, "LIMIT(AON)"),
             Self::Market => write!(f, "MARKET"),
-            Self::Cancel => write!(f, "CANCEL"),
+            Self::Cancel { target } => write!(f, "CANCEL({target})"),
         }
     }
 }
@@ -88,15 +154,19 @@ pub struct Order {
     pub sequence: u64,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Good-till-date expiry. A resting order past this time is swept
+    /// from the book; `None` rests until filled or cancelled.
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
 }
 
 impl Order {
     #[must_use]
     pub fn effective_price(&self) -> Decimal {
         match (self.order_type, self.side) {
-            (OrderType::Limit, _) => self.price.unwrap_or(Decimal::ZERO),
+            (OrderType::Limit { .. }, _) => self.price.unwrap_or(Decimal::ZERO),
             (OrderType::Market, OrderSide::Buy) => Decimal::MAX,
-            (OrderType::Market, OrderSide::Sell) | (OrderType::Cancel, _) => Decimal::ZERO,
+            (OrderType::Market, OrderSide::Sell) | (OrderType::Cancel { .. }, _) => Decimal::ZERO,
         }
     }
 
@@ -115,6 +185,12 @@ impl Order {
         self.remaining_qty.is_zero()
     }
 
+    /// Returns `true` if the order has an expiry at or before `now`.
+    #[must_use]
+    pub fn is_expired_at(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|at| at <= now)
+    }
+
     #[must_use]
     pub fn filled_qty(&self) -> Decimal {
         self.quantity - self.remaining_qty
@@ -128,6 +204,314 @@ impl Order {
             self.filled_qty() / self.quantity
         }
     }
+
+    /// Asset and amount this order must escrow: the quote asset for
+    /// `price × quantity` on a buy, the base asset for `quantity` on a sell.
+    ///
+    /// A market buy has no limit price (its `effective_price` is the
+    /// `Decimal::MAX` sentinel), so it escrows at `reference_price`
+    /// instead. The amount is always finite and normalized.
+    ///
+    /// # Errors
+    /// Returns `InvalidOrder` for a cancel, a limit order without a price,
+    /// a market buy without a reference price, or an amount that overflows.
+    pub fn escrow_requirement(&self, reference_price: Option<Decimal>) -> Result<(&str, Decimal)> {
+        let invalid = |reason: &str| OpenmatchError::InvalidOrder {
+            reason: reason.to_string(),
+        };
+        if self.order_type.is_cancel() {
+            return Err(invalid("cancel orders escrow nothing"));
+        }
+        let price = match (self.side, self.order_type) {
+            // A sell escrows its quantity whatever the price
+            (OrderSide::Sell, _) => Decimal::ZERO,
+            (OrderSide::Buy, OrderType::Market) => {
+                reference_price.ok_or_else(|| invalid("market buy needs a reference price"))?
+            }
+            (OrderSide::Buy, _) => self
+                .price
+                .ok_or_else(|| invalid("limit order requires a price"))?,
+        };
+        let amount = self.market.escrow_amount(self.side, price, self.quantity);
+        if amount == Decimal::MAX {
+            return Err(invalid("escrow amount overflows"));
+        }
+        Ok((self.market.escrow_asset(self.side), amount.normalize()))
+    }
+
+    /// Canonical deterministic ordering: side (buys first), then price
+    /// priority (best first: highest bid, lowest ask, market orders ahead
+    /// of limits), then `sequence`, then `id`.
+    ///
+    /// Use this (or [`Order::sort_canonical`]) instead of ad-hoc sort keys
+    /// wherever order of iteration affects results.
+    #[must_use]
+    pub fn cmp_canonical(&self, other: &Self) -> Ordering {
+        let price_priority = match self.side {
+            OrderSide::Buy => other.effective_price().cmp(&self.effective_price()),
+            OrderSide::Sell => self.effective_price().cmp(&other.effective_price()),
+        };
+        self.side
+            .cmp(&other.side)
+            .then(price_priority)
+            .then(self.sequence.cmp(&other.sequence))
+            .then(self.id.cmp(&other.id))
+    }
+
+    /// Sort `orders` by [`Order::cmp_canonical`].
+    pub fn sort_canonical(orders: &mut [Self]) {
+        orders.sort_by(Self::cmp_canonical);
+    }
+
+    /// Start building `user_id`'s order for the given market and side.
+    ///
+    /// The order type must be chosen with [`OrderBuilder::limit`],
+    /// [`OrderBuilder::all_or_none`] or [`OrderBuilder::market`] before
+    /// [`OrderBuilder::build`].
+    #[must_use]
+    pub fn builder(market: MarketPair, side: OrderSide, user_id: UserId) -> OrderBuilder {
+        OrderBuilder::new(market, side, user_id)
+    }
+
+    /// Check the order's structural invariants.
+    ///
+    /// - `quantity` is positive
+    /// - `remaining_qty` is within `0..=quantity`
+    /// - limit orders carry a positive price
+    ///
+    /// # Errors
+    /// Returns `InvalidOrder` describing the first violated invariant.
+    pub fn validate(&self) -> Result<()> {
+        if self.quantity <= Decimal::ZERO {
+            return Err(OpenmatchError::InvalidOrder {
+                reason: "Quantity must be positive".to_string(),
+            });
+        }
+        if self.remaining_qty.is_sign_negative() || self.remaining_qty > self.quantity {
+            return Err(OpenmatchError::InvalidOrder {
+                reason: format!(
+                    "Remaining quantity {} outside 0..={}",
+                    self.remaining_qty, self.quantity
+                ),
+            });
+        }
+        if self.order_type.is_limit() {
+            match self.price {
+                Some(price) if price > Decimal::ZERO => {}
+                _ => {
+                    return Err(OpenmatchError::InvalidOrder {
+                        reason: "Limit order requires a positive price".to_string(),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Canonical JSON of the client-signed fields, for order signatures.
+    ///
+    /// Fields appear in a fixed order (`id`, `user_id`, `market`, `side`,
+    /// `order_type`, `price`, `quantity`, `sr_id`, `expires_at`) and
+    /// decimals are normalized, so `1` and `1.0` serialize identically.
+    /// `expires_at` is RFC 3339 UTC with nanoseconds, or `null`. Lifecycle
+    /// fields (status, fills, sequencing, creation/update timestamps) are
+    /// excluded so the signature survives the order's progress through
+    /// the engine.
+    #[must_use]
+    pub fn canonical_json(&self) -> String {
+        let canonical = CanonicalOrder {
+            id: self.id,
+            user_id: self.user_id,
+            market: self.market.symbol(),
+            side: self.side,
+            order_type: self.order_type,
+            price: self.price.map(|p| p.normalize().to_string()),
+            quantity: self.quantity.normalize().to_string(),
+            sr_id: self.sr_id,
+            expires_at: self
+                .expires_at
+                .map(|at| at.to_rfc3339_opts(SecondsFormat::Nanos, true)),
+        };
+        serde_json::to_string(&canonical).expect("canonical order serialization cannot fail")
+    }
+
+    /// Verify a client's ed25519 signature over [`Order::canonical_json`].
+    ///
+    /// Returns `false` for a malformed signature or if any signed field
+    /// differs from what the client signed.
+    #[must_use]
+    pub fn verify_canonical_signature(&self, pubkey: &VerifyingKey, signature: &[u8]) -> bool {
+        let Ok(sig) = Signature::from_slice(signature) else {
+            return false;
+        };
+        pubkey
+            .verify(self.canonical_json().as_bytes(), &sig)
+            .is_ok()
+    }
+}
+
+/// Field order here is the canonical order; do not reorder.
+#[derive(Serialize)]
+struct CanonicalOrder {
+    id: OrderId,
+    user_id: UserId,
+    market: String,
+    side: OrderSide,
+    order_type: OrderType,
+    price: Option<String>,
+    quantity: String,
+    sr_id: SpendRightId,
+    expires_at: Option<String>,
+}
+
+/// Builder for [`Order`] that fills in lifecycle defaults.
+///
+/// The owner, market and side are given up front and the order type (with
+/// its price) must be set explicitly; nothing economically meaningful is
+/// defaulted. Defaults: fresh `id` and `sr_id`, `status = Active`,
+/// `remaining_qty = quantity`, `sequence = 0`, zeroed `origin_node`,
+/// timestamps set to now.
+#[derive(Debug, Clone)]
+pub struct OrderBuilder {
+    market: MarketPair,
+    side: OrderSide,
+    /// `None` until `limit`, `all_or_none` or `market` is called.
+    order_type: Option<OrderType>,
+    price: Option<Decimal>,
+    quantity: Decimal,
+    id: OrderId,
+    user_id: UserId,
+    sr_id: SpendRightId,
+    epoch_id: Option<EpochId>,
+    origin_node: NodeId,
+    sequence: u64,
+    expires_at: Option<DateTime<Utc>>,
+}
+
+impl OrderBuilder {
+    fn new(market: MarketPair, side: OrderSide, user_id: UserId) -> Self {
+        Self {
+            market,
+            side,
+            order_type: None,
+            price: None,
+            quantity: Decimal::ZERO,
+            id: OrderId::new(),
+            user_id,
+            sr_id: SpendRightId::new(),
+            epoch_id: None,
+            origin_node: NodeId([0u8; 32]),
+            sequence: 0,
+            expires_at: None,
+        }
+    }
+
+    /// Make this a limit order at `price`.
+    #[must_use]
+    pub fn limit(mut self, price: Decimal) -> Self {
+        self.order_type = Some(OrderType::Limit { all_or_none: false });
+        self.price = Some(price);
+        self
+    }
+
+    /// Make this an all-or-none limit order at `price`.
+    #[must_use]
+    pub fn all_or_none(mut self, price: Decimal) -> Self {
+        self.order_type = Some(OrderType::Limit { all_or_none: true });
+        self.price = Some(price);
+        self
+    }
+
+    /// Make this a market order (no price).
+    #[must_use]
+    pub fn market(mut self) -> Self {
+        self.order_type = Some(OrderType::Market);
+        self.price = None;
+        self
+    }
+
+    /// Set the order quantity (base asset). `remaining_qty` starts equal to it.
+    #[must_use]
+    pub fn quantity(mut self, quantity: Decimal) -> Self {
+        self.quantity = quantity;
+        self
+    }
+
+    /// Set an explicit order ID.
+    #[must_use]
+    pub fn id(mut self, id: OrderId) -> Self {
+        self.id = id;
+        self
+    }
+
+    /// Set the `SpendRight` funding this order.
+    #[must_use]
+    pub fn spend_right(mut self, sr_id: SpendRightId) -> Self {
+        self.sr_id = sr_id;
+        self
+    }
+
+    /// Set the epoch the order belongs to.
+    #[must_use]
+    pub fn epoch(mut self, epoch_id: EpochId) -> Self {
+        self.epoch_id = Some(epoch_id);
+        self
+    }
+
+    /// Set the node that accepted the order.
+    #[must_use]
+    pub fn origin_node(mut self, node_id: NodeId) -> Self {
+        self.origin_node = node_id;
+        self
+    }
+
+    /// Set the ingress sequence number.
+    #[must_use]
+    pub fn sequence(mut self, sequence: u64) -> Self {
+        self.sequence = sequence;
+        self
+    }
+
+    /// Make this a good-till-date order expiring at `at`.
+    #[must_use]
+    pub fn expires_at(mut self, at: DateTime<Utc>) -> Self {
+        self.expires_at = Some(at);
+        self
+    }
+
+    /// Build and validate the order.
+    ///
+    /// # Errors
+    /// Returns `InvalidOrder` if no order type was set or
+    /// [`Order::validate`] fails.
+    pub fn build(self) -> Result<Order> {
+        let order_type = self
+            .order_type
+            .ok_or_else(|| OpenmatchError::InvalidOrder {
+                reason: "order type not set: call limit, all_or_none or market".to_string(),
+            })?;
+        let now = Utc::now();
+        let order = Order {
+            id: self.id,
+            user_id: self.user_id,
+            market: self.market,
+            side: self.side,
+            order_type,
+            status: OrderStatus::Active,
+            price: self.price,
+            quantity: self.quantity,
+            remaining_qty: self.quantity,
+            sr_id: self.sr_id,
+            epoch_id: self.epoch_id,
+            origin_node: self.origin_node,
+            sequence: self.sequence,
+            created_at: now,
+            updated_at: now,
+            expires_at: self.expires_at,
+        };
+        order.validate()?;
+        Ok(order)
+    }
 }
 
 /// Test helpers.
@@ -137,9 +521,9 @@ impl Order {
         Self {
             id: OrderId::new(),
             user_id: UserId::new(),
-            market: MarketPair::new("BTC", "USDT"),
+            market: MarketPair::new(crate::Asset::lit("BTC"), crate::Asset::lit("USDT")),
             side,
-            order_type: OrderType::Limit,
+            order_type: OrderType::Limit { all_or_none: false },
             status: OrderStatus::Active,
             price: Some(price),
             quantity: qty,
@@ -150,6 +534,7 @@ impl Order {
             sequence: 0,
             created_at: Utc::now(),
             updated_at: Utc::now(),
+            expires_at: None,
         }
     }
 
@@ -162,9 +547,9 @@ impl Order {
         Self {
             id: OrderId::new(),
             user_id,
-            market: MarketPair::new("BTC", "USDT"),
+            market: MarketPair::new(crate::Asset::lit("BTC"), crate::Asset::lit("USDT")),
             side,
-            order_type: OrderType::Limit,
+            order_type: OrderType::Limit { all_or_none: false },
             status: OrderStatus::Active,
             price: Some(price),
             quantity: qty,
@@ -175,6 +560,7 @@ impl Order {
             sequence: 0,
             created_at: Utc::now(),
             updated_at: Utc::now(),
+            expires_at: None,
         }
     }
 }
@@ -182,6 +568,7 @@ impl Order {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::Asset;
 
     #[test]
     fn effective_price_limit() {
@@ -200,6 +587,116 @@ mod tests {
         assert!(OrderSide::Buy < OrderSide::Sell);
     }
 
+    #[test]
+    fn canonical_sort_is_total_and_input_independent() {
+        let limit = |side, price: i64, seq: u64| {
+            let mut o = Order::dummy_limit(side, Decimal::new(price, 0), Decimal::ONE);
+            o.sequence = seq;
+            o
+        };
+        let mut market_buy = limit(OrderSide::Buy, 1, 9);
+        market_buy.order_type = OrderType::Market;
+        market_buy.price = None;
+        // Same side, price and sequence: only the id separates these.
+        let tie_a = limit(OrderSide::Sell, 101, 4);
+        let tie_b = limit(OrderSide::Sell, 101, 4);
+
+        let orders = vec![
+            limit(OrderSide::Sell, 102, 1),
+            tie_a,
+            limit(OrderSide::Buy, 99, 2),
+            tie_b,
+            limit(OrderSide::Buy, 100, 7),
+            market_buy,
+            limit(OrderSide::Buy, 100, 3),
+        ];
+        let mut forward = orders.clone();
+        let mut reversed: Vec<Order> = orders.into_iter().rev().collect();
+        Order::sort_canonical(&mut forward);
+        Order::sort_canonical(&mut reversed);
+
+        let ids = |os: &[Order]| os.iter().map(|o| o.id).collect::<Vec<_>>();
+        assert_eq!(ids(&forward), ids(&reversed));
+        let keys: Vec<(OrderSide, Decimal, u64)> = forward
+            .iter()
+            .map(|o| (o.side, o.effective_price(), o.sequence))
+            .collect();
+        assert_eq!(
+            keys,
+            vec![
+                (OrderSide::Buy, Decimal::MAX, 9),
+                (OrderSide::Buy, Decimal::new(100, 0), 3),
+                (OrderSide::Buy, Decimal::new(100, 0), 7),
+                (OrderSide::Buy, Decimal::new(99, 0), 2),
+                (OrderSide::Sell, Decimal::new(101, 0), 4),
+                (OrderSide::Sell, Decimal::new(101, 0), 4),
+                (OrderSide::Sell, Decimal::new(102, 0), 1),
+            ]
+        );
+        for pair in forward.windows(2) {
+            assert_eq!(pair[0].cmp_canonical(&pair[1]), Ordering::Less);
+        }
+    }
+
+    #[test]
+    fn builder_valid_limit() {
+        let user = UserId::new();
+        let order = Order::builder(
+            MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")),
+            OrderSide::Sell,
+            user,
+        )
+        .limit(Decimal::new(50000, 0))
+        .quantity(Decimal::new(2, 0))
+        .sequence(7)
+        .build()
+        .unwrap();
+        assert_eq!(order.order_type, OrderType::Limit { all_or_none: false });
+        assert_eq!(order.status, OrderStatus::Active);
+        assert_eq!(order.price, Some(Decimal::new(50000, 0)));
+        assert_eq!(order.remaining_qty, order.quantity);
+        assert_eq!(order.user_id, user);
+        assert_eq!(order.sequence, 7);
+    }
+
+    #[test]
+    fn builder_rejects_zero_quantity() {
+        let err = Order::builder(
+            MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")),
+            OrderSide::Buy,
+            UserId::new(),
+        )
+        .limit(Decimal::new(50000, 0))
+        .quantity(Decimal::ZERO)
+        .build()
+        .unwrap_err();
+        assert!(matches!(err, OpenmatchError::InvalidOrder { .. }));
+    }
+
+    #[test]
+    fn builder_requires_an_order_type() {
+        let builder = Order::builder(
+            MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")),
+            OrderSide::Buy,
+            UserId::new(),
+        )
+        .quantity(Decimal::ONE);
+        let err = builder.clone().build().unwrap_err();
+        assert!(matches!(err, OpenmatchError::InvalidOrder { .. }));
+
+        let order = builder.market().build().unwrap();
+        assert_eq!(order.order_type, OrderType::Market);
+        assert_eq!(order.price, None);
+    }
+
+    #[test]
+    fn validate_rejects_limit_without_price() {
+        let mut order = Order::dummy_limit(OrderSide::Buy, Decimal::new(100, 0), Decimal::ONE);
+        assert!(order.validate().is_ok());
+        order.price = None;
+        assert!(order.validate().is_err());
+    }
+
     #[test]
     fn fill_tracking() {
         let mut order =
@@ -209,4 +706,79 @@ mod tests {
         assert!(order.is_filled());
         assert_eq!(order.fill_ratio(), Decimal::ONE);
     }
+
+    #[test]
+    fn canonical_json_normalizes_decimals() {
+        let mut a = Order::dummy_limit(OrderSide::Buy, Decimal::new(100, 0), Decimal::ONE);
+        let mut b = a.clone();
+        b.quantity = Decimal::new(10, 1); // 1.0
+        b.price = Some(Decimal::new(10000, 2)); // 100.00
+        assert_eq!(a.canonical_json(), b.canonical_json());
+
+        // Lifecycle fields are not signed
+        b.remaining_qty = Decimal::ZERO;
+        b.status = OrderStatus::Filled;
+        b.sequence = 9;
+        assert_eq!(a.canonical_json(), b.canonical_json());
+
+        a.quantity = Decimal::TWO;
+        assert_ne!(a.canonical_json(), b.canonical_json());
+    }
+
+    #[test]
+    fn canonical_json_field_order_is_fixed() {
+        let order = Order::dummy_limit(OrderSide::Sell, Decimal::new(100, 0), Decimal::ONE);
+        let json = order.canonical_json();
+        let keys = [
+            "\"id\"",
+            "\"user_id\"",
+            "\"market\"",
+            "\"side\"",
+            "\"order_type\"",
+            "\"price\"",
+            "\"quantity\"",
+            "\"sr_id\"",
+            "\"expires_at\"",
+        ];
+        let positions: Vec<usize> = keys.iter().map(|k| json.find(k).unwrap()).collect();
+        assert!(positions.is_sorted(), "{json}");
+        assert!(json.contains("\"market\":\"BTC/USDT\""));
+    }
+
+    #[test]
+    fn canonical_signature_verifies_across_scales() {
+        use ed25519_dalek::{Signer, SigningKey};
+
+        let key = SigningKey::from_bytes(&[3u8; 32]);
+        let order = Order::dummy_limit(OrderSide::Buy, Decimal::new(100, 0), Decimal::ONE);
+        let sig = key.sign(order.canonical_json().as_bytes()).to_bytes();
+
+        let mut rescaled = order.clone();
+        rescaled.quantity = Decimal::new(100, 2);
+        assert!(rescaled.verify_canonical_signature(&key.verifying_key(), &sig));
+
+        let mut tampered = order.clone();
+        tampered.price = Some(Decimal::new(101, 0));
+        assert!(!tampered.verify_canonical_signature(&key.verifying_key(), &sig));
+
+        // Extending a good-till-date order's life voids the signature
+        let mut extended = order.clone();
+        extended.expires_at = Some(DateTime::UNIX_EPOCH);
+        assert!(!extended.verify_canonical_signature(&key.verifying_key(), &sig));
+        assert!(!order.verify_canonical_signature(&key.verifying_key(), &[0u8; 3]));
+    }
+
+    #[test]
+    fn plain_limit_keeps_its_wire_form() {
+        let plain = OrderType::Limit { all_or_none: false };
+        let aon = OrderType::Limit { all_or_none: true };
+        assert_eq!(serde_json::to_string(&plain).unwrap(), r#""Limit""#);
+        assert_eq!(
+            serde_json::from_str::<OrderType>(r#""Limit""#).unwrap(),
+            plain
+        );
+        let json = serde_json::to_string(&aon).unwrap();
+        assert_eq!(serde_json::from_str::<OrderType>(&json).unwrap(), aon);
+        assert!(aon.is_limit() && aon.is_all_or_none() && !plain.is_all_or_none());
+    }
 }
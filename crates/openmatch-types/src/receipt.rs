@@ -2,14 +2,19 @@
 //!
 //! Every significant action (order accepted, trade executed, settlement
 //! completed) produces a signed [`Receipt`] that can be independently verified.
+//!
+//! A [`ReceiptChain`] links receipts by hash: each one records the hash of
+//! its predecessor, so editing, dropping or reordering any receipt breaks
+//! every link after it.
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
-use crate::{EpochId, NodeId, TradeId};
+use crate::{EpochId, NodeId, OpenmatchError, Result, TradeId};
 
 /// The type of action this receipt proves.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ReceiptType {
     /// An order was accepted into the pending buffer.
     OrderAccepted,
@@ -25,6 +30,19 @@ pub enum ReceiptType {
     SpendRightReleased,
     /// A SpendRight was consumed (settlement consumed the SR).
     SpendRightSpent,
+    /// The matcher refused a self-crossing pair (see
+    /// [`WashTradeAttempt`](crate::WashTradeAttempt)).
+    SelfTradeBlocked,
+    /// Funds were credited from an on-chain transaction.
+    Deposit {
+        /// External transaction reference (e.g. chain ID + tx hash).
+        chain_ref: String,
+    },
+    /// Funds were released to an on-chain transaction.
+    Withdrawal {
+        /// External transaction reference (e.g. chain ID + tx hash).
+        chain_ref: String,
+    },
 }
 
 impl std::fmt::Display for ReceiptType {
@@ -37,6 +55,9 @@ impl std::fmt::Display for ReceiptType {
             Self::SpendRightMinted => write!(f, "SPEND_RIGHT_MINTED"),
             Self::SpendRightReleased => write!(f, "SPEND_RIGHT_RELEASED"),
             Self::SpendRightSpent => write!(f, "SPEND_RIGHT_SPENT"),
+            Self::SelfTradeBlocked => write!(f, "SELF_TRADE_BLOCKED"),
+            Self::Deposit { chain_ref } => write!(f, "DEPOSIT({chain_ref})"),
+            Self::Withdrawal { chain_ref } => write!(f, "WITHDRAWAL({chain_ref})"),
         }
     }
 }
@@ -65,6 +86,10 @@ pub struct Receipt {
     pub issuer_node: NodeId,
     /// When this receipt was issued.
     pub issued_at: DateTime<Utc>,
+    /// [`Receipt::chain_hash`] of the previous receipt in its
+    /// [`ReceiptChain`]; all zeros for the first receipt or an unchained one.
+    #[serde(default)]
+    pub prev_hash: [u8; 32],
 }
 
 impl Receipt {
@@ -73,12 +98,173 @@ impl Receipt {
     pub fn signing_bytes(&self) -> &[u8; 32] {
         &self.payload_hash
     }
+
+    /// SHA-256 over every field except the signature, including the payload
+    /// itself and `prev_hash`. The signature covers `payload_hash` and may
+    /// be attached after the receipt is chained.
+    #[must_use]
+    pub fn chain_hash(&self) -> [u8; 32] {
+        let receipt_type = self.receipt_type.to_string();
+        let mut hasher = Sha256::new();
+        hasher.update(b"openmatch:receipt:v1:");
+        hasher.update(self.prev_hash);
+        hasher.update((receipt_type.len() as u64).to_le_bytes());
+        hasher.update(receipt_type.as_bytes());
+        hasher.update(self.epoch_id.0.to_le_bytes());
+        match self.trade_id {
+            Some(trade_id) => {
+                hasher.update([1]);
+                hasher.update(trade_id.0.as_bytes());
+            }
+            None => hasher.update([0]),
+        }
+        hasher.update((self.payload.len() as u64).to_le_bytes());
+        hasher.update(&self.payload);
+        hasher.update(self.payload_hash);
+        hasher.update(self.issuer_node.0);
+        hasher.update(self.issued_at.timestamp().to_le_bytes());
+        hasher.update(self.issued_at.timestamp_subsec_nanos().to_le_bytes());
+        hasher.finalize().into()
+    }
+}
+
+/// Append-only, hash-linked sequence of receipts.
+#[derive(Debug, Clone, Default)]
+pub struct ReceiptChain {
+    receipts: Vec<Receipt>,
+    /// Chain hash of the last receipt (all zeros while empty).
+    head: [u8; 32],
+}
+
+impl ReceiptChain {
+    /// Create an empty chain.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Link `receipt` to the current head and append it. Returns the new
+    /// head hash.
+    pub fn append(&mut self, mut receipt: Receipt) -> [u8; 32] {
+        receipt.prev_hash = self.head;
+        self.head = receipt.chain_hash();
+        self.receipts.push(receipt);
+        self.head
+    }
+
+    /// Receipts in append order.
+    #[must_use]
+    pub fn receipts(&self) -> &[Receipt] {
+        &self.receipts
+    }
+
+    /// Chain hash of the last receipt. Publishing it pins the whole chain.
+    #[must_use]
+    pub fn head(&self) -> [u8; 32] {
+        self.head
+    }
+
+    /// Number of receipts in the chain.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.receipts.len()
+    }
+
+    /// Returns `true` if no receipts were appended.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.receipts.is_empty()
+    }
+
+    /// Re-derive every link and check it against the stored head.
+    ///
+    /// # Errors
+    /// `ReceiptChainBroken` at the first receipt whose payload no longer
+    /// matches its `payload_hash`, or whose `prev_hash` doesn't match its
+    /// predecessor. A change to the last receipt is reported at its index
+    /// because the head no longer matches.
+    pub fn verify_chain(&self) -> Result<()> {
+        let mut prev = [0u8; 32];
+        for (index, receipt) in self.receipts.iter().enumerate() {
+            let payload_hash: [u8; 32] = Sha256::digest(&receipt.payload).into();
+            if receipt.prev_hash != prev || receipt.payload_hash != payload_hash {
+                return Err(OpenmatchError::ReceiptChainBroken { index });
+            }
+            prev = receipt.chain_hash();
+        }
+        if prev != self.head {
+            return Err(OpenmatchError::ReceiptChainBroken {
+                index: self.receipts.len().saturating_sub(1),
+            });
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn make_receipt(amount: u32) -> Receipt {
+        let payload = format!("user|USDT|{amount}").into_bytes();
+        Receipt {
+            receipt_type: ReceiptType::Deposit {
+                chain_ref: format!("eth:{amount}"),
+            },
+            epoch_id: EpochId(1),
+            trade_id: None,
+            payload_hash: Sha256::digest(&payload).into(),
+            payload,
+            signature: Vec::new(),
+            issuer_node: NodeId([0u8; 32]),
+            issued_at: Utc::now(),
+            prev_hash: [0u8; 32],
+        }
+    }
+
+    fn make_chain() -> ReceiptChain {
+        let mut chain = ReceiptChain::new();
+        for amount in [100, 250, 75] {
+            chain.append(make_receipt(amount));
+        }
+        chain
+    }
+
+    #[test]
+    fn receipt_chain_links_and_verifies() {
+        let chain = make_chain();
+        chain.verify_chain().unwrap();
+        ReceiptChain::new().verify_chain().unwrap();
+
+        let receipts = chain.receipts();
+        assert_eq!(receipts[0].prev_hash, [0u8; 32]);
+        assert_eq!(receipts[1].prev_hash, receipts[0].chain_hash());
+        assert_eq!(chain.head(), receipts[2].chain_hash());
+    }
+
+    #[test]
+    fn tampered_middle_receipt_breaks_chain() {
+        // Editing the amount alone no longer matches the payload hash
+        let mut chain = make_chain();
+        chain.receipts[1].payload = b"user|USDT|999".to_vec();
+        assert!(matches!(
+            chain.verify_chain(),
+            Err(OpenmatchError::ReceiptChainBroken { index: 1 })
+        ));
+
+        // Fixing up the payload hash too breaks the next link instead
+        chain.receipts[1].payload_hash = Sha256::digest(&chain.receipts[1].payload).into();
+        assert!(matches!(
+            chain.verify_chain(),
+            Err(OpenmatchError::ReceiptChainBroken { index: 2 })
+        ));
+
+        // So does dropping a receipt
+        let mut chain = make_chain();
+        chain.receipts.remove(1);
+        assert!(chain.verify_chain().is_err());
+    }
+
     #[test]
     fn receipt_type_display() {
         assert_eq!(format!("{}", ReceiptType::TradeExecuted), "TRADE_EXECUTED");
@@ -99,4 +285,27 @@ mod tests {
         let back: ReceiptType = serde_json::from_str(&json).unwrap();
         assert_eq!(rt, back);
     }
+
+    #[test]
+    fn chain_ref_receipt_types_serde_roundtrip() {
+        for rt in [
+            ReceiptType::Deposit {
+                chain_ref: "eth:0xabc".to_string(),
+            },
+            ReceiptType::Withdrawal {
+                chain_ref: "btc:f00d".to_string(),
+            },
+        ] {
+            let json = serde_json::to_string(&rt).unwrap();
+            let back: ReceiptType = serde_json::from_str(&json).unwrap();
+            assert_eq!(rt, back);
+        }
+        assert_eq!(
+            ReceiptType::Deposit {
+                chain_ref: "eth:0xabc".to_string()
+            }
+            .to_string(),
+            "DEPOSIT(eth:0xabc)"
+        );
+    }
 }
@@ -22,7 +22,7 @@
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
-use crate::UserId;
+use crate::{EpochPhase, UserId};
 
 /// Unique identifier for a trading agent.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Ord, PartialOrd, Serialize, Deserialize)]
@@ -176,6 +176,37 @@ pub enum RiskRejectionReason {
     AgentNotActive,
     /// Too many markets.
     TooManyMarkets { current: usize, limit: usize },
+    /// One batch's fills moved the user's net base position in `market`
+    /// (bought minus sold) past `max_batch_net_position` either way.
+    BatchNetPositionExceeded {
+        market: String,
+        net: Decimal,
+        limit: Decimal,
+    },
+    /// The epoch phase doesn't admit this kind of order.
+    PhaseClosed { phase: EpochPhase },
+    /// Order quantity is zero or negative.
+    NonPositiveQuantity { quantity: Decimal },
+    /// Trading in the market is halted.
+    MarketHalted { market: String },
+    /// Limit price is zero or negative.
+    NonPositivePrice { price: Decimal },
+    /// Limit price is not a multiple of the market's tick size.
+    OffTick { price: Decimal, tick: Decimal },
+    /// Limit price is more than `limit` times away from the market's
+    /// reference price.
+    PriceDeviation {
+        price: Decimal,
+        reference: Decimal,
+        ratio: Decimal,
+        limit: Decimal,
+    },
+    /// The user already placed `count` orders this epoch.
+    EpochOrderLimit { count: usize, limit: usize },
+    /// The user already placed `count` orders this batch.
+    BatchOrderLimit { count: usize, limit: usize },
+    /// The order ID's embedded time is `skew_ms` from the node clock.
+    OrderIdSkew { skew_ms: u64, limit_ms: i64 },
 }
 
 impl std::fmt::Display for RiskRejectionReason {
@@ -243,6 +274,41 @@ impl std::fmt::Display for RiskRejectionReason {
             Self::TooManyMarkets { current, limit } => {
                 write!(f, "Trading {current} markets, limit is {limit}")
             }
+            Self::BatchNetPositionExceeded { market, net, limit } => {
+                write!(
+                    f,
+                    "Batch net position {net} in {market} exceeds limit {limit}"
+                )
+            }
+            Self::PhaseClosed { phase } => write!(f, "Order not admitted during {phase:?}"),
+            Self::NonPositiveQuantity { quantity } => {
+                write!(f, "Quantity {quantity} must be positive")
+            }
+            Self::MarketHalted { market } => write!(f, "Market {market} is halted"),
+            Self::NonPositivePrice { price } => write!(f, "Price {price} must be positive"),
+            Self::OffTick { price, tick } => {
+                write!(f, "Price {price} is not a multiple of tick {tick}")
+            }
+            Self::PriceDeviation {
+                price,
+                reference,
+                ratio,
+                limit,
+            } => {
+                write!(
+                    f,
+                    "Price {price} deviates {ratio}x from reference {reference} (max {limit}x)"
+                )
+            }
+            Self::EpochOrderLimit { count, limit } => {
+                write!(f, "{count} orders this epoch, limit is {limit}")
+            }
+            Self::BatchOrderLimit { count, limit } => {
+                write!(f, "{count} orders this batch, limit is {limit}")
+            }
+            Self::OrderIdSkew { skew_ms, limit_ms } => {
+                write!(f, "Order ID time is {skew_ms}ms off (max {limit_ms}ms)")
+            }
         }
     }
 }
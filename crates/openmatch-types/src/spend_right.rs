@@ -28,7 +28,7 @@ use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
-use crate::{EpochId, NodeId, OrderId, SpendRightId, UserId};
+use crate::{Asset, EpochId, NodeId, OrderId, Result, SpendRightId, UserId};
 
 /// The lifecycle state of a SpendRight.
 ///
@@ -80,9 +80,13 @@ pub struct SpendRight {
     /// The user whose balance was frozen.
     pub user_id: UserId,
     /// The asset that was frozen (e.g., "USDT" for a buy, "BTC" for a sell).
-    pub asset: String,
+    pub asset: Asset,
     /// Amount frozen.
     pub amount: Decimal,
+    /// Part of `amount` already paid out by settled fills. Not signed:
+    /// it grows as partial fills settle.
+    #[serde(default)]
+    pub consumed: Decimal,
     /// The node that issued this SR (and signed it).
     pub issuer_node: NodeId,
     /// Current lifecycle state.
@@ -120,13 +124,31 @@ impl SpendRight {
     /// Returns `true` if this SR has expired.
     #[must_use]
     pub fn is_expired(&self) -> bool {
-        Utc::now() > self.expires_at
+        self.is_expired_at(Utc::now())
+    }
+
+    /// Returns `true` if this SR has expired as of `now`.
+    #[must_use]
+    pub fn is_expired_at(&self, now: DateTime<Utc>) -> bool {
+        now > self.expires_at
     }
 
     /// Returns `true` if this SR is currently usable for matching.
     #[must_use]
     pub fn is_active(&self) -> bool {
-        self.state == SpendRightState::Active && !self.is_expired()
+        self.is_active_at(Utc::now())
+    }
+
+    /// Returns `true` if this SR is usable for matching as of `now`.
+    #[must_use]
+    pub fn is_active_at(&self, now: DateTime<Utc>) -> bool {
+        self.state == SpendRightState::Active && !self.is_expired_at(now)
+    }
+
+    /// Frozen amount not yet paid out by fills.
+    #[must_use]
+    pub fn remaining(&self) -> Decimal {
+        self.amount - self.consumed
     }
 
     /// Attempt to transition to SPENT state.
@@ -164,6 +186,32 @@ impl SpendRight {
     }
 }
 
+/// The `SpendRight` bookkeeping settlement drives when it pays fills out
+/// of escrow.
+///
+/// Implemented by the Security Envelope's escrow manager, so the Finality
+/// Plane can spend SRs without depending on it.
+pub trait SpendRightLedger {
+    /// The SR minted for `order_id`, if any.
+    fn spend_right_for_order(&self, order_id: &OrderId) -> Option<&SpendRight>;
+
+    /// Whether `sr_id` is ACTIVE and unexpired.
+    fn is_active(&self, sr_id: &SpendRightId) -> bool;
+
+    /// Record that settled fills paid `amount` out of the SR's escrow.
+    ///
+    /// # Errors
+    /// `InvalidSpendRight` if the SR is unknown, not ACTIVE, or has less
+    /// than `amount` remaining.
+    fn consume(&mut self, sr_id: SpendRightId, amount: Decimal) -> Result<()>;
+
+    /// Mark the SR SPENT: its order is fully filled.
+    ///
+    /// # Errors
+    /// `InvalidSpendRight` if the SR is unknown or not ACTIVE.
+    fn mark_spent(&mut self, sr_id: SpendRightId) -> Result<()>;
+}
+
 /// Dummy SpendRight for testing. **Never use in production.**
 #[cfg(any(test, feature = "test-helpers"))]
 impl SpendRight {
@@ -179,8 +227,9 @@ impl SpendRight {
             id: SpendRightId::new(),
             order_id,
             user_id,
-            asset: asset.to_string(),
+            asset: Asset::lit(asset),
             amount,
+            consumed: Decimal::ZERO,
             issuer_node: NodeId([0u8; 32]),
             state: SpendRightState::Active,
             signature: vec![0u8; 64],
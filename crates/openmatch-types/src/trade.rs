@@ -3,6 +3,8 @@
 //! A [`Trade`] is the immutable record of a fill between a taker and maker
 //! at the epoch's uniform clearing price.
 
+use std::cmp::Ordering;
+
 use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
@@ -34,7 +36,8 @@ pub struct Trade {
     pub price: Decimal,
     /// Executed quantity in base asset.
     pub quantity: Decimal,
-    /// Quote amount = price × quantity.
+    /// Quote amount the seller receives: price × quantity, rounded to the
+    /// matcher's quote scale if one is configured (see `quote_dust`).
     pub quote_amount: Decimal,
     /// Which side the taker was on.
     pub taker_side: OrderSide,
@@ -42,9 +45,53 @@ pub struct Trade {
     pub matcher_node: NodeId,
     /// When this trade was executed.
     pub executed_at: DateTime<Utc>,
+    /// Rounding residual between the exact `price × quantity` the buyer
+    /// escrowed and `quote_amount`, routed to a reserved dust account.
+    /// `None` when the product needed no rounding.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quote_dust: Option<QuoteDust>,
+}
+
+/// The part of a fill's exact quote amount that rounding took away from
+/// (or added to) the seller, settled against a dust account instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuoteDust {
+    /// Reserved account that absorbs rounding residuals.
+    pub account: UserId,
+    /// Exact quote amount minus `Trade::quote_amount`: positive when the
+    /// seller's amount was rounded down (the dust account is credited),
+    /// negative when it was rounded up (the dust account pays).
+    pub amount: Decimal,
 }
 
 impl Trade {
+    /// Quote amount for a fill, normalized; `None` if `price × quantity`
+    /// overflows `Decimal`. Matchers skip a fill they can't price rather
+    /// than saturate or panic.
+    #[must_use]
+    pub fn checked_quote_amount(price: Decimal, quantity: Decimal) -> Option<Decimal> {
+        price.checked_mul(quantity).map(|q| q.normalize())
+    }
+
+    /// Canonical deterministic ordering: by `id`. Deterministic IDs are
+    /// hashes, so this is a stable total order rather than fill order.
+    #[must_use]
+    pub fn cmp_canonical(&self, other: &Self) -> Ordering {
+        self.id.cmp(&other.id)
+    }
+
+    /// Sort `trades` by [`Trade::cmp_canonical`].
+    pub fn sort_canonical(trades: &mut [Self]) {
+        trades.sort_by(Self::cmp_canonical);
+    }
+
+    /// Quote the buyer's escrow pays for this fill: `quote_amount` plus any
+    /// dust, i.e. the exact `price × quantity`.
+    #[must_use]
+    pub fn buyer_quote_amount(&self) -> Decimal {
+        self.quote_amount + self.quote_dust.map_or(Decimal::ZERO, |dust| dust.amount)
+    }
+
     /// Returns the fee-relevant notional value (quote_amount).
     #[must_use]
     pub fn notional(&self) -> Decimal {
@@ -56,6 +103,65 @@ impl Trade {
     pub fn taker_is_buyer(&self) -> bool {
         self.taker_side == OrderSide::Buy
     }
+
+    /// Stable SHA-256 fingerprint over the economically binding fields.
+    ///
+    /// Excludes `executed_at` and `matcher_node`, which differ between nodes
+    /// producing the same trade, so gossiped copies can be deduplicated.
+    /// Decimals are normalized so `100` and `100.00` hash the same.
+    #[must_use]
+    pub fn fingerprint(&self) -> [u8; 32] {
+        use sha2::{Digest, Sha256};
+        let mut hasher = Sha256::new();
+        hasher.update(b"openmatch:trade_fp:v1:");
+        hasher.update(self.id.0.as_bytes());
+        hasher.update(self.market.symbol().as_bytes());
+        hasher.update(b"\0");
+        hasher.update(self.taker_order_id.0.as_bytes());
+        hasher.update(self.taker_user_id.0.as_bytes());
+        hasher.update(self.maker_order_id.0.as_bytes());
+        hasher.update(self.maker_user_id.0.as_bytes());
+        for value in [self.price, self.quantity, self.quote_amount] {
+            hasher.update(value.normalize().to_string().as_bytes());
+            hasher.update(b"\0");
+        }
+        hasher.update(match self.taker_side {
+            OrderSide::Buy => [0u8],
+            OrderSide::Sell => [1u8],
+        });
+        if let Some(dust) = &self.quote_dust {
+            hasher.update(dust.account.0.as_bytes());
+            hasher.update(dust.amount.normalize().to_string().as_bytes());
+        }
+        hasher.finalize().into()
+    }
+
+    /// Anonymized view for the public tape: drops user and order IDs.
+    #[must_use]
+    pub fn public_view(&self) -> PublicTrade {
+        PublicTrade {
+            market: self.market.clone(),
+            price: self.price,
+            quantity: self.quantity,
+            taker_side: self.taker_side,
+            executed_at: self.executed_at,
+        }
+    }
+}
+
+/// A trade as published on the public tape: no user or order IDs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublicTrade {
+    /// The market (e.g., BTC/USDT).
+    pub market: MarketPair,
+    /// Execution price.
+    pub price: Decimal,
+    /// Executed quantity in base asset.
+    pub quantity: Decimal,
+    /// Which side the taker was on.
+    pub taker_side: OrderSide,
+    /// When the trade was executed.
+    pub executed_at: DateTime<Utc>,
 }
 
 impl std::fmt::Display for Trade {
@@ -71,12 +177,13 @@ impl std::fmt::Display for Trade {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::Asset;
 
     fn make_trade() -> Trade {
         Trade {
             id: TradeId::deterministic(1, 0),
             epoch_id: EpochId(1),
-            market: MarketPair::new("BTC", "USDT"),
+            market: MarketPair::new(Asset::lit("BTC"), Asset::lit("USDT")),
             taker_order_id: OrderId::new(),
             taker_user_id: UserId::new(),
             maker_order_id: OrderId::new(),
@@ -87,7 +194,42 @@ mod tests {
             taker_side: OrderSide::Buy,
             matcher_node: NodeId([0u8; 32]),
             executed_at: Utc::now(),
+            quote_dust: None,
+        }
+    }
+
+    #[test]
+    fn public_view_hides_participants() {
+        let t = make_trade();
+        let json = serde_json::to_string(&t.public_view()).unwrap();
+        for id in [
+            t.taker_user_id.0,
+            t.maker_user_id.0,
+            t.taker_order_id.0,
+            t.maker_order_id.0,
+        ] {
+            assert!(!json.contains(&id.to_string()), "{json}");
         }
+        assert_eq!(t.public_view().price, t.price);
+        assert_eq!(t.public_view().taker_side, OrderSide::Buy);
+    }
+
+    #[test]
+    fn canonical_sort_ignores_input_order() {
+        let trades: Vec<Trade> = (0..5)
+            .map(|seq| Trade {
+                id: TradeId::deterministic(1, seq),
+                ..make_trade()
+            })
+            .collect();
+        let mut forward = trades.clone();
+        let mut reversed: Vec<Trade> = trades.into_iter().rev().collect();
+        Trade::sort_canonical(&mut forward);
+        Trade::sort_canonical(&mut reversed);
+
+        let ids = |ts: &[Trade]| ts.iter().map(|t| t.id).collect::<Vec<_>>();
+        assert_eq!(ids(&forward), ids(&reversed));
+        assert!(ids(&forward).is_sorted());
     }
 
     #[test]
@@ -110,6 +252,28 @@ mod tests {
         assert!(s.contains("50000"));
     }
 
+    #[test]
+    fn fingerprint_ignores_volatile_fields() {
+        let a = make_trade();
+        let mut b = a.clone();
+        b.executed_at = a.executed_at + chrono::Duration::seconds(5);
+        b.matcher_node = NodeId([9u8; 32]);
+        b.price = Decimal::new(5_000_000, 2); // 50000.00
+        assert_eq!(a.fingerprint(), b.fingerprint());
+    }
+
+    #[test]
+    fn fingerprint_covers_binding_fields() {
+        let a = make_trade();
+        let mut b = a.clone();
+        b.quantity = Decimal::TWO;
+        assert_ne!(a.fingerprint(), b.fingerprint());
+
+        let mut c = a.clone();
+        c.taker_side = OrderSide::Sell;
+        assert_ne!(a.fingerprint(), c.fingerprint());
+    }
+
     #[test]
     fn trade_serde_roundtrip() {
         let trade = make_trade();